@@ -0,0 +1,218 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Acceptance of the state transfers by their beneficiaries.
+//!
+//! [`accept_transfer`] takes the receiver from a state transfer consignment
+//! to the asset state with the newly received allocations in a single call:
+//! it validates the consignment, reveals the receiver seals and reports the
+//! allocations assigned to the receiver outpoints.
+
+use std::collections::BTreeSet;
+
+use bitcoin::OutPoint;
+use bitcoin_onchain::ResolveTx;
+use commit_verify::CommitConceal;
+use rgb::validation::Validity;
+use rgb::{seal, AtomicValue, Consignment, OwnedValue, RevealSeals, StateTransfer};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+
+use crate::{Asset, Error, StateUpdate, ValidationReport};
+
+/// Errors accepting state transfer with [`accept_transfer`]
+#[derive(Clone, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum AcceptError {
+    /// state transfer is invalid; details are provided by the validation
+    /// report
+    Invalid(Box<ValidationReport>),
+
+    /// {0}
+    #[from]
+    Asset(Error),
+}
+
+/// Report on the state transfer accepted with [`accept_transfer`]
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Acceptance {
+    /// Report on the state transfer validation. The transfer is accepted
+    /// unless it is invalid, so the witness transactions may still be not
+    /// mined.
+    pub validation: ValidationReport,
+
+    /// Blinded seals of the transfer endpoints revealed by the receiver
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub revealed: Vec<seal::Confidential>,
+
+    /// Allocations assigned to the receiver outpoints
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub received: Vec<OwnedValue>,
+}
+
+impl Acceptance {
+    /// Returns total value of the allocations assigned to the receiver
+    pub fn received_value(&self) -> AtomicValue {
+        self.received
+            .iter()
+            .fold(0u64, |sum, coin| sum.saturating_add(coin.state.value))
+    }
+
+    /// Detects whether all witness transactions of the transfer were found
+    /// by the resolver, such that the received allocations are final
+    pub fn is_final(&self) -> bool { self.validation.validity() == Validity::Valid }
+}
+
+/// Accepts state transfer addressed to the receiver owning `my_outpoints`.
+///
+/// The transfer is validated using `resolver` for retrieving witness
+/// transactions, the receiver seals are revealed with `reveals` (blinding
+/// secrets kept by the receiver, for instance in a
+/// [`crate::seal::SealVault`]) and the asset is constructed from the
+/// resulting consignment. Transfers with unresolved witness transactions are
+/// accepted, which is reflected in the returned [`Acceptance`] report.
+///
+/// Errors with [`AcceptError::Invalid`] if the transfer is invalid, and with
+/// [`Error::EndpointAbsent`] if some of the `reveals` does not match any of the
+/// transfer endpoints.
+pub fn accept_transfer(
+    transfer: &StateTransfer,
+    my_outpoints: &BTreeSet<OutPoint>,
+    reveals: &[seal::Revealed],
+    resolver: &impl ResolveTx,
+) -> Result<(Asset, Acceptance), AcceptError> {
+    let validation = Asset::validate_consignment(transfer, resolver);
+    if validation.validity() == Validity::Invalid {
+        return Err(AcceptError::Invalid(Box::new(validation)));
+    }
+
+    let revealed = check_endpoints(transfer, reveals)?;
+    let mut transfer = transfer.clone();
+    transfer.reveal_seals(reveals);
+    let asset = Asset::try_from(&transfer)?;
+
+    let received = my_outpoints
+        .iter()
+        .flat_map(|outpoint| asset.allocations_at(*outpoint))
+        .cloned()
+        .collect();
+
+    Ok((asset, Acceptance {
+        validation,
+        revealed,
+        received,
+    }))
+}
+
+/// Checks that each of the `reveals` matches some of the transfer endpoints,
+/// returning their concealed form
+fn check_endpoints(
+    transfer: &StateTransfer,
+    reveals: &[seal::Revealed],
+) -> Result<Vec<seal::Confidential>, Error> {
+    reveals
+        .iter()
+        .map(|reveal| {
+            let concealed = reveal.commit_conceal();
+            if transfer
+                .endpoints()
+                .any(|(_, endpoint)| endpoint.commit_conceal() == concealed)
+            {
+                Ok(concealed)
+            } else {
+                Err(Error::EndpointAbsent {
+                    contract_id: transfer.contract_id(),
+                    seal: concealed,
+                })
+            }
+        })
+        .collect()
+}
+
+impl Asset {
+    /// Accepts state transfer addressed to the seals which revealed data are
+    /// known to the beneficiary, such that the allocations assigned to these
+    /// seals become known to the asset. Seals are revealed in a copy of the
+    /// consignment with [`RevealSeals::reveal_seals`].
+    ///
+    /// Errors if some of the `reveals` does not match any of the consignment
+    /// endpoints, or if the consignment can't be merged into the asset.
+    pub fn accept_transfer(
+        &mut self,
+        transfer: &StateTransfer,
+        reveals: &[seal::Revealed],
+    ) -> Result<StateUpdate, Error> {
+        check_endpoints(transfer, reveals)?;
+
+        let mut transfer = transfer.clone();
+        transfer.reveal_seals(reveals);
+        self.update_with(&transfer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::Hash;
+    use bitcoin::Txid;
+    use rgb::{SealEndpoint, TransitionBundle};
+    use seals::txout::CloseMethod;
+
+    use super::*;
+    use crate::fixtures::{anchor, builder, outpoint};
+    use crate::OfflineResolver;
+
+    #[test]
+    fn accept_blinded_transfer() {
+        let contract = builder().build().unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+
+        let reveal = seal::Revealed::new(CloseMethod::TapretFirst, outpoint(3));
+        let endpoint = SealEndpoint::ConcealedUtxo(reveal.commit_conceal());
+        let transition = asset
+            .transfer(
+                bset! { outpoint(1) },
+                bmap! { endpoint => 60 },
+                bmap! { seal::Revealed::from(outpoint(2)) => 40 },
+            )
+            .unwrap();
+        let bundle = TransitionBundle::try_from(bmap! { transition => bset! { 0 } }).unwrap();
+        let bundle_id = bundle.bundle_id();
+        let transfer = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            vec![(bundle_id, endpoint)],
+            vec![(anchor(Txid::hash(b"witness")), bundle)]
+                .try_into()
+                .unwrap(),
+            empty!(),
+        );
+
+        let resolver = OfflineResolver::new();
+        let (asset, acceptance) =
+            accept_transfer(&transfer, &bset! { outpoint(3) }, &[reveal], &resolver).unwrap();
+        assert!(!acceptance.is_final());
+        assert_eq!(acceptance.revealed, vec![reveal.commit_conceal()]);
+        assert_eq!(acceptance.received_value(), 60);
+        assert_eq!(asset.allocations_at(outpoint(3)).len(), 1);
+
+        let stranger = seal::Revealed::new(CloseMethod::TapretFirst, outpoint(4));
+        assert!(matches!(
+            accept_transfer(&transfer, &bset! { outpoint(4) }, &[stranger], &resolver),
+            Err(AcceptError::Asset(Error::EndpointAbsent { .. }))
+        ));
+    }
+}
@@ -0,0 +1,168 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::io;
+
+use lnpbp::bech32::{self, FromBech32Str, ToBech32String};
+use rgb::{Contract, Schema, StateTransfer, Transition};
+use strict_encoding::{StrictDecode, StrictEncode};
+
+/// Textual (ASCII-armored) representation of the strict-encoded RGB data,
+/// which can be pasted into chats, QR codes and support tickets.
+///
+/// Armored string is a Bech32m string with type-specific human-readable
+/// prefix and DEFLATE-compressed strict-encoded data as the payload.
+pub trait Armor: StrictEncode + StrictDecode + Clone {
+    /// Bech32 human-readable prefix used by the type
+    const HRP: &'static str;
+
+    /// Encodes data into Bech32m ASCII-armored string
+    fn to_armored_string(&self) -> String { Armored(self.clone()).to_bech32_string() }
+
+    /// Decodes data from Bech32m ASCII-armored string, which must use
+    /// [`Armor::HRP`] prefix. Surrounding whitespaces are ignored.
+    fn from_armored_str(s: &str) -> Result<Self, bech32::Error> {
+        Armored::from_bech32_str(s.trim()).map(|armored| armored.0)
+    }
+
+    /// Splits the ASCII-armored string into parts containing up to
+    /// `fragment_len` characters of the armored data each, for displaying
+    /// them as an animated QR code. The data are reassembled from the parts
+    /// with [`crate::ChunkDecoder`].
+    fn to_armored_chunks(&self, fragment_len: usize) -> Vec<String> {
+        crate::chunks::encode(self, fragment_len)
+    }
+
+    /// Detects whether the data are an ASCII-armored string of the type,
+    /// without validating the string
+    fn is_armored(data: &[u8]) -> bool {
+        let start = data
+            .iter()
+            .position(|byte| !byte.is_ascii_whitespace())
+            .unwrap_or(data.len());
+        let data = &data[start..];
+        data.len() > Self::HRP.len() + 1
+            && data[..Self::HRP.len()].eq_ignore_ascii_case(Self::HRP.as_bytes())
+            && data[Self::HRP.len()] == b'1'
+    }
+
+    /// Decodes data which may be either ASCII-armored or raw strict-encoded
+    fn from_armored_or_strict(data: &[u8]) -> Result<Self, bech32::Error> {
+        if Self::is_armored(data) {
+            Self::from_armored_str(&String::from_utf8_lossy(data))
+        } else {
+            Ok(Self::strict_deserialize(data)?)
+        }
+    }
+}
+
+impl Armor for Transition {
+    const HRP: &'static str = "rgbtr";
+}
+
+impl Armor for StateTransfer {
+    const HRP: &'static str = "rgbst";
+}
+
+/// Uses the same prefix as [`Contract`] string representation
+impl Armor for Contract {
+    const HRP: &'static str = "rgbc";
+}
+
+/// Uses the same prefix as [`Schema`] string representation
+impl Armor for Schema {
+    const HRP: &'static str = "rgbsh";
+}
+
+#[derive(Clone)]
+struct Armored<T: Armor>(T);
+
+impl<T: Armor> bech32::Strategy for Armored<T> {
+    const HRP: &'static str = T::HRP;
+    type Strategy = bech32::strategies::CompressedStrictEncoding;
+}
+
+impl<T: Armor> StrictEncode for Armored<T> {
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, strict_encoding::Error> {
+        self.0.strict_encode(e)
+    }
+}
+
+impl<T: Armor> StrictDecode for Armored<T> {
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, strict_encoding::Error> {
+        T::strict_decode(d).map(Armored)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use lnpbp::chain::Chain;
+    use rgb::{seal, Consignment};
+
+    use super::*;
+    use crate::{vectors, Asset, AssetName, Rgb20Builder, Ticker};
+
+    #[test]
+    fn armor() {
+        let outpoint = vectors::outpoint(1);
+        let contract = Rgb20Builder::new(Chain::Signet)
+            .ticker(Ticker::from_str("TICK").unwrap())
+            .name(AssetName::from_str("Test asset").unwrap())
+            .allocate(outpoint, 100)
+            .build()
+            .unwrap();
+
+        let armored = contract.to_armored_string();
+        assert_eq!(armored, contract.to_string());
+        assert_eq!(Contract::from_armored_str(&armored).unwrap(), contract);
+
+        let transfer = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            empty!(),
+            empty!(),
+            empty!(),
+        );
+        let armored = transfer.to_armored_string();
+        assert!(armored.starts_with("rgbst1"));
+        assert!(StateTransfer::is_armored(armored.as_bytes()));
+        assert!(!Transition::is_armored(armored.as_bytes()));
+        let padded = format!("\n{}\n", armored.to_uppercase());
+        assert_eq!(StateTransfer::from_armored_str(&padded).unwrap(), transfer);
+        assert_eq!(
+            StateTransfer::from_armored_or_strict(padded.as_bytes()).unwrap(),
+            transfer
+        );
+        assert_eq!(
+            StateTransfer::from_armored_or_strict(&transfer.strict_serialize().unwrap()).unwrap(),
+            transfer
+        );
+        assert_eq!(
+            Transition::from_armored_str(&armored),
+            Err(bech32::Error::WrongPrefix)
+        );
+
+        let asset = Asset::try_from(&contract).unwrap();
+        let transition = asset
+            .transfer(
+                bset! { outpoint },
+                empty!(),
+                bmap! { seal::Revealed::from(outpoint) => 100 },
+            )
+            .unwrap();
+        let armored = transition.to_armored_string();
+        assert!(armored.starts_with("rgbtr1"));
+        assert_eq!(Transition::from_armored_str(&armored).unwrap(), transition);
+    }
+}
@@ -9,10 +9,26 @@
 // You should have received a copy of the MIT License along with this software.
 // If not, see <https://opensource.org/licenses/MIT>.
 
-use std::collections::btree_set;
+use std::cmp::Ordering;
+use std::collections::{btree_map, btree_set, BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::str::FromStr;
 
-use bitcoin::OutPoint;
-use rgb::{ConsignmentType, ContractState, InmemConsignment, NodeId, OwnedValue};
+use bitcoin::{OutPoint, Txid};
+use chrono::{DateTime, Utc};
+use rgb::{
+    seal, AtomicValue, ConfidentialDataError, Consignment, ConsignmentType, ContractId,
+    ContractState, Extension, Genesis, GraphApi, InmemConsignment, Node, NodeId, NodeOutpoint,
+    OwnedRight, OwnedValue, Schema, SchemaId, StateRetrievalError, Transition,
+};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr, Same};
+use strict_encoding::{StrictDecode, StrictEncode};
+
+use crate::datetime::utc_time;
+use crate::schema::{FieldType, OwnedRightType, Subschema, TransitionType};
+use crate::{Attachment, AttachmentType, Features};
 
 /// RGB20 asset information.
 ///
@@ -38,22 +54,469 @@ use rgb::{ConsignmentType, ContractState, InmemConsignment, NodeId, OwnedValue};
 /// In both (2) and (3) case there is no need to persist the structure; genesis
 /// /consignment should be persisted instead and the structure must be
 /// reconstructed each time from that data upon the launch
+///
+/// Together with the contract state the structure keeps the genesis and all
+/// known state transitions and extensions, which are required for the asset
+/// history analysis. Asset allocations are indexed by their outpoints upon
+/// construction; the index is not serialized and is rebuilt on decoding.
+///
+/// With `serde` feature the structure is serialized together with the contract
+/// state; on deserialization the state is re-computed from the genesis and the
+/// known state transitions and extensions.
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
-#[derive(StrictEncode, StrictDecode)]
-pub struct Asset(ContractState);
+pub struct Asset {
+    state: ContractState,
+    subschema: Subschema,
+    genesis: Genesis,
+    transitions: BTreeMap<NodeId, (Txid, Transition)>,
+    extensions: BTreeMap<NodeId, Extension>,
+    // Derived from `state`; rebuilt with `Asset::reindex`
+    coins: BTreeMap<OutPoint, Vec<OwnedValue>>,
+    locks: BTreeMap<OutPoint, u32>,
+}
+
+impl StrictEncode for Asset {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, strict_encoding::Error> {
+        Ok(
+            strict_encode_list!(e; self.state, self.genesis, self.transitions, self.extensions, self.locks),
+        )
+    }
+}
+
+impl StrictDecode for Asset {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, strict_encoding::Error> {
+        let mut asset = Asset::strict_decode_legacy(&mut d)?;
+        asset.locks = StrictDecode::strict_decode(&mut d)?;
+        Ok(asset)
+    }
+}
 
 impl Asset {
-    /// Lists all known allocations for the given bitcoin transaction
-    /// [`OutPoint`]
-    pub fn known_coins(&self) -> btree_set::Iter<OwnedValue> { self.0.owned_values.iter() }
+    /// Decodes asset data encoded before allocation locks were introduced,
+    /// i.e. with version 1 of the asset snapshot format
+    pub(crate) fn strict_decode_legacy<D: io::Read>(
+        mut d: D,
+    ) -> Result<Self, strict_encoding::Error> {
+        let state = ContractState::strict_decode(&mut d)?;
+        let subschema = Subschema::from_schema_id(state.schema_id).ok_or(
+            strict_encoding::Error::DataIntegrityError(s!("asset state uses unknown schema")),
+        )?;
+        let mut asset = Asset {
+            state,
+            subschema,
+            genesis: StrictDecode::strict_decode(&mut d)?,
+            transitions: StrictDecode::strict_decode(&mut d)?,
+            extensions: StrictDecode::strict_decode(&mut d)?,
+            coins: empty!(),
+            locks: empty!(),
+        };
+        asset.reindex();
+        Ok(asset)
+    }
+}
+
+/// Known state transition together with its witness transaction id, used
+/// for serde serialization of the asset history
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_crate", rename_all = "camelCase")]
+struct WitnessedTransition<T> {
+    witness_txid: Txid,
+    transition: T,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[serde(crate = "serde_crate", rename_all = "camelCase")]
+struct AssetSer<'asset> {
+    contract_id: ContractId,
+    subschema: Subschema,
+    state: &'asset ContractState,
+    genesis: &'asset Genesis,
+    transitions: Vec<WitnessedTransition<&'asset Transition>>,
+    extensions: Vec<&'asset Extension>,
+    #[serde(with = "As::<&BTreeMap<DisplayFromStr, Same>>")]
+    locks: &'asset BTreeMap<OutPoint, u32>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(crate = "serde_crate", rename_all = "camelCase")]
+struct AssetDe {
+    genesis: Genesis,
+    #[serde(default)]
+    transitions: Vec<WitnessedTransition<Transition>>,
+    #[serde(default)]
+    extensions: Vec<Extension>,
+    #[serde(default, with = "As::<BTreeMap<DisplayFromStr, Same>>")]
+    locks: BTreeMap<OutPoint, u32>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Asset {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        AssetSer {
+            contract_id: self.contract_id(),
+            subschema: self.subschema,
+            state: &self.state,
+            genesis: &self.genesis,
+            transitions: self
+                .transitions
+                .values()
+                .map(|(witness_txid, transition)| WitnessedTransition {
+                    witness_txid: *witness_txid,
+                    transition,
+                })
+                .collect(),
+            extensions: self.extensions.values().collect(),
+            locks: &self.locks,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Asset {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        let data = AssetDe::deserialize(deserializer)?;
+        let subschema = Subschema::from_schema_id(data.genesis.schema_id())
+            .ok_or_else(|| serde::de::Error::custom("asset genesis uses unknown schema"))?;
+        let root_schema_id = subschema.root_schema_id();
+        let mut asset = Asset {
+            state: ContractState::with(
+                subschema.schema_id(),
+                root_schema_id,
+                data.genesis.contract_id(),
+                &data.genesis,
+            ),
+            subschema,
+            genesis: data.genesis,
+            transitions: data
+                .transitions
+                .into_iter()
+                .map(|item| {
+                    (
+                        item.transition.node_id(),
+                        (item.witness_txid, item.transition),
+                    )
+                })
+                .collect(),
+            extensions: data
+                .extensions
+                .into_iter()
+                .map(|extension| (extension.node_id(), extension))
+                .collect(),
+            coins: empty!(),
+            locks: data.locks,
+        };
+        asset.replay_history();
+        asset.reindex();
+        Ok(asset)
+    }
+}
+
+impl Asset {
+    fn with<T>(consignment: &InmemConsignment<T>, subschema: Subschema) -> Asset
+    where T: ConsignmentType {
+        let mut asset = Asset {
+            state: ContractState::from(consignment),
+            subschema,
+            genesis: consignment.genesis().clone(),
+            transitions: empty!(),
+            extensions: empty!(),
+            coins: empty!(),
+            locks: empty!(),
+        };
+        asset.extend_history(consignment);
+        asset.reindex();
+        asset
+    }
+
+    /// Constructs asset with the genesis state only. Transitions and
+    /// extensions are added with [`Asset::add_transition`] and
+    /// [`Asset::add_extension`], after which the asset must be reindexed.
+    pub(crate) fn with_genesis(
+        schema: &Schema,
+        root_schema: Option<&Schema>,
+        genesis: Genesis,
+        subschema: Subschema,
+    ) -> Asset {
+        let mut asset = Asset {
+            state: ContractState::with(
+                schema.schema_id(),
+                root_schema.map(Schema::schema_id),
+                genesis.contract_id(),
+                &genesis,
+            ),
+            subschema,
+            genesis,
+            transitions: empty!(),
+            extensions: empty!(),
+            coins: empty!(),
+            locks: empty!(),
+        };
+        asset.reindex();
+        asset
+    }
+
+    /// Constructs asset from the already computed contract state and the
+    /// history it was computed from
+    #[cfg(feature = "parallel")]
+    pub(crate) fn with_history(
+        state: ContractState,
+        subschema: Subschema,
+        genesis: Genesis,
+        transitions: BTreeMap<NodeId, (Txid, Transition)>,
+        extensions: BTreeMap<NodeId, Extension>,
+    ) -> Asset {
+        let mut asset = Asset {
+            state,
+            subschema,
+            genesis,
+            transitions,
+            extensions,
+            coins: empty!(),
+            locks: empty!(),
+        };
+        asset.reindex();
+        asset
+    }
+
+    /// Adds state transition to the asset history and contract state, unless
+    /// it is already known
+    pub(crate) fn add_transition(&mut self, witness_txid: Txid, transition: Transition) {
+        if let btree_map::Entry::Vacant(entry) = self.transitions.entry(transition.node_id()) {
+            self.state.add_transition(witness_txid, &transition);
+            entry.insert((witness_txid, transition));
+        }
+    }
+
+    /// Adds state extension to the asset history and contract state, unless
+    /// it is already known
+    pub(crate) fn add_extension(&mut self, extension: Extension) {
+        if let btree_map::Entry::Vacant(entry) = self.extensions.entry(extension.node_id()) {
+            self.state.add_extension(&extension);
+            entry.insert(extension);
+        }
+    }
+
+    /// Adds all not yet known state transitions and extensions from the
+    /// consignment to the asset history. Does not update the contract state.
+    fn extend_history<T>(&mut self, consignment: &InmemConsignment<T>)
+    where T: ConsignmentType {
+        for (anchor, bundle) in consignment.anchored_bundles() {
+            for (transition, _) in bundle.revealed_iter() {
+                self.transitions
+                    .entry(transition.node_id())
+                    .or_insert_with(|| (anchor.txid, transition.clone()));
+            }
+        }
+        for extension in consignment.state_extensions() {
+            self.extensions
+                .entry(extension.node_id())
+                .or_insert_with(|| extension.clone());
+        }
+    }
+
+    /// Applies all known extensions and state transitions to the contract
+    /// state, which must contain only the genesis state. Transitions are
+    /// applied after all their known ancestors.
+    #[cfg(feature = "serde")]
+    fn replay_history(&mut self) {
+        for extension in self.extensions.values() {
+            self.state.add_extension(extension);
+        }
+        let mut applied = bset![self.genesis.node_id()];
+        let mut pending = self.transitions.values().collect::<Vec<_>>();
+        while !pending.is_empty() {
+            let count = pending.len();
+            pending.retain(|(txid, transition)| {
+                let ready = transition.parent_owned_rights().keys().all(|parent| {
+                    applied.contains(parent) || !self.transitions.contains_key(parent)
+                });
+                if ready {
+                    self.state.add_transition(*txid, transition);
+                    applied.insert(transition.node_id());
+                }
+                !ready
+            });
+            // Transition graph is acyclic, but we protect against malformed
+            // data anyway
+            if pending.len() == count {
+                break;
+            }
+        }
+    }
+
+    /// Rebuilds outpoint index of the asset allocations. Must be called each
+    /// time the underlying contract state gets updated.
+    pub(crate) fn reindex(&mut self) {
+        self.coins.clear();
+        for coin in self
+            .state
+            .owned_values
+            .iter()
+            .filter(|coin| coin.outpoint.ty == OwnedRightType::Assets as u16)
+        {
+            self.coins.entry(coin.seal).or_default().push(coin.clone());
+        }
+    }
+}
+
+impl Asset {
+    /// Returns contract state underlying the asset
+    pub fn as_state(&self) -> &ContractState { &self.state }
+
+    /// Converts asset into the underlying contract state, dropping the asset
+    /// history and indexes
+    pub fn into_state(self) -> ContractState { self.state }
+
+    /// Returns id of the asset contract
+    pub fn contract_id(&self) -> ContractId { self.state.contract_id }
+
+    /// Returns RGB20 subschema the asset was issued under
+    pub fn subschema(&self) -> Subschema { self.subschema }
+
+    /// Returns decimal precision of the asset, i.e. number of digits reserved
+    /// for the fractional part, as it was defined in the genesis
+    pub fn precision(&self) -> u8 {
+        self.genesis
+            .metadata()
+            .u8(FieldType::Precision)
+            .first()
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns time of the asset issuance as it was defined in the genesis,
+    /// including the time of day. Defaults to the Unix epoch if the genesis
+    /// timestamp is absent or out of range.
+    pub fn issued(&self) -> DateTime<Utc> { utc_time(self.issued_timestamp()) }
+
+    /// Returns raw genesis timestamp of the asset issuance, in seconds since
+    /// the Unix epoch. Defaults to zero if the genesis timestamp is absent.
+    pub fn issued_timestamp(&self) -> i64 {
+        self.genesis
+            .metadata()
+            .i64(FieldType::Timestamp)
+            .first()
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns asset genesis
+    pub fn genesis(&self) -> &Genesis { &self.genesis }
+
+    /// Returns known node (genesis, state transition or extension) with the
+    /// given id together with the witness transaction id, if the node is a
+    /// state transition
+    pub fn node_by_id(&self, node_id: NodeId) -> Option<(&dyn Node, Option<Txid>)> {
+        if node_id == self.genesis.node_id() {
+            return Some((&self.genesis, None));
+        }
+        if let Some((txid, transition)) = self.transitions.get(&node_id) {
+            return Some((transition, Some(*txid)));
+        }
+        self.extensions
+            .get(&node_id)
+            .map(|extension| (extension as &dyn Node, None))
+    }
+
+    /// Resolves bitcoin transaction output of the seal defined by the known
+    /// node for the given `outpoint`. Returns `None` if the node is unknown or
+    /// the seal is confidential.
+    pub fn resolve_seal(&self, outpoint: NodeOutpoint) -> Option<OutPoint> {
+        let (node, witness_txid) = self.node_by_id(outpoint.node_id)?;
+        let seal = node
+            .owned_rights_by_type(outpoint.ty)?
+            .revealed_seal_at(outpoint.no)
+            .ok()??;
+        Some(OutPoint::new(seal.txid.or(witness_txid)?, seal.vout))
+    }
+
+    /// Iterates over all known state transitions, together with their witness
+    /// transaction ids
+    pub fn transitions(&self) -> impl Iterator<Item = (Txid, &Transition)> {
+        self.transitions
+            .values()
+            .map(|(txid, transition)| (*txid, transition))
+    }
+
+    /// Iterates over all known state transitions of the given type, together
+    /// with their witness transaction ids
+    pub fn transitions_by_type(
+        &self,
+        ty: TransitionType,
+    ) -> impl Iterator<Item = (Txid, &Transition)> {
+        self.transitions()
+            .filter(move |(_, transition)| transition.transition_type() == ty as u16)
+    }
+
+    /// Constructs [`AssetAmount`] from the number of atomic units using the
+    /// asset precision
+    pub fn amount(&self, atomic: AtomicValue) -> AssetAmount {
+        AssetAmount {
+            atomic,
+            precision: self.precision(),
+        }
+    }
+
+    /// Lists all known asset allocations. Inflation rights, which are also
+    /// represented by value assignments, are not included.
+    pub fn known_coins(&self) -> impl Iterator<Item = &OwnedValue> { self.coins.values().flatten() }
+
+    /// Lists all known unspent inflation rights, with their state holding the
+    /// remaining secondary issuance allowance
+    pub fn known_inflation(&self) -> impl Iterator<Item = &OwnedValue> {
+        self.state
+            .owned_values
+            .iter()
+            .filter(|right| right.outpoint.ty == OwnedRightType::Inflation as u16)
+    }
+
+    /// Lists all known declarative rights (renomination, epoch and burn &
+    /// replace rights) assigned to bitcoin transaction outputs
+    pub fn known_rights(&self) -> btree_set::Iter<'_, OwnedRight> { self.state.owned_rights.iter() }
 
     /// Lists all known allocations for the given bitcoin transaction
     /// [`OutPoint`]
     pub fn outpoint_coins(&self, outpoint: OutPoint) -> Vec<OwnedValue> {
-        self.known_coins()
-            .filter(|a| a.seal == outpoint)
-            .cloned()
-            .collect()
+        self.allocations_at(outpoint).to_vec()
+    }
+
+    /// Returns all known allocations for the given bitcoin transaction
+    /// [`OutPoint`] without cloning them
+    pub fn allocations_at(&self, outpoint: OutPoint) -> &[OwnedValue] {
+        self.coins
+            .get(&outpoint)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Lists all bitcoin transaction outpoints having known asset allocations
+    pub fn outpoints(&self) -> btree_map::Keys<'_, OutPoint, Vec<OwnedValue>> { self.coins.keys() }
+
+    /// Detects whether the given bitcoin transaction [`OutPoint`] has known
+    /// asset allocations
+    pub fn contains_outpoint(&self, outpoint: OutPoint) -> bool {
+        self.coins.contains_key(&outpoint)
+    }
+
+    /// Marks allocations assigned to the `outpoint` as locked until the
+    /// chain tip reaches `locked_until` height, replacing previous lock of the
+    /// outpoint. The lock is kept if the outpoint has no known allocations,
+    /// so it may be set before the allocations are received.
+    pub fn lock_outpoint(&mut self, outpoint: OutPoint, locked_until: u32) {
+        self.locks.insert(outpoint, locked_until);
+    }
+
+    /// Returns block height until which allocations assigned to the
+    /// `outpoint` are locked, if the outpoint was locked with
+    /// [`Asset::lock_outpoint`]
+    pub fn locked_until(&self, outpoint: OutPoint) -> Option<u32> {
+        self.locks.get(&outpoint).copied()
     }
 }
 
@@ -63,46 +526,961 @@ where T: ConsignmentType
     type Error = Error;
 
     fn try_from(consignment: &InmemConsignment<T>) -> Result<Self, Self::Error> {
-        let state = ContractState::from(consignment);
-        let asset = Asset(state);
-        asset.validate()?;
-        Ok(asset)
+        let subschema = Asset::validate(consignment)?;
+        Ok(Asset::with(consignment, subschema))
+    }
+}
+
+/// Changes to the known asset allocations resulting from
+/// [`Asset::update_with`]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct StateUpdate {
+    /// Allocations which were created by the merged state transitions
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub added: Vec<OwnedValue>,
+
+    /// Previously known allocations which were spent by the merged state
+    /// transitions
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub spent: Vec<OwnedValue>,
+}
+
+impl StateUpdate {
+    /// Detects whether the update has not changed known asset allocations
+    pub fn is_empty(&self) -> bool { self.added.is_empty() && self.spent.is_empty() }
+
+    /// Lists outpoints which received new allocations
+    pub fn added_outpoints(&self) -> BTreeSet<OutPoint> {
+        self.added.iter().map(|coin| coin.seal).collect()
+    }
+
+    /// Lists outpoints which previously known allocations were spent
+    pub fn spent_outpoints(&self) -> BTreeSet<OutPoint> {
+        self.spent.iter().map(|coin| coin.seal).collect()
+    }
+}
+
+impl Asset {
+    /// Merges knowledge about the asset state from an additional consignment,
+    /// like a newly received state transfer. State transitions and extensions
+    /// which are already known are skipped.
+    ///
+    /// Returns the list of asset allocations which were added or spent by the
+    /// update. Fails with [`Error::DoubleSpend`] if the consignment closes a
+    /// seal which is already closed by a different known state transition.
+    pub fn update_with<T>(
+        &mut self,
+        consignment: &InmemConsignment<T>,
+    ) -> Result<StateUpdate, Error>
+    where
+        T: ConsignmentType,
+    {
+        if consignment.contract_id() != self.contract_id() {
+            return Err(Error::ContractMismatch {
+                expected: self.contract_id(),
+                found: consignment.contract_id(),
+            });
+        }
+        Asset::validate(consignment)?;
+        let spent = self.spending_transitions();
+        for (_, bundle) in consignment.anchored_bundles() {
+            for (transition, _) in bundle.revealed_iter() {
+                let node_id = transition.node_id();
+                for outpoint in transition.parent_outputs() {
+                    match spent.get(&outpoint) {
+                        Some(known) if *known != node_id => Err(Error::DoubleSpend {
+                            outpoint,
+                            known: *known,
+                            conflicting: node_id,
+                        })?,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let before = self.known_coins().cloned().collect::<BTreeSet<_>>();
+
+        for (anchor, bundle) in consignment.anchored_bundles() {
+            for (transition, _) in bundle.revealed_iter() {
+                if !self.transitions.contains_key(&transition.node_id()) {
+                    self.state.add_transition(anchor.txid, transition);
+                }
+            }
+        }
+        for extension in consignment.state_extensions() {
+            if !self.extensions.contains_key(&extension.node_id()) {
+                self.state.add_extension(extension);
+            }
+        }
+        self.extend_history(consignment);
+        self.reindex();
+
+        let after = self.known_coins().cloned().collect::<BTreeSet<_>>();
+        Ok(StateUpdate {
+            added: after.difference(&before).cloned().collect(),
+            spent: before.difference(&after).cloned().collect(),
+        })
     }
 }
 
 impl Asset {
-    fn validate(&self) -> Result<(), Error> {
-        if self.0.schema_id != crate::schema().schema_id() {
-            Err(Error::WrongSchemaId)?;
+    /// Performs RGB20-specific semantic validation of the consignment data.
+    ///
+    /// The validation does not replace the consignment validation against the
+    /// schema and blockchain, which must be performed separately; it only
+    /// checks RGB20 rules which can't be expressed by the schema.
+    ///
+    /// Returns RGB20 subschema used by the consignment.
+    pub(crate) fn validate<T>(consignment: &InmemConsignment<T>) -> Result<Subschema, Error>
+    where T: ConsignmentType {
+        let mut validator = Validator::with(
+            consignment.schema(),
+            consignment.root_schema(),
+            consignment.genesis(),
+        )?;
+        let nodes = consignment
+            .anchored_bundles()
+            .flat_map(|(_, bundle)| bundle.known_transitions())
+            .map(|transition| transition as &dyn Node)
+            .chain(
+                consignment
+                    .state_extensions()
+                    .map(|extension| extension as &dyn Node),
+            );
+        for node in nodes {
+            validator.validate_node(node)?;
+        }
+        validator.finish(|node_id| consignment.node_by_id(node_id).is_some())
+    }
+
+    /// Checks that the supply declared by an issuing node matches the sum of
+    /// the asset allocations it defines
+    fn validate_issued_supply(contract_id: ContractId, node: &dyn Node) -> Result<(), Error> {
+        let node_id = node.node_id();
+        let declared = node
+            .metadata()
+            .u64(FieldType::IssuedSupply)
+            .first()
+            .copied()
+            .ok_or(Error::UnsatisfiedSchemaRequirement {
+                node_id,
+                field_type: FieldType::IssuedSupply,
+            })?;
+        let allocations = match node.owned_rights_by_type(OwnedRightType::Assets.into()) {
+            None => return Ok(()),
+            Some(assignments) => assignments,
+        };
+        // Confidential allocations can't be checked here; the consistency of
+        // their amounts is ensured by the schema validation with Pedersen
+        // commitments
+        if let Ok(values) = allocations.as_revealed_state_values() {
+            let allocated = checked_sum(values.into_iter().map(|value| value.value)).ok_or(
+                Error::SupplyOverflow {
+                    contract_id,
+                    node_id,
+                },
+            )?;
+            if allocated != declared {
+                Err(Error::IssuedSupplyMismatch {
+                    node_id,
+                    declared,
+                    allocated,
+                })?;
+            }
         }
-        // TODO: Validate the state
         Ok(())
     }
 }
 
+/// Data collected by [`Validator::check_node`] for the checks involving
+/// multiple consignment nodes
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub(crate) struct NodeChecks {
+    node_id: NodeId,
+    issued: AtomicValue,
+    supply: AtomicValue,
+    epochs: Vec<(NodeId, NodeId)>,
+    closes: Vec<(NodeOutpoint, NodeId)>,
+}
+
+/// Incremental RGB20-specific validation of consignment nodes, which does not
+/// require all consignment data to be present in memory at once
+pub(crate) struct Validator {
+    contract_id: ContractId,
+    subschema: Subschema,
+    features: Features,
+    inflation_allowed: AtomicValue,
+    inflation_issued: AtomicValue,
+    supply: AtomicValue,
+    epochs: BTreeMap<NodeId, NodeId>,
+    closed: BTreeMap<NodeOutpoint, NodeId>,
+}
+
+impl Validator {
+    /// Checks consignment schemata and genesis, returning validator for the
+    /// rest of the consignment nodes
+    pub(crate) fn with(
+        schema: &Schema,
+        root_schema: Option<&Schema>,
+        genesis: &Genesis,
+    ) -> Result<Validator, Error> {
+        let schema_id = schema.schema_id();
+        let subschema =
+            Subschema::from_schema_id(schema_id).ok_or(Error::WrongSchemaId(schema_id))?;
+        if let Some(expected) = subschema.root_schema_id() {
+            let found = root_schema.map(Schema::schema_id);
+            if found != Some(expected) {
+                Err(Error::RootSchemaMismatch { expected, found })?;
+            }
+        }
+
+        let contract_id = genesis.contract_id();
+        let genesis_id = genesis.node_id();
+        let metadata = genesis.metadata();
+        let nomination_absent = |field_type| Error::NominationAbsent {
+            contract_id,
+            field_type,
+        };
+        if metadata.ascii_string(FieldType::Ticker).is_empty() {
+            Err(nomination_absent(FieldType::Ticker))?;
+        }
+        if metadata.ascii_string(FieldType::Name).is_empty() {
+            Err(nomination_absent(FieldType::Name))?;
+        }
+        match metadata.u8(FieldType::Precision).first() {
+            None => Err(nomination_absent(FieldType::Precision))?,
+            Some(precision) if *precision > MAX_PRECISION => Err(Error::PrecisionOverflow {
+                contract_id,
+                precision: *precision,
+            })?,
+            Some(_) => {}
+        }
+        for attachment in AttachmentType::ALL {
+            for data in metadata.bytes(attachment.field_type()) {
+                Attachment::strict_deserialize(data).map_err(|source| {
+                    Error::AttachmentInvalid {
+                        contract_id,
+                        attachment,
+                        source: Box::new(source),
+                    }
+                })?;
+            }
+        }
+        for (ty, assignments) in genesis.owned_rights().iter() {
+            if assignments
+                .revealed_seal_outputs()
+                .iter()
+                .any(|(seal, _)| seal.txid.is_none())
+            {
+                Err(Error::GenesisSeal {
+                    contract_id,
+                    right_type: *ty,
+                })?;
+            }
+        }
+
+        let inflation_allowed = match genesis.owned_rights_by_type(OwnedRightType::Inflation.into())
+        {
+            None => 0,
+            Some(assignments) => assignments
+                .as_revealed_state_values()
+                .map_err(|source| Error::InflationAssignmentConfidential {
+                    node_id: genesis_id,
+                    source,
+                })?
+                .into_iter()
+                .fold(0u64, |sum, value| sum.saturating_add(value.value)),
+        };
+
+        let mut validator = Validator {
+            contract_id,
+            subschema,
+            features: Features::with_genesis(schema, genesis),
+            inflation_allowed,
+            inflation_issued: 0,
+            supply: 0,
+            epochs: empty!(),
+            closed: empty!(),
+        };
+        validator.validate_node(genesis)?;
+        Ok(validator)
+    }
+
+    /// Returns RGB20 subschema used by the consignment
+    pub(crate) fn subschema(&self) -> Subschema { self.subschema }
+
+    /// Validates consignment state transition or extension
+    pub(crate) fn validate_node(&mut self, node: &dyn Node) -> Result<(), Error> {
+        let checks = self.check_node(node)?;
+        self.merge(checks)
+    }
+
+    /// Performs checks of a single consignment state transition or
+    /// extension, which do not depend on the other nodes. The returned data
+    /// must be merged into the validator with [`Validator::merge`].
+    pub(crate) fn check_node(&self, node: &dyn Node) -> Result<NodeChecks, Error> {
+        let node_id = node.node_id();
+        let transition_type = node.transition_type();
+        let mut checks = NodeChecks {
+            node_id,
+            ..NodeChecks::default()
+        };
+
+        if let Some(ty) = transition_type.and_then(|ty| TransitionType::try_from(ty).ok()) {
+            if !self.features.allows(ty) {
+                Err(Error::OperationDenied {
+                    node_id,
+                    transition_type: ty,
+                })?;
+            }
+        }
+
+        if transition_type.is_none() || transition_type == Some(TransitionType::Issue.into()) {
+            Asset::validate_issued_supply(self.contract_id, node)?;
+            checks.supply = checked_sum(node.metadata().u64(FieldType::IssuedSupply)).ok_or(
+                Error::SupplyOverflow {
+                    contract_id: self.contract_id,
+                    node_id,
+                },
+            )?;
+        }
+        if transition_type == Some(TransitionType::Issue.into()) {
+            checks.issued = checks.supply;
+        }
+
+        if let Some(assignments) = node.owned_rights_by_type(OwnedRightType::OpenEpoch.into()) {
+            assignments
+                .revealed_seals()
+                .map_err(|source| Error::EpochSealConfidential { node_id, source })?;
+        }
+        if let Some(assignments) = node.owned_rights_by_type(OwnedRightType::BurnReplace.into()) {
+            assignments
+                .revealed_seals()
+                .map_err(|source| Error::BurnSealConfidential { node_id, source })?;
+        }
+
+        if transition_type == Some(TransitionType::Burn.into())
+            || transition_type == Some(TransitionType::BurnAndReplace.into())
+        {
+            checks.epochs = node
+                .parent_outputs_by_type(OwnedRightType::BurnReplace.into())
+                .iter()
+                .map(|output| (output.node_id, node_id))
+                .collect();
+        }
+
+        checks.closes = node
+            .parent_outputs()
+            .into_iter()
+            .map(|output| (output, node_id))
+            .collect();
+
+        Ok(checks)
+    }
+
+    /// Accounts results of [`Validator::check_node`], failing if the node
+    /// closes a seal already closed by another consignment node or if the
+    /// total asset supply overflows
+    pub(crate) fn merge(&mut self, checks: NodeChecks) -> Result<(), Error> {
+        for (outpoint, node_id) in checks.closes {
+            match self.closed.insert(outpoint, node_id) {
+                Some(known) if known != node_id => Err(Error::DoubleSpend {
+                    outpoint,
+                    known,
+                    conflicting: node_id,
+                })?,
+                _ => {}
+            }
+        }
+        // Inflation allowance may legitimately saturate for assets with
+        // unlimited inflation, while the supply issued by the genesis and
+        // secondary issuances can't exceed `u64::MAX` for any valid asset
+        self.supply = self
+            .supply
+            .checked_add(checks.supply)
+            .ok_or(Error::SupplyOverflow {
+                contract_id: self.contract_id,
+                node_id: checks.node_id,
+            })?;
+        // Secondary issuance is a part of the supply checked above
+        self.inflation_issued += checks.issued;
+        self.epochs.extend(checks.epochs);
+        Ok(())
+    }
+
+    /// Completes validation with the checks requiring knowledge of all
+    /// consignment nodes, which presence is reported by `is_known`
+    pub(crate) fn finish(self, is_known: impl Fn(NodeId) -> bool) -> Result<Subschema, Error> {
+        if let Some((epoch_id, node_id)) = self
+            .epochs
+            .into_iter()
+            .find(|(epoch_id, _)| !is_known(*epoch_id))
+        {
+            Err(Error::EpochAbsent { node_id, epoch_id })?;
+        }
+        if self.inflation_issued > self.inflation_allowed {
+            Err(Error::InflationCapExceeded {
+                contract_id: self.contract_id,
+                allowed: self.inflation_allowed,
+                issued: self.inflation_issued,
+            })?;
+        }
+        Ok(self.subschema)
+    }
+}
+
+/// Sums asset amounts, returning `None` on overflow
+pub(crate) fn checked_sum(values: impl IntoIterator<Item = AtomicValue>) -> Option<AtomicValue> {
+    values.into_iter().try_fold(0u64, u64::checked_add)
+}
+
+/// Maximal decimal precision of RGB20 assets
+pub const MAX_PRECISION: u8 = 18;
+
+/// Errors parsing or converting [`AssetAmount`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum AmountError {
+    /// amount string is empty
+    Empty,
+
+    /// amount contains invalid character; only decimal digits with a single
+    /// optional dot are allowed
+    InvalidCharacter,
+
+    /// decimal precision {0} exceeds the maximum of 18 digits
+    PrecisionOverflow(u8),
+
+    /// amount does not fit into 64-bit atomic value
+    ValueOverflow,
+
+    /// amount has more fractional digits than allowed by the asset precision
+    /// {0}
+    PrecisionLoss(u8),
+}
+
+/// Asset amount, represented by the number of atomic units together with the
+/// decimal precision of the asset.
+///
+/// Amounts are formatted and parsed as decimal strings, like `1.50000000`.
+/// The parsed amount has the precision matching the number of its fractional
+/// digits, so it must be [`AssetAmount::rescale`]d to the asset precision
+/// before being used.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase", try_from = "AssetAmountDe")
+)]
+pub struct AssetAmount {
+    atomic: AtomicValue,
+    precision: u8,
+}
+
+/// Unchecked [`AssetAmount`] data, used for serde deserialization via
+/// [`AssetAmount::with_atomic`]
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(crate = "serde_crate", rename_all = "camelCase")]
+struct AssetAmountDe {
+    atomic: AtomicValue,
+    precision: u8,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<AssetAmountDe> for AssetAmount {
+    type Error = AmountError;
+
+    fn try_from(data: AssetAmountDe) -> Result<Self, Self::Error> {
+        AssetAmount::with_atomic(data.atomic, data.precision)
+    }
+}
+
+impl AssetAmount {
+    /// Constructs amount from the number of atomic units and decimal precision
+    pub fn with_atomic(atomic: AtomicValue, precision: u8) -> Result<Self, AmountError> {
+        if precision > MAX_PRECISION {
+            return Err(AmountError::PrecisionOverflow(precision));
+        }
+        Ok(AssetAmount { atomic, precision })
+    }
+
+    /// Returns number of atomic units
+    pub fn atomic_value(self) -> AtomicValue { self.atomic }
+
+    /// Returns decimal precision
+    pub fn precision(self) -> u8 { self.precision }
+
+    /// Converts amount into a different decimal precision. Errors if the
+    /// conversion results in the loss of fractional digits or overflows atomic
+    /// value.
+    pub fn rescale(self, precision: u8) -> Result<Self, AmountError> {
+        if precision > MAX_PRECISION {
+            return Err(AmountError::PrecisionOverflow(precision));
+        }
+        let atomic = if precision >= self.precision {
+            self.atomic
+                .checked_mul(10u64.pow((precision - self.precision) as u32))
+                .ok_or(AmountError::ValueOverflow)?
+        } else {
+            let divisor = 10u64.pow((self.precision - precision) as u32);
+            if self.atomic % divisor != 0 {
+                return Err(AmountError::PrecisionLoss(precision));
+            }
+            self.atomic / divisor
+        };
+        Ok(AssetAmount { atomic, precision })
+    }
+
+    /// Adds two amounts of the same precision, returning `None` on overflow
+    /// or precision mismatch
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        if self.precision != other.precision {
+            return None;
+        }
+        self.atomic
+            .checked_add(other.atomic)
+            .map(|atomic| AssetAmount { atomic, ..self })
+    }
+
+    /// Subtracts two amounts of the same precision, returning `None` on
+    /// underflow or precision mismatch
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        if self.precision != other.precision {
+            return None;
+        }
+        self.atomic
+            .checked_sub(other.atomic)
+            .map(|atomic| AssetAmount { atomic, ..self })
+    }
+}
+
+impl PartialOrd for AssetAmount {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for AssetAmount {
+    /// Amounts are compared by their decimal value; amounts with the same
+    /// value are ordered by precision
+    fn cmp(&self, other: &Self) -> Ordering {
+        let normalize =
+            |a: &Self| a.atomic as u128 * 10u128.pow((MAX_PRECISION - a.precision) as u32);
+        normalize(self)
+            .cmp(&normalize(other))
+            .then(self.precision.cmp(&other.precision))
+    }
+}
+
+impl From<AssetAmount> for AtomicValue {
+    fn from(amount: AssetAmount) -> Self { amount.atomic }
+}
+
+impl Display for AssetAmount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let divisor = 10u64.pow(self.precision as u32);
+        write!(f, "{}", self.atomic / divisor)?;
+        if self.precision > 0 {
+            write!(
+                f,
+                ".{:0width$}",
+                self.atomic % divisor,
+                width = self.precision as usize
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for AssetAmount {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (int, fract) = match s.split_once('.') {
+            Some((int, fract)) => (int, fract),
+            None => (s, ""),
+        };
+        if int.is_empty() && fract.is_empty() {
+            return Err(AmountError::Empty);
+        }
+        if !int.chars().chain(fract.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(AmountError::InvalidCharacter);
+        }
+        if fract.len() > MAX_PRECISION as usize {
+            return Err(AmountError::PrecisionOverflow(fract.len() as u8));
+        }
+        let precision = fract.len() as u8;
+        let atomic = int
+            .chars()
+            .chain(fract.chars())
+            .try_fold(0u64, |acc, c| {
+                acc.checked_mul(10)?
+                    .checked_add(c.to_digit(10).expect("checked above") as u64)
+            })
+            .ok_or(AmountError::ValueOverflow)?;
+        Ok(AssetAmount { atomic, precision })
+    }
+}
+
 /// Errors generated during RGB20 asset information parsing from the underlying
 /// genesis or consignment data
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, From, Error)]
+#[derive(Clone, PartialEq, Eq, Debug, Display)]
 #[display(doc_comments)]
 pub enum Error {
-    /// genesis schema id does not match any of RGB20 schemata
-    WrongSchemaId,
+    /// genesis schema id {0} does not match any of RGB20 schemata
+    WrongSchemaId(SchemaId),
 
-    /// genesis defines a seal referencing witness transaction while there
-    /// can't be a witness transaction for genesis
-    GenesisSeal,
+    /// RGB20 subschema requires root schema {expected}, while the consignment
+    /// provides {found:?}
+    RootSchemaMismatch {
+        /// Root schema id required by the RGB20 subschema
+        expected: SchemaId,
+        /// Root schema id provided by the consignment
+        found: Option<SchemaId>,
+    },
 
-    /// epoch seal definition for node {0} contains confidential data
-    EpochSealConfidential(NodeId),
+    /// consignment belongs to a contract {found} different from the asset
+    /// contract {expected}
+    ContractMismatch {
+        /// Id of the asset contract
+        expected: ContractId,
+        /// Id of the contract of the consignment
+        found: ContractId,
+    },
 
-    /// nurn & replace seal definition for node {0} contains confidential data
-    BurnSealConfidential(NodeId),
+    /// genesis of contract {contract_id} defines seal for owned right type
+    /// {right_type} referencing witness transaction while there can't be a
+    /// witness transaction for genesis
+    GenesisSeal {
+        /// Id of the asset contract
+        contract_id: ContractId,
+        /// Schema type of the owned right assigned to the seal
+        right_type: rgb::schema::OwnedRightType,
+    },
 
-    /// inflation assignment (seal or state) for node {0} contains confidential
+    /// epoch seal definition for node {node_id} contains confidential data
+    EpochSealConfidential {
+        /// Node defining the epoch seal
+        node_id: NodeId,
+        /// Underlying error
+        source: ConfidentialDataError,
+    },
+
+    /// burn & replace seal definition for node {node_id} contains confidential
     /// data
-    InflationAssignmentConfidential(NodeId),
+    BurnSealConfidential {
+        /// Node defining the burn & replace seal
+        node_id: NodeId,
+        /// Underlying error
+        source: ConfidentialDataError,
+    },
+
+    /// inflation assignment (seal or state) for node {node_id} contains
+    /// confidential data
+    InflationAssignmentConfidential {
+        /// Node defining the inflation assignment
+        node_id: NodeId,
+        /// Underlying error
+        source: StateRetrievalError,
+    },
+
+    /// epoch {epoch_id} referenced by burn or burn & replace operation
+    /// {node_id} is not known from the consignment
+    EpochAbsent {
+        /// Burn or burn & replace operation referencing the epoch
+        node_id: NodeId,
+        /// Id of the unknown epoch node
+        epoch_id: NodeId,
+    },
+
+    /// genesis of contract {contract_id} does not define asset {field_type}
+    NominationAbsent {
+        /// Id of the asset contract
+        contract_id: ContractId,
+        /// Absent genesis metadata field
+        field_type: FieldType,
+    },
+
+    /// precision {precision} of asset {contract_id} exceeds the maximum of 18
+    /// decimal digits
+    PrecisionOverflow {
+        /// Id of the asset contract
+        contract_id: ContractId,
+        /// Precision defined by the genesis
+        precision: u8,
+    },
+
+    /// can't read asset data, since node {node_id} does not satisfy schema
+    /// requirement for {field_type} field
+    UnsatisfiedSchemaRequirement {
+        /// Node lacking the required data
+        node_id: NodeId,
+        /// Absent metadata field
+        field_type: FieldType,
+    },
 
-    /// not of all epochs referenced in burn or burn & replace operation
-    /// history are known from the consignment
-    NotAllEpochsExposed,
+    /// node {node_id} declares issued supply {declared} which does not match
+    /// the sum of the issued allocations {allocated}
+    IssuedSupplyMismatch {
+        /// Issuing node
+        node_id: NodeId,
+        /// Supply declared in the node metadata
+        declared: AtomicValue,
+        /// Sum of the allocations defined by the node
+        allocated: AtomicValue,
+    },
+
+    /// supply of asset {contract_id} issued by node {node_id} together with the
+    /// other known issuances exceeds the maximum of 2^64-1 atomic units
+    SupplyOverflow {
+        /// Id of the asset contract
+        contract_id: ContractId,
+        /// Issuing node which amounts overflow the total supply
+        node_id: NodeId,
+    },
+
+    /// secondary issuance of {issued} exceeds inflation allowance {allowed}
+    /// defined by the genesis of contract {contract_id}
+    InflationCapExceeded {
+        /// Id of the asset contract
+        contract_id: ContractId,
+        /// Total inflation allowed by the genesis
+        allowed: AtomicValue,
+        /// Total supply issued by secondary issuance transitions
+        issued: AtomicValue,
+    },
+
+    /// renomination {node_id} of asset {contract_id} does not follow from the
+    /// genesis through the known renomination history
+    RenominationChainBroken {
+        /// Id of the asset contract
+        contract_id: ContractId,
+        /// Renomination which can't be reached from the genesis
+        node_id: NodeId,
+    },
+
+    /// consignment for contract {contract_id} does not contain endpoint for
+    /// the revealed seal {seal}
+    EndpointAbsent {
+        /// Id of the consignment contract
+        contract_id: ContractId,
+        /// Concealed form of the revealed seal
+        seal: seal::Confidential,
+    },
+
+    /// genesis of contract {contract_id} contains {attachment} attachment which
+    /// data can't be decoded: {source}
+    AttachmentInvalid {
+        /// Id of the asset contract
+        contract_id: ContractId,
+        /// Type of the invalid attachment
+        attachment: AttachmentType,
+        /// Underlying error, boxed to keep the error type small
+        source: Box<strict_encoding::Error>,
+    },
+
+    /// node {node_id} performs {transition_type} operation, which is not
+    /// allowed by the contract schema or genesis
+    OperationDenied {
+        /// Node performing the operation
+        node_id: NodeId,
+        /// Type of the denied operation
+        transition_type: TransitionType,
+    },
+
+    /// seal of {outpoint} is closed by state transition {known} and by a
+    /// conflicting state transition {conflicting}
+    DoubleSpend {
+        /// Node output which seal is closed twice
+        outpoint: NodeOutpoint,
+        /// State transition closing the seal, which is known first
+        known: NodeId,
+        /// Conflicting state transition closing the same seal
+        conflicting: NodeId,
+    },
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::EpochSealConfidential { source, .. }
+            | Error::BurnSealConfidential { source, .. } => Some(source),
+            Error::InflationAssignmentConfidential { source, .. } => Some(source),
+            Error::AttachmentInvalid { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error as _;
+
+    use amplify::Wrapper;
+    use bitcoin::hashes::{sha256t, Hash};
+    use lnpbp::chain::Chain;
+    use proptest::prelude::*;
+    use rgb::fungible::allocation::OutpointValue;
+
+    use super::*;
+    use crate::fixtures::{contract, outpoint};
+    use crate::{AssetName, Rgb20Builder, Ticker};
+
+    fn issue(name: &str) -> rgb::Contract {
+        Rgb20Builder::new(Chain::Signet)
+            .ticker(Ticker::from_str("TICK").unwrap())
+            .name(AssetName::from_str(name).unwrap())
+            .allocate(outpoint(1), 100)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn error_context() {
+        let contract = issue("Test asset");
+        let other = issue("Other asset");
+        let mut asset = Asset::try_from(&contract).unwrap();
+
+        let err = asset.update_with(&other).unwrap_err();
+        assert_eq!(err, Error::ContractMismatch {
+            expected: contract.contract_id(),
+            found: other.contract_id(),
+        });
+        let msg = err.to_string();
+        assert!(msg.contains(&contract.contract_id().to_string()));
+        assert!(msg.contains(&other.contract_id().to_string()));
+        assert!(err.source().is_none());
+
+        let node_id = contract.genesis().node_id();
+        let err = Error::BurnSealConfidential {
+            node_id,
+            source: ConfidentialDataError,
+        };
+        assert!(err.to_string().starts_with("burn & replace seal"));
+        assert_eq!(
+            err.source().map(ToString::to_string),
+            Some(ConfidentialDataError.to_string())
+        );
+    }
+
+    #[test]
+    fn state_accessors() {
+        let contract = issue("Test asset");
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(asset.as_state().contract_id, asset.contract_id());
+        assert_eq!(asset.as_state().schema_id, contract.schema_id());
+        let state = asset.as_state().clone();
+        assert_eq!(asset.into_state(), state);
+    }
+
+    #[test]
+    fn amount_parse() {
+        assert_eq!(
+            AssetAmount::from_str("1."),
+            Ok(AssetAmount::with_atomic(1, 0).unwrap())
+        );
+        assert_eq!(
+            AssetAmount::from_str(".5"),
+            Ok(AssetAmount::with_atomic(5, 1).unwrap())
+        );
+        assert_eq!(
+            AssetAmount::from_str("1.50"),
+            Ok(AssetAmount::with_atomic(150, 2).unwrap())
+        );
+        assert_eq!(AssetAmount::from_str(""), Err(AmountError::Empty));
+        assert_eq!(AssetAmount::from_str("."), Err(AmountError::Empty));
+        assert_eq!(
+            AssetAmount::from_str("1.5.0"),
+            Err(AmountError::InvalidCharacter)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde_json")]
+    fn amount_deserialize() {
+        let amount = AssetAmount::with_atomic(150, 2).unwrap();
+        let json = serde_json::to_string(&amount).unwrap();
+        assert_eq!(json, r#"{"atomic":150,"precision":2}"#);
+        assert_eq!(serde_json::from_str::<AssetAmount>(&json).unwrap(), amount);
+
+        let err =
+            serde_json::from_str::<AssetAmount>(r#"{"atomic":150,"precision":19}"#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            AmountError::PrecisionOverflow(19).to_string()
+        );
+    }
+
+    fn issue_allocations(values: &[AtomicValue]) -> rgb::Contract {
+        contract(
+            8,
+            values
+                .iter()
+                .enumerate()
+                .map(|(vout, value)| OutpointValue {
+                    value: *value,
+                    outpoint: outpoint(vout as u32),
+                })
+                .collect(),
+            bmap! { outpoint(100) => u64::MAX },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn checked_sum_overflow(values in prop::collection::vec(u64::MAX / 4..=u64::MAX, 0..6)) {
+            let sum = values.iter().map(|value| *value as u128).sum::<u128>();
+            prop_assert_eq!(
+                checked_sum(values),
+                AtomicValue::try_from(sum).ok()
+            );
+        }
+    }
+
+    proptest! {
+        // Each case issues a new contract, which is slow
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        #[test]
+        fn genesis_supply_overflow(a in u64::MAX / 4..=u64::MAX / 4 * 3, b in u64::MAX / 4..=u64::MAX / 4 * 3) {
+            let contract = issue_allocations(&[a, b]);
+            let res = Asset::try_from(&contract).map(|_| ());
+            if a.checked_add(b).is_some() {
+                prop_assert_eq!(res, Ok(()));
+            } else {
+                prop_assert_eq!(res, Err(Error::SupplyOverflow {
+                    contract_id: contract.contract_id(),
+                    node_id: contract.genesis().node_id(),
+                }));
+            }
+        }
+
+        #[test]
+        fn issued_supply_overflow(a in u64::MAX / 4..=u64::MAX / 4 * 3, b in u64::MAX / 4..=u64::MAX / 4 * 3) {
+            let contract = issue_allocations(&[a]);
+            let mut validator =
+                Validator::with(contract.schema(), None, contract.genesis()).unwrap();
+            let node_id = NodeId::from_inner(sha256t::Hash::hash(b"issue"));
+            let res = validator.merge(NodeChecks {
+                node_id,
+                issued: b,
+                supply: b,
+                ..NodeChecks::default()
+            });
+            if a.checked_add(b).is_some() {
+                prop_assert_eq!(res, Ok(()));
+                prop_assert_eq!(validator.finish(|_| true).map(|_| ()), Ok(()));
+            } else {
+                prop_assert_eq!(res, Err(Error::SupplyOverflow {
+                    contract_id: contract.contract_id(),
+                    node_id,
+                }));
+            }
+        }
+    }
 }
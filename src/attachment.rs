@@ -0,0 +1,142 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeMap;
+use std::io;
+
+use commit_verify::CommitVerify;
+use rgb::{AttachmentId, Node};
+use strict_encoding::{StrictDecode, StrictEncode};
+
+use crate::schema::FieldType;
+use crate::Asset;
+
+/// Types of media which can be attached to the asset issued under the media
+/// RGB20 schema
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub enum AttachmentType {
+    /// Asset logo
+    #[display("logo")]
+    Logo,
+
+    /// Small asset icon, suitable for lists of assets
+    #[display("icon")]
+    Icon,
+
+    /// Document with the asset terms and conditions
+    #[display("terms")]
+    Terms,
+}
+
+impl AttachmentType {
+    /// All supported attachment types
+    pub const ALL: [AttachmentType; 3] =
+        [AttachmentType::Logo, AttachmentType::Icon, AttachmentType::Terms];
+
+    /// Returns genesis field type keeping attachment of this type
+    pub fn field_type(self) -> FieldType {
+        match self {
+            AttachmentType::Logo => FieldType::Logo,
+            AttachmentType::Icon => FieldType::Icon,
+            AttachmentType::Terms => FieldType::Terms,
+        }
+    }
+}
+
+impl StrictEncode for AttachmentType {
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, strict_encoding::Error> {
+        (*self as u8).strict_encode(e)
+    }
+}
+
+impl StrictDecode for AttachmentType {
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, strict_encoding::Error> {
+        let ty = u8::strict_decode(d)?;
+        AttachmentType::ALL
+            .into_iter()
+            .find(|known| *known as u8 == ty)
+            .ok_or_else(|| {
+                strict_encoding::Error::DataIntegrityError(format!(
+                    "unknown attachment type {}",
+                    ty
+                ))
+            })
+    }
+}
+
+/// Media attachment referenced by the asset genesis. The attachment data are
+/// not a part of the contract and must be distributed separately; they are
+/// identified by [`AttachmentId`] committing to the data.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[derive(StrictEncode, StrictDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+#[display("{id}~{mime}")]
+pub struct Attachment {
+    /// Identifier of the attached data
+    pub id: AttachmentId,
+
+    /// MIME type of the attached data
+    pub mime: String,
+}
+
+impl Attachment {
+    /// Constructs attachment for the given `data` of `mime` type
+    pub fn with(mime: impl ToString, data: impl AsRef<[u8]>) -> Attachment {
+        Attachment {
+            id: AttachmentId::commit(&data.as_ref()),
+            mime: mime.to_string(),
+        }
+    }
+
+    /// Checks whether the provided data match the attachment id
+    pub fn verify(&self, data: impl AsRef<[u8]>) -> bool {
+        AttachmentId::commit(&data.as_ref()) == self.id
+    }
+}
+
+impl Asset {
+    /// Returns media attachments defined by the asset genesis. Assets issued
+    /// under schemata other than [`crate::Subschema::Media`] never have
+    /// attachments.
+    pub fn attachments(&self) -> BTreeMap<AttachmentType, Attachment> {
+        let metadata = self.genesis().metadata();
+        AttachmentType::ALL
+            .into_iter()
+            .filter_map(|ty| {
+                let data = metadata.bytes(ty.field_type()).into_iter().next()?;
+                let attachment = Attachment::strict_deserialize(data).ok()?;
+                Some((ty, attachment))
+            })
+            .collect()
+    }
+
+    /// Returns Ricardian contract text defined by the asset genesis. The text
+    /// may be either the contract itself or its double SHA256 hash in
+    /// hexadecimal form, optionally followed by `\n` and the contract URL.
+    /// Assets issued under schemata other than [`crate::Subschema::Media`]
+    /// never have the contract text.
+    pub fn ricardian_contract(&self) -> Option<String> {
+        self.genesis()
+            .metadata()
+            .unicode_string(FieldType::ContractText)
+            .into_iter()
+            .next()
+    }
+}
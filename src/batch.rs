@@ -0,0 +1,187 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeMap;
+
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::{OutPoint, Txid};
+use rgb::{Consignment, Contract, ContractId, Node};
+use strict_encoding::StrictEncode;
+
+use crate::{IssueError, Rgb20Builder};
+
+/// Tag for deriving commitment to the batch of issued contracts
+const BATCH_COMMITMENT_TAG: &[u8] = b"rgb20:batch:commitment";
+
+/// Errors happening during batch issuance of RGB20 assets with
+/// [`BatchIssuer`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum BatchError {
+    /// batch does not contain any assets
+    Empty,
+
+    /// asset #{0} in the batch can't be issued: {1}
+    Issue(usize, IssueError),
+
+    /// asset #{0} in the batch assigns genesis seal to the output {1} which
+    /// does not belong to the batch funding transaction
+    ForeignSeal(usize, OutPoint),
+
+    /// asset #{0} in the batch repeats already issued contract {1}
+    DuplicateContract(usize, ContractId),
+}
+
+/// Issuer of multiple RGB20 assets which genesis seals are all defined on the
+/// outputs of a single funding transaction.
+///
+/// # Example
+///
+/// ```ignore
+/// let batch = BatchIssuer::new(funding_txid)
+///     .asset(Rgb20Builder::new(Chain::Signet).ticker(usdt).name(tether))
+///     .asset(Rgb20Builder::new(Chain::Signet).ticker(eurt).name(euro))
+///     .issue()?;
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BatchIssuer {
+    txid: Txid,
+    assets: Vec<Rgb20Builder>,
+}
+
+/// Result of the batch issuance
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BatchIssue {
+    /// Funding transaction, outputs of which hold all genesis seals
+    pub txid: Txid,
+
+    /// Issued asset contracts
+    pub contracts: BTreeMap<ContractId, Contract>,
+
+    /// Commitment to the ids of all issued contracts, which may be embedded
+    /// into the funding transaction to timestamp the batch issuance
+    pub commitment: sha256::Hash,
+}
+
+impl BatchIssuer {
+    /// Constructs issuer for the assets with genesis seals defined on the
+    /// outputs of the `txid` transaction
+    pub fn new(txid: Txid) -> BatchIssuer {
+        BatchIssuer {
+            txid,
+            assets: empty!(),
+        }
+    }
+
+    /// Adds asset to the batch
+    pub fn asset(mut self, builder: Rgb20Builder) -> Self {
+        self.assets.push(builder);
+        self
+    }
+
+    /// Validates and issues all assets in the batch
+    pub fn issue(self) -> Result<BatchIssue, BatchError> {
+        if self.assets.is_empty() {
+            return Err(BatchError::Empty);
+        }
+
+        let mut contracts = BTreeMap::new();
+        for (no, builder) in self.assets.into_iter().enumerate() {
+            let contract = builder.build().map_err(|err| BatchError::Issue(no, err))?;
+            for assignments in contract.genesis().owned_rights().values() {
+                for (seal, _) in assignments.revealed_seal_outputs() {
+                    if seal.txid != Some(self.txid) {
+                        return Err(BatchError::ForeignSeal(
+                            no,
+                            OutPoint::new(seal.txid.unwrap_or_else(Txid::all_zeros), seal.vout),
+                        ));
+                    }
+                }
+            }
+            let contract_id = contract.contract_id();
+            if contracts.insert(contract_id, contract).is_some() {
+                return Err(BatchError::DuplicateContract(no, contract_id));
+            }
+        }
+
+        Ok(BatchIssue {
+            txid: self.txid,
+            commitment: BatchIssue::commit(contracts.keys()),
+            contracts,
+        })
+    }
+}
+
+impl BatchIssue {
+    /// Computes tagged hash of the contract ids, which must be provided in
+    /// the lexicographic order
+    fn commit<'a>(contract_ids: impl IntoIterator<Item = &'a ContractId>) -> sha256::Hash {
+        let mut engine = sha256::Hash::engine();
+        engine.input(BATCH_COMMITMENT_TAG);
+        for contract_id in contract_ids {
+            contract_id
+                .strict_encode(&mut engine)
+                .expect("in-memory hash engine does not fail");
+        }
+        sha256::Hash::from_engine(engine)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use lnpbp::chain::Chain;
+
+    use super::*;
+    use crate::fixtures::{outpoint, txid};
+    use crate::{AssetName, Ticker};
+
+    fn builder(ticker: &str, vout: u32) -> Rgb20Builder {
+        Rgb20Builder::new(Chain::Signet)
+            .ticker(Ticker::from_str(ticker).unwrap())
+            .name(AssetName::from_str("Test asset").unwrap())
+            .allocate(outpoint(vout), 1000)
+    }
+
+    #[test]
+    fn batch_issue() {
+        let batch = BatchIssuer::new(txid())
+            .asset(builder("TICKA", 0))
+            .asset(builder("TICKB", 1).renomination(outpoint(2)))
+            .issue()
+            .unwrap();
+        assert_eq!(batch.txid, txid());
+        assert_eq!(batch.contracts.len(), 2);
+        assert_eq!(batch.commitment, BatchIssue::commit(batch.contracts.keys()));
+    }
+
+    #[test]
+    fn batch_errors() {
+        assert_eq!(BatchIssuer::new(txid()).issue(), Err(BatchError::Empty));
+
+        let foreign = OutPoint::new(Txid::all_zeros(), 1);
+        assert_eq!(
+            BatchIssuer::new(txid())
+                .asset(builder("TICKA", 0))
+                .asset(builder("TICKB", 1).epoch(foreign))
+                .issue(),
+            Err(BatchError::ForeignSeal(1, foreign))
+        );
+
+        assert_eq!(
+            BatchIssuer::new(txid())
+                .asset(Rgb20Builder::new(Chain::Signet))
+                .issue(),
+            Err(BatchError::Issue(0, IssueError::TickerMissing))
+        );
+    }
+}
@@ -15,43 +15,339 @@ extern crate amplify;
 extern crate clap;
 extern crate serde_crate as serde;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Formatter};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::{fs, io};
+use std::{fs, io, process};
 
-use bitcoin::OutPoint;
-use clap::Parser;
+use bitcoin::hashes::hex::ToHex;
+use bitcoin::hashes::{sha256d, Hash};
+use bitcoin::psbt::PartiallySignedTransaction;
+use bitcoin::{Address, Network, OutPoint, PackedLockTime, Sequence, Transaction, TxIn, TxOut};
+use chrono::{DateTime, Utc};
+use clap::{CommandFactory, Parser};
+use clap_complete::Shell;
 use colored::Colorize;
+use electrum_client::{Client as ElectrumClient, ElectrumApi};
 use lnpbp::bech32::Bech32ZipString;
 use lnpbp::chain::Chain;
-use rgb::fungible::allocation::{AllocatedValue, OutpointValue, UtxobValue};
-use rgb::{Consignment, Contract, IntoRevealedSeal, Schema, StateTransfer};
-use rgb20::{Asset, Rgb20};
-use seals::txout::CloseMethod;
-use stens::AsciiString;
+use psbt::Psbt;
+use rgb::fungible::allocation::AllocatedValue;
+use rgb::psbt::RgbExt;
+use rgb::{
+    seal, Anchor, AtomicValue, Consignment, Contract, ContractId, Node, NodeOutpoint, Schema,
+    SealEndpoint, StateTransfer, Transition, TransitionBundle, Validity,
+};
+use rgb20::invoice::{Beneficiary, Invoice};
+use rgb20::psbt::embed_transition;
+use rgb20::schema::{OwnedRightType, TransitionType};
+use rgb20::seal::SealVault;
+use rgb20::{
+    format_time, format_timestamp, AllocationTarget, Armor, Asset, AssetAmount, AssetName,
+    CoinSelection, ConsignmentSummary, Features, GenesisAllocation, InflationAllocations,
+    OfflineResolver, Rgb20Builder, Rgb20Schemata, Subschema, Ticker, ValidationReport,
+    WitnessResolver, MAX_PRECISION,
+};
+use seals::txout::{CloseMethod, ExplicitSeal};
+use serde_json::json;
 use strict_encoding::{StrictDecode, StrictEncode};
 
+/// Value of the witness transaction outputs created for the beneficiaries
+/// paid to bitcoin addresses, in satoshis
+const DUST_LIMIT: u64 = 546;
+
+/// Command output, printed either as human-readable text or as a structured
+/// document, depending on [`Opts::output`]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Output(OutputMode);
+
+impl Output {
+    /// Detects whether a structured document is printed to STDOUT
+    pub fn is_structured(self) -> bool { self.0 != OutputMode::Text }
+
+    /// Prints human-readable text, which goes to STDOUT, or to STDERR if
+    /// structured output is requested and STDOUT is reserved for the document
+    pub fn text(self, text: impl Display) {
+        if self.is_structured() {
+            eprintln!("{}", text)
+        } else {
+            println!("{}", text)
+        }
+    }
+
+    /// Prints structured command output to STDOUT if it is requested
+    pub fn document(self, value: serde_json::Value) {
+        match self.0 {
+            OutputMode::Text => {}
+            OutputMode::Json => println!("{}", value),
+            OutputMode::Yaml => print!(
+                "{}",
+                serde_yaml::to_string(&value).expect("JSON value is serializable")
+            ),
+        }
+    }
+
+    /// Writes exported `data` to the `file`, or to STDOUT if no file is given.
+    /// With structured output the data printed to STDOUT are embedded into
+    /// the `doc` as `data` field, either as a string or, for binary data, in
+    /// hexadecimal encoding.
+    pub fn export(
+        self,
+        file: Option<PathBuf>,
+        data: Vec<u8>,
+        mut doc: serde_json::Value,
+    ) -> Result<(), String> {
+        match file {
+            Some(file) => {
+                fs::write(&file, data).map_err(|err| format!("{}: {}", file.display(), err))?;
+                doc["file"] = json!(file);
+            }
+            None if !self.is_structured() => {
+                io::stdout()
+                    .write_all(&data)
+                    .and_then(|_| io::stdout().flush())
+                    .map_err(|err| err.to_string())?;
+                return Ok(());
+            }
+            None => {
+                doc["data"] = match String::from_utf8(data) {
+                    Ok(text) => json!(text),
+                    Err(err) => json!(err.into_bytes().to_hex()),
+                };
+            }
+        }
+        self.document(doc);
+        Ok(())
+    }
+
+    /// Reports command failure, optionally identified by a `code`, to STDERR
+    /// or as a structured document with `error` field
+    pub fn error(self, code: Option<CheckFailure>, message: impl Display) {
+        if self.is_structured() {
+            self.document(json!({
+                "error": message.to_string(),
+                "code": code.map(|code| code.to_string()),
+            }));
+            return;
+        }
+        match code {
+            Some(code) => eprintln!("error[{}]: {}", code, message),
+            None => eprintln!("error: {}", message),
+        }
+    }
+}
+
 /// invalid argument name `{0}`
 #[derive(Clone, Debug, Display, Error)]
 #[display(doc_comments)]
 pub struct InvalidName(String);
 
+/// invalid value `{0}`; expected <amount>@<seal>, where amount is a decimal
+/// number
+#[derive(Clone, Debug, Display, Error)]
+#[display(doc_comments)]
+pub struct InvalidValue(String);
+
+/// invalid seal or bitcoin address `{0}`
+#[derive(Clone, Debug, Display, Error)]
+#[display(doc_comments)]
+pub struct InvalidSeal(String);
+
+/// Seal definition or bitcoin address, which is resolved into a seal with
+/// [`AddressResolver`]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum SealOrAddress<T> {
+    Seal(T),
+    Address(Address),
+}
+
+impl<T> FromStr for SealOrAddress<T>
+where T: FromStr
+{
+    type Err = InvalidSeal;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(SealOrAddress::Seal)
+            .or_else(|_| Address::from_str(s).map(SealOrAddress::Address))
+            .map_err(|_| InvalidSeal(s.to_owned()))
+    }
+}
+
+/// Resolves bitcoin addresses into seals defined over new outputs of the
+/// witness transaction, paying to the addresses.
+///
+/// Resolved seals must be revealed to their owners, who can't otherwise
+/// accept the transfer.
+pub struct AddressResolver {
+    chain: Chain,
+    network: Network,
+    method: CloseMethod,
+    reserved: BTreeSet<u32>,
+    resolved: Vec<(Address, seal::Revealed)>,
+}
+
+impl AddressResolver {
+    /// Constructs resolver for addresses on the asset `chain`, which will not
+    /// assign witness transaction outputs already used by the `reserved`
+    /// seals
+    pub fn new(
+        chain: &Chain,
+        method: CloseMethod,
+        reserved: impl IntoIterator<Item = ExplicitSeal>,
+    ) -> Result<Self, String> {
+        let network = Network::try_from(chain.clone())
+            .map_err(|_| format!("{} chain is not supported by bitcoin addresses", chain))?;
+        let reserved = reserved
+            .into_iter()
+            .filter(|seal| seal.txid.is_none())
+            .map(|seal| seal.vout)
+            .collect();
+        Ok(AddressResolver {
+            chain: chain.clone(),
+            network,
+            method,
+            reserved,
+            resolved: empty!(),
+        })
+    }
+
+    /// Resolves address into a seal over a witness transaction output.
+    ///
+    /// The address must be valid for the network of the asset chain; testnet
+    /// and signet addresses share the same encoding.
+    pub fn resolve(&mut self, address: Address) -> Result<seal::Revealed, String> {
+        if !address.is_valid_for_network(self.network) {
+            return Err(format!(
+                "address {} does not belong to {} network",
+                address, self.chain
+            ));
+        }
+        let vout = (0..)
+            .find(|vout| {
+                !self.reserved.contains(vout)
+                    && !self.resolved.iter().any(|(_, seal)| seal.vout == *vout)
+            })
+            .expect("number of outputs is limited");
+        let seal = seal::Revealed::from(ExplicitSeal {
+            method: self.method,
+            txid: None,
+            vout,
+        });
+        self.resolved.push((address, seal));
+        Ok(seal)
+    }
+
+    /// Resolves seal definition or address into a revealed seal
+    pub fn resolve_seal<T>(&mut self, target: SealOrAddress<T>) -> Result<seal::Revealed, String>
+    where seal::Revealed: From<T> {
+        match target {
+            SealOrAddress::Seal(seal) => Ok(seal.into()),
+            SealOrAddress::Address(address) => self.resolve(address),
+        }
+    }
+
+    /// Resolves blinded seal or address into a transfer endpoint
+    pub fn resolve_endpoint(
+        &mut self,
+        target: SealOrAddress<seal::Confidential>,
+    ) -> Result<SealEndpoint, String> {
+        match target {
+            SealOrAddress::Seal(seal) => Ok(seal.into()),
+            SealOrAddress::Address(address) => self.resolve(address).map(SealEndpoint::from),
+        }
+    }
+
+    /// Prints seals defined for the resolved addresses, together with the
+    /// outputs which must be added to the witness transaction
+    pub fn print_resolved(&self, out: Output) {
+        if self.resolved.is_empty() {
+            return;
+        }
+        out.text("Seals defined for bitcoin addresses:".bold());
+        for (address, seal) in &self.resolved {
+            out.text(format_args!(
+                "  {}: {} (witness transaction output #{})",
+                address, seal, seal.vout
+            ));
+        }
+    }
+
+    /// Lists seals defined for the resolved addresses for JSON output
+    pub fn resolved_json(&self) -> serde_json::Value {
+        self.resolved
+            .iter()
+            .map(|(address, seal)| {
+                json!({
+                    "address": address.to_string(),
+                    "seal": seal.to_string(),
+                    "witnessOutput": seal.vout,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Asset amount in decimal notation assigned to a seal
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct DecimalValue<T> {
+    pub amount: AssetAmount,
+    pub seal: T,
+}
+
+impl<T> DecimalValue<T> {
+    /// Converts the amount into atomic units of an asset with the given
+    /// `precision`
+    pub fn atomic(&self, precision: u8) -> Result<AtomicValue, String> {
+        atomic(self.amount, precision)
+    }
+}
+
+impl<T> FromStr for DecimalValue<T>
+where T: FromStr
+{
+    type Err = InvalidValue;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (amount, seal) = s
+            .split_once('@')
+            .ok_or_else(|| InvalidValue(s.to_owned()))?;
+        Ok(DecimalValue {
+            amount: amount.parse().map_err(|_| InvalidValue(s.to_owned()))?,
+            seal: seal.parse().map_err(|_| InvalidValue(s.to_owned()))?,
+        })
+    }
+}
+
 #[derive(ArgEnum, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[non_exhaustive]
 pub enum SchemaName {
     LegacyBasic,
     LegacyComplete,
+    Deflationary,
+    Media,
 }
 
 impl SchemaName {
-    pub fn schema(&self) -> Schema {
+    pub const ALL: [SchemaName; 4] = [
+        SchemaName::LegacyBasic,
+        SchemaName::LegacyComplete,
+        SchemaName::Deflationary,
+        SchemaName::Media,
+    ];
+
+    pub fn subschema(&self) -> Subschema {
         match self {
-            SchemaName::LegacyBasic => rgb20::schema(),
-            SchemaName::LegacyComplete => rgb20::subschema(),
+            SchemaName::LegacyBasic => Subschema::Full,
+            SchemaName::LegacyComplete => Subschema::NoReplace,
+            SchemaName::Deflationary => Subschema::Deflationary,
+            SchemaName::Media => Subschema::Media,
         }
     }
+
+    pub fn schema(&self) -> Schema { self.subschema().schema() }
 }
 
 impl FromStr for SchemaName {
@@ -59,13 +355,26 @@ impl FromStr for SchemaName {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
-            "legacy-basic" => SchemaName::LegacyBasic,
-            "legacy-complete" => SchemaName::LegacyComplete,
+            "legacy-basic" | "full" => SchemaName::LegacyBasic,
+            "legacy-complete" | "no-replace" => SchemaName::LegacyComplete,
+            "deflationary" => SchemaName::Deflationary,
+            "media" => SchemaName::Media,
             wrong => return Err(InvalidName(wrong.to_owned())),
         })
     }
 }
 
+impl Display for SchemaName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SchemaName::LegacyBasic => "legacy-basic",
+            SchemaName::LegacyComplete => "legacy-complete",
+            SchemaName::Deflationary => "deflationary",
+            SchemaName::Media => "media",
+        })
+    }
+}
+
 #[derive(ArgEnum, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub enum ExportFormat {
     Binary,
@@ -90,6 +399,118 @@ impl FromStr for ExportFormat {
     }
 }
 
+#[derive(ArgEnum, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum StateFormat {
+    Binary,
+    Json,
+    Yaml,
+}
+
+impl FromStr for StateFormat {
+    type Err = InvalidName;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "bin" => StateFormat::Binary,
+            "json" => StateFormat::Json,
+            "yaml" => StateFormat::Yaml,
+            wrong => return Err(InvalidName(wrong.to_owned())),
+        })
+    }
+}
+
+#[derive(ArgEnum, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
+
+impl FromStr for GraphFormat {
+    type Err = InvalidName;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "dot" => GraphFormat::Dot,
+            "json" => GraphFormat::Json,
+            wrong => return Err(InvalidName(wrong.to_owned())),
+        })
+    }
+}
+
+#[derive(ArgEnum, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum FileFormat {
+    Binary,
+    Bech32,
+}
+
+impl FromStr for FileFormat {
+    type Err = InvalidName;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "bin" => FileFormat::Binary,
+            "bech32" => FileFormat::Bech32,
+            wrong => return Err(InvalidName(wrong.to_owned())),
+        })
+    }
+}
+
+#[derive(ArgEnum, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ShellName {
+    Bash,
+    Elvish,
+    Fish,
+    PowerShell,
+    Zsh,
+}
+
+impl ShellName {
+    pub fn shell(self) -> Shell {
+        match self {
+            ShellName::Bash => Shell::Bash,
+            ShellName::Elvish => Shell::Elvish,
+            ShellName::Fish => Shell::Fish,
+            ShellName::PowerShell => Shell::PowerShell,
+            ShellName::Zsh => Shell::Zsh,
+        }
+    }
+}
+
+impl FromStr for ShellName {
+    type Err = InvalidName;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "bash" => ShellName::Bash,
+            "elvish" => ShellName::Elvish,
+            "fish" => ShellName::Fish,
+            "powershell" => ShellName::PowerShell,
+            "zsh" => ShellName::Zsh,
+            wrong => return Err(InvalidName(wrong.to_owned())),
+        })
+    }
+}
+
+#[derive(ArgEnum, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum OutputMode {
+    Text,
+    Json,
+    Yaml,
+}
+
+impl FromStr for OutputMode {
+    type Err = InvalidName;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "text" => OutputMode::Text,
+            "json" => OutputMode::Json,
+            "yaml" => OutputMode::Yaml,
+            wrong => return Err(InvalidName(wrong.to_owned())),
+        })
+    }
+}
+
 #[derive(Parser, Clone, Debug)]
 #[clap(
     name = "rgb20",
@@ -103,37 +524,55 @@ pub struct Opts {
     #[clap(short, long, default_value = "signet", env = "RGB_NETWORK")]
     pub network: Chain,
 
+    /// Output mode ('text', 'json' or 'yaml'). With 'json' or 'yaml' each
+    /// command prints a single document in that format to STDOUT, while
+    /// human-readable text goes to STDERR. Data exported to STDOUT when no
+    /// file is given are embedded into the document, and failures are
+    /// reported as a document with `error` field.
+    #[clap(long = "output", default_value = "text", env = "RGB_OUTPUT")]
+    pub output: OutputMode,
+
     /// Command to execute
     #[clap(subcommand)]
     pub command: Command,
 }
 
-#[derive(Subcommand, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[derive(Subcommand, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Command {
     /// Schema-related commands
     #[clap(subcommand)]
     Schema(SchemaCommand),
 
+    /// Contract-related commands
+    #[clap(subcommand)]
+    Contract(ContractCommand),
+
     /// Issue a new asset
     Issue {
-        /// Asset ticker (up to 8 characters, always converted to uppercase)
-        #[clap(validator = ticker_validator)]
-        ticker: AsciiString,
+        /// Name of an RGB20 schema to issue the asset under
+        #[clap(short, long, default_value = "legacy-basic")]
+        schema: SchemaName,
+
+        /// Asset ticker (from 3 to 8 latin letters, always converted to
+        /// uppercase)
+        ticker: Ticker,
 
-        /// Asset name (up to 32 characters)
-        name: AsciiString,
+        /// Asset name (up to 32 printable ASCII characters)
+        name: AssetName,
 
         /// Precision, i.e. number of digits reserved for fractional part
         #[clap(short, long, default_value = "8")]
         precision: u8,
 
-        /// Asset allocation, in form of <amount>@<txid>:<vout>
-        allocation: Vec<OutpointValue>,
+        /// Asset allocation, in form of <amount>@<seal>, where amount is a
+        /// decimal number with up to `precision` fractional digits and seal
+        /// is either <txid>:<vout> or blinded UTXO
+        allocation: Vec<DecimalValue<AllocationTarget>>,
 
         /// Outputs controlling inflation (secondary issue);
         /// in form of <amount>@<txid>:<vout>
         #[clap(short, long)]
-        inflation: Vec<OutpointValue>,
+        inflation: Vec<DecimalValue<OutPoint>>,
 
         /// Method for seal closing ('tapret1st' or 'opret1st')
         #[clap(short, long, default_value = "tapret1st")]
@@ -148,6 +587,37 @@ pub enum Command {
         /// right of opening the first epoch
         #[clap(short, long)]
         epoch: Option<OutPoint>,
+
+        /// File with Ricardian contract document; its double SHA256 hash is
+        /// attached to the genesis as the contract text. Requires 'media'
+        /// schema
+        #[clap(long)]
+        contract_file: Option<PathBuf>,
+
+        /// URL of the Ricardian contract document, attached to the genesis
+        /// after the document hash
+        #[clap(long, requires = "contract-file")]
+        contract_url: Option<String>,
+
+        /// Genesis timestamp in RFC 3339 format, like 2022-10-10T00:00:00Z;
+        /// defaults to the current time. Makes the contract id reproducible,
+        /// such that dry run reports the id of the issued contract
+        #[clap(long)]
+        timestamp: Option<DateTime<Utc>>,
+
+        /// File to save the contract consignment to, which can be used as an
+        /// input for the other commands
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// Format of the output file ('bin' or 'bech32')
+        #[clap(short, long, default_value = "bin")]
+        format: FileFormat,
+
+        /// Print contract id, allocations, rights and supply the asset would
+        /// be issued with, without saving the contract
+        #[clap(long, conflicts_with = "output")]
+        dry_run: bool,
     },
 
     /// Prepares state transition for assets transfer.
@@ -156,191 +626,1778 @@ pub enum Command {
         /// inputs.
         consignment: PathBuf,
 
+        /// File to store state transition transferring assets to the
+        /// beneficiaries and onto change outputs.
+        output: PathBuf,
+
+        /// Format of the output file ('bin' or 'bech32')
+        #[clap(short, long, default_value = "bin")]
+        format: FileFormat,
+
         /// Bitcoin transaction UTXOs which will be spent by the transfer
-        #[clap(short = 'u', long = "utxo", required = true)]
+        #[clap(
+            short = 'u',
+            long = "utxo",
+            required_unless_present = "amount",
+            conflicts_with = "amount"
+        )]
         outpoints: Vec<OutPoint>,
 
-        /// List of transfer beneficiaries
-        #[clap(required = true)]
-        beneficiaries: Vec<UtxobValue>,
+        /// List of transfer beneficiaries, in form of <amount>@<utxob> or
+        /// <amount>@<address>, where amount is a decimal number
+        #[clap(required_unless_present = "amount", conflicts_with = "amount")]
+        beneficiaries: Vec<DecimalValue<SealOrAddress<seal::Confidential>>>,
 
-        /// Change output; one per schema state type.
-        #[clap(short, long)]
-        change: Vec<AllocatedValue>,
+        /// Change output, in form of <amount>@<seal> or <amount>@<address>;
+        /// one per schema state type.
+        #[clap(short, long, conflicts_with = "amount")]
+        change: Vec<DecimalValue<SealOrAddress<ExplicitSeal>>>,
 
-        /// File to store state transition transferring assets to the
-        /// beneficiaries and onto change outputs.
-        output: PathBuf,
-    },
-}
+        /// Amount to transfer to the `to` beneficiary, as a decimal number.
+        /// Inputs are selected automatically among the known allocations and
+        /// the change is assigned to `change-outpoint`.
+        #[clap(short, long, requires_all = &["to", "change-outpoint"])]
+        amount: Option<AssetAmount>,
 
-/// Schema commands
-#[derive(Subcommand, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
-pub enum SchemaCommand {
-    /// Print out shema ID
-    Id {
-        /// Name of an RGB20 schema
-        #[clap(short, long, default_value = "legacy-complete")]
-        schema: SchemaName,
-    },
+        /// Blinded seal or bitcoin address of the beneficiary receiving
+        /// `amount`
+        #[clap(short, long, requires = "amount")]
+        to: Option<SealOrAddress<seal::Confidential>>,
 
-    /// Export schema
-    Export {
-        /// File to save the schema to. If no file is given, exports to STDOUT.
-        file: Option<PathBuf>,
+        /// Transaction output, in form of <txid>:<vout>, or bitcoin address
+        /// receiving the change from the automatically selected inputs
+        #[clap(long, requires = "amount")]
+        change_outpoint: Option<SealOrAddress<OutPoint>>,
 
-        /// Export format
-        #[clap(short, long, default_value = "yaml")]
-        format: ExportFormat,
+        /// Method for closing the change seal and the seals defined for
+        /// bitcoin addresses ('tapret1st' or 'opret1st')
+        #[clap(short, long, default_value = "tapret1st")]
+        method: CloseMethod,
 
-        /// Name of an RGB20 schema to export
-        #[clap(short, long, default_value = "legacy-complete")]
-        schema: SchemaName,
+        /// Coin selection strategy used for selecting inputs: 'largest-first',
+        /// 'smallest-first', 'exact-match' or 'branch-and-bound'
+        #[clap(short = 's', long, default_value = "largest-first", requires = "amount")]
+        coin_selection: CoinSelection,
     },
-}
-
-fn main() -> Result<(), String> {
-    let opts = Opts::parse();
 
-    match opts.command {
-        Command::Schema(SchemaCommand::Id { schema }) => {
-            println!("{}", schema.schema().schema_id());
-        }
+    /// Pays an invoice or a blinded seal: composes transfer state transition,
+    /// builds witness transaction PSBT committing to it and composes state
+    /// transfer consignment for the beneficiary.
+    Pay {
+        /// File with state transfer consignment, which endpoints will act as
+        /// inputs.
+        consignment: PathBuf,
 
-        Command::Schema(SchemaCommand::Export {
-            file,
-            format,
-            schema,
-        }) => {
-            let mut fd = open_file_or_stdout(file).unwrap();
-            let schema = schema.schema();
-            match format {
-                ExportFormat::Binary => {
-                    schema.strict_encode(&mut fd).unwrap();
-                }
-                ExportFormat::Bech32 => {
-                    let data = schema.strict_serialize().unwrap();
-                    fd.write_all(data.bech32_zip_string().as_bytes()).unwrap()
-                }
-                ExportFormat::Base64 => {
-                    let data = schema.strict_serialize().unwrap();
-                    fd.write_all(base64::encode(&data).as_bytes()).unwrap()
-                }
-                ExportFormat::Json => serde_json::to_writer(&mut fd, &schema).unwrap(),
-                ExportFormat::Yaml => serde_yaml::to_writer(&mut fd, &schema).unwrap(),
-            }
-            fd.flush().unwrap();
-        }
+        /// File to store the witness transaction PSBT, which must be signed
+        /// and published by the payer wallet.
+        psbt: PathBuf,
 
-        Command::Issue {
-            ticker,
-            name,
-            precision,
-            allocation,
-            inflation,
-            method,
-            renomination,
-            epoch,
-        } => {
-            let inflation = inflation.into_iter().fold(
-                BTreeMap::new(),
-                |mut map, OutpointValue { value, outpoint }| {
-                    // We may have only a single secondary issuance right per
-                    // outpoint, so folding all outpoints
-                    map.entry(outpoint)
-                        .and_modify(|amount| *amount += value)
-                        .or_insert(value);
-                    map
-                },
-            );
-            let contract = Contract::create_rgb20(
-                opts.network,
-                ticker,
-                name,
-                precision,
-                allocation,
-                inflation,
-                method,
-                renomination,
-                epoch,
-            );
+        /// File to store the state transfer consignment for the beneficiary.
+        output: PathBuf,
 
-            let _asset =
-                Asset::try_from(&contract).expect("create_rgb20 does not match RGB20 schema");
+        /// Format of the consignment file ('bin' or 'bech32')
+        #[clap(short, long, default_value = "bin")]
+        format: FileFormat,
 
-            eprintln!(
-                "{} {}\n",
-                "Contract ID:".bright_green(),
-                contract.contract_id().to_string().bright_yellow()
-            );
+        /// RGB20 invoice to pay
+        #[clap(short, long, conflicts_with_all = &["to", "amount"], required_unless_present = "to")]
+        invoice: Option<Invoice>,
 
-            eprintln!("{}", "Contract YAML:".bright_green());
-            eprintln!("{}", serde_yaml::to_string(contract.genesis()).unwrap());
+        /// Amount to transfer, as a decimal number
+        #[clap(short, long, requires = "to")]
+        amount: Option<AssetAmount>,
 
-            eprintln!("{}", "Contract JSON:".bright_green());
-            println!("{}\n", serde_json::to_string(contract.genesis()).unwrap());
+        /// Blinded seal or bitcoin address of the beneficiary
+        #[clap(short, long, requires = "amount")]
+        to: Option<SealOrAddress<seal::Confidential>>,
 
-            eprintln!("{}", "Contract source:".bright_green());
-            println!("{}\n", contract);
+        /// Additional bitcoin transaction outputs funding the witness
+        /// transaction, in form of <txid>:<vout>
+        #[clap(short = 'u', long = "utxo")]
+        outpoints: Vec<OutPoint>,
 
-            // eprintln!("{}", "Asset details:".bright_green());
-            // eprintln!("{}\n", serde_yaml::to_string(&asset).unwrap());
-        }
+        /// Bitcoin address receiving the bitcoin change of the witness
+        /// transaction; the asset change is assigned to the same output. Must
+        /// be a taproot address for 'tapret1st' seal closing method.
+        #[clap(short = 'c', long)]
+        change_address: Address,
 
-        Command::Transfer {
-            consignment,
-            outpoints,
-            beneficiaries,
-            change,
-            output,
-        } => {
-            let transfer = StateTransfer::strict_file_load(consignment).unwrap();
+        /// Witness transaction fee, in satoshis
+        #[clap(long, default_value = "1000")]
+        fee: u64,
 
-            let asset = Asset::try_from(&transfer).unwrap();
+        /// Method for closing the change seal and the seals defined for
+        /// bitcoin addresses ('tapret1st' or 'opret1st')
+        #[clap(short, long, default_value = "tapret1st")]
+        method: CloseMethod,
 
-            let beneficiaries = beneficiaries
+        /// Electrum server, in form of <host>:<port>, used for retrieving
+        /// transactions spent by the witness transaction
+        #[clap(short, long)]
+        electrum: String,
+
+        /// Coin selection strategy used for selecting asset inputs:
+        /// 'largest-first', 'smallest-first', 'exact-match' or
+        /// 'branch-and-bound'
+        #[clap(short = 's', long, default_value = "largest-first")]
+        coin_selection: CoinSelection,
+    },
+
+    /// Prepares secondary issuance state transition.
+    Inflate {
+        /// File with contract or state transfer consignment containing the
+        /// inflation rights.
+        consignment: PathBuf,
+
+        /// File to store the secondary issuance state transition.
+        output: PathBuf,
+
+        /// Format of the output file ('bin' or 'bech32')
+        #[clap(short, long, default_value = "bin")]
+        format: FileFormat,
+
+        /// Outputs with inflation rights which will be spent by the issuance
+        #[clap(short = 'u', long = "utxo", required = true)]
+        closing: Vec<OutPoint>,
+
+        /// Allocation of the issued assets, in form of <amount>@<seal>, where
+        /// amount is a decimal number
+        allocation: Vec<DecimalValue<ExplicitSeal>>,
+
+        /// Rights for the future secondary issuance, in form of
+        /// <amount>@<seal>; must receive all of the remaining allowance
+        #[clap(short, long)]
+        inflation: Vec<DecimalValue<ExplicitSeal>>,
+    },
+
+    /// Prepares state transition burning asset supply.
+    Burn {
+        /// File with contract or state transfer consignment containing the
+        /// burn right.
+        consignment: PathBuf,
+
+        /// File to store the burn state transition.
+        output: PathBuf,
+
+        /// Format of the output file ('bin' or 'bech32')
+        #[clap(short, long, default_value = "bin")]
+        format: FileFormat,
+
+        /// Output with the burn right which will be spent by the operation
+        #[clap(short = 'u', long = "utxo")]
+        closing: OutPoint,
+
+        /// Burned amount, as a decimal number
+        #[clap(short, long)]
+        amount: AssetAmount,
+
+        /// Outputs holding the burned assets
+        #[clap(short, long = "burned", required = true)]
+        burned_utxos: Vec<OutPoint>,
+
+        /// Seal receiving the right for the next burn operation
+        #[clap(short, long)]
+        next: Option<ExplicitSeal>,
+    },
+
+    /// Prepares state transition burning asset supply and replacing it with
+    /// a newly issued one.
+    BurnReplace {
+        /// File with contract or state transfer consignment containing the
+        /// burn & replace right.
+        consignment: PathBuf,
+
+        /// File to store the burn & replace state transition.
+        output: PathBuf,
+
+        /// Format of the output file ('bin' or 'bech32')
+        #[clap(short, long, default_value = "bin")]
+        format: FileFormat,
+
+        /// Output with the burn & replace right which will be spent by the
+        /// operation
+        #[clap(short = 'u', long = "utxo")]
+        closing: OutPoint,
+
+        /// Burned amount, as a decimal number
+        #[clap(short, long)]
+        amount: AssetAmount,
+
+        /// Outputs holding the burned assets
+        #[clap(short, long = "burned", required = true)]
+        burned_utxos: Vec<OutPoint>,
+
+        /// Seal receiving the right for the next burn & replace operation
+        #[clap(short, long)]
+        next: Option<ExplicitSeal>,
+
+        /// Allocation of the replacement supply, in form of <amount>@<seal>
+        allocation: Vec<DecimalValue<ExplicitSeal>>,
+
+        /// Beneficiaries of the replacement supply, in form of
+        /// <amount>@<utxob>
+        #[clap(short = 't', long = "to")]
+        beneficiaries: Vec<DecimalValue<seal::Confidential>>,
+    },
+
+    /// Prepares state transition changing asset ticker, name or precision.
+    Renominate {
+        /// File with contract or state transfer consignment containing the
+        /// renomination right.
+        consignment: PathBuf,
+
+        /// File to store the renomination state transition.
+        output: PathBuf,
+
+        /// Format of the output file ('bin' or 'bech32')
+        #[clap(short, long, default_value = "bin")]
+        format: FileFormat,
+
+        /// Output with the renomination right which will be spent by the
+        /// operation
+        #[clap(short = 'u', long = "utxo")]
+        closing: OutPoint,
+
+        /// New asset ticker
+        #[clap(short, long)]
+        ticker: Option<Ticker>,
+
+        /// New asset name
+        #[clap(short = 'N', long)]
+        name: Option<AssetName>,
+
+        /// New decimal precision
+        #[clap(short, long)]
+        precision: Option<u8>,
+
+        /// Seal receiving the right for the next renomination
+        #[clap(short, long)]
+        next: Option<ExplicitSeal>,
+    },
+
+    /// Prepares state transition opening new burn & replace epoch.
+    EpochOpen {
+        /// File with contract or state transfer consignment containing the
+        /// epoch opening right.
+        consignment: PathBuf,
+
+        /// File to store the epoch state transition.
+        output: PathBuf,
+
+        /// Format of the output file ('bin' or 'bech32')
+        #[clap(short, long, default_value = "bin")]
+        format: FileFormat,
+
+        /// Output with the epoch opening right which will be spent by the
+        /// operation
+        #[clap(short = 'u', long = "utxo")]
+        closing: OutPoint,
+
+        /// Seal receiving the right to open the next epoch
+        #[clap(short, long)]
+        next: Option<ExplicitSeal>,
+
+        /// Seal receiving the right to burn or burn & replace the asset within
+        /// the opened epoch
+        #[clap(short, long)]
+        burn: Option<ExplicitSeal>,
+    },
+
+    /// Prepares state transition splitting rights and allocations assigned to
+    /// the same output.
+    RightsSplit {
+        /// File with contract or state transfer consignment containing the
+        /// rights.
+        consignment: PathBuf,
+
+        /// File to store the rights split state transition.
+        output: PathBuf,
+
+        /// Format of the output file ('bin' or 'bech32')
+        #[clap(short, long, default_value = "bin")]
+        format: FileFormat,
+
+        /// Output holding the rights which will be spent by the operation
+        #[clap(short = 'u', long = "utxo")]
+        closing: OutPoint,
+
+        /// Seal receiving the asset allocations
+        #[clap(short, long)]
+        assets: Option<ExplicitSeal>,
+
+        /// Seal receiving the inflation rights
+        #[clap(short, long)]
+        inflation: Option<ExplicitSeal>,
+
+        /// Seal receiving the epoch opening right
+        #[clap(short, long)]
+        epoch: Option<ExplicitSeal>,
+
+        /// Seal receiving the burn & replace right
+        #[clap(short, long)]
+        burn: Option<ExplicitSeal>,
+
+        /// Seal receiving the renomination right
+        #[clap(short, long)]
+        renomination: Option<ExplicitSeal>,
+    },
+
+    /// Composes state transfer consignment for the transfer beneficiaries
+    /// from an anchored transition bundle.
+    Consign {
+        /// File with state transfer consignment, which endpoints were used as
+        /// transfer inputs.
+        consignment: PathBuf,
+
+        /// File with transition bundle committed to the witness transaction.
+        bundle: PathBuf,
+
+        /// File with anchor of the bundle to the witness transaction.
+        anchor: PathBuf,
+
+        /// Seal endpoints of the beneficiaries which state must be revealed
+        #[clap(short = 'e', long = "endpoint", required = true)]
+        endpoints: Vec<SealEndpoint>,
+
+        /// File to store the composed state transfer consignment.
+        output: PathBuf,
+
+        /// Format of the output file ('bin' or 'bech32')
+        #[clap(short, long, default_value = "bin")]
+        format: FileFormat,
+    },
+
+    /// Validates consignment against RGB consensus and RGB20 rules, printing
+    /// validation report.
+    Validate {
+        /// File with contract or state transfer consignment.
+        consignment: PathBuf,
+
+        /// Electrum server used for retrieving witness transactions, in form
+        /// of <host>:<port>
+        #[clap(short, long, conflicts_with = "tx-file", required_unless_present = "tx-file")]
+        electrum: Option<String>,
+
+        /// File with hex-encoded raw witness transactions, one per line, used
+        /// for offline validation
+        #[clap(short, long)]
+        tx_file: Option<PathBuf>,
+
+        /// Treat consignment as a contract rather than a state transfer
+        #[clap(short, long)]
+        contract: bool,
+    },
+
+    /// Prints summary of a contract or state transfer consignment: asset
+    /// nomination, supply, allocations, operation graph and endpoints.
+    Inspect {
+        /// File with contract or state transfer consignment.
+        consignment: PathBuf,
+
+        /// Treat consignment as a contract rather than a state transfer
+        #[clap(short, long)]
+        contract: bool,
+    },
+
+    /// Exports asset state parsed from a consignment: allocations, supply
+    /// and operation history.
+    ExportState {
+        /// File with contract or state transfer consignment.
+        consignment: PathBuf,
+
+        /// File to save the asset state to. If no file is given, exports to
+        /// STDOUT.
+        file: Option<PathBuf>,
+
+        /// Output format ('bin' for versioned binary snapshot, 'json' or
+        /// 'yaml')
+        #[clap(short, long, default_value = "bin")]
+        format: StateFormat,
+
+        /// Treat consignment as a contract rather than a state transfer
+        #[clap(short, long)]
+        contract: bool,
+    },
+
+    /// Imports asset state previously exported with `export-state`, merges
+    /// state transfers into it and saves the updated state.
+    ImportState {
+        /// File with the exported asset state.
+        state: PathBuf,
+
+        /// Files with state transfer consignments to merge into the state.
+        consignments: Vec<PathBuf>,
+
+        /// Format of the asset state ('bin', 'json' or 'yaml')
+        #[clap(short, long, default_value = "bin")]
+        format: StateFormat,
+
+        /// File to save the updated asset state to, in the same format. If no
+        /// file is given, the imported state file is overwritten.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Exports graph of the asset operations known from a consignment, with
+    /// edges for the closed seals, for visualization in explorers.
+    Graph {
+        /// File with contract or state transfer consignment.
+        consignment: PathBuf,
+
+        /// File to save the graph to. If no file is given, exports to STDOUT.
+        file: Option<PathBuf>,
+
+        /// Output format ('dot' for Graphviz or 'json')
+        #[clap(short, long, default_value = "dot")]
+        format: GraphFormat,
+
+        /// Treat consignment as a contract rather than a state transfer
+        #[clap(short, long)]
+        contract: bool,
+    },
+
+    /// Creates blinded seal over a transaction output, which can be provided
+    /// to a payer for receiving assets.
+    Blind {
+        /// Transaction output to define the seal over, in form of
+        /// <txid>:<vout>
+        outpoint: OutPoint,
+
+        /// Method for seal closing ('tapret1st' or 'opret1st')
+        #[clap(short, long, default_value = "tapret1st")]
+        method: CloseMethod,
+
+        /// File storing reveal data of the created blinded seals, to which the
+        /// new seal is added.
+        #[clap(short, long)]
+        vault: Option<PathBuf>,
+    },
+
+    /// Prints shell completion script to STDOUT.
+    Completions {
+        /// Shell to generate the script for ('bash', 'elvish', 'fish',
+        /// 'powershell' or 'zsh')
+        shell: ShellName,
+    },
+
+    /// Generates man pages for the tool and each of its subcommands.
+    Manpages {
+        /// Directory to save the man pages to; created if absent.
+        dir: PathBuf,
+    },
+}
+
+/// Schema commands
+#[derive(Subcommand, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum SchemaCommand {
+    /// Print out shema ID
+    Id {
+        /// Name of an RGB20 schema
+        #[clap(short, long, default_value = "legacy-complete")]
+        schema: SchemaName,
+
+        /// Print out ids of all known RGB20 schemata together with their
+        /// names, ignoring `--schema`
+        #[clap(long)]
+        all: bool,
+    },
+
+    /// Export schema
+    Export {
+        /// File to save the schema to. If no file is given, exports to STDOUT.
+        file: Option<PathBuf>,
+
+        /// Export format
+        #[clap(short, long, default_value = "yaml")]
+        format: ExportFormat,
+
+        /// Name of an RGB20 schema to export
+        #[clap(short, long, default_value = "legacy-complete")]
+        schema: SchemaName,
+
+        /// Export all known RGB20 schemata together with their names and ids,
+        /// ignoring `--schema`. Binary export contains the strict-encoded
+        /// list of the schemata; textual formats list them in the order of
+        /// `rgb20 schema id --all` output.
+        #[clap(long)]
+        all: bool,
+    },
+
+    /// Check whether a schema, for instance received with a contract created
+    /// by other software, is compatible with RGB20 and print out the asset
+    /// operations it allows
+    Verify {
+        /// File with the schema in binary, ASCII-armored, base64, JSON or YAML
+        /// format
+        file: PathBuf,
+    },
+}
+
+/// Contract commands
+#[derive(Subcommand, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum ContractCommand {
+    /// Check contract genesis, suitable for CI pipelines of issuers.
+    ///
+    /// Failures are reported to STDERR as `error[<CODE>]: <details>`, or as
+    /// a document with `error` and `code` fields with structured output, and
+    /// terminate the process with the exit code specific to each failure:
+    /// E_LOAD (2), E_SCHEMA (3), E_ASSET (4), E_NOMINATION (5) and
+    /// E_CONTRACT_ID (6).
+    Check {
+        /// File with the contract
+        file: PathBuf,
+
+        /// Contract id which the contract is expected to have
+        #[clap(short, long)]
+        contract_id: Option<ContractId>,
+    },
+}
+
+/// Failures detected by `rgb20 contract check`; the enum discriminants are
+/// used as process exit codes
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
+pub enum CheckFailure {
+    /// Contract file can't be read or parsed
+    #[display("E_LOAD")]
+    Load = 2,
+
+    /// Contract uses unknown schema or its genesis does not conform to the
+    /// schema
+    #[display("E_SCHEMA")]
+    Schema = 3,
+
+    /// Contract violates RGB20 semantic rules
+    #[display("E_ASSET")]
+    Asset = 4,
+
+    /// Asset ticker, name or precision violates RGB20 constraints
+    #[display("E_NOMINATION")]
+    Nomination = 5,
+
+    /// Contract id does not match the expected one
+    #[display("E_CONTRACT_ID")]
+    ContractId = 6,
+}
+
+fn main() {
+    let opts = Opts::parse();
+    let out = Output(opts.output);
+    if let Err(err) = run(opts, out) {
+        out.error(None, err);
+        process::exit(1);
+    }
+}
+
+fn run(opts: Opts, out: Output) -> Result<(), String> {
+    match opts.command {
+        Command::Schema(SchemaCommand::Id { schema, all: false }) => {
+            let schema_id = schema.schema().schema_id();
+            out.text(schema_id);
+            out.document(json!({ "name": schema.to_string(), "schemaId": schema_id.to_string() }));
+        }
+
+        Command::Schema(SchemaCommand::Id { all: true, .. }) => {
+            for name in SchemaName::ALL {
+                out.text(format_args!("{}\t{}", name.schema().schema_id(), name));
+            }
+            out.document(
+                SchemaName::ALL
+                    .iter()
+                    .map(|name| {
+                        json!({
+                            "name": name.to_string(),
+                            "schemaId": name.schema().schema_id().to_string(),
+                        })
+                    })
+                    .collect(),
+            );
+        }
+
+        Command::Schema(SchemaCommand::Export {
+            file,
+            format,
+            all: true,
+            ..
+        }) => {
+            let mut fd = vec![];
+            let schemata = SchemaName::ALL.map(|name| (name, name.schema()));
+            match format {
+                ExportFormat::Binary => {
+                    schemata
+                        .iter()
+                        .map(|(_, schema)| schema.clone())
+                        .collect::<Vec<_>>()
+                        .strict_encode(&mut fd)
+                        .unwrap();
+                }
+                ExportFormat::Bech32 | ExportFormat::Base64 => {
+                    for (name, schema) in schemata {
+                        let data = schema.strict_serialize().unwrap();
+                        let data = match format {
+                            ExportFormat::Bech32 => data.bech32_zip_string(),
+                            _ => base64::encode(&data),
+                        };
+                        writeln!(fd, "{}\t{}\t{}", name, schema.schema_id(), data).unwrap();
+                    }
+                }
+                ExportFormat::Json | ExportFormat::Yaml => {
+                    let schemata = schemata
+                        .iter()
+                        .map(|(name, schema)| {
+                            bmap! { name.to_string() => bmap! {
+                                s!("id") => serde_json::Value::String(schema.schema_id().to_string()),
+                                s!("schema") => serde_json::to_value(schema).unwrap()
+                            }}
+                        })
+                        .collect::<Vec<_>>();
+                    if format == ExportFormat::Json {
+                        serde_json::to_writer(&mut fd, &schemata).unwrap()
+                    } else {
+                        serde_yaml::to_writer(&mut fd, &schemata).unwrap()
+                    }
+                }
+            }
+            out.export(file, fd, json!({}))?;
+        }
+
+        Command::Schema(SchemaCommand::Export {
+            file,
+            format,
+            schema,
+            all: false,
+        }) => {
+            let mut fd = vec![];
+            let schema = schema.schema();
+            match format {
+                ExportFormat::Binary => {
+                    schema.strict_encode(&mut fd).unwrap();
+                }
+                ExportFormat::Bech32 => {
+                    let data = schema.strict_serialize().unwrap();
+                    fd.write_all(data.bech32_zip_string().as_bytes()).unwrap()
+                }
+                ExportFormat::Base64 => {
+                    let data = schema.strict_serialize().unwrap();
+                    fd.write_all(base64::encode(&data).as_bytes()).unwrap()
+                }
+                ExportFormat::Json => serde_json::to_writer(&mut fd, &schema).unwrap(),
+                ExportFormat::Yaml => serde_yaml::to_writer(&mut fd, &schema).unwrap(),
+            }
+            out.export(
+                file,
+                fd,
+                json!({ "schemaId": schema.schema_id().to_string() }),
+            )?;
+        }
+
+        Command::Schema(SchemaCommand::Verify { file }) => {
+            let schema = load_schema(file)?;
+            out.text(format_args!(
+                "{} {}",
+                "Schema ID:".bright_green(),
+                schema.schema_id().to_string().bright_yellow()
+            ));
+            let compatibility = Rgb20Schemata::check(&schema);
+            match &compatibility {
+                Ok(compatibility) => out.text(format_args!(
+                    "{} {}",
+                    "Status:".bright_green(),
+                    compatibility
+                )),
+                Err(err) => out.text(format_args!(
+                    "{} {}",
+                    "Status:".bright_green(),
+                    err.to_string().red()
+                )),
+            }
+
+            let features = Features::with_schema(&schema);
+            let mut operations = serde_json::Map::new();
+            out.text(format_args!("\n{}", "Operations:".bright_green()));
+            for ty in TransitionType::ALL {
+                let allowed = schema.transitions.contains_key(&ty.into());
+                operations.insert(ty.to_string(), allowed.into());
+                out.text(format_args!(
+                    "  {:16} {}",
+                    ty.to_string(),
+                    if allowed { "yes".green() } else { "no".yellow() }
+                ));
+            }
+            out.text(format_args!("\n{}", "Features:".bright_green()));
+            for (name, enabled) in [
+                ("inflatable", features.inflatable),
+                ("burnable", features.burnable),
+                ("replaceable", features.replaceable),
+                ("renominatable", features.renominatable),
+            ] {
+                out.text(format_args!(
+                    "  {:16} {}",
+                    name,
+                    if enabled { "yes".green() } else { "no".yellow() }
+                ));
+            }
+
+            out.document(json!({
+                "schemaId": schema.schema_id().to_string(),
+                "compatibility": compatibility.as_ref().ok().map(ToString::to_string),
+                "error": compatibility.as_ref().err().map(ToString::to_string),
+                "operations": operations,
+                "features": features,
+            }));
+            compatibility.map_err(|err| err.to_string())?;
+        }
+
+        Command::Contract(ContractCommand::Check { file, contract_id }) => {
+            let summary = check_contract(file, contract_id).unwrap_or_else(|(failure, details)| {
+                out.error(Some(failure), details);
+                process::exit(failure as i32)
+            });
+            print_summary(out, &summary);
+            out.text(format_args!(
+                "\n{}",
+                "Contract is valid".bold().bright_green()
+            ));
+            out.document(json!({ "valid": true, "summary": summary }));
+        }
+
+        Command::Issue {
+            schema,
+            ticker,
+            name,
+            precision,
+            allocation,
+            inflation,
+            method,
+            renomination,
+            epoch,
+            contract_file,
+            contract_url,
+            timestamp,
+            output,
+            format,
+            dry_run,
+        } => {
+            let allocation = allocation
                 .into_iter()
-                .map(|v| (v.seal_confidential.into(), v.value))
-                .collect();
-            let change = change
+                .map(|v| {
+                    Ok(GenesisAllocation {
+                        value: v.atomic(precision)?,
+                        target: v.seal,
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let inflation = InflationAllocations::from_values(
+                inflation
+                    .into_iter()
+                    .map(|v| Ok((v.seal, v.atomic(precision)?)))
+                    .collect::<Result<Vec<_>, String>>()?,
+            )
+            .map_err(|err| err.to_string())?;
+            let issued = allocation
+                .iter()
+                .try_fold(0u64, |sum, a| sum.checked_add(a.value))
+                .ok_or("total issued supply exceeds maximum 64-bit value")?;
+            let max_supply = inflation
+                .total()
+                .and_then(|inflation| inflation.checked_add(issued))
+                .ok_or("maximum supply exceeds maximum 64-bit value")?;
+            let contract_text = match contract_file {
+                Some(_) if schema != SchemaName::Media => {
+                    return Err(format!(
+                        "{} schema does not support Ricardian contracts",
+                        schema
+                    ));
+                }
+                Some(path) => {
+                    let document =
+                        fs::read(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+                    let mut text = sha256d::Hash::hash(&document).into_inner().to_hex();
+                    if let Some(url) = contract_url {
+                        text.push('\n');
+                        text.push_str(&url);
+                    }
+                    Some(text)
+                }
+                None => None,
+            };
+            let mut builder = Rgb20Builder::new(opts.network)
+                .subschema(schema.subschema())
+                .ticker(ticker)
+                .name(name)
+                .precision(precision)
+                .close_method(method);
+            for a in &allocation {
+                builder = builder.allocate(a.target, a.value);
+            }
+            for (outpoint, value) in inflation.as_inner() {
+                builder = builder.inflation_right(*outpoint, *value);
+            }
+            if let Some(outpoint) = renomination {
+                builder = builder.renomination(outpoint);
+            }
+            if let Some(outpoint) = epoch {
+                builder = builder.epoch(outpoint);
+            }
+            if let Some(text) = &contract_text {
+                builder = builder.contract_text(text);
+            }
+            if let Some(timestamp) = timestamp {
+                builder = builder.timestamp(timestamp);
+            }
+            let contract = builder.build().map_err(|err| err.to_string())?;
+            let asset = Asset::try_from(&contract).map_err(|err| err.to_string())?;
+
+            if dry_run {
+                let amount = |atomic| {
+                    AssetAmount::with_atomic(atomic, precision)
+                        .map(|amount| amount.to_string())
+                        .unwrap_or_else(|_| atomic.to_string())
+                };
+                out.text(format_args!(
+                    "{} {}",
+                    "Contract ID:".bright_green(),
+                    contract.contract_id().to_string().bright_yellow()
+                ));
+                out.text(format_args!("{} {}", "Schema:".bright_green(), schema));
+                out.text(format_args!(
+                    "{} {}",
+                    "Seal closing method:".bright_green(),
+                    method
+                ));
+                out.text(format_args!(
+                    "{} {}",
+                    "Timestamp:".bright_green(),
+                    format_timestamp(asset.issued_timestamp())
+                ));
+
+                out.text(format_args!("\n{}", "Allocations:".bright_green()));
+                for a in &allocation {
+                    out.text(format_args!("  {}@{}", amount(a.value), a.target));
+                }
+                out.text(format_args!("\n{}", "Rights:".bright_green()));
+                for (outpoint, value) in inflation.as_inner() {
+                    out.text(format_args!(
+                        "  inflation up to {}@{}",
+                        amount(*value),
+                        outpoint
+                    ));
+                }
+                if let Some(outpoint) = renomination {
+                    out.text(format_args!("  renomination@{}", outpoint));
+                }
+                if let Some(outpoint) = epoch {
+                    out.text(format_args!("  epoch@{}", outpoint));
+                }
+                if let Some(text) = &contract_text {
+                    out.text(format_args!("\n{}", "Ricardian contract:".bright_green()));
+                    for line in text.lines() {
+                        out.text(format_args!("  {}", line));
+                    }
+                }
+
+                out.text(format_args!("\n{}", "Supply:".bright_green()));
+                out.text(format_args!("  issued: {}", amount(issued)));
+                out.text(format_args!("  max:    {}", amount(max_supply)));
+
+                if timestamp.is_some() {
+                    eprintln!("\n{}", "Dry run: the contract is not saved".yellow());
+                } else {
+                    eprintln!(
+                        "\n{}",
+                        "Dry run: the contract is not saved; its id will differ when issued \
+                         since it commits to the issue time; use --timestamp to fix it"
+                            .yellow()
+                    );
+                }
+                out.document(json!({
+                    "dryRun": true,
+                    "contractId": contract.contract_id().to_string(),
+                    "schema": schema.to_string(),
+                    "method": method.to_string(),
+                    "allocations": allocation
+                        .iter()
+                        .map(|a| json!({ "amount": amount(a.value), "seal": a.target.to_string() }))
+                        .collect::<Vec<_>>(),
+                    "inflation": inflation
+                        .as_inner()
+                        .iter()
+                        .map(|(outpoint, value)| {
+                            json!({ "amount": amount(*value), "seal": outpoint.to_string() })
+                        })
+                        .collect::<Vec<_>>(),
+                    "renomination": renomination.map(|outpoint| outpoint.to_string()),
+                    "epoch": epoch.map(|outpoint| outpoint.to_string()),
+                    "contractText": contract_text,
+                    "timestamp": format_timestamp(asset.issued_timestamp()),
+                    "supply": { "issued": amount(issued), "max": amount(max_supply) },
+                }));
+                return Ok(());
+            }
+
+            eprintln!(
+                "{} {}\n",
+                "Contract ID:".bright_green(),
+                contract.contract_id().to_string().bright_yellow()
+            );
+
+            eprintln!("{}", "Contract YAML:".bright_green());
+            eprintln!("{}", serde_yaml::to_string(contract.genesis()).unwrap());
+
+            eprintln!("{}", "Contract JSON:".bright_green());
+            out.text(format_args!(
+                "{}\n",
+                serde_json::to_string(contract.genesis()).unwrap()
+            ));
+
+            eprintln!("{}", "Contract source:".bright_green());
+            out.text(format_args!("{}\n", contract));
+
+            eprintln!("{}", "Asset details:".bright_green());
+            eprintln!("{}\n", serde_yaml::to_string(&asset).unwrap());
+
+            if let Some(output) = &output {
+                save_file(&contract, output.clone(), format)?;
+            }
+            out.document(json!({
+                "contractId": contract.contract_id().to_string(),
+                "genesis": contract.genesis(),
+                "asset": asset,
+                "file": output,
+            }));
+        }
+
+        Command::Transfer {
+            consignment,
+            output,
+            format,
+            outpoints,
+            beneficiaries,
+            change,
+            amount,
+            to,
+            change_outpoint,
+            method,
+            coin_selection,
+        } => {
+            let asset = load_asset(consignment)?;
+            let precision = asset.precision();
+            let reserved = change.iter().filter_map(|v| match v.seal {
+                SealOrAddress::Seal(seal) => Some(seal),
+                SealOrAddress::Address(_) => None,
+            });
+            let mut resolver = AddressResolver::new(asset.genesis().chain(), method, reserved)?;
+            let transition = match (amount, to, change_outpoint) {
+                (Some(amount), Some(to), Some(change_outpoint)) => {
+                    let value = atomic(amount, precision)?;
+                    let payment = bmap! { resolver.resolve_endpoint(to)? => value };
+                    let change = match change_outpoint {
+                        SealOrAddress::Seal(outpoint) => seal::Revealed::new(method, outpoint),
+                        SealOrAddress::Address(address) => resolver.resolve(address)?,
+                    };
+                    asset.transfer_selected(coin_selection, payment, change)
+                }
+                _ => {
+                    let mut payment = BTreeMap::new();
+                    for v in beneficiaries {
+                        let value = atomic(v.amount, precision)?;
+                        payment.insert(resolver.resolve_endpoint(v.seal)?, value);
+                    }
+                    let mut change_map = BTreeMap::new();
+                    for v in change {
+                        let value = atomic(v.amount, precision)?;
+                        change_map.insert(resolver.resolve_seal(v.seal)?, value);
+                    }
+                    let outpoints = outpoints.into_iter().collect();
+                    asset.transfer(outpoints, payment, change_map)
+                }
+            }
+            .map_err(|err| err.to_string())?;
+
+            let mut doc = save_transition(out, &transition, output, format)?;
+            resolver.print_resolved(out);
+            doc["resolved"] = resolver.resolved_json();
+            out.document(doc);
+        }
+        Command::Pay {
+            consignment,
+            psbt,
+            output,
+            format,
+            invoice,
+            amount,
+            to,
+            outpoints,
+            change_address,
+            fee,
+            method,
+            electrum,
+            coin_selection,
+        } => {
+            let source = load_file::<StateTransfer>(consignment)?;
+            let asset = Asset::try_from(&source).map_err(|err| err.to_string())?;
+            let contract_id = asset.contract_id();
+            let (amount, to) = match (invoice, amount, to) {
+                (Some(invoice), _, _) => {
+                    if invoice.contract_id != contract_id {
+                        return Err(format!(
+                            "invoice requests asset {} while the consignment is for {}",
+                            invoice.contract_id, contract_id
+                        ));
+                    }
+                    if invoice.is_expired() {
+                        return Err(s!("invoice is expired"));
+                    }
+                    let to = match invoice.beneficiary {
+                        Beneficiary::BlindedSeal(seal) => SealOrAddress::Seal(seal),
+                        Beneficiary::Address(address) => SealOrAddress::Address(address),
+                    };
+                    (invoice.amount, to)
+                }
+                (None, Some(amount), Some(to)) => (amount, to),
+                _ => unreachable!("clap requires either invoice or amount with beneficiary"),
+            };
+
+            // Witness transaction output #0 receives both bitcoin and asset
+            // change; beneficiary addresses receive outputs following it
+            let change = ExplicitSeal {
+                method,
+                txid: None,
+                vout: 0,
+            };
+            let mut resolver = AddressResolver::new(asset.genesis().chain(), method, [change])?;
+            let endpoint = resolver.resolve_endpoint(to)?;
+            let value = atomic(amount, asset.precision())?;
+            let transition = asset
+                .transfer_selected(coin_selection, bmap! { endpoint => value }, change.into())
+                .map_err(|err| err.to_string())?;
+
+            let mut inputs = outpoints.into_iter().collect::<BTreeSet<_>>();
+            for (node_id, rights) in transition.parent_owned_rights().iter() {
+                for (ty, indexes) in rights.iter() {
+                    for no in indexes {
+                        let closed = NodeOutpoint::new(*node_id, *ty, *no);
+                        inputs.insert(
+                            asset
+                                .resolve_seal(closed)
+                                .expect("transfer spends only known allocations"),
+                        );
+                    }
+                }
+            }
+            let client = ElectrumClient::new(&electrum).map_err(|err| err.to_string())?;
+            let mut prev_txs = Vec::with_capacity(inputs.len());
+            let mut total = 0u64;
+            for outpoint in &inputs {
+                let tx = client
+                    .transaction_get(&outpoint.txid)
+                    .map_err(|err| format!("{}: {}", outpoint, err))?;
+                let prevout = tx
+                    .output
+                    .get(outpoint.vout as usize)
+                    .ok_or_else(|| format!("transaction output {} does not exist", outpoint))?;
+                total += prevout.value;
+                prev_txs.push(tx);
+            }
+            let mut outputs = vec![TxOut {
+                value: 0,
+                script_pubkey: change_address.script_pubkey(),
+            }];
+            for (address, _) in &resolver.resolved {
+                outputs.push(TxOut {
+                    value: DUST_LIMIT,
+                    script_pubkey: address.script_pubkey(),
+                });
+            }
+            let spent = fee + DUST_LIMIT * (outputs.len() as u64 - 1);
+            outputs[0].value = total
+                .checked_sub(spent)
+                .filter(|change| *change >= DUST_LIMIT)
+                .ok_or_else(|| {
+                    format!(
+                        "inputs value {} sats is not sufficient to pay {} sats",
+                        total, spent
+                    )
+                })?;
+            let tx = Transaction {
+                version: 2,
+                lock_time: PackedLockTime::ZERO,
+                input: inputs
+                    .iter()
+                    .map(|outpoint| TxIn {
+                        previous_output: *outpoint,
+                        script_sig: empty!(),
+                        sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                        witness: empty!(),
+                    })
+                    .collect(),
+                output: outputs,
+            };
+
+            let mut witness = Psbt::from(
+                PartiallySignedTransaction::from_unsigned_tx(tx).map_err(|err| err.to_string())?,
+            );
+            for ((input, outpoint), prev_tx) in witness.inputs.iter_mut().zip(&inputs).zip(prev_txs)
+            {
+                input.witness_utxo = Some(prev_tx.output[outpoint.vout as usize].clone());
+                input.non_witness_utxo = Some(prev_tx);
+            }
+            let mut witness = embed_transition(witness, &asset, transition, method)
+                .map_err(|err| err.to_string())?;
+
+            let anchor = Anchor::commit(&mut witness).map_err(|err| err.to_string())?;
+            let bundle = witness
+                .rgb_bundles()
+                .map_err(|err| err.to_string())?
+                .remove(&contract_id)
+                .expect("transition was embedded into PSBT");
+            let anchor = anchor
+                .to_merkle_proof(contract_id)
+                .map_err(|err| err.to_string())?;
+            let transfer = asset
+                .compose_transfer(&source, bundle, anchor, &bset! { endpoint })
+                .map_err(|err| err.to_string())?;
+
+            fs::write(&psbt, witness.to_string()).map_err(|err| err.to_string())?;
+            save_file(&transfer, output.clone(), format)?;
+            resolver.print_resolved(out);
+            let witness_txid = witness.to_unsigned_tx().txid();
+            eprintln!(
+                "{} {}",
+                "Witness transaction:".bright_green(),
+                witness_txid.to_string().bright_yellow()
+            );
+            out.text("Success".bold().bright_green());
+            out.document(json!({
+                "witnessTxid": witness_txid.to_string(),
+                "psbt": psbt,
+                "file": output,
+                "resolved": resolver.resolved_json(),
+            }));
+        }
+
+        Command::Inflate {
+            consignment,
+            output,
+            format,
+            closing,
+            allocation,
+            inflation,
+        } => {
+            let asset = load_asset(consignment)?;
+            let precision = asset.precision();
+            let allocation = allocation
                 .into_iter()
-                .map(|v| (v.into_revealed_seal(), v.value))
-                .collect();
-            let outpoints = outpoints.into_iter().collect();
-            let transition = asset.transfer(outpoints, beneficiaries, change).unwrap();
+                .map(|v| {
+                    Ok(AllocatedValue {
+                        value: v.atomic(precision)?,
+                        seal: v.seal,
+                    })
+                })
+                .collect::<Result<_, String>>()?;
+            let inflation = InflationAllocations::from_values(
+                inflation
+                    .into_iter()
+                    .map(|v| Ok((v.seal, v.atomic(precision)?)))
+                    .collect::<Result<Vec<_>, String>>()?,
+            )
+            .map_err(|err| err.to_string())?;
+            let transition = asset
+                .inflate(closing.into_iter().collect(), inflation, allocation)
+                .map_err(|err| err.to_string())?;
+            out.document(save_transition(out, &transition, output, format)?);
+        }
+
+        Command::Burn {
+            consignment,
+            output,
+            format,
+            closing,
+            amount,
+            burned_utxos,
+            next,
+        } => {
+            let asset = load_asset(consignment)?;
+            let burned = atomic(amount, asset.precision())?;
+            let transition = asset
+                .burn(closing, burned, burned_utxos.into_iter().collect(), next)
+                .map_err(|err| err.to_string())?;
+            out.document(save_transition(out, &transition, output, format)?);
+        }
+
+        Command::BurnReplace {
+            consignment,
+            output,
+            format,
+            closing,
+            amount,
+            burned_utxos,
+            next,
+            allocation,
+            beneficiaries,
+        } => {
+            let asset = load_asset(consignment)?;
+            let precision = asset.precision();
+            let burned = atomic(amount, precision)?;
+            let allocation = allocation
+                .into_iter()
+                .map(|v| Ok((v.seal.into(), v.atomic(precision)?)))
+                .collect::<Result<_, String>>()?;
+            let beneficiaries = beneficiaries
+                .into_iter()
+                .map(|v| Ok((v.seal.into(), v.atomic(precision)?)))
+                .collect::<Result<_, String>>()?;
+            let transition = asset
+                .burn_and_replace(
+                    closing,
+                    burned,
+                    burned_utxos.into_iter().collect(),
+                    next,
+                    allocation,
+                    beneficiaries,
+                )
+                .map_err(|err| err.to_string())?;
+            out.document(save_transition(out, &transition, output, format)?);
+        }
 
-            transition.strict_file_save(output).unwrap();
-            //consignment.strict_file_save(output).unwrap();
+        Command::Renominate {
+            consignment,
+            output,
+            format,
+            closing,
+            ticker,
+            name,
+            precision,
+            next,
+        } => {
+            let asset = load_asset(consignment)?;
+            let transition = asset
+                .renominate(closing, ticker, name, precision, next)
+                .map_err(|err| err.to_string())?;
+            out.document(save_transition(out, &transition, output, format)?);
+        }
 
-            println!("{}", serde_yaml::to_string(&transition).unwrap());
-            println!("{}", "Success".bold().bright_green());
+        Command::EpochOpen {
+            consignment,
+            output,
+            format,
+            closing,
+            next,
+            burn,
+        } => {
+            let asset = load_asset(consignment)?;
+            let transition = asset
+                .open_epoch(closing, next, burn)
+                .map_err(|err| err.to_string())?;
+            out.document(save_transition(out, &transition, output, format)?);
+        }
+
+        Command::RightsSplit {
+            consignment,
+            output,
+            format,
+            closing,
+            assets,
+            inflation,
+            epoch,
+            burn,
+            renomination,
+        } => {
+            let asset = load_asset(consignment)?;
+            let seals = [
+                (OwnedRightType::Assets, assets),
+                (OwnedRightType::Inflation, inflation),
+                (OwnedRightType::OpenEpoch, epoch),
+                (OwnedRightType::BurnReplace, burn),
+                (OwnedRightType::Renomination, renomination),
+            ]
+            .into_iter()
+            .filter_map(|(ty, seal)| Some((ty, seal?)))
+            .collect();
+            let transition = asset
+                .split_rights(closing, seals)
+                .map_err(|err| err.to_string())?;
+            out.document(save_transition(out, &transition, output, format)?);
+        }
+
+        Command::Consign {
+            consignment,
+            bundle,
+            anchor,
+            endpoints,
+            output,
+            format,
+        } => {
+            let source = load_file::<StateTransfer>(consignment)?;
+            let bundle = TransitionBundle::strict_file_load(bundle).unwrap();
+            let anchor = Anchor::strict_file_load(anchor).unwrap();
+
+            let asset = Asset::try_from(&source).unwrap();
+            let endpoints = endpoints.into_iter().collect();
+            let transfer = asset
+                .compose_transfer(&source, bundle, anchor, &endpoints)
+                .unwrap();
+
+            save_file(&transfer, output.clone(), format)?;
+
+            out.text("Success".bold().bright_green());
+            out.document(json!({
+                "contractId": transfer.contract_id().to_string(),
+                "file": output,
+            }));
+        }
+
+        Command::Validate {
+            consignment,
+            electrum,
+            tx_file,
+            contract,
+        } => {
+            let report = match (electrum, tx_file) {
+                (Some(server), _) => {
+                    let resolver = ElectrumClient::new(&server).unwrap();
+                    validate(consignment, contract, &resolver)
+                }
+                (None, Some(path)) => {
+                    let resolver = OfflineResolver::load(path).unwrap();
+                    validate(consignment, contract, &resolver)
+                }
+                (None, None) => unreachable!("clap requires one of the resolvers"),
+            };
+
+            out.text(serde_yaml::to_string(&report).unwrap());
+            out.document(json!({ "validity": report.validity().to_string(), "report": report }));
+            match report.validity() {
+                Validity::Valid => out.text("Consignment is valid".bold().bright_green()),
+                validity => {
+                    return Err(format!(
+                        "{} {}",
+                        "Consignment validity:".bright_red(),
+                        validity
+                    ))
+                }
+            }
+        }
+
+        Command::Inspect {
+            consignment,
+            contract,
+        } => {
+            let summary = if contract {
+                let contract = load_file::<Contract>(consignment)?;
+                ConsignmentSummary::with(&contract)
+            } else {
+                let transfer = load_file::<StateTransfer>(consignment)?;
+                ConsignmentSummary::with(&transfer)
+            }
+            .map_err(|err| err.to_string())?;
+
+            print_summary(out, &summary);
+            out.document(json!(summary));
+        }
+
+        Command::ExportState {
+            consignment,
+            file,
+            format,
+            contract,
+        } => {
+            let asset = if contract {
+                let contract = load_file::<Contract>(consignment)?;
+                Asset::try_from(&contract)
+            } else {
+                let transfer = load_file::<StateTransfer>(consignment)?;
+                Asset::try_from(&transfer)
+            }
+            .map_err(|err| err.to_string())?;
+
+            let mut fd = vec![];
+            save_state(&asset, &mut fd, format)?;
+            out.export(
+                file,
+                fd,
+                json!({ "contractId": asset.contract_id().to_string() }),
+            )?;
+        }
+
+        Command::ImportState {
+            state,
+            consignments,
+            format,
+            output,
+        } => {
+            let mut asset = load_state(&state, format)?;
+            let mut merged = vec![];
+            for consignment in consignments {
+                let transfer = load_file::<StateTransfer>(&consignment)?;
+                let update = asset
+                    .update_with(&transfer)
+                    .map_err(|err| format!("{}: {}", consignment.display(), err))?;
+                eprintln!(
+                    "{} {}",
+                    "Merged".bright_green(),
+                    consignment.display().to_string().bright_yellow()
+                );
+                for coin in &update.added {
+                    out.text(format_args!("  + {}", coin));
+                }
+                for coin in &update.spent {
+                    out.text(format_args!("  - {}", coin));
+                }
+                merged.push(json!({
+                    "consignment": consignment,
+                    "added": update.added.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                    "spent": update.spent.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                }));
+            }
+
+            let output = output.unwrap_or(state);
+            let fd = fs::File::create(&output).map_err(|err| err.to_string())?;
+            save_state(&asset, fd, format)?;
+            eprintln!("{}", "Success".bold().bright_green());
+            out.document(json!({ "file": output, "merged": merged }));
+        }
+
+        Command::Graph {
+            consignment,
+            file,
+            format,
+            contract,
+        } => {
+            let asset = if contract {
+                let contract = load_file::<Contract>(consignment)?;
+                Asset::try_from(&contract)
+            } else {
+                let transfer = load_file::<StateTransfer>(consignment)?;
+                Asset::try_from(&transfer)
+            }
+            .map_err(|err| err.to_string())?;
+            let graph = asset.history_graph();
+
+            let data = match format {
+                GraphFormat::Dot => graph.to_dot().into_bytes(),
+                GraphFormat::Json => serde_json::to_vec(&graph).map_err(|err| err.to_string())?,
+            };
+            out.export(
+                file,
+                data,
+                json!({ "contractId": asset.contract_id().to_string() }),
+            )?;
+        }
+
+        Command::Blind {
+            outpoint,
+            method,
+            vault,
+        } => {
+            let (concealed, revealed) = match vault {
+                Some(ref path) => {
+                    let mut vault = SealVault::load(path).unwrap();
+                    let concealed = vault.blind(outpoint, method);
+                    vault.save(path).unwrap();
+                    (
+                        concealed,
+                        vault.reveal(concealed).expect("seal was just added"),
+                    )
+                }
+                None => rgb20::seal::blind(outpoint, method),
+            };
+
+            eprint!("{} ", "Blinded seal:".bright_green());
+            out.text(concealed);
+            eprint!("{} ", "Blinding secret:".bright_green());
+            out.text(revealed.blinding);
+            eprint!("{} ", "Reveal data:".bright_green());
+            out.text(revealed);
+            out.document(json!({
+                "blindedSeal": concealed.to_string(),
+                "blindingSecret": revealed.blinding.to_string(),
+                "revealData": revealed.to_string(),
+            }));
+        }
+
+        Command::Completions { shell } => {
+            let mut data = vec![];
+            clap_complete::generate(shell.shell(), &mut Opts::command(), "rgb20", &mut data);
+            out.export(None, data, json!({}))?;
+        }
+
+        Command::Manpages { dir } => {
+            fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+            let mut files = vec![];
+            write_manpages(Opts::command(), &dir, &mut files)?;
+            for file in &files {
+                eprintln!("{} {}", "Saved".bright_green(), file.display());
+            }
+            out.document(json!({ "dir": dir, "files": files }));
         }
     }
 
     Ok(())
 }
 
-fn ticker_validator(name: &str) -> Result<(), String> {
-    if name.len() < 3 || name.len() > 8 || name.chars().any(|c| c < 'A' && c > 'Z') {
-        Err(
-            "Ticker name must be between 3 and 8 chars, contain no spaces and \
-            consist only of capital letters\
-            "
-            .to_string(),
-        )
+fn atomic(amount: AssetAmount, precision: u8) -> Result<AtomicValue, String> {
+    amount
+        .rescale(precision)
+        .map(AssetAmount::atomic_value)
+        .map_err(|err| format!("invalid amount {}: {}", amount, err))
+}
+
+fn load_asset(consignment: PathBuf) -> Result<Asset, String> {
+    let consignment = load_file::<StateTransfer>(consignment)?;
+    Asset::try_from(&consignment).map_err(|err| err.to_string())
+}
+
+fn load_state(path: &Path, format: StateFormat) -> Result<Asset, String> {
+    let data = fs::read(path).map_err(|err| err.to_string())?;
+    match format {
+        StateFormat::Binary => Asset::from_snapshot(data).map_err(|err| err.to_string()),
+        StateFormat::Json => serde_json::from_slice(&data).map_err(|err| err.to_string()),
+        StateFormat::Yaml => serde_yaml::from_slice(&data).map_err(|err| err.to_string()),
+    }
+}
+
+fn save_state(asset: &Asset, mut fd: impl Write, format: StateFormat) -> Result<(), String> {
+    match format {
+        StateFormat::Binary => fd
+            .write_all(&asset.to_snapshot())
+            .map_err(|err| err.to_string())?,
+        StateFormat::Json => {
+            serde_json::to_writer(&mut fd, asset).map_err(|err| err.to_string())?
+        }
+        StateFormat::Yaml => {
+            serde_yaml::to_writer(&mut fd, asset).map_err(|err| err.to_string())?
+        }
+    }
+    fd.flush().map_err(|err| err.to_string())
+}
+
+/// Saves the transition, returning JSON document describing it
+fn save_transition(
+    out: Output,
+    transition: &Transition,
+    output: PathBuf,
+    format: FileFormat,
+) -> Result<serde_json::Value, String> {
+    save_file(transition, output.clone(), format)?;
+    out.text(serde_yaml::to_string(transition).unwrap());
+    out.text("Success".bold().bright_green());
+    Ok(json!({
+        "nodeId": transition.node_id().to_string(),
+        "transition": transition,
+        "file": output,
+    }))
+}
+
+fn check_contract(
+    file: PathBuf,
+    expected_id: Option<ContractId>,
+) -> Result<ConsignmentSummary, (CheckFailure, String)> {
+    let contract = load_file::<Contract>(file).map_err(|err| (CheckFailure::Load, err))?;
+    let genesis = contract.genesis();
+
+    let schema_id = contract.schema_id();
+    if genesis.schema_id() != schema_id {
+        return Err((
+            CheckFailure::Schema,
+            format!(
+                "genesis declares schema {}, while the contract provides schema {}",
+                genesis.schema_id(),
+                schema_id
+            ),
+        ));
+    }
+    if Subschema::from_schema_id(schema_id).is_none() {
+        return Err((
+            CheckFailure::Schema,
+            format!("schema {} is not one of RGB20 schemata", schema_id),
+        ));
+    }
+    let report = Asset::validate_consignment(&contract, &OfflineResolver::new());
+    if report.validity() == Validity::Invalid {
+        return Err((
+            CheckFailure::Schema,
+            serde_json::to_string(&report).unwrap_or_else(|_| report.validity().to_string()),
+        ));
+    }
+
+    let asset = Asset::try_from(&contract).map_err(|err| (CheckFailure::Asset, err.to_string()))?;
+    let nomination = asset.genesis_nomination();
+    Ticker::from_str(&nomination.ticker)
+        .map_err(|err| (CheckFailure::Nomination, format!("ticker: {}", err)))?;
+    AssetName::from_str(&nomination.name)
+        .map_err(|err| (CheckFailure::Nomination, format!("name: {}", err)))?;
+    if nomination.precision > MAX_PRECISION {
+        return Err((
+            CheckFailure::Nomination,
+            format!(
+                "precision {} exceeds maximum {}",
+                nomination.precision, MAX_PRECISION
+            ),
+        ));
+    }
+
+    let contract_id = genesis.contract_id();
+    if let Some(expected_id) = expected_id.filter(|id| *id != contract_id) {
+        return Err((
+            CheckFailure::ContractId,
+            format!(
+                "expected contract id {}, found {}",
+                expected_id, contract_id
+            ),
+        ));
+    }
+
+    ConsignmentSummary::with(&contract).map_err(|err| (CheckFailure::Asset, err.to_string()))
+}
+
+/// Loads schema in any of the formats supported by `rgb20 schema export`,
+/// except the zipped Bech32 one
+fn load_schema(path: impl AsRef<Path>) -> Result<Schema, String> {
+    let path = path.as_ref();
+    let data = fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    if let Ok(schema) = Schema::from_armored_or_strict(&data) {
+        return Ok(schema);
+    }
+    let text = String::from_utf8_lossy(&data);
+    base64::decode(text.trim())
+        .ok()
+        .and_then(|data| Schema::strict_deserialize(data).ok())
+        .or_else(|| serde_json::from_str(&text).ok())
+        .or_else(|| serde_yaml::from_str(&text).ok())
+        .ok_or_else(|| format!("{}: unrecognized schema data", path.display()))
+}
+
+fn load_file<T: Armor>(path: impl AsRef<Path>) -> Result<T, String> {
+    let path = path.as_ref();
+    let data = fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    T::from_armored_or_strict(&data).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+fn save_file<T: Armor>(data: &T, path: PathBuf, format: FileFormat) -> Result<(), String> {
+    match format {
+        FileFormat::Binary => data
+            .strict_file_save(path)
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+        FileFormat::Bech32 => {
+            fs::write(path, data.to_armored_string()).map_err(|err| err.to_string())
+        }
+    }
+}
+
+fn validate(
+    consignment: PathBuf,
+    contract: bool,
+    resolver: &impl WitnessResolver,
+) -> ValidationReport {
+    if contract {
+        let contract = load_file::<Contract>(consignment).unwrap();
+        Asset::validate_consignment(&contract, resolver)
     } else {
-        Ok(())
+        let transfer = load_file::<StateTransfer>(consignment).unwrap();
+        Asset::validate_consignment(&transfer, resolver)
     }
 }
 
-pub fn open_file_or_stdout(
-    filename: Option<impl AsRef<Path>>,
-) -> Result<Box<dyn Write>, io::Error> {
-    Ok(match filename {
-        Some(filename) => {
-            let file = fs::File::create(filename)?;
-            Box::new(file)
+fn print_summary(out: Output, summary: &ConsignmentSummary) {
+    out.text(format_args!(
+        "{} {}",
+        "Contract ID:".bright_green(),
+        summary.contract_id.to_string().bright_yellow()
+    ));
+    out.text(format_args!(
+        "{} {} ({})",
+        "Schema ID:".bright_green(),
+        summary.schema_id,
+        summary.subschema
+    ));
+    out.text(format_args!(
+        "{} {}",
+        "Issued:".bright_green(),
+        format_time(summary.issued)
+    ));
+
+    let nomination = &summary.nomination;
+    out.text(format_args!("\n{}", "Nomination:".bright_green()));
+    out.text(format_args!("  ticker:    {}", nomination.ticker));
+    out.text(format_args!("  name:      {}", nomination.name));
+    out.text(format_args!("  precision: {}", nomination.precision));
+
+    let supply = &summary.supply;
+    let precision = nomination.precision;
+    let amount = |atomic| {
+        AssetAmount::with_atomic(atomic, precision)
+            .map(|amount| amount.to_string())
+            .unwrap_or_else(|_| atomic.to_string())
+    };
+    out.text(format_args!("\n{}", "Supply:".bright_green()));
+    out.text(format_args!("  issued:      {}", amount(supply.issued)));
+    out.text(format_args!("  burned:      {}", amount(supply.burned)));
+    out.text(format_args!("  replaced:    {}", amount(supply.replaced)));
+    out.text(format_args!(
+        "  circulating: {}",
+        amount(supply.circulating)
+    ));
+    out.text(format_args!("  max:         {}", amount(supply.max)));
+    if !supply.is_complete {
+        out.text(format_args!(
+            "  {}",
+            "issuance history is incomplete".yellow()
+        ));
+    }
+
+    out.text(format_args!("\n{}", "Allocations:".bright_green()));
+    for allocation in &summary.allocations {
+        out.text(format_args!(
+            "  {}@{}",
+            allocation.amount, allocation.outpoint
+        ));
+    }
+
+    out.text(format_args!("\n{}", "Operations:".bright_green()));
+    for operation in &summary.operations {
+        match (operation.transition_type, operation.witness_txid) {
+            (None, None) => out.text(format_args!("  {} genesis", operation.node_id)),
+            (ty, txid) => out.text(format_args!(
+                "  {} {} in {}",
+                operation.node_id,
+                ty.map(|ty| ty.to_string())
+                    .unwrap_or_else(|| s!("unknown transition")),
+                txid.map(|txid| txid.to_string())
+                    .unwrap_or_else(|| s!("unknown witness"))
+            )),
+        }
+        for parent in &operation.parents {
+            out.text(format_args!("    <- {}", parent));
         }
-        None => Box::new(io::stdout()),
-    })
+    }
+
+    if !summary.endpoints.is_empty() {
+        out.text(format_args!("\n{}", "Endpoints:".bright_green()));
+        for endpoint in &summary.endpoints {
+            out.text(format_args!(
+                "  {} in bundle {}",
+                endpoint.seal, endpoint.bundle_id
+            ));
+        }
+    }
+}
+
+/// Writes man page for the `cmd` and, recursively, for all its subcommands,
+/// which pages are named after the parent command, like `rgb20-schema-id.1`
+fn write_manpages(cmd: clap::Command, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let name = cmd.get_name().to_owned();
+    let mut page = vec![];
+    clap_mangen::Man::new(cmd.clone())
+        .render(&mut page)
+        .map_err(|err| err.to_string())?;
+    let file = dir.join(format!("{}.1", name));
+    fs::write(&file, page).map_err(|err| format!("{}: {}", file.display(), err))?;
+    files.push(file);
+
+    for sub in cmd.get_subcommands() {
+        let sub = sub.clone().name(format!("{}-{}", name, sub.get_name()));
+        write_manpages(sub, dir, files)?;
+    }
+    Ok(())
 }
@@ -0,0 +1,306 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use bitcoin::{Transaction, Txid};
+use bitcoin_onchain::ResolveTx;
+use rgb::prelude::*;
+
+use crate::disclosure::{ancestry, exposed_seals, prune};
+use crate::schema::TransitionType;
+use crate::{transitions, Armor, Asset, BurnReplace, OfflineResolver, ValidationReport};
+
+/// Errors constructing and verifying [`BurnProof`]
+#[derive(Clone, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum BurnProofError {
+    /// node {0} is not a known burn or burn & replace state transition of the
+    /// asset
+    NotBurn(NodeId),
+
+    /// witness transaction {0} is unknown to the resolver
+    WitnessUnknown(Txid),
+
+    /// burn proof is invalid; details are provided by the validation report
+    Invalid(Box<ValidationReport>),
+
+    /// amount burned by node {0} does not match the sum of the asset
+    /// allocations opened by its history proofs
+    AmountMismatch(NodeId),
+
+    /// {0}
+    #[from]
+    Asset(crate::Error),
+
+    /// {0}
+    #[from]
+    Transition(transitions::Error),
+}
+
+/// Self-contained proof of a burn or burn & replace operation, which can be
+/// verified by auditors without access to the full asset history.
+///
+/// The proof contains the asset genesis, the burn state transition and the
+/// chain of epoch and burn transitions which closed the seals of the burn
+/// right before it, with their anchors and witness transactions. All other
+/// transitions and concealable state are pruned.
+#[derive(Clone, Debug, StrictEncode, StrictDecode)]
+pub struct BurnProof {
+    /// Id of the burn or burn & replace state transition
+    pub node_id: NodeId,
+
+    /// Consignment with the genesis and the pruned burn history
+    pub consignment: StateTransfer,
+
+    /// Witness transactions of the anchors in the consignment
+    pub witnesses: Vec<Transaction>,
+}
+
+impl Armor for BurnProof {
+    const HRP: &'static str = "rgbburn";
+}
+
+impl Asset {
+    /// Exports proof of the burn or burn & replace operation performed by the
+    /// state transition `node_id`, taking anchors from the `source`
+    /// consignment and witness transactions from the `resolver`.
+    pub fn burn_proof<T>(
+        &self,
+        source: &InmemConsignment<T>,
+        node_id: NodeId,
+        resolver: &impl ResolveTx,
+    ) -> Result<BurnProof, BurnProofError>
+    where
+        T: ConsignmentType,
+    {
+        let contract_id = source.contract_id();
+        if contract_id != self.contract_id() {
+            return Err(transitions::Error::ContractMismatch(contract_id).into());
+        }
+        if !self
+            .transitions_by_type(TransitionType::Burn)
+            .chain(self.transitions_by_type(TransitionType::BurnAndReplace))
+            .any(|(_, transition)| transition.node_id() == node_id)
+        {
+            return Err(BurnProofError::NotBurn(node_id));
+        }
+
+        let ancestors = ancestry(source, [node_id])?;
+        let expose = exposed_seals(source, &ancestors);
+        let consignment = prune(source, &ancestors, &expose, empty!())?;
+        let witnesses = consignment
+            .anchored_bundles()
+            .map(|(anchor, _)| {
+                resolver
+                    .resolve_tx(anchor.txid)
+                    .map_err(|_| BurnProofError::WitnessUnknown(anchor.txid))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(BurnProof {
+            node_id,
+            consignment,
+            witnesses,
+        })
+    }
+}
+
+/// Verifies burn proof: validates the consignment against its schema, RGB20
+/// rules and the witness transactions contained in the proof, and checks
+/// that the proven node is a burn or burn & replace state transition which
+/// history proofs, if present, match the burned amount. Returns details of
+/// the burn operation.
+pub fn verify_burn_proof(proof: &BurnProof) -> Result<BurnReplace, BurnProofError> {
+    let mut resolver = OfflineResolver::new();
+    for tx in &proof.witnesses {
+        resolver.insert(tx.clone());
+    }
+    let report = Asset::validate_consignment(&proof.consignment, &resolver);
+    if !report.is_valid() {
+        return Err(BurnProofError::Invalid(Box::new(report)));
+    }
+
+    let asset = Asset::try_from(&proof.consignment)?;
+    let burn = asset
+        .burns()
+        .into_iter()
+        .find(|burn| burn.node_id == proof.node_id)
+        .ok_or(BurnProofError::NotBurn(proof.node_id))?;
+    if !burn.proofs.is_empty() && !asset.verify_history_proofs(&burn) {
+        return Err(BurnProofError::AmountMismatch(proof.node_id));
+    }
+    Ok(burn)
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::{OutPoint, PackedLockTime};
+    use seals::txout::ExplicitSeal;
+
+    use super::*;
+    use crate::fixtures::{anchor, builder, outpoint};
+
+    fn witness(lock_time: u32) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime(lock_time),
+            input: vec![],
+            output: vec![],
+        }
+    }
+
+    #[test]
+    fn burn_proof() {
+        let contract = builder()
+            .allocate(outpoint(2), 50)
+            .epoch(outpoint(3))
+            .build()
+            .unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+
+        let (epoch_tx, transfer_tx, burn_tx) = (witness(1), witness(2), witness(3));
+        let burn_seal = OutPoint::new(epoch_tx.txid(), 1);
+        let epoch = asset
+            .open_epoch(outpoint(3), None, Some(ExplicitSeal::from(burn_seal)))
+            .unwrap();
+        let transfer = asset
+            .transfer(
+                bset! { outpoint(2) },
+                bmap! { SealEndpoint::from(seal::Revealed::from(outpoint(4))) => 50 },
+                empty!(),
+            )
+            .unwrap();
+        let bundle =
+            |transition| TransitionBundle::try_from(bmap! { transition => bset! { 0 } }).unwrap();
+        let mut consignment = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            empty!(),
+            vec![
+                (anchor(epoch_tx.txid()), bundle(epoch.clone())),
+                (anchor(transfer_tx.txid()), bundle(transfer.clone())),
+            ]
+            .try_into()
+            .unwrap(),
+            empty!(),
+        );
+        let asset = Asset::try_from(&consignment).unwrap();
+        let burn = asset
+            .burn(burn_seal, 100, bset! { outpoint(1) }, None)
+            .unwrap();
+        let burn_id = burn.node_id();
+        consignment = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            empty!(),
+            vec![
+                (anchor(epoch_tx.txid()), bundle(epoch.clone())),
+                (anchor(transfer_tx.txid()), bundle(transfer.clone())),
+                (anchor(burn_tx.txid()), bundle(burn)),
+            ]
+            .try_into()
+            .unwrap(),
+            empty!(),
+        );
+        let asset = Asset::try_from(&consignment).unwrap();
+
+        let mut resolver = OfflineResolver::new();
+        resolver.insert(epoch_tx.clone());
+        assert!(matches!(
+            asset.burn_proof(&consignment, burn_id, &resolver),
+            Err(BurnProofError::WitnessUnknown(txid)) if txid == burn_tx.txid()
+        ));
+        resolver.insert(burn_tx);
+        assert!(matches!(
+            asset.burn_proof(&consignment, transfer.node_id(), &resolver),
+            Err(BurnProofError::NotBurn(_))
+        ));
+
+        let proof = asset.burn_proof(&consignment, burn_id, &resolver).unwrap();
+        assert_eq!(proof.witnesses.len(), 2);
+        assert!(proof.consignment.transition_by_id(epoch.node_id()).is_ok());
+        assert!(proof.consignment.transition_by_id(burn_id).is_ok());
+        assert!(proof
+            .consignment
+            .transition_by_id(transfer.node_id())
+            .is_err());
+
+        let armored = BurnProof::from_armored_str(&proof.to_armored_string()).unwrap();
+        assert_eq!(armored.node_id, burn_id);
+        assert_eq!(armored.witnesses, proof.witnesses);
+
+        let burn = verify_burn_proof(&proof).unwrap();
+        assert_eq!(burn.node_id, burn_id);
+        assert_eq!(burn.burned, 100);
+        assert_eq!(burn.burned_utxos, vec![outpoint(1)]);
+        assert_eq!(burn.closed_seal, Some(burn_seal));
+
+        let proven = Asset::try_from(&proof.consignment).unwrap();
+        assert_eq!(burn.proofs.len(), 1);
+        assert!(proven.verify_history_proofs(&burn));
+        let mut forged = burn;
+        forged.proofs[0].state.value = 99;
+        forged.burned = 99;
+        assert!(!proven.verify_history_proofs(&forged));
+    }
+
+    #[test]
+    fn burn_amount_mismatch() {
+        let contract = builder().epoch(outpoint(3)).build().unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+
+        let (epoch_tx, burn_tx) = (witness(1), witness(3));
+        let burn_seal = OutPoint::new(epoch_tx.txid(), 1);
+        let epoch = asset
+            .open_epoch(outpoint(3), None, Some(ExplicitSeal::from(burn_seal)))
+            .unwrap();
+        let bundle =
+            |transition| TransitionBundle::try_from(bmap! { transition => bset! { 0 } }).unwrap();
+        let consignment = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            empty!(),
+            vec![(anchor(epoch_tx.txid()), bundle(epoch.clone()))]
+                .try_into()
+                .unwrap(),
+            empty!(),
+        );
+        // History proof opens the allocation of 100 units, while only 99 are
+        // declared as burned
+        let burn = Asset::try_from(&consignment)
+            .unwrap()
+            .burn(burn_seal, 99, bset! { outpoint(1) }, None)
+            .unwrap();
+        let burn_id = burn.node_id();
+        let consignment = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            empty!(),
+            vec![(anchor(epoch_tx.txid()), bundle(epoch)), (anchor(burn_tx.txid()), bundle(burn))]
+                .try_into()
+                .unwrap(),
+            empty!(),
+        );
+        let asset = Asset::try_from(&consignment).unwrap();
+
+        let mut resolver = OfflineResolver::new();
+        resolver.insert(epoch_tx);
+        resolver.insert(burn_tx);
+        let proof = asset.burn_proof(&consignment, burn_id, &resolver).unwrap();
+        assert!(matches!(
+            verify_burn_proof(&proof),
+            Err(BurnProofError::AmountMismatch(node_id)) if node_id == burn_id
+        ));
+    }
+}
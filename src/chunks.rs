@@ -0,0 +1,265 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Multi-part encoding of ASCII-armored data for animated QR codes.
+//!
+//! Each part has form of `UR:<HRP>/<seq>-<total>/<checksum>/<fragment>`,
+//! where `HRP` is the [`Armor::HRP`] of the encoded type, `seq` is the
+//! 1-based part number, `checksum` identifies the encoded message and
+//! `fragment` is a piece of the Bech32m data of the armored string. All
+//! parts are uppercase, such that QR codes can use the compact alphanumeric
+//! mode. Integrity of the reassembled data is guaranteed by the Bech32m
+//! checksum of the armored string.
+
+use std::collections::BTreeMap;
+
+use bitcoin::hashes::{sha256, Hash};
+use lnpbp::bech32;
+
+use crate::Armor;
+
+/// Prefix of each encoded part
+const PART_PREFIX: &str = "UR:";
+
+/// Errors happening during reassembling of multi-part encoded data
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ChunkError {
+    /// part `{0}` is not a valid multi-part encoded data fragment
+    InvalidPart(String),
+
+    /// part belongs to data of type `{found}` while data of type `{expected}`
+    /// are being reassembled
+    TypeMismatch {
+        /// Armor prefix of the reassembled data type
+        expected: String,
+        /// Armor prefix used by the part
+        found: String,
+    },
+
+    /// part belongs to a different message than the previously received parts
+    MessageMismatch,
+
+    /// not all parts were received yet; {0} of {1} parts are known
+    Incomplete(usize, usize),
+
+    /// reassembled data are invalid: {0}
+    #[from]
+    Armor(bech32::Error),
+}
+
+/// Implementation of [`Armor::to_armored_chunks`]
+pub(crate) fn encode<T: Armor>(data: &T, fragment_len: usize) -> Vec<String> {
+    let armored = data.to_armored_string();
+    let checksum = checksum(&armored);
+    let payload = &armored[T::HRP.len() + 1..];
+    let fragments = payload
+        .as_bytes()
+        .chunks(fragment_len.max(1))
+        .map(|fragment| String::from_utf8_lossy(fragment))
+        .collect::<Vec<_>>();
+    let total = fragments.len();
+    fragments
+        .into_iter()
+        .enumerate()
+        .map(|(no, fragment)| {
+            format!(
+                "{}{}/{}-{}/{}/{}",
+                PART_PREFIX,
+                T::HRP,
+                no + 1,
+                total,
+                checksum,
+                fragment
+            )
+            .to_uppercase()
+        })
+        .collect()
+}
+
+fn checksum(armored: &str) -> String {
+    let hash = sha256::Hash::hash(armored.as_bytes());
+    hash[..4]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Reassembles data from parts produced by [`Armor::to_armored_chunks`],
+/// which may be received in any order and repeatedly, as happens when
+/// scanning animated QR codes
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ChunkDecoder {
+    hrp: Option<String>,
+    checksum: Option<String>,
+    total: usize,
+    fragments: BTreeMap<usize, String>,
+}
+
+impl ChunkDecoder {
+    /// Constructs decoder which has not received any parts yet
+    pub fn new() -> ChunkDecoder { ChunkDecoder::default() }
+
+    /// Accounts a received part. Returns `true` if all parts are received and
+    /// the data can be decoded with [`ChunkDecoder::decode`].
+    pub fn receive(&mut self, part: &str) -> Result<bool, ChunkError> {
+        let invalid = || ChunkError::InvalidPart(part.to_owned());
+        let lowercase = part.trim().to_lowercase();
+        let lowercase = lowercase
+            .strip_prefix(&PART_PREFIX.to_lowercase())
+            .ok_or_else(invalid)?;
+        let mut split = lowercase.split('/');
+        let (hrp, seq, checksum, fragment) = match (
+            split.next(),
+            split.next(),
+            split.next(),
+            split.next(),
+            split.next(),
+        ) {
+            (Some(hrp), Some(seq), Some(checksum), Some(fragment), None) => {
+                (hrp, seq, checksum, fragment)
+            }
+            _ => return Err(invalid()),
+        };
+        let (no, total) = seq.split_once('-').ok_or_else(invalid)?;
+        let no = no.parse::<usize>().map_err(|_| invalid())?;
+        let total = total.parse::<usize>().map_err(|_| invalid())?;
+        if no == 0 || no > total || fragment.is_empty() {
+            return Err(invalid());
+        }
+
+        match &self.hrp {
+            Some(expected) if expected != hrp => {
+                return Err(ChunkError::TypeMismatch {
+                    expected: expected.clone(),
+                    found: hrp.to_owned(),
+                })
+            }
+            _ => {}
+        }
+        if self
+            .checksum
+            .as_deref()
+            .map_or(false, |known| known != checksum)
+            || (self.total != 0 && self.total != total)
+        {
+            return Err(ChunkError::MessageMismatch);
+        }
+
+        self.hrp = Some(hrp.to_owned());
+        self.checksum = Some(checksum.to_owned());
+        self.total = total;
+        self.fragments.insert(no, fragment.to_owned());
+        Ok(self.is_complete())
+    }
+
+    /// Returns number of the distinct received parts and the total number of
+    /// parts, which is zero if no parts were received yet
+    pub fn progress(&self) -> (usize, usize) { (self.fragments.len(), self.total) }
+
+    /// Detects whether all parts were received
+    pub fn is_complete(&self) -> bool { self.total > 0 && self.fragments.len() == self.total }
+
+    /// Decodes data from the received parts
+    pub fn decode<T: Armor>(&self) -> Result<T, ChunkError> {
+        if !self.is_complete() {
+            return Err(ChunkError::Incomplete(self.fragments.len(), self.total));
+        }
+        let hrp = self.hrp.as_deref().unwrap_or_default();
+        if hrp != T::HRP {
+            return Err(ChunkError::TypeMismatch {
+                expected: T::HRP.to_owned(),
+                found: hrp.to_owned(),
+            });
+        }
+        let mut armored = format!("{}1", hrp);
+        armored.extend(self.fragments.values().map(String::as_str));
+        if self.checksum.as_deref() != Some(&checksum(&armored)) {
+            return Err(ChunkError::MessageMismatch);
+        }
+        Ok(T::from_armored_str(&armored)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use lnpbp::chain::Chain;
+    use rgb::{Consignment, Contract, StateTransfer};
+
+    use super::*;
+    use crate::fixtures::outpoint;
+    use crate::{AssetName, Rgb20Builder, Ticker};
+
+    fn issue(name: &str) -> Contract {
+        Rgb20Builder::new(Chain::Signet)
+            .ticker(Ticker::from_str("TICK").unwrap())
+            .name(AssetName::from_str(name).unwrap())
+            .allocate(outpoint(1), 100)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn chunks() {
+        let contract = issue("Test asset");
+        let transfer = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            empty!(),
+            empty!(),
+            empty!(),
+        );
+        let chunks = transfer.to_armored_chunks(100);
+        assert!(chunks.len() > 1);
+        assert!(chunks[0].starts_with(&format!("UR:RGBST/1-{}/", chunks.len())));
+        assert!(chunks.iter().all(|chunk| chunk == &chunk.to_uppercase()));
+
+        let mut decoder = ChunkDecoder::new();
+        assert_eq!(
+            decoder.decode::<StateTransfer>(),
+            Err(ChunkError::Incomplete(0, 0))
+        );
+        for chunk in chunks.iter().rev().skip(1) {
+            assert!(!decoder.receive(chunk).unwrap());
+        }
+        assert!(!decoder.receive(&chunks[1]).unwrap());
+        assert_eq!(decoder.progress(), (chunks.len() - 1, chunks.len()));
+        assert!(matches!(
+            decoder.decode::<StateTransfer>(),
+            Err(ChunkError::Incomplete(..))
+        ));
+        assert!(decoder.receive(chunks.last().unwrap()).unwrap());
+        assert_eq!(decoder.decode::<StateTransfer>().unwrap(), transfer);
+        assert!(matches!(
+            decoder.decode::<Contract>(),
+            Err(ChunkError::TypeMismatch { .. })
+        ));
+
+        let other = contract.to_armored_chunks(100);
+        assert!(matches!(
+            decoder.receive(&other[0]),
+            Err(ChunkError::TypeMismatch { .. })
+        ));
+        let other = issue("Other asset").to_armored_chunks(100);
+        let mut decoder = ChunkDecoder::new();
+        decoder
+            .receive(&contract.to_armored_chunks(100)[0])
+            .unwrap();
+        assert_eq!(decoder.receive(&other[1]), Err(ChunkError::MessageMismatch));
+        assert!(matches!(
+            decoder.receive("UR:RGBC/0-1/00/QQ"),
+            Err(ChunkError::InvalidPart(_))
+        ));
+    }
+}
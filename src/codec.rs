@@ -0,0 +1,122 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! CBOR serialization of the RGB20 data, used by hardware wallets and
+//! embedded devices, alongside the strict encoding.
+//!
+//! CBOR data follow the same structure as the data produced by the serde
+//! serializers of the other formats, like JSON.
+
+use rgb::{StateTransfer, Transition};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::invoice::Invoice;
+use crate::Asset;
+
+/// Errors happening during CBOR serialization
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum CborError {
+    /// unable to encode data into CBOR: {0}
+    Encoding(String),
+
+    /// invalid CBOR data: {0}
+    Decoding(String),
+}
+
+/// Data which can be serialized into CBOR
+pub trait Cbor: Serialize + DeserializeOwned {
+    /// Serializes data into CBOR
+    fn to_cbor(&self) -> Result<Vec<u8>, CborError> {
+        let mut data = vec![];
+        ciborium::ser::into_writer(self, &mut data)
+            .map_err(|err| CborError::Encoding(err.to_string()))?;
+        Ok(data)
+    }
+
+    /// Deserializes data from CBOR
+    fn from_cbor(data: impl AsRef<[u8]>) -> Result<Self, CborError> {
+        ciborium::de::from_reader(data.as_ref()).map_err(|err| CborError::Decoding(err.to_string()))
+    }
+}
+
+impl Cbor for Asset {}
+impl Cbor for Transition {}
+impl Cbor for StateTransfer {}
+impl Cbor for Invoice {}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use lnpbp::chain::Chain;
+    use rgb::{seal, Consignment};
+
+    use super::*;
+    use crate::invoice::Beneficiary;
+    use crate::{vectors, AssetAmount, AssetName, Rgb20Builder, Ticker};
+
+    #[test]
+    fn cbor() {
+        let outpoint = vectors::outpoint(1);
+        let contract = Rgb20Builder::new(Chain::Signet)
+            .ticker(Ticker::from_str("TICK").unwrap())
+            .name(AssetName::from_str("Test asset").unwrap())
+            .allocate(outpoint, 100)
+            .inflation_right(outpoint, 1000)
+            .build()
+            .unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(Asset::from_cbor(asset.to_cbor().unwrap()).unwrap(), asset);
+
+        let transfer = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            empty!(),
+            empty!(),
+            empty!(),
+        );
+        assert_eq!(
+            StateTransfer::from_cbor(transfer.to_cbor().unwrap()).unwrap(),
+            transfer
+        );
+
+        let transition = asset
+            .transfer(
+                bset! { outpoint },
+                empty!(),
+                bmap! { seal::Revealed::from(outpoint) => 100 },
+            )
+            .unwrap();
+        assert_eq!(
+            Transition::from_cbor(transition.to_cbor().unwrap()).unwrap(),
+            transition
+        );
+
+        let invoice = Invoice {
+            contract_id: contract.contract_id(),
+            amount: AssetAmount::with_atomic(100, 8).unwrap(),
+            beneficiary: Beneficiary::BlindedSeal(seal::Confidential::from(outpoint)),
+            expiry: None,
+        };
+        assert_eq!(
+            Invoice::from_cbor(invoice.to_cbor().unwrap()).unwrap(),
+            invoice
+        );
+
+        assert!(matches!(
+            Asset::from_cbor(invoice.to_cbor().unwrap()),
+            Err(CborError::Decoding(_))
+        ));
+    }
+}
@@ -0,0 +1,335 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeMap;
+
+use rgb::{ContractId, Genesis};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+
+use crate::{Asset, Nomination};
+
+/// Default minimal similarity score, in percents, for the nominations to be
+/// reported as colliding
+pub const DEFAULT_SIMILARITY_THRESHOLD: u8 = 80;
+
+/// Characters looking alike, mapped to the same representative. Applied after
+/// conversion to the upper case.
+const HOMOGLYPHS: [(char, char); 9] = [
+    ('0', 'O'),
+    ('Q', 'O'),
+    ('1', 'I'),
+    ('L', 'I'),
+    ('|', 'I'),
+    ('!', 'I'),
+    ('5', 'S'),
+    ('8', 'B'),
+    ('2', 'Z'),
+];
+
+/// Character sequences looking alike a single character. Applied after
+/// conversion to the upper case.
+const HOMOGLYPH_SEQUENCES: [(&str, &str); 2] = [("RN", "M"), ("VV", "W")];
+
+/// Kind of the nomination collision
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub enum CollisionKind {
+    /// ticker or name is identical to the one of the known asset (ignoring
+    /// letter case and whitespaces)
+    #[display("exact")]
+    Exact,
+
+    /// ticker or name differs from the one of the known asset only by
+    /// characters looking alike, like `0` and `O`
+    #[display("homoglyph")]
+    Homoglyph,
+
+    /// ticker or name is similar to the one of the known asset
+    #[display("similar")]
+    Similar,
+}
+
+/// Known asset which nomination collides with the checked one
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Collision {
+    /// Id of the known asset contract
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub contract_id: ContractId,
+
+    /// Nomination of the known asset
+    pub nomination: Nomination,
+
+    /// Similarity of the tickers, in percents
+    pub ticker_score: u8,
+
+    /// Similarity of the asset names, in percents
+    pub name_score: u8,
+
+    /// The most severe kind of collision of the ticker or name
+    pub kind: CollisionKind,
+}
+
+impl Collision {
+    /// Returns maximum of the ticker and name similarity scores
+    pub fn score(&self) -> u8 { self.ticker_score.max(self.name_score) }
+}
+
+/// Index of the known asset nominations, detecting new assets which tickers
+/// or names collide with the known ones.
+///
+/// Wallets and explorers should warn users about such assets, since they may
+/// be spoofing well-known assets. Similarity of tickers and names is measured
+/// by the edit distance of their normalized forms, where letter case and
+/// whitespaces are ignored and characters looking alike (homoglyphs) are
+/// considered equal.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TickerIndex {
+    assets: BTreeMap<ContractId, Nomination>,
+    threshold: u8,
+}
+
+impl Default for TickerIndex {
+    fn default() -> Self {
+        TickerIndex {
+            assets: empty!(),
+            threshold: DEFAULT_SIMILARITY_THRESHOLD,
+        }
+    }
+}
+
+impl TickerIndex {
+    /// Constructs empty index using [`DEFAULT_SIMILARITY_THRESHOLD`]
+    pub fn new() -> TickerIndex { TickerIndex::default() }
+
+    /// Constructs empty index reporting collisions with similarity score
+    /// (in percents) of at least `threshold`
+    pub fn with_threshold(threshold: u8) -> TickerIndex {
+        TickerIndex {
+            assets: empty!(),
+            threshold: threshold.min(100),
+        }
+    }
+
+    /// Returns similarity threshold of the index, in percents
+    pub fn threshold(&self) -> u8 { self.threshold }
+
+    /// Registers nomination of a known asset, returning the nomination
+    /// previously registered for the same contract, if any
+    pub fn register(
+        &mut self,
+        contract_id: ContractId,
+        nomination: Nomination,
+    ) -> Option<Nomination> {
+        self.assets.insert(contract_id, nomination)
+    }
+
+    /// Registers currently effective nomination of the asset, falling back to
+    /// the genesis nomination if the renomination history is inconsistent
+    pub fn register_asset(&mut self, asset: &Asset) -> Option<Nomination> {
+        let nomination = asset
+            .renominations()
+            .map(|renominations| renominations.current)
+            .unwrap_or_else(|_| asset.genesis_nomination());
+        self.register(asset.contract_id(), nomination)
+    }
+
+    /// Removes asset from the index, returning its nomination
+    pub fn remove(&mut self, contract_id: ContractId) -> Option<Nomination> {
+        self.assets.remove(&contract_id)
+    }
+
+    /// Returns nomination registered for the contract
+    pub fn get(&self, contract_id: ContractId) -> Option<&Nomination> {
+        self.assets.get(&contract_id)
+    }
+
+    /// Returns number of the registered assets
+    pub fn len(&self) -> usize { self.assets.len() }
+
+    /// Detects whether the index has no registered assets
+    pub fn is_empty(&self) -> bool { self.assets.is_empty() }
+
+    /// Finds known assets colliding with the `nomination`, ordered by
+    /// descending similarity score. The asset with the `contract_id`, if
+    /// registered, is not reported.
+    pub fn collisions(&self, contract_id: ContractId, nomination: &Nomination) -> Vec<Collision> {
+        let ticker = normalize(&nomination.ticker);
+        let name = normalize(&nomination.name);
+        let mut collisions = self
+            .assets
+            .iter()
+            .filter(|(id, _)| **id != contract_id)
+            .filter_map(|(id, known)| {
+                let (ticker_score, ticker_kind) = compare(&ticker, &normalize(&known.ticker));
+                let (name_score, name_kind) = compare(&name, &normalize(&known.name));
+                let collision = Collision {
+                    contract_id: *id,
+                    nomination: known.clone(),
+                    ticker_score,
+                    name_score,
+                    kind: ticker_kind.min(name_kind),
+                };
+                Some(collision).filter(|collision| collision.score() >= self.threshold)
+            })
+            .collect::<Vec<_>>();
+        collisions.sort_by(|a, b| b.score().cmp(&a.score()).then(a.kind.cmp(&b.kind)));
+        collisions
+    }
+
+    /// Finds known assets colliding with the nomination defined by the
+    /// `genesis` of a new asset, like [`TickerIndex::collisions`]
+    pub fn check_genesis(&self, genesis: &Genesis) -> Vec<Collision> {
+        self.collisions(genesis.contract_id(), &Nomination::default().apply(genesis))
+    }
+}
+
+/// Normalized form of the string: upper case, without whitespaces, in the
+/// raw (0) and homoglyph-folded (1) forms
+struct Normalized(Vec<char>, Vec<char>);
+
+fn normalize(s: &str) -> Normalized {
+    let raw = s
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect::<String>();
+    let mut folded = raw.clone();
+    for (seq, replacement) in HOMOGLYPH_SEQUENCES {
+        folded = folded.replace(seq, replacement);
+    }
+    let folded = folded
+        .chars()
+        .map(|c| {
+            HOMOGLYPHS
+                .iter()
+                .find(|(glyph, _)| *glyph == c)
+                .map(|(_, replacement)| *replacement)
+                .unwrap_or(c)
+        })
+        .collect();
+    Normalized(raw.chars().collect(), folded)
+}
+
+fn compare(a: &Normalized, b: &Normalized) -> (u8, CollisionKind) {
+    if a.0 == b.0 {
+        return (100, CollisionKind::Exact);
+    }
+    if a.1 == b.1 {
+        return (100, CollisionKind::Homoglyph);
+    }
+    (similarity(&a.1, &b.1), CollisionKind::Similar)
+}
+
+/// Similarity in percents, based on the Levenshtein distance
+fn similarity(a: &[char], b: &[char]) -> u8 {
+    let len = a.len().max(b.len());
+    if len == 0 {
+        return 100;
+    }
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.iter().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev + (ca != cb) as usize;
+            prev = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(prev + 1);
+        }
+    }
+    let distance = row[b.len()];
+    ((len - distance) * 100 / len) as u8
+}
+
+#[cfg(test)]
+mod test {
+    use commit_verify::TaggedHash;
+
+    use super::*;
+
+    fn nomination(ticker: &str, name: &str) -> Nomination {
+        Nomination {
+            ticker: ticker.to_owned(),
+            name: name.to_owned(),
+            precision: 8,
+        }
+    }
+
+    fn contract_id(no: u8) -> ContractId { ContractId::from_array([no; 32]) }
+
+    #[test]
+    fn similarity_scores() {
+        let score = |a: &str, b: &str| similarity(&normalize(a).1, &normalize(b).1);
+        assert_eq!(score("USDT", "USDT"), 100);
+        assert_eq!(score("USDT", "USDC"), 75);
+        assert_eq!(score("USDT", "BTC"), 0);
+        assert_eq!(score("", ""), 100);
+        assert_eq!(
+            compare(&normalize("Tether USD"), &normalize("tether usd")),
+            (100, CollisionKind::Exact)
+        );
+        assert_eq!(
+            compare(&normalize("Tether USD"), &normalize("Tether U5D")),
+            (100, CollisionKind::Homoglyph)
+        );
+        assert_eq!(
+            compare(&normalize("Modern"), &normalize("Mociern")).1,
+            CollisionKind::Similar
+        );
+        assert_eq!(
+            compare(&normalize("Modern"), &normalize("Modem")),
+            (100, CollisionKind::Homoglyph)
+        );
+    }
+
+    #[test]
+    fn index_collisions() {
+        let mut index = TickerIndex::new();
+        assert!(index.is_empty());
+        index.register(contract_id(1), nomination("USDT", "Tether USD"));
+        index.register(contract_id(2), nomination("BTC", "Bitcoin"));
+        index.register(contract_id(3), nomination("EURT", "Tether EUR"));
+        assert_eq!(index.len(), 3);
+
+        let collisions = index.collisions(contract_id(4), &nomination("USDT", "Tether"));
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].contract_id, contract_id(1));
+        assert_eq!(collisions[0].kind, CollisionKind::Exact);
+
+        let collisions = index.collisions(contract_id(4), &nomination("USDI", "Tether U5D"));
+        assert_eq!(collisions[0].contract_id, contract_id(1));
+        assert_eq!(collisions[0].name_score, 100);
+        assert_eq!(collisions[0].kind, CollisionKind::Homoglyph);
+
+        assert!(index
+            .collisions(contract_id(1), &nomination("USDT", "Tether USD"))
+            .is_empty());
+        assert!(index
+            .collisions(contract_id(4), &nomination("DOGE", "Dogecoin"))
+            .is_empty());
+
+        let mut loose = TickerIndex::with_threshold(50);
+        loose.register(contract_id(2), nomination("BTC", "Bitcoin"));
+        let collisions = loose.collisions(contract_id(4), &nomination("DOGE", "Dogecoin"));
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].name_score, 50);
+        assert_eq!(collisions[0].kind, CollisionKind::Similar);
+    }
+}
@@ -0,0 +1,176 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use bitcoin::schnorr::UntweakedPublicKey;
+use bitcoin::secp256k1::{Secp256k1, Verification};
+use bitcoin::{Script, Transaction, TxOut};
+use seals::txout::CloseMethod;
+
+use crate::{opret, tapret};
+
+/// Errors verifying deterministic bitcoin commitment of a witness transaction
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum CommitmentError {
+    /// witness transaction has no output which can host {0} commitment
+    NoHost(CloseMethod),
+
+    /// witness transaction output #{0} hosting the commitment does not commit
+    /// to the provided message
+    Mismatch(u32),
+}
+
+/// Method of committing to RGB20 state transition bundles in witness
+/// transactions, together with the data required for constructing the
+/// commitment. Each of the methods corresponds to a seal [`CloseMethod`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CommitmentMethod {
+    /// Tapret commitment in the first taproot output of the witness
+    /// transaction, which has no script leaves other than the commitment
+    Tapret {
+        /// Internal key of the taproot output
+        internal_key: UntweakedPublicKey,
+        /// Commitment nonce
+        nonce: u8,
+    },
+
+    /// Opret commitment in the first `OP_RETURN` output of the witness
+    /// transaction
+    Opret,
+}
+
+impl From<CommitmentMethod> for CloseMethod {
+    fn from(method: CommitmentMethod) -> Self { method.close_method() }
+}
+
+impl CommitmentMethod {
+    /// Returns seal closing method matching the commitment method
+    pub fn close_method(self) -> CloseMethod {
+        match self {
+            CommitmentMethod::Tapret { .. } => CloseMethod::TapretFirst,
+            CommitmentMethod::Opret => CloseMethod::OpretFirst,
+        }
+    }
+
+    /// Constructs script pubkey of the output hosting commitment to the
+    /// LNPBP-4 commitment `mpc`
+    pub fn script_pubkey<C: Verification>(self, secp: &Secp256k1<C>, mpc: [u8; 32]) -> Script {
+        match self {
+            CommitmentMethod::Tapret {
+                internal_key,
+                nonce,
+            } => tapret::script_pubkey(secp, internal_key, mpc, nonce),
+            CommitmentMethod::Opret => opret::commitment_script(mpc),
+        }
+    }
+
+    /// Constructs output hosting commitment to the LNPBP-4 commitment `mpc`,
+    /// with the given amount of satoshis. Opret outputs are always
+    /// zero-valued.
+    pub fn host_output<C: Verification>(
+        self,
+        secp: &Secp256k1<C>,
+        mpc: [u8; 32],
+        value: u64,
+    ) -> TxOut {
+        match self {
+            CommitmentMethod::Opret => opret::commitment_output(mpc),
+            method => TxOut {
+                value,
+                script_pubkey: method.script_pubkey(secp, mpc),
+            },
+        }
+    }
+
+    /// Returns number of the witness transaction output which must host the
+    /// commitment according to the output position rules of the method
+    pub fn host_vout(self, tx: &Transaction) -> Option<u32> {
+        match self {
+            CommitmentMethod::Tapret { .. } => tx
+                .output
+                .iter()
+                .position(|output| output.script_pubkey.is_v1_p2tr())
+                .map(|vout| vout as u32),
+            CommitmentMethod::Opret => opret::host_output(tx),
+        }
+    }
+
+    /// Verifies that the witness transaction commits to `mpc`, returning the
+    /// number of the output hosting the commitment
+    pub fn verify<C: Verification>(
+        self,
+        secp: &Secp256k1<C>,
+        tx: &Transaction,
+        mpc: [u8; 32],
+    ) -> Result<u32, CommitmentError> {
+        let vout = self
+            .host_vout(tx)
+            .ok_or_else(|| CommitmentError::NoHost(self.close_method()))?;
+        if tx.output[vout as usize].script_pubkey != self.script_pubkey(secp, mpc) {
+            return Err(CommitmentError::Mismatch(vout));
+        }
+        Ok(vout)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::PackedLockTime;
+
+    use super::*;
+
+    fn tx(output: Vec<TxOut>) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![],
+            output,
+        }
+    }
+
+    #[test]
+    fn commitment_positions() {
+        let secp = Secp256k1::verification_only();
+        let mpc = [1u8; 32];
+        let tapret = CommitmentMethod::Tapret {
+            internal_key: UntweakedPublicKey::from_str(
+                "1b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f",
+            )
+            .unwrap(),
+            nonce: 0,
+        };
+        let opret = CommitmentMethod::Opret;
+        assert_eq!(CloseMethod::from(tapret), CloseMethod::TapretFirst);
+        assert_eq!(CloseMethod::from(opret), CloseMethod::OpretFirst);
+
+        let payment = TxOut {
+            value: 1000,
+            script_pubkey: Script::new(),
+        };
+        let witness = tx(vec![
+            payment.clone(),
+            tapret.host_output(&secp, mpc, 1000),
+            opret.host_output(&secp, mpc, 1000),
+        ]);
+        assert_eq!(tapret.verify(&secp, &witness, mpc), Ok(1));
+        assert_eq!(opret.verify(&secp, &witness, mpc), Ok(2));
+        assert_eq!(
+            opret.verify(&secp, &witness, [2u8; 32]),
+            Err(CommitmentError::Mismatch(2))
+        );
+        assert_eq!(
+            tapret.verify(&secp, &tx(vec![payment]), mpc),
+            Err(CommitmentError::NoHost(CloseMethod::TapretFirst))
+        );
+    }
+}
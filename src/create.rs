@@ -10,33 +10,212 @@
 // If not, see <https://opensource.org/licenses/MIT>.
 
 use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::str::FromStr;
 
+use bitcoin::hashes::{sha256, Hash, HashEngine};
 use bitcoin::OutPoint;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use lnpbp::chain::Chain;
-use rgb::fungible::allocation::{AllocationMap, OutpointValueMap, OutpointValueVec};
+use rgb::fungible::allocation::{
+    AllocatedValue, OutpointValue, OutpointValueMap, OutpointValueVec, ParseError, UtxobValue,
+};
 use rgb::{
-    data, secp256k1zkp, value, Assignment, Consignment, Contract, Genesis, SealValueMap,
+    data, seal, secp256k1zkp, value, Assignment, AtomicValue, Consignment, Contract, Genesis,
     TypedAssignments,
 };
-use seals::txout::CloseMethod;
-use stens::AsciiString;
+use seals::txout::{CloseMethod, ExplicitSeal};
+use strict_encoding::StrictEncode;
+
+use crate::schema::{FieldType, OwnedRightType, Subschema};
+use crate::{
+    AssetName, Attachment, AttachmentType, NominationPolicy, PolicyError, Ticker, MAX_PRECISION,
+};
+
+/// Tag for deriving genesis blinding factors
+const GENESIS_BLINDING_TAG: &[u8] = b"rgb20:genesis:blinding";
+
+/// Earliest genesis timestamp accepted by RGB20 schema validation, equal to
+/// 10/10/2020 @ 2:37pm (UTC), since no RGB assets existed before that date
+pub const MIN_TIMESTAMP: i64 = 1_602_340_666;
+
+/// Seal receiving asset allocation defined by the genesis. Witness
+/// transaction outputs can't be used, since the genesis has no witness
+/// transaction.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, From)]
+pub enum AllocationTarget {
+    /// Transaction outpoint revealed to the issuer, in form of
+    /// `<txid>:<vout>`
+    #[display(inner)]
+    #[from]
+    Outpoint(OutPoint),
+
+    /// Blinded transaction outpoint provided by a beneficiary who does not
+    /// reveal the outpoint to the issuer, in form of `utxob1...` string
+    #[display(inner)]
+    #[from]
+    Blinded(seal::Confidential),
+}
+
+impl FromStr for AllocationTarget {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains(':') {
+            Ok(AllocationTarget::Outpoint(s.parse()?))
+        } else {
+            Ok(AllocationTarget::Blinded(s.parse()?))
+        }
+    }
+}
+
+/// Asset allocation defined by the genesis
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[display("{value}@{target}")]
+pub struct GenesisAllocation {
+    /// Seal receiving the allocation
+    pub target: AllocationTarget,
+
+    /// Allocated value of the asset
+    pub value: AtomicValue,
+}
+
+impl From<OutpointValue> for GenesisAllocation {
+    fn from(allocation: OutpointValue) -> Self {
+        GenesisAllocation {
+            target: allocation.outpoint.into(),
+            value: allocation.value,
+        }
+    }
+}
+
+impl From<UtxobValue> for GenesisAllocation {
+    fn from(allocation: UtxobValue) -> Self {
+        GenesisAllocation {
+            target: allocation.seal_confidential.into(),
+            value: allocation.value,
+        }
+    }
+}
 
-use crate::schema;
-use crate::schema::{FieldType, OwnedRightType};
+/// Inflation allowance assigned to the seal `S` exceeds maximum 64-bit value
+/// once all allowances for the seal are merged
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct InflationOverflow<S>(pub S);
+
+impl<S: Display> Display for InflationOverflow<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "total inflation allowance assigned to {} exceeds maximum 64-bit value",
+            self.0
+        )
+    }
+}
+
+impl<S: Display + Debug> std::error::Error for InflationOverflow<S> {}
+
+/// Inflation rights (secondary issue allowances) assigned to seals of type
+/// `S`: outpoints for the genesis and explicit seals for the secondary
+/// issuance.
+///
+/// A seal may hold only a single inflation right, so allowances provided for
+/// the same seal multiple times are merged into one.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct InflationAllocations<S = OutPoint>(BTreeMap<S, AtomicValue>)
+where S: Ord;
+
+impl<S: Ord> Default for InflationAllocations<S> {
+    fn default() -> Self { InflationAllocations(empty!()) }
+}
+
+impl<S> InflationAllocations<S>
+where S: Ord + Copy
+{
+    /// Merges allowances assigned to the same seal. Errors if the merged
+    /// allowance for some seal exceeds maximum 64-bit value.
+    pub fn from_values(
+        values: impl IntoIterator<Item = (S, AtomicValue)>,
+    ) -> Result<Self, InflationOverflow<S>> {
+        let mut map = BTreeMap::<S, AtomicValue>::new();
+        for (seal, value) in values {
+            let allowance = map.entry(seal).or_insert(0);
+            *allowance = allowance
+                .checked_add(value)
+                .ok_or(InflationOverflow(seal))?;
+        }
+        Ok(InflationAllocations(map))
+    }
+
+    /// Returns total inflation allowance, or `None` if it exceeds maximum
+    /// 64-bit value
+    pub fn total(&self) -> Option<AtomicValue> {
+        self.0
+            .values()
+            .try_fold(0u64, |sum, value| sum.checked_add(*value))
+    }
+
+    /// Returns allowance assigned to the seal, if any
+    pub fn get(&self, seal: &S) -> Option<AtomicValue> { self.0.get(seal).copied() }
+
+    /// Returns number of seals holding inflation rights
+    pub fn len(&self) -> usize { self.0.len() }
+
+    /// Detects whether there are no inflation rights
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    /// Returns map of allowances per seal
+    pub fn as_inner(&self) -> &BTreeMap<S, AtomicValue> { &self.0 }
+
+    /// Converts into map of allowances per seal
+    pub fn into_inner(self) -> BTreeMap<S, AtomicValue> { self.0 }
+}
+
+impl InflationAllocations<OutPoint> {
+    /// Merges genesis inflation allowances assigned to the same outpoint
+    pub fn from_outpoint_values(
+        values: impl IntoIterator<Item = OutpointValue>,
+    ) -> Result<Self, InflationOverflow<OutPoint>> {
+        Self::from_values(values.into_iter().map(|v| (v.outpoint, v.value)))
+    }
+}
+
+impl InflationAllocations<ExplicitSeal> {
+    /// Merges secondary issue allowances assigned to the same seal
+    pub fn from_allocated_values(
+        values: impl IntoIterator<Item = AllocatedValue>,
+    ) -> Result<Self, InflationOverflow<ExplicitSeal>> {
+        Self::from_values(values.into_iter().map(|v| (v.seal, v.value)))
+    }
+}
+
+/// Constructs from a map which already has a single allowance per seal
+impl<S: Ord> From<BTreeMap<S, AtomicValue>> for InflationAllocations<S> {
+    fn from(map: BTreeMap<S, AtomicValue>) -> Self { InflationAllocations(map) }
+}
+
+impl<S: Ord> From<InflationAllocations<S>> for BTreeMap<S, AtomicValue> {
+    fn from(allocations: InflationAllocations<S>) -> Self { allocations.0 }
+}
 
 /// Extension trait for consignments defining RGB20-specific API.
 #[allow(clippy::too_many_arguments)]
 pub trait Rgb20<'consignment>: Consignment<'consignment> {
     /// Performs primary asset issue, producing [`Contract`] consignment.
+    ///
+    /// The asset is issued under the full RGB20 schema with the current time
+    /// as the genesis timestamp; the provided data are not validated. Use
+    /// [`Rgb20Builder`] to select other subschema, timestamp, media
+    /// attachments or Ricardian contract, and to allocate the asset to
+    /// blinded seals.
     fn create_rgb20(
         chain: Chain,
-        ticker: AsciiString,
-        name: AsciiString,
+        ticker: Ticker,
+        name: AssetName,
         precision: u8,
         allocations: OutpointValueVec,
         inflation: OutpointValueMap,
-        method: CloseMethod,
+        method: impl Into<CloseMethod>,
         renomination: Option<OutPoint>,
         epoch: Option<OutPoint>,
     ) -> Contract;
@@ -45,87 +224,724 @@ pub trait Rgb20<'consignment>: Consignment<'consignment> {
 impl<'consignment> Rgb20<'consignment> for Contract {
     fn create_rgb20(
         chain: Chain,
-        ticker: AsciiString,
-        name: AsciiString,
+        ticker: Ticker,
+        name: AssetName,
         precision: u8,
         allocations: OutpointValueVec,
         inflation: OutpointValueMap,
-        method: CloseMethod,
+        method: impl Into<CloseMethod>,
         renomination: Option<OutPoint>,
         epoch: Option<OutPoint>,
     ) -> Contract {
+        let builder = Rgb20Builder {
+            precision,
+            allocations: allocations
+                .into_iter()
+                .map(GenesisAllocation::from)
+                .collect(),
+            method: method.into(),
+            renomination,
+            epoch,
+            ..Rgb20Builder::new(chain)
+        };
+        builder.issue(ticker, name, inflation, Utc::now().timestamp())
+    }
+}
+
+/// Deterministic source of blinding factors for the genesis seals and
+/// allocated values
+struct GenesisBlinding {
+    secp: secp256k1zkp::Secp256k1,
+    seed: sha256::Hash,
+    counter: u64,
+}
+
+impl GenesisBlinding {
+    fn with(
+        builder: &Rgb20Builder,
+        ticker: &Ticker,
+        name: &AssetName,
+        inflation: &OutpointValueMap,
+        timestamp: i64,
+    ) -> GenesisBlinding {
+        let seed = (|| -> Result<_, strict_encoding::Error> {
+            let mut engine = sha256::Hash::engine();
+            engine.input(GENESIS_BLINDING_TAG);
+            strict_encode_list!(engine;
+                builder.chain, ticker.as_str(), name.as_str(), builder.precision,
+                builder.method as u8, builder.renomination, builder.epoch, timestamp
+            );
+            for GenesisAllocation { target, value } in &builder.allocations {
+                match target {
+                    AllocationTarget::Outpoint(outpoint) => {
+                        strict_encode_list!(engine; 0u8, outpoint, value)
+                    }
+                    AllocationTarget::Blinded(seal) => {
+                        strict_encode_list!(engine; 1u8, seal, value)
+                    }
+                };
+            }
+            for (outpoint, value) in inflation {
+                strict_encode_list!(engine; outpoint, value);
+            }
+            Ok(sha256::Hash::from_engine(engine))
+        })()
+        .expect("in-memory hash engine does not fail");
+        GenesisBlinding {
+            secp: secp256k1zkp::Secp256k1::with_caps(secp256k1zkp::ContextFlag::Commit),
+            seed,
+            counter: 0,
+        }
+    }
+
+    fn next(&mut self) -> sha256::Hash {
+        let mut engine = sha256::Hash::engine();
+        engine.input(&self.seed[..]);
+        engine.input(&self.counter.to_le_bytes());
+        self.counter += 1;
+        sha256::Hash::from_engine(engine)
+    }
+
+    fn seal(&mut self, method: CloseMethod, outpoint: OutPoint) -> seal::Revealed {
+        let mut blinding = [0u8; 8];
+        blinding.copy_from_slice(&self.next()[..8]);
+        seal::Revealed {
+            method,
+            txid: Some(outpoint.txid),
+            vout: outpoint.vout,
+            blinding: u64::from_le_bytes(blinding),
+        }
+    }
+
+    fn value(&mut self) -> secp256k1zkp::SecretKey {
+        loop {
+            let entropy = self.next();
+            if let Ok(key) = secp256k1zkp::SecretKey::from_slice(&self.secp, &entropy[..]) {
+                return key;
+            }
+        }
+    }
+
+    /// Constructs allocated value states with blinding factors summing up to
+    /// the blinding factor of the issued supply, i.e.
+    /// [`secp256k1zkp::key::ONE_KEY`]
+    fn zero_balanced(&mut self, values: Vec<AtomicValue>) -> Vec<value::Revealed> {
+        let mut factors = Vec::with_capacity(values.len());
+        for _ in 1..values.len() {
+            factors.push(self.value());
+        }
+        if !values.is_empty() {
+            let correction = self
+                .secp
+                .blind_sum(vec![secp256k1zkp::key::ONE_KEY], factors.clone())
+                .expect("secp256k1zkp failure has negligible probability");
+            factors.push(correction);
+        }
+        values
+            .into_iter()
+            .zip(factors)
+            .map(|(value, blinding)| value::Revealed {
+                value,
+                blinding: blinding.into(),
+            })
+            .collect()
+    }
+}
+
+/// Errors happening during construction of RGB20 genesis with [`Rgb20Builder`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum IssueError {
+    /// asset ticker is not provided
+    TickerMissing,
+
+    /// asset name is not provided
+    NameMissing,
+
+    /// decimal precision {0} exceeds the maximum of 18 digits
+    PrecisionOverflow(u8),
+
+    /// total issued supply exceeds maximum 64-bit value
+    SupplyOverflow,
+
+    /// total inflation allowance assigned to the outpoint {0} exceeds maximum
+    /// 64-bit value
+    InflationOverflow(OutPoint),
+
+    /// genesis timestamp {0} precedes the earliest timestamp allowed by RGB20
+    /// schema
+    TimestampTooEarly(i64),
+
+    /// genesis timestamp {0} is in the future
+    TimestampInFuture(i64),
+
+    /// {0} schema does not support media attachments
+    AttachmentsUnsupported(Subschema),
+
+    /// {0} schema does not support Ricardian contract text
+    ContractTextUnsupported(Subschema),
+
+    /// asset nomination violates the nomination policy: {0}
+    Policy(PolicyError),
+}
+
+/// Builder for RGB20 asset genesis, providing more readable alternative to
+/// [`Rgb20::create_rgb20`].
+///
+/// # Example
+///
+/// ```ignore
+/// let contract = Rgb20Builder::new(Chain::Signet)
+///     .ticker(ticker)
+///     .name(name)
+///     .precision(2)
+///     .allocate(outpoint, 100_000)
+///     .close_method(CloseMethod::OpretFirst)
+///     .build()?;
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Rgb20Builder {
+    chain: Chain,
+    subschema: Subschema,
+    ticker: Option<Ticker>,
+    name: Option<AssetName>,
+    precision: u8,
+    allocations: Vec<GenesisAllocation>,
+    inflation: Vec<OutpointValue>,
+    method: CloseMethod,
+    renomination: Option<OutPoint>,
+    epoch: Option<OutPoint>,
+    timestamp: Option<i64>,
+    attachments: BTreeMap<AttachmentType, Attachment>,
+    contract_text: Option<String>,
+    policy: NominationPolicy,
+}
+
+impl Rgb20Builder {
+    /// Constructs builder for an asset issued on a given `chain`, using the
+    /// full RGB20 schema, decimal precision of 8 digits and tapret seal
+    /// closing method by default.
+    pub fn new(chain: Chain) -> Rgb20Builder {
+        Rgb20Builder {
+            chain,
+            subschema: Subschema::Full,
+            ticker: None,
+            name: None,
+            precision: 8,
+            allocations: empty!(),
+            inflation: empty!(),
+            method: CloseMethod::TapretFirst,
+            renomination: None,
+            epoch: None,
+            timestamp: None,
+            attachments: empty!(),
+            contract_text: None,
+            policy: NominationPolicy::lnpbp20(),
+        }
+    }
+
+    /// Sets RGB20 subschema the asset is issued under
+    pub fn subschema(mut self, subschema: Subschema) -> Self {
+        self.subschema = subschema;
+        self
+    }
+
+    /// Sets asset ticker
+    pub fn ticker(mut self, ticker: Ticker) -> Self {
+        self.ticker = Some(ticker);
+        self
+    }
+
+    /// Sets asset name
+    pub fn name(mut self, name: AssetName) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets decimal precision, i.e. number of digits reserved for the
+    /// fractional part
+    pub fn precision(mut self, precision: u8) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Allocates `value` of the asset to the given `target`, which may be
+    /// either revealed or blinded outpoint. May be called multiple times for
+    /// the same target.
+    pub fn allocate(mut self, target: impl Into<AllocationTarget>, value: AtomicValue) -> Self {
+        self.allocations.push(GenesisAllocation {
+            target: target.into(),
+            value,
+        });
+        self
+    }
+
+    /// Assigns inflation (secondary issuance) right for up to `value` of the
+    /// asset to the given `outpoint`. Multiple calls for the same outpoint sum
+    /// up the allowances.
+    pub fn inflation_right(mut self, outpoint: OutPoint, value: AtomicValue) -> Self {
+        self.inflation.push(OutpointValue { value, outpoint });
+        self
+    }
+
+    /// Enables renomination procedure, assigning the right to the `outpoint`
+    pub fn renomination(mut self, outpoint: OutPoint) -> Self {
+        self.renomination = Some(outpoint);
+        self
+    }
+
+    /// Enables epoch-based burn & replace procedure, assigning the right to
+    /// open the first epoch to the `outpoint`
+    pub fn epoch(mut self, outpoint: OutPoint) -> Self {
+        self.epoch = Some(outpoint);
+        self
+    }
+
+    /// Sets the method for closing all genesis-defined seals; accepts either
+    /// [`CloseMethod`] or [`crate::CommitmentMethod`]
+    pub fn close_method(mut self, method: impl Into<CloseMethod>) -> Self {
+        self.method = method.into();
+        self
+    }
+
+    /// Sets genesis timestamp, which otherwise defaults to the time the
+    /// contract is built
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp.timestamp());
+        self
+    }
+
+    /// Attaches media of the given type, replacing previous attachment of the
+    /// same type. Requires [`Subschema::Media`] to be used.
+    pub fn attach(mut self, ty: AttachmentType, attachment: Attachment) -> Self {
+        self.attachments.insert(ty, attachment);
+        self
+    }
+
+    /// Sets Ricardian contract text: either the contract itself or its double
+    /// SHA256 hash in hexadecimal form, optionally followed by `\n` and the
+    /// contract URL. Requires [`Subschema::Media`] to be used.
+    pub fn contract_text(mut self, text: impl ToString) -> Self {
+        self.contract_text = Some(text.to_string());
+        self
+    }
+
+    /// Sets policy which the asset ticker and name must comply with, which
+    /// defaults to [`NominationPolicy::lnpbp20`]
+    pub fn policy(mut self, policy: NominationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Validates provided data and constructs the asset [`Contract`]
+    pub fn build(mut self) -> Result<Contract, IssueError> {
+        let ticker = self.ticker.take().ok_or(IssueError::TickerMissing)?;
+        let name = self.name.take().ok_or(IssueError::NameMissing)?;
+        self.policy
+            .check_ticker(&ticker)
+            .and_then(|_| self.policy.check_name(&name))
+            .map_err(IssueError::Policy)?;
+        if self.precision > MAX_PRECISION {
+            return Err(IssueError::PrecisionOverflow(self.precision));
+        }
+
+        if !self.attachments.is_empty() && self.subschema != Subschema::Media {
+            return Err(IssueError::AttachmentsUnsupported(self.subschema));
+        }
+        if self.contract_text.is_some() && self.subschema != Subschema::Media {
+            return Err(IssueError::ContractTextUnsupported(self.subschema));
+        }
+
         let now = Utc::now().timestamp();
+        let timestamp = self.timestamp.unwrap_or(now);
+        if timestamp < MIN_TIMESTAMP {
+            return Err(IssueError::TimestampTooEarly(timestamp));
+        }
+        if timestamp > now {
+            return Err(IssueError::TimestampInFuture(timestamp));
+        }
+
+        self.allocations
+            .iter()
+            .try_fold(0u64, |sum, a| sum.checked_add(a.value))
+            .ok_or(IssueError::SupplyOverflow)?;
+
+        let inflation = InflationAllocations::from_outpoint_values(self.inflation.drain(..))
+            .map_err(|InflationOverflow(outpoint)| IssueError::InflationOverflow(outpoint))?;
+
+        Ok(self.issue(ticker, name, inflation.into_inner(), timestamp))
+    }
+
+    /// Constructs RGB20 genesis in a canonical form.
+    ///
+    /// Asset allocations are ordered by their seals (and then values), such
+    /// that assignment indexes do not depend on the order in which they were
+    /// provided. Seal and value blinding factors are derived from the genesis
+    /// parameters, so the same parameters always produce the same contract
+    /// id. This does not reduce privacy since genesis seals and allocated
+    /// values are revealed to everybody knowing the contract.
+    fn issue(
+        mut self,
+        ticker: Ticker,
+        name: AssetName,
+        inflation: OutpointValueMap,
+        timestamp: i64,
+    ) -> Contract {
+        self.allocations.sort();
+
+        let mut blinding = GenesisBlinding::with(&self, &ticker, &name, &inflation, timestamp);
+        let method = self.method;
+
         let mut metadata = type_map! {
-            FieldType::Ticker => field!(AsciiString, ticker),
-            FieldType::Name => field!(AsciiString, name),
-            FieldType::Precision => field!(U8, precision),
-            FieldType::Timestamp => field!(I64, now)
+            FieldType::Ticker => field!(AsciiString, ticker.into()),
+            FieldType::Name => field!(AsciiString, name.into()),
+            FieldType::Precision => field!(U8, self.precision),
+            FieldType::Timestamp => field!(I64, timestamp)
         };
 
-        let issued_supply = allocations.iter().map(|v| v.value).sum();
+        // Overflowing allocations are rejected by `Rgb20Builder::build` and by
+        // the asset validation; here they just must not panic
+        let issued_supply = self
+            .allocations
+            .iter()
+            .fold(0u64, |sum, a| sum.saturating_add(a.value));
         let mut owned_rights = BTreeMap::new();
+        let states = blinding.zero_balanced(self.allocations.iter().map(|a| a.value).collect());
+        let allocations = self
+            .allocations
+            .into_iter()
+            .zip(states)
+            .map(|(a, state)| match a.target {
+                AllocationTarget::Outpoint(outpoint) => Assignment::Revealed {
+                    seal: blinding.seal(method, outpoint),
+                    state,
+                },
+                AllocationTarget::Blinded(seal) => Assignment::ConfidentialSeal { seal, state },
+            })
+            .collect();
         owned_rights.insert(
             OwnedRightType::Assets.into(),
-            TypedAssignments::zero_balanced(
-                vec![value::Revealed {
-                    value: issued_supply,
-                    blinding: secp256k1zkp::key::ONE_KEY.into(),
-                }],
-                allocations
-                    .into_iter()
-                    .map(|outpoint_value| {
-                        (
-                            rgb::seal::Revealed::new(method, outpoint_value.outpoint),
-                            outpoint_value.value,
-                        )
-                    })
-                    .collect(),
-                empty![],
-            ),
+            TypedAssignments::Value(allocations),
         );
         metadata.insert(FieldType::IssuedSupply.into(), field!(U64, issued_supply));
 
+        for (ty, attachment) in self.attachments {
+            let data = attachment
+                .strict_serialize()
+                .expect("in-memory encoding does not fail");
+            metadata.insert(ty.field_type().into(), field!(Bytes, data));
+        }
+        if let Some(text) = self.contract_text {
+            metadata.insert(FieldType::ContractText.into(), field!(UnicodeString, text));
+        }
+
         if !inflation.is_empty() {
+            let inflation = inflation
+                .into_iter()
+                .map(|(outpoint, value)| Assignment::Revealed {
+                    seal: blinding.seal(method, outpoint),
+                    state: value::Revealed {
+                        value,
+                        blinding: blinding.value().into(),
+                    },
+                })
+                .collect();
             owned_rights.insert(
                 OwnedRightType::Inflation.into(),
-                inflation
-                    .into_iter()
-                    .map(|(outpoint, value)| (rgb::seal::Revealed::new(method, outpoint), value))
-                    .collect::<SealValueMap>()
-                    .into_assignments(),
+                TypedAssignments::Value(inflation),
             );
         }
 
-        if let Some(outpoint) = renomination {
+        if let Some(outpoint) = self.renomination {
             owned_rights.insert(
                 OwnedRightType::Renomination.into(),
                 TypedAssignments::Void(vec![Assignment::Revealed {
-                    seal: outpoint.into(),
+                    seal: blinding.seal(method, outpoint),
                     state: data::Void(),
                 }]),
             );
         }
 
-        if let Some(outpoint) = epoch {
+        if let Some(outpoint) = self.epoch {
             owned_rights.insert(
-                OwnedRightType::BurnReplace.into(),
+                OwnedRightType::OpenEpoch.into(),
                 TypedAssignments::Void(vec![Assignment::Revealed {
-                    seal: outpoint.into(),
+                    seal: blinding.seal(method, outpoint),
                     state: data::Void(),
                 }]),
             );
         }
 
-        let schema = schema::schema();
+        let schema = self.subschema.schema();
+        let root_schema = self.subschema.root_schema();
 
         let genesis = Genesis::with(
             schema.schema_id(),
-            chain,
+            self.chain,
             metadata.into(),
             owned_rights,
             bset![],
         );
 
-        Contract::with(schema, None, genesis, empty!(), empty!(), empty!())
+        Contract::with(schema, root_schema, genesis, empty!(), empty!(), empty!())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use chrono::TimeZone;
+    use commit_verify::CommitConceal;
+    use rgb::Schema;
+
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+    use crate::Asset;
+
+    const TIMESTAMP: i64 = 1_650_000_000;
+
+    fn contract(allocations: OutpointValueVec) -> Contract {
+        allocations
+            .into_iter()
+            .fold(
+                Rgb20Builder::new(Chain::Signet)
+                    .ticker(Ticker::from_str("TICK").unwrap())
+                    .name(AssetName::from_str("Test asset").unwrap())
+                    .inflation_right(outpoint(10), 1000)
+                    .inflation_right(outpoint(11), 500)
+                    .renomination(outpoint(20))
+                    .epoch(outpoint(21))
+                    .timestamp(Utc.timestamp_opt(TIMESTAMP, 0).unwrap()),
+                |builder, allocation| builder.allocate(allocation.outpoint, allocation.value),
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn allocations() -> OutpointValueVec {
+        vec![
+            OutpointValue {
+                value: 300,
+                outpoint: outpoint(3),
+            },
+            OutpointValue {
+                value: 100,
+                outpoint: outpoint(1),
+            },
+            OutpointValue {
+                value: 200,
+                outpoint: outpoint(2),
+            },
+            OutpointValue {
+                value: 50,
+                outpoint: outpoint(1),
+            },
+        ]
+    }
+
+    #[test]
+    fn repeated_issue() {
+        let contract_id = contract(allocations()).contract_id();
+        for _ in 0..10 {
+            assert_eq!(contract(allocations()).contract_id(), contract_id);
+        }
+    }
+
+    #[test]
+    fn subschema_issue() {
+        let contract = builder().subschema(Subschema::NoReplace).build().unwrap();
+        assert_eq!(contract.schema_id(), Subschema::NoReplace.schema_id());
+        assert_eq!(
+            contract.root_schema().map(Schema::schema_id),
+            Some(Subschema::Full.schema_id())
+        );
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(asset.subschema(), Subschema::NoReplace);
+    }
+
+    #[test]
+    fn timestamp() {
+        let builder = builder();
+
+        let contract = builder
+            .clone()
+            .timestamp(Utc.timestamp_opt(TIMESTAMP, 0).unwrap())
+            .build()
+            .unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(asset.issued().timestamp(), TIMESTAMP);
+        assert_eq!(asset.issued_timestamp(), TIMESTAMP);
+
+        assert_eq!(
+            builder
+                .clone()
+                .timestamp(Utc.timestamp_opt(MIN_TIMESTAMP - 1, 0).unwrap())
+                .build(),
+            Err(IssueError::TimestampTooEarly(MIN_TIMESTAMP - 1))
+        );
+        let future = Utc::now().timestamp() + 3600;
+        assert_eq!(
+            builder
+                .timestamp(Utc.timestamp_opt(future, 0).unwrap())
+                .build(),
+            Err(IssueError::TimestampInFuture(future))
+        );
+    }
+
+    #[test]
+    fn precision_overflow() {
+        let builder = builder();
+        assert!(builder.clone().precision(MAX_PRECISION).build().is_ok());
+        assert_eq!(
+            builder.precision(MAX_PRECISION + 1).build(),
+            Err(IssueError::PrecisionOverflow(MAX_PRECISION + 1))
+        );
+    }
+
+    #[test]
+    fn nomination_policy() {
+        let policy = NominationPolicy::default()
+            .with_ticker_len(3, 4)
+            .unwrap()
+            .reserve_ticker(Ticker::from_str("RSVD").unwrap());
+        let builder = Rgb20Builder::new(Chain::Signet)
+            .name(AssetName::from_str("Test asset").unwrap())
+            .allocate(outpoint(1), 100)
+            .policy(policy);
+
+        assert!(builder
+            .clone()
+            .ticker(Ticker::from_str("TICK").unwrap())
+            .build()
+            .is_ok());
+        assert_eq!(
+            builder
+                .clone()
+                .ticker(Ticker::from_str("TICKER").unwrap())
+                .build(),
+            Err(IssueError::Policy(PolicyError::TickerLength {
+                len: 6,
+                min: 3,
+                max: 4
+            }))
+        );
+        assert_eq!(
+            builder.ticker(Ticker::from_str("RSVD").unwrap()).build(),
+            Err(IssueError::Policy(PolicyError::TickerReserved))
+        );
+    }
+
+    #[test]
+    fn attachments() {
+        let logo = Attachment::with("image/png", b"logo data");
+        let terms = Attachment::with("application/pdf", b"terms data");
+        assert!(logo.verify(b"logo data"));
+        assert!(!logo.verify(b"terms data"));
+
+        let attached = builder()
+            .attach(AttachmentType::Logo, logo.clone())
+            .attach(AttachmentType::Terms, terms.clone());
+        assert_eq!(
+            attached.clone().build(),
+            Err(IssueError::AttachmentsUnsupported(Subschema::Full))
+        );
+        assert_eq!(
+            builder().contract_text("Terms of the asset").build(),
+            Err(IssueError::ContractTextUnsupported(Subschema::Full))
+        );
+
+        let contract = attached
+            .subschema(Subschema::Media)
+            .contract_text("Terms of the asset")
+            .build()
+            .unwrap();
+        assert_eq!(contract.schema_id(), Subschema::Media.schema_id());
+        assert_eq!(contract.root_schema(), None);
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(asset.subschema(), Subschema::Media);
+        assert_eq!(
+            asset.attachments(),
+            bmap! { AttachmentType::Logo => logo, AttachmentType::Terms => terms }
+        );
+        assert_eq!(asset.ricardian_contract(), Some(s!("Terms of the asset")));
+    }
+
+    #[test]
+    fn ricardian_contract() {
+        let text = s!("Terms of the asset");
+        let media = builder()
+            .subschema(Subschema::Media)
+            .timestamp(Utc.timestamp_opt(TIMESTAMP, 0).unwrap())
+            .contract_text(text.clone())
+            .build()
+            .unwrap();
+        let report = Asset::validate_consignment(&media, &crate::OfflineResolver::new());
+        assert!(report.is_valid());
+        let asset = Asset::try_from(&media).unwrap();
+        assert_eq!(asset.ricardian_contract(), Some(text));
+
+        let asset = Asset::try_from(&contract(allocations())).unwrap();
+        assert_eq!(asset.ricardian_contract(), None);
+    }
+
+    #[test]
+    fn allocation_order() {
+        let contract_id = contract(allocations()).contract_id();
+        let mut reversed = allocations();
+        reversed.reverse();
+        let contract = contract(reversed);
+        assert_eq!(contract.contract_id(), contract_id);
+
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(asset.known_coins().count(), 4);
+        for (no, (vout, value)) in [(1, 50), (1, 100), (2, 200), (3, 300)]
+            .into_iter()
+            .enumerate()
+        {
+            let coin = asset
+                .outpoint_coins(outpoint(vout))
+                .into_iter()
+                .find(|coin| coin.state.value == value)
+                .unwrap();
+            assert_eq!(coin.outpoint.no, no as u16);
+        }
+    }
+
+    #[test]
+    fn allocation_targets() {
+        let blinded = seal::Revealed::from(outpoint(5)).commit_conceal();
+        let targets = [AllocationTarget::Outpoint(outpoint(1)), AllocationTarget::Blinded(blinded)];
+        for target in targets {
+            assert_eq!(AllocationTarget::from_str(&target.to_string()), Ok(target));
+        }
+
+        let contract = builder()
+            .allocate(blinded, 50)
+            .allocate(outpoint(2), 25)
+            .build()
+            .unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(asset.supply().issued, 175);
+        assert_eq!(asset.allocations_at(outpoint(1)).len(), 1);
+        assert_eq!(asset.allocations_at(outpoint(2)).len(), 1);
+        assert_eq!(asset.known_coins().count(), 2);
+    }
+
+    #[test]
+    fn inflation_merge() {
+        let inflation = InflationAllocations::from_outpoint_values(allocations()).unwrap();
+        assert_eq!(inflation.len(), 3);
+        assert_eq!(inflation.get(&outpoint(1)), Some(150));
+        assert_eq!(inflation.get(&outpoint(4)), None);
+        assert_eq!(inflation.total(), Some(650));
+
+        assert_eq!(
+            InflationAllocations::from_values([(outpoint(1), u64::MAX), (outpoint(1), 1)]),
+            Err(InflationOverflow(outpoint(1)))
+        );
+        let inflation =
+            InflationAllocations::from_values([(outpoint(1), u64::MAX), (outpoint(2), 1)]).unwrap();
+        assert_eq!(inflation.total(), None);
     }
 }
@@ -0,0 +1,49 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+
+/// Converts raw Unix timestamp into UTC time, falling back to the Unix epoch
+/// if the timestamp is out of range
+pub fn utc_time(timestamp: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .unwrap_or_else(|| Utc.timestamp_opt(0, 0).unwrap())
+}
+
+/// Formats time as RFC 3339 string with second precision and explicit `Z`
+/// UTC designator, which does not depend on the local timezone
+pub fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Formats raw Unix timestamp like [`format_time`]; out of range timestamps
+/// are printed as a raw number
+pub fn format_timestamp(timestamp: i64) -> String {
+    match Utc.timestamp_opt(timestamp, 0).single() {
+        Some(time) => format_time(time),
+        None => timestamp.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn formatting() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(1_650_000_000), "2022-04-15T05:20:00Z");
+        assert_eq!(format_timestamp(i64::MAX), i64::MAX.to_string());
+        assert_eq!(utc_time(i64::MAX), utc_time(0));
+        assert_eq!(format_time(utc_time(1_650_000_000)), "2022-04-15T05:20:00Z");
+    }
+}
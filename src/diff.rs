@@ -0,0 +1,176 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bitcoin::OutPoint;
+use rgb::{AtomicValue, NodeOutpoint, OwnedValue};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+
+use crate::{Asset, Nomination, SupplyMeasures};
+
+/// Value of an asset property before and after a change
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Change<T> {
+    /// Value before the change
+    pub before: T,
+
+    /// Value after the change
+    pub after: T,
+}
+
+/// Differences between two views of the same asset, computed with
+/// [`Asset::diff`]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct AssetDiff {
+    /// Allocations known to the new view only
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub added: Vec<OwnedValue>,
+
+    /// Allocations known to the old view which are spent in the new one
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub spent: Vec<OwnedValue>,
+
+    /// Change of the asset supply figures, if any
+    pub supply: Option<Change<SupplyMeasures>>,
+
+    /// Change of the current asset nomination, if any. Not reported if the
+    /// renomination history of any of the views is inconsistent.
+    pub nomination: Option<Change<Nomination>>,
+}
+
+impl AssetDiff {
+    /// Detects whether the views of the asset are identical
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.spent.is_empty()
+            && self.supply.is_none()
+            && self.nomination.is_none()
+    }
+
+    /// Returns total value of the new allocations assigned to any of the
+    /// `outpoints`, i.e. value received by a wallet controlling them
+    pub fn received_at(&self, outpoints: &BTreeSet<OutPoint>) -> AtomicValue {
+        sum_at(&self.added, outpoints)
+    }
+
+    /// Returns total value of the spent allocations assigned to any of the
+    /// `outpoints`, i.e. value sent by a wallet controlling them
+    pub fn spent_at(&self, outpoints: &BTreeSet<OutPoint>) -> AtomicValue {
+        sum_at(&self.spent, outpoints)
+    }
+}
+
+fn change<T: PartialEq>(before: T, after: T) -> Option<Change<T>> {
+    if before != after {
+        Some(Change { before, after })
+    } else {
+        None
+    }
+}
+
+fn sum_at(coins: &[OwnedValue], outpoints: &BTreeSet<OutPoint>) -> AtomicValue {
+    coins
+        .iter()
+        .filter(|coin| outpoints.contains(&coin.seal))
+        .fold(0u64, |sum, coin| sum.saturating_add(coin.state.value))
+}
+
+impl Asset {
+    /// Computes differences between this view of the asset and the `other`
+    /// one, taking this view as the old and `other` as the new state. Used
+    /// for instance to notify wallet users about the received value after
+    /// merging a new consignment with [`Asset::update_with`].
+    ///
+    /// Both views must belong to the same asset contract.
+    pub fn diff(&self, other: &Asset) -> AssetDiff {
+        let known = |asset: &Asset| -> BTreeMap<NodeOutpoint, OwnedValue> {
+            asset
+                .known_coins()
+                .map(|coin| (coin.outpoint, coin.clone()))
+                .collect()
+        };
+        let before = known(self);
+        let after = known(other);
+
+        let nomination = match (self.renominations(), other.renominations()) {
+            (Ok(before), Ok(after)) => change(before.current, after.current),
+            _ => None,
+        };
+
+        AssetDiff {
+            added: after
+                .iter()
+                .filter(|(outpoint, _)| !before.contains_key(outpoint))
+                .map(|(_, coin)| coin.clone())
+                .collect(),
+            spent: before
+                .iter()
+                .filter(|(outpoint, _)| !after.contains_key(outpoint))
+                .map(|(_, coin)| coin.clone())
+                .collect(),
+            supply: change(self.supply(), other.supply()),
+            nomination,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::Txid;
+    use rgb::seal;
+
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+
+    #[test]
+    fn transfer_diff() {
+        let contract = builder().build().unwrap();
+        let before = Asset::try_from(&contract).unwrap();
+        assert!(before.diff(&before).is_empty());
+
+        let transition = before
+            .transfer(bset! { outpoint(1) }, empty!(), bmap! {
+                seal::Revealed::from(outpoint(2)) => 50,
+                seal::Revealed::from(outpoint(3)) => 50
+            })
+            .unwrap();
+        let mut after = before.clone();
+        after.add_transition(
+            Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap(),
+            transition,
+        );
+        after.reindex();
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.added.len(), 2);
+        assert_eq!(diff.spent.len(), 1);
+        assert_eq!(diff.supply, None);
+        assert_eq!(diff.nomination, None);
+        assert_eq!(diff.received_at(&bset! { outpoint(2) }), 50);
+        assert_eq!(diff.spent_at(&bset! { outpoint(1) }), 100);
+        assert_eq!(after.diff(&before).added, diff.spent);
+    }
+}
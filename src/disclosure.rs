@@ -0,0 +1,305 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeSet;
+
+use bitcoin::OutPoint;
+use commit_verify::{lnpbp4, CommitConceal};
+use rgb::prelude::*;
+use rgb::{ConcealState, ConcealTransitions};
+
+use crate::schema::OwnedRightType;
+use crate::transitions::Error;
+use crate::Asset;
+
+impl Asset {
+    /// Composes state transfer consignment disclosing the asset allocations at
+    /// `outpoints` together with the minimal history required to validate
+    /// them.
+    ///
+    /// Transitions of the `source` consignment which are not ancestors of the
+    /// disclosed allocations are concealed within their bundles, and bundles
+    /// without such ancestors are dropped. Asset allocations of the remaining
+    /// transitions are concealed unless they are disclosed or closed by
+    /// another disclosed transition. Genesis and state extensions are kept
+    /// intact.
+    pub fn disclose<T>(
+        &self,
+        source: &InmemConsignment<T>,
+        outpoints: &BTreeSet<OutPoint>,
+    ) -> Result<StateTransfer, Error>
+    where
+        T: ConsignmentType,
+    {
+        let contract_id = source.contract_id();
+        if contract_id != self.contract_id() {
+            return Err(Error::ContractMismatch(contract_id));
+        }
+
+        let mut disclosed = BTreeSet::<NodeOutpoint>::new();
+        for outpoint in outpoints {
+            let coins = self.allocations_at(*outpoint);
+            if coins.is_empty() {
+                return Err(Error::UnrelatedInput(*outpoint));
+            }
+            disclosed.extend(coins.iter().map(|coin| coin.outpoint));
+        }
+
+        let genesis_id = source.genesis().node_id();
+        let roots = disclosed
+            .iter()
+            .map(|output| output.node_id)
+            .filter(|node_id| *node_id != genesis_id);
+        let ancestors = ancestry(source, roots)?;
+        let mut expose = exposed_seals(source, &ancestors);
+        let mut endpoints = Vec::new();
+        for output in &disclosed {
+            if output.node_id == genesis_id {
+                continue;
+            }
+            let seal = match concealed_seal(source, *output) {
+                Some(seal) => seal,
+                None => continue,
+            };
+            expose.insert(seal);
+            if let Some((_, bundle)) = source
+                .anchored_bundles()
+                .find(|(_, bundle)| bundle.is_revealed(output.node_id))
+            {
+                endpoints.push((bundle.bundle_id(), SealEndpoint::ConcealedUtxo(seal)));
+            }
+        }
+
+        prune(source, &ancestors, &expose, endpoints)
+    }
+
+    /// Composes state transfer consignment like [`Asset::compose_transfer`],
+    /// but disclosing only the minimal history of the transferred state.
+    ///
+    /// Ancestor bundles are pruned in the same way as by [`Asset::disclose`],
+    /// such that the beneficiaries learn neither unrelated transitions nor the
+    /// amounts and seals of the unrelated allocations.
+    pub fn compose_minimal_transfer<T>(
+        &self,
+        source: &InmemConsignment<T>,
+        bundle: TransitionBundle,
+        anchor: Anchor<lnpbp4::MerkleProof>,
+        endpoints: &BTreeSet<SealEndpoint>,
+    ) -> Result<StateTransfer, Error>
+    where
+        T: ConsignmentType,
+    {
+        let roots = bundle
+            .known_transitions()
+            .map(Transition::node_id)
+            .collect::<Vec<_>>();
+        let transfer = self.compose_transfer(source, bundle, anchor, endpoints)?;
+
+        let ancestors = ancestry(&transfer, roots)?;
+        let mut expose = exposed_seals(&transfer, &ancestors);
+        expose.extend(endpoints.iter().map(SealEndpoint::commit_conceal));
+        let endpoints = transfer.endpoints().copied().collect();
+
+        prune(&transfer, &ancestors, &expose, endpoints)
+    }
+}
+
+/// Returns genesis or state transition with the given id from the consignment
+fn node<T>(consignment: &InmemConsignment<T>, node_id: NodeId) -> Option<&dyn Node>
+where T: ConsignmentType {
+    if consignment.genesis().node_id() == node_id {
+        return Some(consignment.genesis());
+    }
+    consignment
+        .transition_by_id(node_id)
+        .ok()
+        .map(|transition| transition as &dyn Node)
+}
+
+/// Returns blinded seal of the node output
+fn concealed_seal<T>(
+    consignment: &InmemConsignment<T>,
+    output: NodeOutpoint,
+) -> Option<seal::Confidential>
+where
+    T: ConsignmentType,
+{
+    node(consignment, output.node_id)?
+        .owned_rights_by_type(output.ty)?
+        .to_confidential_seals()
+        .get(output.no as usize)
+        .copied()
+}
+
+/// Collects ids of the `roots` state transitions and all of their ancestor
+/// transitions, excluding genesis
+pub(crate) fn ancestry<T>(
+    consignment: &InmemConsignment<T>,
+    roots: impl IntoIterator<Item = NodeId>,
+) -> Result<BTreeSet<NodeId>, Error>
+where
+    T: ConsignmentType,
+{
+    let genesis_id = consignment.genesis().node_id();
+    let mut ancestors = BTreeSet::new();
+    let mut queue = roots.into_iter().collect::<Vec<_>>();
+    while let Some(node_id) = queue.pop() {
+        if node_id == genesis_id || !ancestors.insert(node_id) {
+            continue;
+        }
+        let transition = consignment
+            .transition_by_id(node_id)
+            .map_err(|_| Error::AncestorAbsent(node_id))?;
+        queue.extend(transition.parent_owned_rights().keys().copied());
+    }
+    Ok(ancestors)
+}
+
+/// Collects blinded seals which must be left revealed for the `transitions`
+/// to be validated: seals closed by the transitions and seals of the rights
+/// other than asset allocations, which are required to be revealed by RGB20
+/// semantic validation
+pub(crate) fn exposed_seals<T>(
+    consignment: &InmemConsignment<T>,
+    transitions: &BTreeSet<NodeId>,
+) -> BTreeSet<seal::Confidential>
+where
+    T: ConsignmentType,
+{
+    let mut seals = BTreeSet::new();
+    for node_id in transitions {
+        let transition = match consignment.transition_by_id(*node_id) {
+            Ok(transition) => transition,
+            Err(_) => continue,
+        };
+        seals.extend(
+            transition
+                .parent_outputs()
+                .into_iter()
+                .filter_map(|output| concealed_seal(consignment, output)),
+        );
+        for (ty, assignments) in transition.owned_rights().iter() {
+            if OwnedRightType::try_from(*ty) != Ok(OwnedRightType::Assets) {
+                seals.extend(assignments.to_confidential_seals());
+            }
+        }
+    }
+    seals
+}
+
+/// Constructs state transfer consignment keeping only the `transitions` from
+/// the `consignment` bundles and concealing all state assigned to the seals
+/// not listed in `expose`
+pub(crate) fn prune<T>(
+    consignment: &InmemConsignment<T>,
+    transitions: &BTreeSet<NodeId>,
+    expose: &BTreeSet<seal::Confidential>,
+    endpoints: Vec<(BundleId, SealEndpoint)>,
+) -> Result<StateTransfer, Error>
+where
+    T: ConsignmentType,
+{
+    let expose = expose.iter().copied().collect::<Vec<_>>();
+    let mut anchored_bundles = Vec::new();
+    for (anchor, bundle) in consignment.anchored_bundles() {
+        let kept = bundle
+            .known_transitions()
+            .map(Transition::node_id)
+            .filter(|node_id| transitions.contains(node_id))
+            .collect::<Vec<_>>();
+        if kept.is_empty() {
+            continue;
+        }
+        let mut bundle = bundle.clone();
+        bundle.conceal_transitions_except(&kept);
+        bundle.conceal_state_except(&expose);
+        anchored_bundles.push((anchor.clone(), bundle));
+    }
+
+    Ok(StateTransfer::with(
+        consignment.schema().clone(),
+        consignment.root_schema().cloned(),
+        consignment.genesis().clone(),
+        endpoints.into_iter().collect(),
+        anchored_bundles
+            .try_into()
+            .map_err(|_| Error::TooManyBundles)?,
+        consignment
+            .state_extensions()
+            .cloned()
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|_| Error::TooManyBundles)?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::Hash;
+    use bitcoin::Txid;
+
+    use super::*;
+    use crate::fixtures::{anchor, builder, outpoint};
+
+    #[test]
+    fn disclose_prunes_unrelated_history() {
+        let contract = builder().allocate(outpoint(2), 50).build().unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+
+        let related = asset
+            .transfer(
+                bset! { outpoint(1) },
+                empty!(),
+                bmap! { seal::Revealed::from(outpoint(3)) => 100 },
+            )
+            .unwrap();
+        let unrelated = asset
+            .transfer(
+                bset! { outpoint(2) },
+                bmap! { SealEndpoint::from(seal::Revealed::from(outpoint(4))) => 50 },
+                empty!(),
+            )
+            .unwrap();
+        let related_id = related.node_id();
+        let unrelated_id = unrelated.node_id();
+        let bundle =
+            |transition| TransitionBundle::try_from(bmap! { transition => bset! { 0 } }).unwrap();
+        let transfer = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            empty!(),
+            vec![
+                (anchor(Txid::hash(b"related")), bundle(related)),
+                (anchor(Txid::hash(b"unrelated")), bundle(unrelated)),
+            ]
+            .try_into()
+            .unwrap(),
+            empty!(),
+        );
+        let asset = Asset::try_from(&transfer).unwrap();
+
+        let disclosure = asset.disclose(&transfer, &bset! { outpoint(3) }).unwrap();
+        assert_eq!(disclosure.anchored_bundles().count(), 1);
+        assert!(disclosure.transition_by_id(related_id).is_ok());
+        assert!(disclosure.transition_by_id(unrelated_id).is_err());
+        assert_eq!(disclosure.endpoints().count(), 1);
+
+        let disclosed = Asset::try_from(&disclosure).unwrap();
+        assert_eq!(disclosed.allocations_at(outpoint(3)).len(), 1);
+        assert!(disclosed.allocations_at(outpoint(4)).is_empty());
+
+        assert!(matches!(
+            asset.disclose(&transfer, &bset! { outpoint(5) }),
+            Err(Error::UnrelatedInput(o)) if o == outpoint(5)
+        ));
+    }
+}
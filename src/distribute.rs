@@ -0,0 +1,134 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeMap;
+
+use bitcoin::OutPoint;
+use rgb::{Assignment, ConcealSeals, Consignment, Contract, Node, StateTransfer, TypedAssignments};
+
+use crate::schema::OwnedRightType;
+use crate::{AllocationTarget, IssueError, Rgb20Builder};
+
+/// Asset issued with [`Rgb20Builder::issue_and_distribute`] together with
+/// the consignments for each of its initial beneficiaries
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Distribution {
+    /// Issued asset contract, containing all genesis seals
+    pub contract: Contract,
+
+    /// Consignments for the beneficiaries of the genesis allocations. Each of
+    /// them reveals only the seals of the allocations assigned to its
+    /// beneficiary, while the rest of the allocations remain concealed.
+    pub transfers: BTreeMap<AllocationTarget, StateTransfer>,
+}
+
+impl Rgb20Builder {
+    /// Validates provided data, constructs the asset [`Contract`] and
+    /// composes a ready-to-send state transfer consignment for each of the
+    /// allocation targets, so the asset can be distributed without
+    /// additional transfers.
+    ///
+    /// Since genesis seals are not revealed by
+    /// [`rgb::RevealSeals::reveal_seals`], allocations to blinded targets
+    /// remain unknown to the [`crate::Asset`] constructed from the consignment
+    /// by their beneficiaries.
+    pub fn issue_and_distribute(self) -> Result<Distribution, IssueError> {
+        let contract = self.build()?;
+        let genesis = contract.genesis();
+
+        let mut seals = BTreeMap::new();
+        if let Some(TypedAssignments::Value(assignments)) =
+            genesis.owned_rights_by_type(OwnedRightType::Assets.into())
+        {
+            for assignment in assignments {
+                let target = match assignment {
+                    Assignment::Revealed { seal, .. }
+                    | Assignment::ConfidentialState { seal, .. } => {
+                        let txid = seal.txid.expect("genesis seals always have txid");
+                        AllocationTarget::Outpoint(OutPoint::new(txid, seal.vout))
+                    }
+                    Assignment::Confidential { seal, .. }
+                    | Assignment::ConfidentialSeal { seal, .. } => AllocationTarget::Blinded(*seal),
+                };
+                seals
+                    .entry(target)
+                    .or_insert_with(Vec::new)
+                    .push(assignment.to_confidential_seal());
+            }
+        }
+
+        let transfers = seals
+            .keys()
+            .map(|target| {
+                let foreign = seals
+                    .iter()
+                    .filter(|(other, _)| *other != target)
+                    .flat_map(|(_, seals)| seals.iter().copied())
+                    .collect::<Vec<_>>();
+                let mut genesis = genesis.clone();
+                for assignments in genesis.owned_rights_mut().values_mut() {
+                    assignments.conceal_seals(&foreign);
+                }
+                let transfer = StateTransfer::with(
+                    contract.schema().clone(),
+                    contract.root_schema().cloned(),
+                    genesis,
+                    empty!(),
+                    empty!(),
+                    empty!(),
+                );
+                (*target, transfer)
+            })
+            .collect();
+
+        Ok(Distribution {
+            contract,
+            transfers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use commit_verify::CommitConceal;
+    use rgb::seal;
+
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+    use crate::Asset;
+
+    #[test]
+    fn issue_and_distribute() {
+        let blinded = seal::Revealed::from(outpoint(5)).commit_conceal();
+        let distribution = builder()
+            .allocate(outpoint(1), 20)
+            .allocate(outpoint(2), 50)
+            .allocate(blinded, 30)
+            .inflation_right(outpoint(3), 1000)
+            .issue_and_distribute()
+            .unwrap();
+        let contract_id = distribution.contract.contract_id();
+        assert_eq!(distribution.transfers.len(), 3);
+
+        let transfer = &distribution.transfers[&AllocationTarget::Outpoint(outpoint(1))];
+        assert_eq!(transfer.contract_id(), contract_id);
+        let asset = Asset::try_from(transfer).unwrap();
+        assert_eq!(asset.allocations_at(outpoint(1)).len(), 2);
+        assert!(asset.allocations_at(outpoint(2)).is_empty());
+        assert_eq!(asset.known_inflation().count(), 1);
+
+        let transfer = &distribution.transfers[&AllocationTarget::Blinded(blinded)];
+        assert_eq!(transfer.contract_id(), contract_id);
+        let asset = Asset::try_from(transfer).unwrap();
+        assert_eq!(asset.known_coins().count(), 0);
+        assert_eq!(asset.supply().issued, 200);
+    }
+}
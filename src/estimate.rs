@@ -0,0 +1,203 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bitcoin::OutPoint;
+use rgb::{EndpointValueMap, Node, NodeId, SealValueMap, Transition};
+use seals::txout::CloseMethod;
+use strict_encoding::StrictEncode;
+
+use crate::transitions::Error;
+use crate::Asset;
+
+/// Approximate size of a strict-encoded anchor with LNPBP-4 multi-protocol
+/// commitment and DBC proofs, together with the transition bundle overhead
+const ANCHORED_BUNDLE_OVERHEAD: usize = 256;
+
+/// Size of the bundle id prefixing each consignment endpoint
+const ENDPOINT_BUNDLE_ID_SIZE: usize = 32;
+
+/// Size of the consignment version, type tag and length prefixes of its
+/// collections
+const CONSIGNMENT_OVERHEAD: usize = 16;
+
+/// Weight of an additional `OP_RETURN` output with 32-byte commitment: 8 bytes
+/// of value, 1 byte of script length and 34 bytes of script, all in
+/// non-witness data
+const OPRET_OUTPUT_WEIGHT: usize = (8 + 1 + 34) * 4;
+
+/// Size and weight estimates for a planned asset transfer, allowing wallets
+/// to account for RGB data in the witness transaction fee before signing
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct TransitionEstimate {
+    /// Size of the strict-encoded transfer state transition, in bytes
+    pub transition_size: usize,
+
+    /// Expected size of the state transfer consignment sent to the
+    /// beneficiaries, in bytes
+    pub consignment_size: usize,
+
+    /// Weight added to the witness transaction by the commitment, in weight
+    /// units
+    pub commitment_weight: usize,
+}
+
+impl TransitionEstimate {
+    /// Estimates sizes for the `transition` of the `asset`, which state is
+    /// assigned to the given `endpoints`, and commitment weight for the
+    /// seal closing `method`.
+    ///
+    /// Consignment size includes schema, genesis and all known ancestor
+    /// transitions of the `transition`, while anchors are accounted with
+    /// their typical size; the actual consignment size may differ by a few
+    /// dozens of bytes per transition.
+    pub fn with<'endpoints>(
+        asset: &Asset,
+        transition: &Transition,
+        endpoints: impl IntoIterator<Item = &'endpoints rgb::SealEndpoint>,
+        method: CloseMethod,
+    ) -> TransitionEstimate {
+        let transition_size = strict_size(transition);
+
+        let subschema = asset.subschema();
+        let mut consignment_size = CONSIGNMENT_OVERHEAD
+            + strict_size(&subschema.schema())
+            + subschema
+                .root_schema()
+                .as_ref()
+                .map(strict_size)
+                .unwrap_or(1)
+            + strict_size(asset.genesis())
+            + transition_size
+            + ANCHORED_BUNDLE_OVERHEAD;
+
+        let known = asset
+            .transitions()
+            .map(|(_, transition)| (transition.node_id(), transition))
+            .collect::<BTreeMap<_, _>>();
+        let mut queue = transition
+            .parent_owned_rights()
+            .keys()
+            .copied()
+            .collect::<Vec<NodeId>>();
+        let mut visited = BTreeSet::new();
+        while let Some(node_id) = queue.pop() {
+            if !visited.insert(node_id) {
+                continue;
+            }
+            if let Some(ancestor) = known.get(&node_id) {
+                consignment_size += strict_size(*ancestor) + ANCHORED_BUNDLE_OVERHEAD;
+                queue.extend(ancestor.parent_owned_rights().keys().copied());
+            }
+        }
+
+        consignment_size += endpoints
+            .into_iter()
+            .map(|endpoint| ENDPOINT_BUNDLE_ID_SIZE + strict_size(endpoint))
+            .sum::<usize>();
+
+        TransitionEstimate {
+            transition_size,
+            consignment_size,
+            commitment_weight: TransitionEstimate::commitment_weight(method),
+        }
+    }
+
+    /// Returns weight added to the witness transaction by the commitment with
+    /// the given seal closing `method`.
+    ///
+    /// Tapret commitment tweaks the key of already present taproot output and
+    /// does not change the transaction weight. Opret commitment is assumed to
+    /// add a new `OP_RETURN` output; unknown methods are estimated in the same
+    /// way.
+    pub fn commitment_weight(method: CloseMethod) -> usize {
+        match method {
+            CloseMethod::TapretFirst => 0,
+            _ => OPRET_OUTPUT_WEIGHT,
+        }
+    }
+}
+
+impl Asset {
+    /// Estimates transition and consignment sizes and commitment weight for
+    /// the transfer which would be created by [`Asset::transfer`] with the
+    /// same arguments
+    pub fn estimate_transfer(
+        &self,
+        inputs: BTreeSet<OutPoint>,
+        payment: EndpointValueMap,
+        change: SealValueMap,
+        method: CloseMethod,
+    ) -> Result<TransitionEstimate, Error> {
+        let endpoints = payment.keys().copied().collect::<Vec<_>>();
+        let transition = self.transfer(inputs, payment, change)?;
+        Ok(TransitionEstimate::with(
+            self,
+            &transition,
+            &endpoints,
+            method,
+        ))
+    }
+}
+
+/// Returns length of the strict-encoded data
+fn strict_size(data: &impl StrictEncode) -> usize {
+    data.strict_serialize()
+        .expect("in-memory encoding does not fail")
+        .len()
+}
+
+#[cfg(test)]
+mod test {
+    use rgb::SealEndpoint;
+
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+
+    #[test]
+    fn estimate_transfer() {
+        let contract = builder().build().unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        let endpoint = SealEndpoint::WitnessVout {
+            method: CloseMethod::TapretFirst,
+            vout: 0,
+            blinding: 1,
+        };
+
+        let estimate = asset
+            .estimate_transfer(
+                bset! { outpoint(1) },
+                bmap! { endpoint => 100 },
+                empty!(),
+                CloseMethod::OpretFirst,
+            )
+            .unwrap();
+        let transition = asset
+            .transfer(bset! { outpoint(1) }, bmap! { endpoint => 100 }, empty!())
+            .unwrap();
+        assert_eq!(estimate.transition_size, strict_size(&transition));
+        assert!(
+            estimate.consignment_size
+                > strict_size(&contract) + estimate.transition_size + ANCHORED_BUNDLE_OVERHEAD
+        );
+        assert_eq!(estimate.commitment_weight, 172);
+        assert_eq!(
+            TransitionEstimate::commitment_weight(CloseMethod::TapretFirst),
+            0
+        );
+    }
+}
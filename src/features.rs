@@ -0,0 +1,128 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use rgb::{Genesis, Node, Schema};
+
+use crate::schema::{OwnedRightType, Subschema, TransitionType};
+use crate::Asset;
+
+/// Asset operations allowed by RGB20 (sub)schema or by a specific contract
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Features {
+    /// Secondary issuance is allowed
+    pub inflatable: bool,
+
+    /// Burn operations are allowed
+    pub burnable: bool,
+
+    /// Burn & replace operations are allowed
+    pub replaceable: bool,
+
+    /// Asset renomination is allowed
+    pub renominatable: bool,
+}
+
+impl Features {
+    /// Detects operations defined by the schema
+    pub fn with_schema(schema: &Schema) -> Features {
+        let defines = |ty: TransitionType| schema.transitions.contains_key(&ty.into());
+        Features {
+            inflatable: defines(TransitionType::Issue),
+            burnable: defines(TransitionType::Burn),
+            replaceable: defines(TransitionType::BurnAndReplace),
+            renominatable: defines(TransitionType::Renomination),
+        }
+    }
+
+    /// Detects operations which are both defined by the schema and enabled by
+    /// the rights assigned in the contract genesis
+    pub fn with_genesis(schema: &Schema, genesis: &Genesis) -> Features {
+        let features = Features::with_schema(schema);
+        let assigns = |ty: OwnedRightType| genesis.owned_rights_by_type(ty.into()).is_some();
+        let epochs = assigns(OwnedRightType::OpenEpoch);
+        Features {
+            inflatable: features.inflatable && assigns(OwnedRightType::Inflation),
+            burnable: features.burnable && epochs,
+            replaceable: features.replaceable && epochs,
+            renominatable: features.renominatable && assigns(OwnedRightType::Renomination),
+        }
+    }
+
+    /// Checks whether operation of the given type is allowed. Transfers and
+    /// rights splits are always allowed; opening of the new epoch is allowed
+    /// if either burn or burn & replace operations are allowed.
+    pub fn allows(self, transition_type: TransitionType) -> bool {
+        match transition_type {
+            TransitionType::Transfer | TransitionType::RightsSplit => true,
+            TransitionType::Issue => self.inflatable,
+            TransitionType::Epoch => self.burnable || self.replaceable,
+            TransitionType::Burn => self.burnable,
+            TransitionType::BurnAndReplace => self.replaceable,
+            TransitionType::Renomination => self.renominatable,
+        }
+    }
+}
+
+impl From<Subschema> for Features {
+    fn from(subschema: Subschema) -> Self { Features::with_schema(&subschema.schema()) }
+}
+
+impl Asset {
+    /// Returns operations allowed for the asset by its schema and genesis
+    pub fn features(&self) -> Features {
+        Features::with_genesis(&self.subschema().schema(), self.genesis())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+
+    #[test]
+    fn schema_features() {
+        assert_eq!(Features::from(Subschema::Full), Features {
+            inflatable: true,
+            burnable: true,
+            replaceable: true,
+            renominatable: true,
+        });
+        assert_eq!(Features::from(Subschema::Deflationary), Features {
+            inflatable: false,
+            burnable: true,
+            replaceable: false,
+            renominatable: false,
+        });
+        assert!(Features::default().allows(TransitionType::Transfer));
+        assert!(!Features::default().allows(TransitionType::Epoch));
+    }
+
+    #[test]
+    fn asset_features() {
+        let contract = builder()
+            .inflation_right(outpoint(2), 100)
+            .epoch(outpoint(3))
+            .build()
+            .unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(asset.features(), Features {
+            inflatable: true,
+            burnable: true,
+            replaceable: true,
+            renominatable: false,
+        });
+    }
+}
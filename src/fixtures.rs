@@ -0,0 +1,63 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Fixtures shared by the unit tests of the crate modules.
+
+use std::str::FromStr;
+
+use bitcoin::Txid;
+use commit_verify::lnpbp4;
+use lnpbp::chain::Chain;
+use rgb::fungible::allocation::{OutpointValueMap, OutpointValueVec};
+use rgb::{Anchor, Contract};
+use seals::txout::CloseMethod;
+use strict_encoding::{StrictDecode, StrictEncode};
+
+pub use crate::vectors::{outpoint, txid};
+use crate::{AssetName, Rgb20, Rgb20Builder, Ticker};
+
+/// Constructs anchor of the witness transaction `txid` with an empty
+/// multi-protocol commitment proof
+pub fn anchor(txid: Txid) -> Anchor<lnpbp4::MerkleProof> {
+    let mut data = txid.strict_serialize().unwrap();
+    data.extend([0u8; 5]);
+    Anchor::strict_deserialize(data).unwrap()
+}
+
+/// Constructs builder of the test asset, allocating 100 atomic units to
+/// [`outpoint`]`(1)`
+pub fn builder() -> Rgb20Builder {
+    Rgb20Builder::new(Chain::Signet)
+        .ticker(Ticker::from_str("TICK").unwrap())
+        .name(AssetName::from_str("Test asset").unwrap())
+        .allocate(outpoint(1), 100)
+}
+
+/// Issues the test asset with the given decimal `precision`, `allocations`
+/// and `inflation` rights using [`Rgb20::create_rgb20`], which does not
+/// validate them
+pub fn contract(
+    precision: u8,
+    allocations: OutpointValueVec,
+    inflation: OutpointValueMap,
+) -> Contract {
+    Contract::create_rgb20(
+        Chain::Signet,
+        Ticker::from_str("TICK").unwrap(),
+        AssetName::from_str("Test asset").unwrap(),
+        precision,
+        allocations,
+        inflation,
+        CloseMethod::TapretFirst,
+        None,
+        None,
+    )
+}
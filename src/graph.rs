@@ -0,0 +1,248 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::fmt;
+
+use bitcoin::{OutPoint, Txid};
+use rgb::{ContractId, NodeId};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+
+use crate::schema::{OwnedRightType, TransitionType};
+use crate::Asset;
+
+/// Directed acyclic graph of the known asset operations, which can be
+/// exported for visualization by client-side-validation explorers
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct HistoryGraph {
+    /// Id of the asset contract
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub contract_id: ContractId,
+
+    /// Genesis and known state transitions, parents always preceding their
+    /// descendants
+    pub nodes: Vec<GraphNode>,
+
+    /// Seals defined by the graph nodes and closed by the known state
+    /// transitions
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Genesis or state transition in the [`HistoryGraph`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct GraphNode {
+    /// Id of the genesis or state transition
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub node_id: NodeId,
+
+    /// Type of the state transition; `None` for the genesis
+    #[cfg_attr(feature = "serde", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub transition_type: Option<TransitionType>,
+
+    /// Witness transaction of the state transition; `None` for the genesis
+    #[cfg_attr(feature = "serde", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub witness_txid: Option<Txid>,
+}
+
+/// Seal defined by one [`HistoryGraph`] node and closed by another
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct GraphEdge {
+    /// Node defining the seal
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub from: NodeId,
+
+    /// State transition closing the seal
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub to: NodeId,
+
+    /// Type of the owned right assigned to the seal
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub right_type: OwnedRightType,
+
+    /// Index of the assignment within the owned rights of the type
+    pub index: u16,
+
+    /// Transaction output of the seal, if the seal is revealed
+    #[cfg_attr(feature = "serde", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub seal: Option<OutPoint>,
+}
+
+impl GraphNode {
+    fn label(&self) -> String {
+        match self.transition_type {
+            None => s!("Genesis"),
+            Some(ty) => ty.to_string(),
+        }
+    }
+}
+
+impl HistoryGraph {
+    /// Renders the graph in Graphviz DOT language
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        self.write_dot(&mut dot)
+            .expect("writing to a string does not fail");
+        dot
+    }
+
+    /// Writes the graph in Graphviz DOT language
+    pub fn write_dot(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        writeln!(w, "digraph \"{}\" {{", self.contract_id)?;
+        for node in &self.nodes {
+            write!(w, "    \"{}\" [label=\"{}", node.node_id, node.label())?;
+            if let Some(txid) = node.witness_txid {
+                write!(w, "\\nwitness {}", txid)?;
+            }
+            writeln!(w, "\"];")?;
+        }
+        for edge in &self.edges {
+            write!(
+                w,
+                "    \"{}\" -> \"{}\" [label=\"{} #{}",
+                edge.from, edge.to, edge.right_type, edge.index
+            )?;
+            if let Some(seal) = edge.seal {
+                write!(w, "\\n{}", seal)?;
+            }
+            writeln!(w, "\"];")?;
+        }
+        writeln!(w, "}}")
+    }
+}
+
+impl Asset {
+    /// Constructs graph of the genesis and known state transitions with edges
+    /// for the seals closed by the transitions
+    pub fn history_graph(&self) -> HistoryGraph {
+        let nodes = self
+            .operations()
+            .into_iter()
+            .map(|operation| GraphNode {
+                node_id: operation.node_id,
+                transition_type: operation.transition_type,
+                witness_txid: operation.witness_txid,
+            })
+            .collect::<Vec<_>>();
+
+        let edges = nodes
+            .iter()
+            .filter_map(|node| Some((node.node_id, self.node_by_id(node.node_id)?.0)))
+            .flat_map(|(node_id, node)| {
+                node.parent_outputs().into_iter().filter_map(move |output| {
+                    Some(GraphEdge {
+                        from: output.node_id,
+                        to: node_id,
+                        right_type: OwnedRightType::try_from(output.ty).ok()?,
+                        index: output.no,
+                        seal: self.resolve_seal(output),
+                    })
+                })
+            })
+            .collect();
+
+        HistoryGraph {
+            contract_id: self.contract_id(),
+            nodes,
+            edges,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::Hash;
+    use rgb::{seal, Consignment, Node, SealEndpoint, StateTransfer, TransitionBundle};
+    use seals::txout::CloseMethod;
+
+    use super::*;
+    use crate::fixtures::{anchor, builder, outpoint};
+
+    #[test]
+    fn history_graph() {
+        let contract = builder().build().unwrap();
+        let genesis_id = contract.genesis().node_id();
+        let asset = Asset::try_from(&contract).unwrap();
+
+        let graph = asset.history_graph();
+        assert_eq!(graph.nodes.len(), 1);
+        assert!(graph.edges.is_empty());
+
+        let witness_txid = Txid::hash(b"witness");
+        let transition = asset
+            .transfer(
+                bset! { outpoint(1) },
+                bmap! { SealEndpoint::WitnessVout {
+                    method: CloseMethod::TapretFirst,
+                    vout: 0,
+                    blinding: 1,
+                } => 60 },
+                bmap! { seal::Revealed::from(outpoint(2)) => 40 },
+            )
+            .unwrap();
+        let transition_id = transition.node_id();
+        let bundle = TransitionBundle::try_from(bmap! { transition => bset! { 0 } }).unwrap();
+        let transfer = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            empty!(),
+            vec![(anchor(witness_txid), bundle)].try_into().unwrap(),
+            empty!(),
+        );
+        let graph = Asset::try_from(&transfer).unwrap().history_graph();
+
+        assert_eq!(graph.nodes, vec![
+            GraphNode {
+                node_id: genesis_id,
+                transition_type: None,
+                witness_txid: None,
+            },
+            GraphNode {
+                node_id: transition_id,
+                transition_type: Some(TransitionType::Transfer),
+                witness_txid: Some(witness_txid),
+            }
+        ]);
+        assert_eq!(graph.edges, vec![GraphEdge {
+            from: genesis_id,
+            to: transition_id,
+            right_type: OwnedRightType::Assets,
+            index: 0,
+            seal: Some(outpoint(1)),
+        }]);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with(&format!("digraph \"{}\" {{\n", graph.contract_id)));
+        assert!(dot.contains(&format!("\"{}\" [label=\"Genesis\"];", genesis_id)));
+        assert!(dot.contains(&format!(
+            "\"{}\" -> \"{}\" [label=\"Assets #0\\n{}\"];",
+            genesis_id,
+            transition_id,
+            outpoint(1)
+        )));
+        assert!(dot.ends_with("}\n"));
+    }
+}
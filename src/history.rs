@@ -0,0 +1,564 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Records of the asset operations extracted from the known asset history.
+
+use std::collections::BTreeMap;
+
+use bitcoin::{OutPoint, Txid};
+use commit_verify::CommitVerify;
+use rgb::{value, AtomicValue, Node, NodeId, NodeOutpoint, Transition};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+use strict_encoding::StrictDecode;
+
+use crate::asset::checked_sum;
+use crate::schema::{FieldType, OwnedRightType, TransitionType};
+use crate::{Asset, Error, NominationPolicy, PolicyError};
+
+/// Primary (genesis) or secondary issuance of the asset
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Issuance {
+    /// Id of the genesis or secondary issuance state transition
+    pub node_id: NodeId,
+
+    /// Witness transaction of the secondary issuance; `None` for the genesis
+    pub witness_txid: Option<Txid>,
+
+    /// Issued supply
+    pub amount: AtomicValue,
+
+    /// Inflation-controlling seals closed by the secondary issuance; empty for
+    /// the genesis. Seals which definitions are not known are not listed.
+    pub closed_seals: Vec<OutPoint>,
+
+    /// Seals for the inflation rights assigned by the issuance; confidential
+    /// seals are not listed
+    pub inflation_seals: Vec<OutPoint>,
+}
+
+/// Opening of a burn & replace epoch
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Epoch {
+    /// Id of the epoch state transition
+    pub node_id: NodeId,
+
+    /// Number of the epoch, starting from zero for the first epoch opened with
+    /// the seal defined by the genesis
+    pub no: u32,
+
+    /// Witness transaction of the epoch state transition
+    pub witness_txid: Txid,
+
+    /// Closed seal of the previous epoch (or genesis), if known
+    pub closed_seal: Option<OutPoint>,
+
+    /// Seal for opening the next epoch, if assigned and revealed
+    pub next_epoch_seal: Option<OutPoint>,
+
+    /// Seal for the first burn or burn & replace operation within the epoch, if
+    /// assigned and revealed
+    pub burn_seal: Option<OutPoint>,
+}
+
+/// History proof of the asset allocation burned by burn or burn & replace
+/// operation, revealing the amount behind the allocation Pedersen commitment
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, StrictEncode, StrictDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct HistoryProof {
+    /// Burned allocation
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub outpoint: NodeOutpoint,
+
+    /// Amount and blinding factor of the burned allocation
+    pub state: value::Revealed,
+}
+
+/// Burn or burn & replace operation
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct BurnReplace {
+    /// Id of the burn or burn & replace state transition
+    pub node_id: NodeId,
+
+    /// Number of the epoch the operation belongs to; `None` if some of the
+    /// preceding operations in the epoch are not known
+    pub epoch: Option<u32>,
+
+    /// Witness transaction of the operation
+    pub witness_txid: Txid,
+
+    /// Burned supply
+    pub burned: AtomicValue,
+
+    /// Supply re-issued by the operation; `None` for a pure burn
+    pub replaced: Option<AtomicValue>,
+
+    /// Transaction outputs holding the burned assets
+    pub burned_utxos: Vec<OutPoint>,
+
+    /// History proofs of the burned allocations; they are not verified
+    /// unless checked with [`Asset::verify_history_proofs`]
+    pub proofs: Vec<HistoryProof>,
+
+    /// Closed seal of the epoch or preceding burn operation, if known
+    pub closed_seal: Option<OutPoint>,
+
+    /// Seal for the next burn or burn & replace operation within the epoch, if
+    /// assigned and revealed
+    pub next_seal: Option<OutPoint>,
+}
+
+/// Supply burned and replaced within a burn & replace epoch
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct EpochTotals {
+    /// Number of the epoch
+    pub epoch: u32,
+
+    /// Id of the epoch state transition
+    pub node_id: NodeId,
+
+    /// Total supply burned by the known burn and burn & replace operations
+    /// within the epoch
+    pub burned: AtomicValue,
+
+    /// Total supply re-issued by the known burn & replace operations within
+    /// the epoch
+    pub replaced: AtomicValue,
+
+    /// Number of the known burn and burn & replace operations within the
+    /// epoch
+    pub operations: u32,
+}
+
+impl EpochTotals {
+    /// Returns supply burned within the epoch which was not replaced yet and
+    /// thus may be re-issued by the following burn & replace operations
+    pub fn replace_budget(&self) -> AtomicValue { self.burned.saturating_sub(self.replaced) }
+}
+
+/// Asset nomination: ticker, name and decimal precision
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, StrictEncode, StrictDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Nomination {
+    /// Asset ticker
+    pub ticker: String,
+
+    /// Asset name
+    pub name: String,
+
+    /// Decimal precision
+    pub precision: u8,
+}
+
+impl Nomination {
+    /// Applies nomination fields defined by the node on top of the current
+    /// nomination
+    pub(crate) fn apply(&self, node: &dyn Node) -> Nomination {
+        let metadata = node.metadata();
+        Nomination {
+            ticker: metadata
+                .ascii_string(FieldType::Ticker)
+                .first()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| self.ticker.clone()),
+            name: metadata
+                .ascii_string(FieldType::Name)
+                .first()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| self.name.clone()),
+            precision: metadata
+                .u8(FieldType::Precision)
+                .first()
+                .copied()
+                .unwrap_or(self.precision),
+        }
+    }
+}
+
+/// Change of the asset nomination
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Renomination {
+    /// Id of the renomination state transition
+    pub node_id: NodeId,
+
+    /// Witness transaction of the renomination
+    pub witness_txid: Txid,
+
+    /// Nomination before the renomination
+    pub old: Nomination,
+
+    /// Nomination after the renomination
+    pub new: Nomination,
+}
+
+/// Known renomination history of the asset
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Renominations {
+    /// Renominations in the order they were performed
+    pub chain: Vec<Renomination>,
+
+    /// Nomination which is effective after the last known renomination
+    pub current: Nomination,
+}
+
+impl Asset {
+    /// Returns nomination defined by the asset genesis, before any
+    /// renominations
+    pub fn genesis_nomination(&self) -> Nomination { Nomination::default().apply(self.genesis()) }
+
+    /// Lists primary issuance followed by all known secondary issuances of the
+    /// asset
+    pub fn issuances(&self) -> impl Iterator<Item = Issuance> + '_ {
+        let genesis = self.genesis();
+        let secondary = self
+            .transitions_by_type(TransitionType::Issue)
+            .map(|(txid, transition)| (transition as &dyn Node, Some(txid)));
+        [(genesis as &dyn Node, None)]
+            .into_iter()
+            .chain(secondary)
+            .map(|(node, witness_txid)| Issuance {
+                node_id: node.node_id(),
+                witness_txid,
+                amount: node
+                    .metadata()
+                    .u64(FieldType::IssuedSupply)
+                    .first()
+                    .copied()
+                    .unwrap_or_default(),
+                closed_seals: self.closed_seals(node, OwnedRightType::Inflation).collect(),
+                inflation_seals: self
+                    .assigned_seals(node, witness_txid, OwnedRightType::Inflation)
+                    .collect(),
+            })
+    }
+
+    /// Resolves seals of the given type closed by the node
+    pub(crate) fn closed_seals<'a>(
+        &'a self,
+        node: &dyn Node,
+        ty: OwnedRightType,
+    ) -> impl Iterator<Item = OutPoint> + 'a {
+        node.parent_outputs_by_type(ty.into())
+            .into_iter()
+            .filter_map(|outpoint| self.resolve_seal(outpoint))
+    }
+
+    /// Resolves revealed seals of the given type defined by the node
+    pub(crate) fn assigned_seals(
+        &self,
+        node: &dyn Node,
+        witness_txid: Option<Txid>,
+        ty: OwnedRightType,
+    ) -> impl Iterator<Item = OutPoint> {
+        node.owned_rights_by_type(ty.into())
+            .map(|assignments| assignments.revealed_seal_outputs())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(move |(seal, _)| {
+                Some(OutPoint::new(seal.txid.or(witness_txid)?, seal.vout))
+            })
+    }
+
+    /// Lists the chain of known epochs starting from the genesis. The listing
+    /// stops at the first epoch which is not known.
+    pub fn epochs(&self) -> Vec<Epoch> {
+        let mut children = self
+            .transitions_by_type(TransitionType::Epoch)
+            .filter_map(|(txid, transition)| {
+                let parent = transition
+                    .parent_outputs_by_type(OwnedRightType::OpenEpoch.into())
+                    .first()?
+                    .node_id;
+                Some((parent, (txid, transition)))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut epochs = vec![];
+        let mut prev = self.genesis().node_id();
+        while let Some((txid, transition)) = children.remove(&prev) {
+            prev = transition.node_id();
+            epochs.push(Epoch {
+                node_id: prev,
+                no: epochs.len() as u32,
+                witness_txid: txid,
+                closed_seal: self
+                    .closed_seals(transition, OwnedRightType::OpenEpoch)
+                    .next(),
+                next_epoch_seal: self
+                    .assigned_seals(transition, Some(txid), OwnedRightType::OpenEpoch)
+                    .next(),
+                burn_seal: self
+                    .assigned_seals(transition, Some(txid), OwnedRightType::BurnReplace)
+                    .next(),
+            });
+        }
+        epochs
+    }
+
+    /// Lists all known burn and burn & replace operations, ordered by the
+    /// epoch number. Operations with unknown epoch go last.
+    pub fn burns(&self) -> Vec<BurnReplace> {
+        let epochs = self
+            .epochs()
+            .into_iter()
+            .map(|epoch| (epoch.node_id, epoch.no))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut burns = self
+            .transitions_by_type(TransitionType::Burn)
+            .chain(self.transitions_by_type(TransitionType::BurnAndReplace))
+            .map(|(txid, transition)| {
+                let metadata = transition.metadata();
+                BurnReplace {
+                    node_id: transition.node_id(),
+                    epoch: self.burn_epoch(transition, &epochs),
+                    witness_txid: txid,
+                    burned: metadata
+                        .u64(FieldType::BurnedSupply)
+                        .first()
+                        .copied()
+                        .unwrap_or_default(),
+                    replaced: if transition.transition_type()
+                        == TransitionType::BurnAndReplace as u16
+                    {
+                        metadata.u64(FieldType::IssuedSupply).first().copied()
+                    } else {
+                        None
+                    },
+                    burned_utxos: metadata
+                        .bytes(FieldType::BurnUtxo)
+                        .iter()
+                        .filter_map(|data| OutPoint::strict_deserialize(data).ok())
+                        .collect(),
+                    proofs: metadata
+                        .bytes(FieldType::HistoryProof)
+                        .iter()
+                        .filter_map(|data| HistoryProof::strict_deserialize(data).ok())
+                        .collect(),
+                    closed_seal: self
+                        .closed_seals(transition, OwnedRightType::BurnReplace)
+                        .next(),
+                    next_seal: self
+                        .assigned_seals(transition, Some(txid), OwnedRightType::BurnReplace)
+                        .next(),
+                }
+            })
+            .collect::<Vec<_>>();
+        burns.sort_by_key(|burn| burn.epoch.unwrap_or(u32::MAX));
+        burns
+    }
+
+    /// Verifies history proofs of the burn or burn & replace operation
+    /// against the known asset history. Each proof must open the Pedersen
+    /// commitment of a known asset allocation assigned to one of the burned
+    /// UTXOs, and the proven amounts must sum up to the burned supply.
+    pub fn verify_history_proofs(&self, burn: &BurnReplace) -> bool {
+        let mut proven = Vec::with_capacity(burn.proofs.len());
+        for proof in &burn.proofs {
+            let outpoint = proof.outpoint;
+            if outpoint.ty != OwnedRightType::Assets as u16 {
+                return false;
+            }
+            let commitment = self
+                .node_by_id(outpoint.node_id)
+                .and_then(|(node, _)| node.owned_rights_by_type(outpoint.ty))
+                .and_then(|assignments| {
+                    assignments
+                        .to_confidential_state_pedersen()
+                        .get(outpoint.no as usize)
+                        .map(|state| state.commitment)
+                });
+            if commitment != Some(value::Confidential::commit(&proof.state).commitment) {
+                return false;
+            }
+            match self.resolve_seal(outpoint) {
+                Some(seal) if burn.burned_utxos.contains(&seal) => {}
+                _ => return false,
+            }
+            proven.push(proof.state.value);
+        }
+        checked_sum(proven) == Some(burn.burned)
+    }
+
+    /// Summarizes supply burned and replaced within each of the known epochs,
+    /// in the order of the epochs. Operations with unknown epoch are not
+    /// accounted.
+    pub fn epoch_totals(&self) -> Vec<EpochTotals> {
+        let mut totals = self
+            .epochs()
+            .into_iter()
+            .map(|epoch| EpochTotals {
+                epoch: epoch.no,
+                node_id: epoch.node_id,
+                burned: 0,
+                replaced: 0,
+                operations: 0,
+            })
+            .collect::<Vec<_>>();
+        for burn in self.burns() {
+            let epoch = match burn.epoch {
+                Some(no) => &mut totals[no as usize],
+                None => continue,
+            };
+            epoch.burned = epoch.burned.saturating_add(burn.burned);
+            epoch.replaced = epoch
+                .replaced
+                .saturating_add(burn.replaced.unwrap_or_default());
+            epoch.operations += 1;
+        }
+        totals
+    }
+
+    /// Returns totals of the epoch to which the burn right assigned by the
+    /// `parent` node (epoch or preceding burn operation) belongs, if the epoch
+    /// is known
+    pub(crate) fn burn_right_epoch(&self, parent: NodeId) -> Option<EpochTotals> {
+        let epochs = self
+            .epochs()
+            .into_iter()
+            .map(|epoch| (epoch.node_id, epoch.no))
+            .collect::<BTreeMap<_, _>>();
+        let no = self.epoch_from(parent, &epochs)?;
+        self.epoch_totals().into_iter().nth(no as usize)
+    }
+
+    /// Follows the chain of burn operations back to the epoch which opened it
+    fn burn_epoch(&self, transition: &Transition, epochs: &BTreeMap<NodeId, u32>) -> Option<u32> {
+        let parent = transition
+            .parent_outputs_by_type(OwnedRightType::BurnReplace.into())
+            .first()?
+            .node_id;
+        self.epoch_from(parent, epochs)
+    }
+
+    /// Follows the chain of burn operations starting from the `parent` node
+    /// back to the epoch which opened it
+    fn epoch_from(&self, mut parent: NodeId, epochs: &BTreeMap<NodeId, u32>) -> Option<u32> {
+        let burn_right = OwnedRightType::BurnReplace.into();
+        loop {
+            if let Some(no) = epochs.get(&parent) {
+                return Some(*no);
+            }
+            let (node, _) = self.node_by_id(parent)?;
+            parent = node.parent_outputs_by_type(burn_right).first()?.node_id;
+        }
+    }
+
+    /// Returns the chain of known renominations starting from the genesis
+    /// together with the currently effective nomination.
+    ///
+    /// Errors with [`Error::RenominationChainBroken`] if some of the known
+    /// renominations can't be reached from the genesis through the known
+    /// renomination history.
+    pub fn renominations(&self) -> Result<Renominations, Error> {
+        let renomination_right = OwnedRightType::Renomination.into();
+        // Renomination right may be also moved by rights split transitions
+        let mut children = self
+            .transitions_by_type(TransitionType::Renomination)
+            .chain(self.transitions_by_type(TransitionType::RightsSplit))
+            .filter_map(|(txid, transition)| {
+                let parent = transition
+                    .parent_outputs_by_type(renomination_right)
+                    .first()?
+                    .node_id;
+                Some((parent, (txid, transition)))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut current = self.genesis_nomination();
+        let mut chain = vec![];
+        let mut prev = self.genesis().node_id();
+        while let Some((txid, transition)) = children.remove(&prev) {
+            prev = transition.node_id();
+            if transition.transition_type() != TransitionType::Renomination as u16 {
+                continue;
+            }
+            let new = current.apply(transition);
+            chain.push(Renomination {
+                node_id: prev,
+                witness_txid: txid,
+                old: current,
+                new: new.clone(),
+            });
+            current = new;
+        }
+
+        if let Some((_, transition)) = children
+            .values()
+            .find(|(_, t)| t.transition_type() == TransitionType::Renomination as u16)
+        {
+            return Err(Error::RenominationChainBroken {
+                contract_id: self.contract_id(),
+                node_id: transition.node_id(),
+            });
+        }
+
+        Ok(Renominations { chain, current })
+    }
+
+    /// Checks the genesis nomination and all nominations set by the known
+    /// renominations against the `policy`.
+    ///
+    /// Renominations which can't be reached from the genesis (see
+    /// [`Asset::renominations`]) are not checked.
+    pub fn check_nomination_policy(&self, policy: &NominationPolicy) -> Result<(), PolicyError> {
+        let renominations = self
+            .renominations()
+            .map(|renominations| renominations.chain)
+            .unwrap_or_default();
+        [self.genesis_nomination()]
+            .into_iter()
+            .chain(
+                renominations
+                    .into_iter()
+                    .map(|renomination| renomination.new),
+            )
+            .try_for_each(|nomination| policy.check_str(&nomination.ticker, &nomination.name))
+    }
+}
@@ -0,0 +1,196 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! RGB20 payment invoices, which receivers provide to senders in order to
+//! request a payment.
+//!
+//! Invoices are represented as Bech32m strings with `rgb20` HRP, encoding
+//! strict-serialized invoice data.
+
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::str::FromStr;
+
+use bitcoin::Address;
+use chrono::{DateTime, TimeZone, Utc};
+use lnpbp::bech32::{self, FromBech32Str, ToBech32String};
+use rgb::{seal, ContractId};
+#[cfg(feature = "serde")]
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use strict_encoding::{StrictDecode, StrictEncode};
+
+use crate::AssetAmount;
+
+/// Bech32 HRP used by RGB20 invoices
+pub const RGB20_INVOICE_HRP: &str = "rgb20";
+
+/// Receiver of the payment requested by an invoice
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[display(inner)]
+pub enum Beneficiary {
+    /// Blinded transaction output (seal) controlled by the receiver, to which
+    /// the assets must be assigned
+    BlindedSeal(seal::Confidential),
+
+    /// Bitcoin address to which the payer must send a witness transaction
+    /// output; the assets are assigned to that output
+    Address(Address),
+}
+
+impl StrictEncode for Beneficiary {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, strict_encoding::Error> {
+        Ok(match self {
+            Beneficiary::BlindedSeal(seal) => strict_encode_list!(e; 0u8, seal),
+            Beneficiary::Address(address) => strict_encode_list!(e; 1u8, address),
+        })
+    }
+}
+
+impl StrictDecode for Beneficiary {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, strict_encoding::Error> {
+        Ok(match u8::strict_decode(&mut d)? {
+            0 => Beneficiary::BlindedSeal(StrictDecode::strict_decode(&mut d)?),
+            1 => Beneficiary::Address(StrictDecode::strict_decode(&mut d)?),
+            wrong => {
+                return Err(strict_encoding::Error::EnumValueNotKnown(
+                    "Beneficiary",
+                    wrong as usize,
+                ))
+            }
+        })
+    }
+}
+
+/// RGB20 payment invoice.
+///
+/// The invoice is formatted and parsed as a Bech32m string with
+/// [`RGB20_INVOICE_HRP`] prefix.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(SerializeDisplay, DeserializeFromStr))]
+pub struct Invoice {
+    /// Contract of the requested asset
+    pub contract_id: ContractId,
+
+    /// Requested amount, in the asset decimal precision
+    pub amount: AssetAmount,
+
+    /// Receiver of the payment
+    pub beneficiary: Beneficiary,
+
+    /// Time after which the receiver will not accept the payment
+    pub expiry: Option<DateTime<Utc>>,
+}
+
+impl Invoice {
+    /// Detects whether the invoice is expired at the given moment of time
+    pub fn is_expired_at(&self, time: DateTime<Utc>) -> bool {
+        matches!(self.expiry, Some(expiry) if expiry <= time)
+    }
+
+    /// Detects whether the invoice is already expired
+    pub fn is_expired(&self) -> bool { self.is_expired_at(Utc::now()) }
+}
+
+impl StrictEncode for Invoice {
+    fn strict_encode<E: io::Write>(&self, mut e: E) -> Result<usize, strict_encoding::Error> {
+        Ok(strict_encode_list!(e;
+            self.contract_id,
+            self.amount.atomic_value(),
+            self.amount.precision(),
+            self.beneficiary,
+            self.expiry.map(|expiry| expiry.timestamp())
+        ))
+    }
+}
+
+impl StrictDecode for Invoice {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, strict_encoding::Error> {
+        let contract_id = ContractId::strict_decode(&mut d)?;
+        let atomic = u64::strict_decode(&mut d)?;
+        let precision = u8::strict_decode(&mut d)?;
+        let amount = AssetAmount::with_atomic(atomic, precision)
+            .map_err(|err| strict_encoding::Error::DataIntegrityError(err.to_string()))?;
+        let beneficiary = Beneficiary::strict_decode(&mut d)?;
+        let expiry = Option::<i64>::strict_decode(&mut d)?
+            .map(|timestamp| {
+                Utc.timestamp_opt(timestamp, 0).single().ok_or_else(|| {
+                    strict_encoding::Error::DataIntegrityError(s!("invalid invoice expiry time"))
+                })
+            })
+            .transpose()?;
+        Ok(Invoice {
+            contract_id,
+            amount,
+            beneficiary,
+            expiry,
+        })
+    }
+}
+
+impl bech32::Strategy for Invoice {
+    const HRP: &'static str = RGB20_INVOICE_HRP;
+    type Strategy = bech32::strategies::UsingStrictEncoding;
+}
+
+impl Display for Invoice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { f.write_str(&self.to_bech32_string()) }
+}
+
+impl FromStr for Invoice {
+    type Err = bech32::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> { Invoice::from_bech32_str(s) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::outpoint;
+
+    fn blinded_seal() -> Beneficiary {
+        Beneficiary::BlindedSeal(seal::Confidential::from(outpoint(1)))
+    }
+
+    fn invoice(beneficiary: Beneficiary) -> Invoice {
+        Invoice {
+            contract_id: ContractId::from_str(
+                "rgb1qgtsk88m0xaaf8v0a65pn9w3gqsyf5x9vcrfz8shk8yy82gr6wyqmmwz9g",
+            )
+            .unwrap(),
+            amount: AssetAmount::from_str("12.50").unwrap(),
+            beneficiary,
+            expiry: Some(Utc.timestamp_opt(1_700_000_000, 0).unwrap()),
+        }
+    }
+
+    #[test]
+    fn invoice_roundtrip() {
+        let blinded = blinded_seal();
+        let address = Beneficiary::Address(
+            Address::from_str("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx").unwrap(),
+        );
+        for beneficiary in [blinded, address] {
+            let invoice = invoice(beneficiary);
+            let s = invoice.to_string();
+            assert!(s.starts_with("rgb201"));
+            assert_eq!(Invoice::from_str(&s).unwrap(), invoice);
+        }
+    }
+
+    #[test]
+    fn invoice_expiry() {
+        let mut invoice = invoice(blinded_seal());
+        assert!(!invoice.is_expired_at(Utc.timestamp_opt(1_699_999_999, 0).unwrap()));
+        assert!(invoice.is_expired_at(Utc.timestamp_opt(1_700_000_000, 0).unwrap()));
+        invoice.expiry = None;
+        assert!(!invoice.is_expired());
+    }
+}
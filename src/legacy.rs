@@ -0,0 +1,92 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Pre-0.9 API for constructing RGB20 schemata, available with `legacy`
+//! feature.
+//!
+//! The API is superseded by [`Subschema`] and [`Rgb20Schemata`]; it is kept
+//! for the integrations which were not migrated yet and will be removed in
+//! the future releases. Functions [`to_subschema`] and [`from_subschema`]
+//! help migrating the code which stores or passes around the schema objects,
+//! while assets issued with the legacy versions of the library are read with
+//! [`crate::LegacyAsset`].
+
+#![allow(deprecated)]
+
+use rgb::Schema;
+
+use crate::{Rgb20Schemata, Subschema};
+
+/// Schema identifier for full RGB20 fungible asset
+#[deprecated(since = "0.9.0", note = "use `Subschema::Full.schema_id()`")]
+pub const SCHEMA_ID_BECH32: &str =
+    "rgbsh18kp34t5nn5zu4hz6g7lqjdjskw8aaf84ecdntrtrdvzs7gn3rnzskscfq8";
+
+/// Schema identifier for full RGB20 fungible asset subschema prohibiting burn &
+/// replace operations
+#[deprecated(since = "0.9.0", note = "use `Subschema::NoReplace.schema_id()`")]
+pub const SUBSCHEMA_ID_BECH32: &str =
+    "rgbsh1636y76cxrnsfqg7zjnl08f0kqt9j09tre2wfxzrrs86f76ssp7cqnn0yyf";
+
+/// Builds & returns complete RGB20 schema (root schema object)
+#[deprecated(since = "0.9.0", note = "use `Subschema::Full.schema()`")]
+pub fn schema() -> Schema { Subschema::Full.schema() }
+
+/// Provides the only defined RGB20 subschema, which prohibits replace procedure
+/// and allows only burn operations
+#[deprecated(since = "0.9.0", note = "use `Subschema::NoReplace.schema()`")]
+pub fn subschema() -> Schema { Subschema::NoReplace.schema() }
+
+/// Provides deflationary RGB20 subschema, which allows asset transfers and
+/// burn operations, but prohibits secondary issuance, burn & replace procedure
+/// and renomination
+#[deprecated(since = "0.9.0", note = "use `Subschema::Deflationary.schema()`")]
+pub fn deflationary_subschema() -> Schema { Subschema::Deflationary.schema() }
+
+/// Builds & returns RGB20 schema extending the root schema with media
+/// attachments
+#[deprecated(since = "0.9.0", note = "use `Subschema::Media.schema()`")]
+pub fn media_schema() -> Schema { Subschema::Media.schema() }
+
+/// Converts schema object constructed with the legacy API into [`Subschema`].
+/// Returns `None` if the schema is not one of RGB20 schemata.
+pub fn to_subschema(schema: &Schema) -> Option<Subschema> {
+    Subschema::from_schema_id(schema.schema_id())
+}
+
+/// Converts [`Subschema`] into the schema object used by the legacy API,
+/// taking it from [`Rgb20Schemata`] registry
+pub fn from_subschema(subschema: Subschema) -> Schema {
+    Rgb20Schemata::by_id(subschema.schema_id()).expect("registry contains all subschemata")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn legacy_conversion() {
+        for (legacy, subschema) in [
+            (schema(), Subschema::Full),
+            (subschema(), Subschema::NoReplace),
+            (deflationary_subschema(), Subschema::Deflationary),
+            (media_schema(), Subschema::Media),
+        ] {
+            assert_eq!(to_subschema(&legacy), Some(subschema));
+            assert_eq!(from_subschema(subschema), legacy);
+        }
+        assert_eq!(Subschema::Full.schema_id().to_string(), SCHEMA_ID_BECH32);
+        assert_eq!(
+            Subschema::NoReplace.schema_id().to_string(),
+            SUBSCHEMA_ID_BECH32
+        );
+    }
+}
@@ -22,13 +22,20 @@
 )]
 
 //! RGB20 library for working with fungible asset types, operating under
-//! schemata, defined with LNPBP-20 standard:
-//! - Root RGB20 schema, returned by [`schema::schema()`] with id
-//!   [`SCHEMA_ID_BECH32`]
-//! - RGB20 subschema, returned by [`schema::subschema()`], prohibiting asset
-//!   replacement procedure and having id [`SUBSCHEMA_ID_BECH32`]
+//! schemata, defined with LNPBP-20 standard and listed by [`Subschema`]:
+//! - Root RGB20 schema, [`Subschema::Full`]
+//! - RGB20 subschema, [`Subschema::NoReplace`], prohibiting asset replacement
+//!   procedure
+//! - Deflationary RGB20 subschema, [`Subschema::Deflationary`], allowing only
+//!   asset transfers and burn operations
+//! - RGB20 media schema, [`Subschema::Media`], extending the root schema with
+//!   asset logo, icon and terms attachments and Ricardian contract text
 //! - High-level RGB20 API performing asset issuance, transfers and other
 //!   asset-management operations
+//!
+//! Schema objects are constructed with [`Subschema::schema`] and looked up by
+//! their ids with [`Rgb20Schemata`]. The pre-0.9 free functions and constants
+//! are available from the `legacy` module with `legacy` feature enabled.
 
 #[macro_use]
 extern crate amplify;
@@ -40,15 +47,136 @@ extern crate rgb;
 extern crate stens;
 
 #[cfg(feature = "serde")]
+#[macro_use]
 extern crate serde_crate as serde;
 #[cfg(feature = "serde")]
 extern crate serde_with;
 
 pub mod schema;
+pub mod seal;
+pub mod invoice;
+pub mod psbt;
+pub mod tapret;
+pub mod opret;
+pub mod accept;
+pub mod watch;
+pub mod registry;
+pub mod templates;
+pub mod vectors;
+#[cfg(feature = "legacy")]
+pub mod legacy;
+#[cfg(feature = "store")]
+pub mod store;
+#[cfg(feature = "cbor")]
+pub mod codec;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "testutils")]
+pub mod testutils;
+#[cfg(feature = "ln")]
+pub mod ln;
+mod armor;
 mod create;
+mod datetime;
+mod diff;
+mod batch;
+mod burnproof;
+mod chunks;
+mod collision;
+mod commitment;
+mod disclosure;
+mod distribute;
+mod estimate;
+mod plan;
+mod privacy;
+mod provenance;
+mod receipt;
+mod features;
+mod graph;
 mod asset;
+mod attachment;
 mod transitions;
+mod select;
+mod supply;
+mod history;
+mod merge;
+mod metadata;
+mod migrate;
+mod multisig;
+mod reserves;
+mod resolver;
+mod nomination;
+mod validation;
+mod summary;
+mod sync;
+mod stream;
+mod snapshot;
+mod vesting;
+mod spent;
+#[cfg(test)]
+mod fixtures;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "async")]
+mod nonblocking;
 
-pub use asset::{Asset, Error};
-pub use create::Rgb20;
-pub use schema::{schema, subschema, SCHEMA_ID_BECH32, SUBSCHEMA_ID_BECH32};
+pub use accept::{accept_transfer, AcceptError, Acceptance};
+pub use armor::Armor;
+pub use asset::{AmountError, Asset, AssetAmount, Error, StateUpdate, MAX_PRECISION};
+pub use attachment::{Attachment, AttachmentType};
+pub use batch::{BatchError, BatchIssue, BatchIssuer};
+pub use burnproof::{verify_burn_proof, BurnProof, BurnProofError};
+pub use chunks::{ChunkDecoder, ChunkError};
+pub use collision::{Collision, CollisionKind, TickerIndex, DEFAULT_SIMILARITY_THRESHOLD};
+pub use commitment::{CommitmentError, CommitmentMethod};
+pub use create::{
+    AllocationTarget, GenesisAllocation, InflationAllocations, InflationOverflow, IssueError,
+    Rgb20, Rgb20Builder, MIN_TIMESTAMP,
+};
+pub use datetime::{format_time, format_timestamp, utc_time};
+pub use diff::{AssetDiff, Change};
+pub use distribute::Distribution;
+pub use estimate::TransitionEstimate;
+pub use features::Features;
+pub use graph::{GraphEdge, GraphNode, HistoryGraph};
+pub use history::{
+    BurnReplace, Epoch, EpochTotals, HistoryProof, Issuance, Nomination, Renomination,
+    Renominations,
+};
+#[cfg(feature = "legacy")]
+#[allow(deprecated)]
+pub use legacy::{
+    deflationary_subschema, media_schema, schema, subschema, SCHEMA_ID_BECH32, SUBSCHEMA_ID_BECH32,
+};
+pub use merge::{merge_transfers, MergeError};
+pub use metadata::MetadataFields;
+pub use migrate::{LegacyAsset, MigrationError};
+pub use multisig::{
+    CoSignError, CoSignSession, DescriptorError, MultisigDescriptor, MULTISIG_MAX_KEYS,
+};
+pub use nomination::{
+    AssetName, NominationError, NominationPolicy, PolicyError, Ticker, NAME_MAX_LEN,
+    TICKER_MAX_LEN, TICKER_MIN_LEN,
+};
+#[cfg(feature = "async")]
+pub use nonblocking::{AsyncUtxoProvider, AsyncWitnessResolver};
+pub use plan::TransferPlan;
+pub use privacy::{AssignmentDisclosure, Audience, MetadataDisclosure, PrivacyReport};
+pub use provenance::AllocationRecord;
+pub use receipt::{Receipt, ReceiptError};
+pub use reserves::{ReservesError, ReservesReport, SignedReserves};
+#[cfg(feature = "esplora")]
+pub use resolver::EsploraResolver;
+pub use resolver::{ResolverError, WitnessResolver};
+pub use schema::{Rgb20Schemata, SchemaCompatibility, Subschema, SubschemaBuilder, SubschemaError};
+pub use select::{CoinSelection, UnknownCoinSelection};
+pub use snapshot::{SnapshotError, SNAPSHOT_VERSION};
+pub use spent::SpentAllocation;
+#[cfg(feature = "store")]
+pub use store::{AssetStore, FileStore, StoreError};
+pub use stream::{ConsignmentReader, StreamError};
+pub use summary::{Allocation, ConsignmentSummary, Endpoint, Operation};
+pub use supply::SupplyMeasures;
+pub use sync::{AllocationSync, UtxoProvider};
+pub use validation::{OfflineResolver, ValidationReport};
+pub use vesting::{Balance, LockedAllocation, LockedSeal, VestingError};
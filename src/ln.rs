@@ -0,0 +1,280 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Helpers for RGB20 assets held by lightning network channels.
+//!
+//! The channel funding transaction output, locked with 2-of-2 multisig of the
+//! channel peers, is used as a seal receiving the asset allocated to the
+//! channel. Each channel commitment transaction closes this seal with a state
+//! transition distributing the channel asset balance among the commitment
+//! transaction outputs. Since commitment transactions are never published in
+//! the normal channel operation, an asset view is re-materialized for each
+//! new commitment from the asset state at channel funding.
+//!
+//! Commitment transitions are created by one of the peers and sent to the
+//! other one, which checks them with [`Asset::verify_channel_update`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bitcoin::blockdata::opcodes::all::OP_CHECKMULTISIG;
+use bitcoin::blockdata::script::Builder;
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::{OutPoint, PublicKey, Script, Txid};
+use rgb::prelude::*;
+use seals::txout::{CloseMethod, ExplicitSeal};
+
+use crate::schema::{OwnedRightType, TransitionType};
+use crate::transitions::Error;
+use crate::Asset;
+
+/// Tag for deriving blinding factors of the commitment transaction seals
+const COMMITMENT_SEAL_TAG: &[u8] = b"rgb20:ln:commitment";
+
+/// Constructs 2-of-2 multisig witness script locking the channel funding
+/// output, with the peer keys sorted lexicographically as required by BOLT-3
+pub fn funding_script(local_key: PublicKey, remote_key: PublicKey) -> Script {
+    let (first, second) = if local_key.to_bytes() < remote_key.to_bytes() {
+        (local_key, remote_key)
+    } else {
+        (remote_key, local_key)
+    };
+    Builder::new()
+        .push_int(2)
+        .push_key(&first)
+        .push_key(&second)
+        .push_int(2)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script()
+}
+
+/// Constructs P2WSH script pubkey of the channel funding output
+pub fn funding_script_pubkey(local_key: PublicKey, remote_key: PublicKey) -> Script {
+    funding_script(local_key, remote_key).to_v0_p2wsh()
+}
+
+/// Constructs seal over the output `vout` of the commitment transaction with
+/// number `commitment_no` spending the `funding` output. The seal blinding
+/// factor is derived deterministically, such that both channel peers define
+/// the same seals.
+pub fn commitment_seal(
+    funding: OutPoint,
+    commitment_no: u64,
+    vout: u32,
+    method: CloseMethod,
+) -> seal::Revealed {
+    let mut engine = sha256::Hash::engine();
+    engine.input(COMMITMENT_SEAL_TAG);
+    engine.input(&funding.txid[..]);
+    engine.input(&funding.vout.to_le_bytes());
+    engine.input(&commitment_no.to_le_bytes());
+    engine.input(&vout.to_le_bytes());
+    let hash = sha256::Hash::from_engine(engine);
+    let mut blinding = [0u8; 8];
+    blinding.copy_from_slice(&hash[..8]);
+    seal::Revealed {
+        method,
+        txid: None,
+        vout,
+        blinding: u64::from_le_bytes(blinding),
+    }
+}
+
+impl Asset {
+    /// Creates state transition allocating `value` to the channel funding
+    /// output `funding_vout` of the witness transaction. The remaining input
+    /// value must be assigned to the `change` seals.
+    pub fn fund_channel(
+        &self,
+        inputs: BTreeSet<OutPoint>,
+        funding_vout: u32,
+        value: AtomicValue,
+        change: SealValueMap,
+        method: CloseMethod,
+    ) -> Result<Transition, Error> {
+        let funding = seal::Revealed::from(ExplicitSeal {
+            method,
+            txid: None,
+            vout: funding_vout,
+        });
+        self.transfer(
+            inputs,
+            bmap! { SealEndpoint::from(funding) => value },
+            change,
+        )
+    }
+
+    /// Creates state transition template for the commitment transaction with
+    /// number `commitment_no`, distributing all asset allocations of the
+    /// channel `funding` output among the commitment transaction `outputs`,
+    /// indexed by their number.
+    pub fn channel_update(
+        &self,
+        funding: OutPoint,
+        commitment_no: u64,
+        outputs: &BTreeMap<u32, AtomicValue>,
+        method: CloseMethod,
+    ) -> Result<Transition, Error> {
+        let change = outputs
+            .iter()
+            .filter(|(_, value)| **value > 0)
+            .map(|(vout, value)| {
+                (
+                    commitment_seal(funding, commitment_no, *vout, method),
+                    *value,
+                )
+            })
+            .collect();
+        self.transfer(bset! { funding }, empty!(), change)
+    }
+
+    /// Verifies that the commitment transition received from the channel
+    /// peer spends all allocations of the `funding` output and assigns
+    /// exactly the `outputs` values to the commitment transaction outputs
+    pub fn verify_channel_update(
+        &self,
+        transition: &Transition,
+        funding: OutPoint,
+        commitment_no: u64,
+        outputs: &BTreeMap<u32, AtomicValue>,
+        method: CloseMethod,
+    ) -> bool {
+        if transition.transition_type() != TransitionType::Transfer as u16 {
+            return false;
+        }
+        let closed = transition
+            .parent_outputs()
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        let funded = self
+            .allocations_at(funding)
+            .iter()
+            .map(|coin| coin.outpoint)
+            .collect::<BTreeSet<_>>();
+        if closed != funded {
+            return false;
+        }
+
+        let expected = outputs
+            .iter()
+            .filter(|(_, value)| **value > 0)
+            .map(|(vout, value)| {
+                (
+                    commitment_seal(funding, commitment_no, *vout, method),
+                    *value,
+                )
+            })
+            .collect::<BTreeSet<_>>();
+        let assigned = match transition.owned_rights_by_type(OwnedRightType::Assets.into()) {
+            Some(TypedAssignments::Value(assignments)) => assignments
+                .iter()
+                .map(|assignment| {
+                    Some((
+                        assignment.revealed_seal()?,
+                        assignment.as_revealed_state()?.value,
+                    ))
+                })
+                .collect::<Option<BTreeSet<_>>>(),
+            _ => None,
+        };
+        assigned == Some(expected)
+    }
+
+    /// Re-materializes asset view for the channel commitment transaction
+    /// `commitment_txid` closing the funding seal with the commitment
+    /// `transition`.
+    ///
+    /// The method must be called on the asset state at channel funding (i.e.
+    /// without any previous commitment transitions), since all commitment
+    /// transitions spend the same funding output.
+    pub fn with_commitment(&self, transition: Transition, commitment_txid: Txid) -> Asset {
+        let mut asset = self.clone();
+        asset.add_transition(commitment_txid, transition);
+        asset.reindex();
+        asset
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use lnpbp::chain::Chain;
+
+    use super::*;
+    use crate::fixtures::outpoint;
+    use crate::{AssetName, Rgb20Builder, Ticker};
+
+    fn pubkey(byte: u8) -> PublicKey {
+        let secp = Secp256k1::new();
+        let key = SecretKey::from_slice(&[byte; 32]).unwrap();
+        PublicKey::new(bitcoin::secp256k1::PublicKey::from_secret_key(&secp, &key))
+    }
+
+    #[test]
+    fn funding_script_is_symmetric() {
+        let script = funding_script(pubkey(1), pubkey(2));
+        assert_eq!(script, funding_script(pubkey(2), pubkey(1)));
+        assert!(funding_script_pubkey(pubkey(1), pubkey(2)).is_v0_p2wsh());
+    }
+
+    #[test]
+    fn commitment_update() {
+        let funding = outpoint(0);
+        let contract = Rgb20Builder::new(Chain::Signet)
+            .ticker(Ticker::from_str("TICK").unwrap())
+            .name(AssetName::from_str("Test asset").unwrap())
+            .allocate(funding, 100)
+            .build()
+            .unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+
+        let outputs = bmap! { 0 => 70, 1 => 30, 2 => 0 };
+        let transition = asset
+            .channel_update(funding, 1, &outputs, CloseMethod::TapretFirst)
+            .unwrap();
+        assert!(asset.verify_channel_update(
+            &transition,
+            funding,
+            1,
+            &outputs,
+            CloseMethod::TapretFirst
+        ));
+        assert!(!asset.verify_channel_update(
+            &transition,
+            funding,
+            2,
+            &outputs,
+            CloseMethod::TapretFirst
+        ));
+
+        let commitment_txid =
+            Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap();
+        let view = asset.with_commitment(transition, commitment_txid);
+        assert!(view.allocations_at(funding).is_empty());
+        assert_eq!(
+            view.allocations_at(OutPoint::new(commitment_txid, 0))[0]
+                .state
+                .value,
+            70
+        );
+        assert_eq!(
+            view.allocations_at(OutPoint::new(commitment_txid, 1))[0]
+                .state
+                .value,
+            30
+        );
+        assert!(asset
+            .allocations_at(OutPoint::new(commitment_txid, 0))
+            .is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use rgb::{
+    reveal, BundleId, Consignment, ConsignmentType, ContractId, Extension, InmemConsignment,
+    MergeReveal, Node, NodeId, SchemaId, Transition, TransitionBundle,
+};
+
+/// Errors happening during merging of multiple consignments with
+/// [`merge_transfers`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, From, Error)]
+#[display(doc_comments)]
+pub enum MergeError {
+    /// no consignments are provided for merging
+    NoConsignments,
+
+    /// consignment belongs to a different contract {0}
+    ContractMismatch(ContractId),
+
+    /// consignment uses schema {0} which does not match schema of the other
+    /// consignments
+    SchemaMismatch(SchemaId),
+
+    /// consignments contain conflicting versions of the same node. Details:
+    /// {0}
+    #[from]
+    NodeMismatch(reveal::Error),
+}
+
+/// Merges multiple consignments for the same contract into a single one.
+///
+/// Genesis, state transitions and extensions shared by several consignments
+/// are included only once, combining all data revealed by any of them.
+/// Transition bundles keep the order in which they first appear in the
+/// provided consignments; endpoints are deduplicated. Merged consignment uses
+/// the schema (and root schema) of the first consignment.
+pub fn merge_transfers<T>(
+    consignments: impl IntoIterator<Item = InmemConsignment<T>>,
+) -> Result<InmemConsignment<T>, MergeError>
+where T: ConsignmentType {
+    let mut consignments = consignments.into_iter();
+    let first = consignments.next().ok_or(MergeError::NoConsignments)?;
+
+    let contract_id = first.contract_id();
+    let schema_id = first.schema_id();
+    let schema = first.schema().clone();
+    let root_schema = first.root_schema().cloned();
+    let mut genesis = first.genesis().clone();
+    let mut endseals = vec![];
+    let mut bundle_ids = vec![];
+    let mut bundles = BTreeMap::<BundleId, (_, MergedBundle)>::new();
+    let mut extensions = BTreeMap::<NodeId, Extension>::new();
+
+    for consignment in Some(first).into_iter().chain(consignments) {
+        if consignment.contract_id() != contract_id {
+            return Err(MergeError::ContractMismatch(consignment.contract_id()));
+        }
+        if consignment.schema_id() != schema_id {
+            return Err(MergeError::SchemaMismatch(consignment.schema_id()));
+        }
+
+        genesis = genesis.merge_reveal(consignment.genesis().clone())?;
+
+        for endseal in consignment.endpoints() {
+            if !endseals.contains(endseal) {
+                endseals.push(*endseal);
+            }
+        }
+
+        for (anchor, bundle) in consignment.anchored_bundles() {
+            let bundle_id = bundle.bundle_id();
+            match bundles.get_mut(&bundle_id) {
+                Some((_, merged)) => merged.merge(bundle)?,
+                None => {
+                    bundle_ids.push(bundle_id);
+                    bundles.insert(bundle_id, (anchor.clone(), MergedBundle::from(bundle)));
+                }
+            }
+        }
+
+        for extension in consignment.state_extensions() {
+            let node_id = extension.node_id();
+            let extension = match extensions.remove(&node_id) {
+                Some(known) => known.merge_reveal(extension.clone())?,
+                None => extension.clone(),
+            };
+            extensions.insert(node_id, extension);
+        }
+    }
+
+    let anchored_bundles = bundle_ids
+        .into_iter()
+        .map(|bundle_id| {
+            let (anchor, merged) = bundles
+                .remove(&bundle_id)
+                .expect("bundle ids are taken from the map");
+            (anchor, merged.into())
+        })
+        .collect::<Vec<_>>();
+
+    Ok(InmemConsignment::with(
+        schema,
+        root_schema,
+        genesis,
+        endseals,
+        anchored_bundles
+            .try_into()
+            .expect("merged bundles are taken from the consignments and fit the size limit"),
+        extensions
+            .into_values()
+            .collect::<Vec<_>>()
+            .try_into()
+            .expect("merged extensions are taken from the consignments and fit the size limit"),
+    ))
+}
+
+/// Transition bundle accumulating revealed data from multiple consignments
+struct MergedBundle {
+    revealed: BTreeMap<NodeId, (Transition, BTreeSet<u16>)>,
+    concealed: BTreeMap<NodeId, BTreeSet<u16>>,
+}
+
+impl From<&TransitionBundle> for MergedBundle {
+    fn from(bundle: &TransitionBundle) -> Self {
+        MergedBundle {
+            revealed: bundle
+                .revealed_iter()
+                .map(|(transition, inputs)| {
+                    (transition.node_id(), (transition.clone(), inputs.clone()))
+                })
+                .collect(),
+            concealed: bundle
+                .concealed_iter()
+                .map(|(id, inputs)| (*id, inputs.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl From<MergedBundle> for TransitionBundle {
+    fn from(merged: MergedBundle) -> Self {
+        TransitionBundle::with(merged.revealed.into_values().collect(), merged.concealed)
+            .expect("merged bundle is never empty")
+    }
+}
+
+impl MergedBundle {
+    /// Adds data revealed by another copy of the same bundle
+    fn merge(&mut self, bundle: &TransitionBundle) -> Result<(), reveal::Error> {
+        for (transition, inputs) in bundle.revealed_iter() {
+            let node_id = transition.node_id();
+            let transition = match self.revealed.remove(&node_id) {
+                Some((known, _)) => known.merge_reveal(transition.clone())?,
+                None => transition.clone(),
+            };
+            self.concealed.remove(&node_id);
+            self.revealed.insert(node_id, (transition, inputs.clone()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use chrono::{TimeZone, Utc};
+    use lnpbp::chain::Chain;
+    use rgb::Contract;
+
+    use super::*;
+    use crate::fixtures::outpoint;
+    use crate::{Asset, AssetName, Rgb20Builder, Ticker};
+
+    fn contract(ticker: &str) -> Contract {
+        Rgb20Builder::new(Chain::Signet)
+            .ticker(Ticker::from_str(ticker).unwrap())
+            .name(AssetName::from_str("Test asset").unwrap())
+            .allocate(outpoint(1), 1000)
+            .timestamp(Utc.timestamp_opt(1_650_000_000, 0).unwrap())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn merge_same_contract() {
+        let contract = contract("TICK");
+        let merged = merge_transfers([contract.clone(), contract.clone()]).unwrap();
+        assert_eq!(merged.contract_id(), contract.contract_id());
+        assert_eq!(merged.anchored_bundles().count(), 0);
+        assert_eq!(
+            Asset::try_from(&merged).unwrap().known_coins().count(),
+            Asset::try_from(&contract).unwrap().known_coins().count()
+        );
+    }
+
+    #[test]
+    fn merge_errors() {
+        assert_eq!(
+            merge_transfers(Vec::<Contract>::new()).unwrap_err(),
+            MergeError::NoConsignments
+        );
+
+        let other = contract("OTHER");
+        assert_eq!(
+            merge_transfers([contract("TICK"), other.clone()]).unwrap_err(),
+            MergeError::ContractMismatch(other.contract_id())
+        );
+    }
+}
@@ -0,0 +1,109 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeMap;
+
+use amplify::Wrapper;
+use rgb::{data, Metadata, Node, NodeId};
+
+use crate::schema::FieldType;
+use crate::Asset;
+
+/// Raw metadata fields of the asset genesis or state transition
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct MetadataFields {
+    /// Fields of the types defined by RGB20 schemata
+    pub known: BTreeMap<FieldType, Vec<data::Revealed>>,
+
+    /// Fields of the types which are not defined by RGB20 schemata, keyed by
+    /// the raw field type
+    pub custom: BTreeMap<rgb::schema::FieldType, Vec<data::Revealed>>,
+}
+
+impl MetadataFields {
+    /// Returns values of the field with the given type; empty if the field is
+    /// absent
+    pub fn get(&self, ty: FieldType) -> &[data::Revealed] {
+        self.known.get(&ty).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Detects whether there are no metadata fields
+    pub fn is_empty(&self) -> bool { self.known.is_empty() && self.custom.is_empty() }
+}
+
+impl From<&Metadata> for MetadataFields {
+    fn from(metadata: &Metadata) -> Self {
+        let mut fields = MetadataFields::default();
+        for (ty, values) in metadata.as_inner() {
+            match FieldType::try_from(*ty) {
+                Ok(ty) => fields.known.insert(ty, values.clone()),
+                Err(ty) => fields.custom.insert(ty, values.clone()),
+            };
+        }
+        fields
+    }
+}
+
+impl Asset {
+    /// Returns raw metadata fields of the asset genesis
+    pub fn genesis_metadata(&self) -> MetadataFields { self.genesis().metadata().into() }
+
+    /// Returns raw metadata fields of the known state transition, or `None`
+    /// if the transition with `node_id` is not known
+    pub fn transition_metadata(&self, node_id: NodeId) -> Option<MetadataFields> {
+        match self.node_by_id(node_id)? {
+            (node, Some(_)) => Some(node.metadata().into()),
+            (_, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::schema::TransitionType;
+    use crate::{vectors, Ticker};
+
+    #[test]
+    fn raw_metadata() {
+        let mut asset = vectors::asset();
+        let genesis = asset.genesis_metadata();
+        assert!(genesis.custom.is_empty());
+        assert_eq!(genesis.get(FieldType::Ticker), &[
+            data::Revealed::AsciiString(Ticker::from_str(vectors::TICKER).unwrap().into())
+        ]);
+        assert_eq!(genesis.get(FieldType::Precision), &[data::Revealed::U8(
+            vectors::PRECISION
+        )]);
+        assert!(genesis.get(FieldType::BurnedSupply).is_empty());
+        assert_eq!(asset.transition_metadata(asset.genesis().node_id()), None);
+
+        let burn = vectors::transitions()[&TransitionType::Burn].clone();
+        let node_id = burn.node_id();
+        assert_eq!(asset.transition_metadata(node_id), None);
+        asset.add_transition(vectors::txid(), burn);
+        let fields = asset.transition_metadata(node_id).unwrap();
+        assert_eq!(fields.get(FieldType::BurnedSupply), &[data::Revealed::U64(
+            vectors::BURNED_SUPPLY
+        )]);
+        assert!(!fields.get(FieldType::HistoryProof).is_empty());
+
+        let metadata: BTreeMap<rgb::schema::FieldType, _> = bmap! {
+            FieldType::Ticker.into() => vec![data::Revealed::U8(1)],
+            0xFFF0 => vec![data::Revealed::U8(2)]
+        };
+        let fields = MetadataFields::from(&Metadata::from(metadata));
+        assert_eq!(fields.known.len(), 1);
+        assert_eq!(fields.custom[&0xFFF0], vec![data::Revealed::U8(2)]);
+    }
+}
@@ -0,0 +1,148 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use rgb::prelude::*;
+use rgb::AttachmentId;
+
+use crate::schema::OwnedRightType;
+use crate::{Asset, Subschema};
+
+/// Errors migrating assets issued with pre-0.9 versions of the library
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum MigrationError {
+    /// contract is issued under schema {0}, which is not known to pre-0.9
+    /// versions of the library
+    NotLegacy(SchemaId),
+
+    /// {0}
+    #[from]
+    Asset(crate::Error),
+}
+
+/// Read-only view over an asset issued with pre-0.9 version of the library.
+///
+/// Legacy assets are issued under the root RGB20 schema or its subschema
+/// prohibiting burn & replace procedure. Both schemata are unchanged in this
+/// library, so the legacy geneses and consignments are consumed by [`Asset`]
+/// as they are. Legacy library, however, defined Ricardian contract of the
+/// asset with the attachment assigned to the genesis renomination right,
+/// while this library keeps contract text in the genesis metadata of
+/// [`Subschema::Media`]; the view exposes the legacy contract with
+/// [`LegacyAsset::ricardian_contract`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LegacyAsset {
+    asset: Asset,
+    ricardian_contract: Option<AttachmentId>,
+}
+
+impl LegacyAsset {
+    /// Returns asset constructed from the legacy consignment
+    pub fn asset(&self) -> &Asset { &self.asset }
+
+    /// Returns id of the Ricardian contract attached to the genesis
+    /// renomination right, if any
+    pub fn ricardian_contract(&self) -> Option<AttachmentId> { self.ricardian_contract }
+
+    /// Converts into the asset, discarding legacy data
+    pub fn into_asset(self) -> Asset { self.asset }
+}
+
+impl<T> TryFrom<&InmemConsignment<T>> for LegacyAsset
+where T: ConsignmentType
+{
+    type Error = MigrationError;
+
+    fn try_from(consignment: &InmemConsignment<T>) -> Result<Self, Self::Error> {
+        let genesis = consignment.genesis();
+        let schema_id = genesis.schema_id();
+        match Subschema::from_schema_id(schema_id) {
+            Some(Subschema::Full | Subschema::NoReplace) => {}
+            _ => return Err(MigrationError::NotLegacy(schema_id)),
+        }
+
+        let ricardian_contract = genesis
+            .owned_rights_by_type(OwnedRightType::Renomination.into())
+            .and_then(|assignments| {
+                assignments
+                    .filter_revealed_state_attachments()
+                    .first()
+                    .map(|attachment| attachment.id)
+            });
+
+        Ok(LegacyAsset {
+            asset: Asset::try_from(consignment)?,
+            ricardian_contract,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::Wrapper;
+    use commit_verify::CommitVerify;
+
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+    use crate::OfflineResolver;
+
+    #[test]
+    fn legacy_ricardian_contract() {
+        let builder = builder();
+        let contract = builder.clone().renomination(outpoint(2)).build().unwrap();
+
+        // Legacy library assigned Ricardian contract to the renomination right
+        let contract_id = AttachmentId::commit(b"Ricardian contract");
+        let genesis = contract.genesis();
+        let mut owned_rights = genesis.owned_rights().as_inner().clone();
+        owned_rights.insert(
+            OwnedRightType::Renomination.into(),
+            TypedAssignments::Attachment(vec![Assignment::Revealed {
+                seal: seal::Revealed::from(outpoint(2)),
+                state: rgb::contract::attachment::Revealed {
+                    id: contract_id,
+                    mime: "text/plain".try_into().unwrap(),
+                    salt: 0,
+                },
+            }]),
+        );
+        let genesis = Genesis::with(
+            genesis.schema_id(),
+            genesis.chain().clone(),
+            genesis.metadata().clone(),
+            owned_rights,
+            empty!(),
+        );
+        let legacy = Contract::with(
+            contract.schema().clone(),
+            None,
+            genesis,
+            empty!(),
+            empty!(),
+            empty!(),
+        );
+        assert!(Asset::validate_consignment(&legacy, &OfflineResolver::new()).is_valid());
+
+        let migrated = LegacyAsset::try_from(&legacy).unwrap();
+        assert_eq!(migrated.ricardian_contract(), Some(contract_id));
+        assert_eq!(migrated.asset().contract_id(), legacy.contract_id());
+        assert_eq!(migrated.into_asset().allocations_at(outpoint(1)).len(), 1);
+
+        let migrated = LegacyAsset::try_from(&contract).unwrap();
+        assert_eq!(migrated.ricardian_contract(), None);
+
+        let media = builder.subschema(Subschema::Media).build().unwrap();
+        assert_eq!(
+            LegacyAsset::try_from(&media),
+            Err(MigrationError::NotLegacy(Subschema::Media.schema_id()))
+        );
+    }
+}
@@ -0,0 +1,612 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Inflation rights controlled by n-of-m multisig.
+//!
+//! Issuers may assign inflation rights to transaction outputs paying to a
+//! P2WSH multisig script, described with [`MultisigDescriptor`]. Secondary
+//! issuance closing such rights requires the witness transaction to be signed
+//! by the threshold number of the signers; [`CoSignSession`] coordinates
+//! construction of the issue state transition, its anchoring to the witness
+//! transaction and collection of the signatures.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use ::psbt::Psbt;
+use bitcoin::blockdata::opcodes::all::OP_CHECKMULTISIG;
+use bitcoin::blockdata::script::Builder;
+use bitcoin::psbt::PartiallySignedTransaction;
+use bitcoin::secp256k1::{self, Message, Secp256k1};
+use bitcoin::util::sighash::SighashCache;
+use bitcoin::{
+    Address, Network, OutPoint, PackedLockTime, PublicKey, Script, Sequence, Transaction, TxIn,
+    TxOut, Witness,
+};
+use commit_verify::lnpbp4;
+use rgb::fungible::allocation::AllocationValueVec;
+use rgb::psbt::RgbExt;
+use rgb::{Anchor, ContractId, Node, NodeId, TransitionBundle};
+use seals::txout::{CloseMethod, ExplicitSeal};
+
+use crate::psbt::embed_transition;
+use crate::{psbt, transitions, Asset, InflationAllocations};
+
+/// Maximum number of keys in a multisig script
+pub const MULTISIG_MAX_KEYS: usize = 20;
+
+/// Errors parsing and constructing [`MultisigDescriptor`]
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum DescriptorError {
+    /// descriptor `{0}` is not of `wsh(multi(...))` or `wsh(sortedmulti(...))`
+    /// form
+    InvalidFormat(String),
+
+    /// invalid compressed public key `{0}` in multisig descriptor
+    InvalidKey(String),
+
+    /// multisig threshold {threshold} is invalid for {keys} keys
+    InvalidThreshold {
+        /// Number of signatures required by the descriptor
+        threshold: usize,
+        /// Number of keys in the descriptor
+        keys: usize,
+    },
+
+    /// public key {0} is used multiple times in multisig descriptor
+    DuplicateKey(PublicKey),
+}
+
+/// Descriptor of P2WSH n-of-m multisig script, in `wsh(multi(k,...))` or
+/// `wsh(sortedmulti(k,...))` form with hex-encoded compressed public keys
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct MultisigDescriptor {
+    threshold: usize,
+    keys: Vec<PublicKey>,
+    sorted: bool,
+}
+
+impl MultisigDescriptor {
+    /// Constructs descriptor requiring `threshold` signatures by the `keys`.
+    /// If `sorted` is set, keys are put into the script in lexicographic
+    /// order (BIP-67), otherwise in the provided order.
+    pub fn with(
+        threshold: usize,
+        keys: Vec<PublicKey>,
+        sorted: bool,
+    ) -> Result<MultisigDescriptor, DescriptorError> {
+        if threshold == 0 || threshold > keys.len() || keys.len() > MULTISIG_MAX_KEYS {
+            return Err(DescriptorError::InvalidThreshold {
+                threshold,
+                keys: keys.len(),
+            });
+        }
+        for (no, key) in keys.iter().enumerate() {
+            if !key.compressed {
+                return Err(DescriptorError::InvalidKey(key.to_string()));
+            }
+            if keys[..no].contains(key) {
+                return Err(DescriptorError::DuplicateKey(*key));
+            }
+        }
+        Ok(MultisigDescriptor {
+            threshold,
+            keys,
+            sorted,
+        })
+    }
+
+    /// Returns number of signatures required for spending
+    pub fn threshold(&self) -> usize { self.threshold }
+
+    /// Returns keys in the order they are put into the script
+    pub fn keys(&self) -> Vec<PublicKey> {
+        let mut keys = self.keys.clone();
+        if self.sorted {
+            keys.sort_by_key(|key| key.to_bytes());
+        }
+        keys
+    }
+
+    /// Detects whether the key is one of the multisig keys
+    pub fn contains(&self, key: &PublicKey) -> bool { self.keys.contains(key) }
+
+    /// Constructs multisig witness script
+    pub fn witness_script(&self) -> Script {
+        let mut builder = Builder::new().push_int(self.threshold as i64);
+        for key in self.keys() {
+            builder = builder.push_key(&key);
+        }
+        builder
+            .push_int(self.keys.len() as i64)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script()
+    }
+
+    /// Constructs P2WSH script pubkey of the outputs controlled by the
+    /// multisig
+    pub fn script_pubkey(&self) -> Script {
+        Script::new_v0_p2wsh(&self.witness_script().wscript_hash())
+    }
+
+    /// Returns P2WSH address of the multisig
+    pub fn address(&self, network: Network) -> Address {
+        Address::p2wsh(&self.witness_script(), network)
+    }
+
+    /// Constructs transaction output with `value` satoshis controlled by the
+    /// multisig, which may be used as an inflation right seal
+    pub fn output(&self, value: u64) -> TxOut {
+        TxOut {
+            value,
+            script_pubkey: self.script_pubkey(),
+        }
+    }
+
+    /// Lists outputs of the transaction controlled by the multisig, which may
+    /// be used for assigning inflation rights with
+    /// [`crate::Rgb20Builder::inflation_right`]
+    pub fn outpoints(&self, tx: &Transaction) -> Vec<OutPoint> {
+        let script_pubkey = self.script_pubkey();
+        let txid = tx.txid();
+        tx.output
+            .iter()
+            .enumerate()
+            .filter(|(_, output)| output.script_pubkey == script_pubkey)
+            .map(|(vout, _)| OutPoint::new(txid, vout as u32))
+            .collect()
+    }
+}
+
+impl Display for MultisigDescriptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = if self.sorted { "sortedmulti" } else { "multi" };
+        write!(f, "wsh({}({}", name, self.threshold)?;
+        for key in &self.keys {
+            write!(f, ",{}", key)?;
+        }
+        f.write_str("))")
+    }
+}
+
+impl FromStr for MultisigDescriptor {
+    type Err = DescriptorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DescriptorError::InvalidFormat(s.to_owned());
+        let inner = s
+            .trim()
+            .strip_prefix("wsh(")
+            .and_then(|s| s.strip_suffix("))"))
+            .ok_or_else(invalid)?;
+        let (sorted, args) = if let Some(args) = inner.strip_prefix("sortedmulti(") {
+            (true, args)
+        } else if let Some(args) = inner.strip_prefix("multi(") {
+            (false, args)
+        } else {
+            return Err(invalid());
+        };
+        let mut args = args.split(',').map(str::trim);
+        let threshold = args
+            .next()
+            .and_then(|threshold| threshold.parse().ok())
+            .ok_or_else(invalid)?;
+        let keys = args
+            .map(|key| {
+                PublicKey::from_str(key).map_err(|_| DescriptorError::InvalidKey(key.to_owned()))
+            })
+            .collect::<Result<_, _>>()?;
+        MultisigDescriptor::with(threshold, keys, sorted)
+    }
+}
+
+/// Errors coordinating multisig issuance with [`CoSignSession`]
+#[derive(Clone, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum CoSignError {
+    /// output {0} closed by the issuance is not controlled by the multisig
+    NotControlled(OutPoint),
+
+    /// unable to anchor issue state transition to the witness transaction.
+    /// Details: {0}
+    Anchor(String),
+
+    /// signed PSBT has witness transaction {0}, which differs from the
+    /// transaction of the session
+    TxMismatch(bitcoin::Txid),
+
+    /// signature by key {0}, which is not a part of the multisig
+    UnknownSigner(PublicKey),
+
+    /// signature by key {0} for the witness transaction input #{1} is
+    /// invalid
+    InvalidSignature(PublicKey, usize),
+
+    /// witness transaction input #{input} has {present} signatures, while
+    /// the multisig requires {required}
+    NotEnoughSignatures {
+        /// Number of the witness transaction input
+        input: usize,
+        /// Number of collected signatures
+        present: usize,
+        /// Number of signatures required by the multisig
+        required: usize,
+    },
+
+    /// {0}
+    #[from]
+    Transition(transitions::Error),
+
+    /// {0}
+    #[from]
+    Psbt(psbt::Error),
+}
+
+/// Session coordinating secondary issuance closing inflation rights controlled
+/// by a multisig.
+///
+/// The coordinator starts the session with [`CoSignSession::start`], which
+/// constructs the issue state transition and the witness transaction PSBT
+/// committing to it. The PSBT is sent to the signers, and the signed copies
+/// are merged back with [`CoSignSession::add_signatures`]. Once the threshold
+/// number of signatures is collected for each of the inputs, the witness
+/// transaction is produced with [`CoSignSession::finalize`]; the transition
+/// bundle and the anchor are used for composing the issue consignment.
+#[derive(Clone, Debug)]
+pub struct CoSignSession {
+    contract_id: ContractId,
+    descriptor: MultisigDescriptor,
+    node_id: NodeId,
+    psbt: Psbt,
+    bundle: TransitionBundle,
+    anchor: Anchor<lnpbp4::MerkleProof>,
+}
+
+impl CoSignSession {
+    /// Starts session for the secondary issuance closing inflation rights
+    /// assigned to the `closing` outputs, which must be controlled by the
+    /// multisig `descriptor`. The outputs are provided together with their
+    /// previous transaction outputs.
+    ///
+    /// Arguments `next_inflation` and `allocations` are the same as for
+    /// [`Asset::inflate`]; the remaining inflation rights may be kept under
+    /// the multisig control by assigning them to witness transaction outputs
+    /// produced with [`MultisigDescriptor::output`]. Witness transaction has the provided `outputs`, which must include the ones
+    /// used by the seals, and commits to the transition with the `method`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn start(
+        asset: &Asset,
+        descriptor: MultisigDescriptor,
+        closing: Vec<(OutPoint, TxOut)>,
+        next_inflation: InflationAllocations<ExplicitSeal>,
+        allocations: AllocationValueVec,
+        outputs: Vec<TxOut>,
+        method: impl Into<CloseMethod>,
+    ) -> Result<CoSignSession, CoSignError> {
+        let script_pubkey = descriptor.script_pubkey();
+        if let Some((outpoint, _)) = closing
+            .iter()
+            .find(|(_, prevout)| prevout.script_pubkey != script_pubkey)
+        {
+            return Err(CoSignError::NotControlled(*outpoint));
+        }
+
+        let transition = asset.inflate(
+            closing.iter().map(|(outpoint, _)| *outpoint).collect(),
+            next_inflation,
+            allocations,
+        )?;
+        let node_id = transition.node_id();
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: closing
+                .iter()
+                .map(|(outpoint, _)| TxIn {
+                    previous_output: *outpoint,
+                    script_sig: empty!(),
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    witness: empty!(),
+                })
+                .collect(),
+            output: outputs,
+        };
+        let mut psbt = Psbt::from(
+            PartiallySignedTransaction::from_unsigned_tx(tx)
+                .map_err(|err| CoSignError::Anchor(err.to_string()))?,
+        );
+        let witness_script = descriptor.witness_script();
+        for (input, (_, prevout)) in psbt.inputs.iter_mut().zip(closing) {
+            input.witness_utxo = Some(prevout);
+            input.witness_script = Some(witness_script.clone().into());
+        }
+        let mut psbt = embed_transition(psbt, asset, transition, method)?;
+
+        let contract_id = asset.contract_id();
+        let anchor =
+            Anchor::commit(&mut psbt).map_err(|err| CoSignError::Anchor(err.to_string()))?;
+        let bundle = psbt
+            .rgb_bundles()
+            .map_err(psbt::Error::from)?
+            .remove(&contract_id)
+            .expect("transition was embedded into PSBT");
+        let anchor = anchor
+            .to_merkle_proof(contract_id)
+            .map_err(|err| CoSignError::Anchor(err.to_string()))?;
+
+        Ok(CoSignSession {
+            contract_id,
+            descriptor,
+            node_id,
+            psbt,
+            bundle,
+            anchor,
+        })
+    }
+
+    /// Returns id of the asset contract
+    pub fn contract_id(&self) -> ContractId { self.contract_id }
+
+    /// Returns descriptor of the multisig controlling the inflation rights
+    pub fn descriptor(&self) -> &MultisigDescriptor { &self.descriptor }
+
+    /// Returns id of the issue state transition
+    pub fn node_id(&self) -> NodeId { self.node_id }
+
+    /// Returns witness transaction PSBT with the signatures collected so far,
+    /// which is sent to the signers
+    pub fn psbt(&self) -> &Psbt { &self.psbt }
+
+    /// Returns transition bundle containing the issue state transition
+    pub fn bundle(&self) -> &TransitionBundle { &self.bundle }
+
+    /// Returns anchor of the transition bundle to the witness transaction
+    pub fn anchor(&self) -> &Anchor<lnpbp4::MerkleProof> { &self.anchor }
+
+    /// Returns number of signatures which are still missing, summed over all
+    /// witness transaction inputs
+    pub fn missing_signatures(&self) -> usize {
+        self.psbt
+            .inputs
+            .iter()
+            .map(|input| {
+                self.descriptor
+                    .threshold
+                    .saturating_sub(input.partial_sigs.len())
+            })
+            .sum()
+    }
+
+    /// Detects whether all witness transaction inputs have the threshold
+    /// number of signatures
+    pub fn is_complete(&self) -> bool { self.missing_signatures() == 0 }
+
+    /// Merges signatures from the PSBT signed by some of the signers,
+    /// returning number of the new signatures. Signatures are checked to be
+    /// made by the multisig keys and to be valid for the witness transaction.
+    pub fn add_signatures(&mut self, signed: &Psbt) -> Result<usize, CoSignError> {
+        let tx = self.psbt.to_unsigned_tx();
+        let txid = signed.to_unsigned_tx().txid();
+        if txid != tx.txid() {
+            return Err(CoSignError::TxMismatch(txid));
+        }
+
+        let secp = Secp256k1::verification_only();
+        let witness_script = self.descriptor.witness_script();
+        let mut sighash_cache = SighashCache::new(&tx);
+        let mut added = 0usize;
+        for (no, (input, signed)) in self.psbt.inputs.iter_mut().zip(&signed.inputs).enumerate() {
+            let value = input
+                .witness_utxo
+                .as_ref()
+                .map(|prevout| prevout.value)
+                .unwrap_or_default();
+            for (key, sig) in &signed.partial_sigs {
+                if !self.descriptor.contains(key) {
+                    return Err(CoSignError::UnknownSigner(*key));
+                }
+                if input.partial_sigs.contains_key(key) {
+                    continue;
+                }
+                let sighash = sighash_cache
+                    .segwit_signature_hash(no, &witness_script, value, sig.hash_ty)
+                    .map_err(|_| CoSignError::InvalidSignature(*key, no))?;
+                let msg = Message::from_slice(&sighash[..]).expect("sighash is 32 bytes");
+                secp.verify_ecdsa(&msg, &sig.sig, &key.inner)
+                    .map_err(|_: secp256k1::Error| CoSignError::InvalidSignature(*key, no))?;
+                input.partial_sigs.insert(*key, *sig);
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Produces fully signed witness transaction, using signatures of the
+    /// first keys (in the script order) which provided them
+    pub fn finalize(&self) -> Result<Transaction, CoSignError> {
+        let mut tx = self.psbt.to_unsigned_tx();
+        let keys = self.descriptor.keys();
+        let witness_script = self.descriptor.witness_script();
+        let required = self.descriptor.threshold;
+        for (no, (txin, input)) in tx.input.iter_mut().zip(&self.psbt.inputs).enumerate() {
+            let sigs = keys
+                .iter()
+                .filter_map(|key| input.partial_sigs.get(key))
+                .take(required)
+                .map(|sig| sig.to_vec())
+                .collect::<Vec<_>>();
+            if sigs.len() < required {
+                return Err(CoSignError::NotEnoughSignatures {
+                    input: no,
+                    present: sigs.len(),
+                    required,
+                });
+            }
+            let mut witness = vec![vec![]];
+            witness.extend(sigs);
+            witness.push(witness_script.to_bytes());
+            txin.witness = Witness::from_vec(witness);
+        }
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::secp256k1::SecretKey;
+    use bitcoin::{EcdsaSig, EcdsaSighashType, PrivateKey};
+    use lnpbp::chain::Chain;
+    use rgb::fungible::allocation::AllocatedValue;
+
+    use super::*;
+    use crate::{AssetName, Rgb20Builder, Ticker};
+
+    fn keys() -> Vec<PrivateKey> {
+        (1u8..=3)
+            .map(|no| PrivateKey::new(SecretKey::from_slice(&[no; 32]).unwrap(), Network::Signet))
+            .collect()
+    }
+
+    fn descriptor() -> MultisigDescriptor {
+        let secp = Secp256k1::new();
+        MultisigDescriptor::with(
+            2,
+            keys().iter().map(|key| key.public_key(&secp)).collect(),
+            true,
+        )
+        .unwrap()
+    }
+
+    fn sign(session: &CoSignSession, key: &PrivateKey) -> Psbt {
+        let secp = Secp256k1::new();
+        let mut psbt = session.psbt().clone();
+        let tx = psbt.to_unsigned_tx();
+        let mut sighash_cache = SighashCache::new(&tx);
+        for (no, input) in psbt.inputs.iter_mut().enumerate() {
+            let sighash = sighash_cache
+                .segwit_signature_hash(
+                    no,
+                    input.witness_script.as_ref().unwrap(),
+                    input.witness_utxo.as_ref().unwrap().value,
+                    EcdsaSighashType::All,
+                )
+                .unwrap();
+            let sig = secp.sign_ecdsa(&Message::from_slice(&sighash[..]).unwrap(), &key.inner);
+            input
+                .partial_sigs
+                .insert(key.public_key(&secp), EcdsaSig::sighash_all(sig));
+        }
+        psbt
+    }
+
+    #[test]
+    fn descriptor_parsing() {
+        let descriptor = descriptor();
+        let s = descriptor.to_string();
+        assert!(s.starts_with("wsh(sortedmulti(2,"));
+        assert_eq!(MultisigDescriptor::from_str(&s), Ok(descriptor.clone()));
+        assert!(descriptor
+            .address(Network::Signet)
+            .to_string()
+            .starts_with("tb1q"));
+
+        assert!(matches!(
+            MultisigDescriptor::from_str("wsh(multi(4,02aa))"),
+            Err(DescriptorError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            MultisigDescriptor::from_str("sh(multi(1,02aa))"),
+            Err(DescriptorError::InvalidFormat(_))
+        ));
+        assert_eq!(
+            MultisigDescriptor::with(4, descriptor.keys(), false),
+            Err(DescriptorError::InvalidThreshold {
+                threshold: 4,
+                keys: 3
+            })
+        );
+    }
+
+    #[test]
+    fn cosign_issue() {
+        let descriptor = descriptor();
+        let funding = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![],
+            output: vec![descriptor.output(10_000)],
+        };
+        let right = descriptor.outpoints(&funding)[0];
+        let contract = Rgb20Builder::new(Chain::Signet)
+            .ticker(Ticker::from_str("TICK").unwrap())
+            .name(AssetName::from_str("Test asset").unwrap())
+            .allocate(OutPoint::new(funding.txid(), 1), 100)
+            .inflation_right(right, 1000)
+            .build()
+            .unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+
+        let mut session = CoSignSession::start(
+            &asset,
+            descriptor.clone(),
+            vec![(right, funding.output[0].clone())],
+            bmap! {
+                ExplicitSeal {
+                    method: CloseMethod::OpretFirst,
+                    txid: None,
+                    vout: 0,
+                } => 600
+            }
+            .into(),
+            vec![AllocatedValue {
+                value: 400,
+                seal: ExplicitSeal::from(OutPoint::new(funding.txid(), 2)),
+            }],
+            vec![descriptor.output(9_000)],
+            CloseMethod::OpretFirst,
+        )
+        .unwrap();
+        assert_eq!(session.contract_id(), asset.contract_id());
+        assert_eq!(session.missing_signatures(), 2);
+        assert!(matches!(
+            session.finalize(),
+            Err(CoSignError::NotEnoughSignatures { present: 0, .. })
+        ));
+
+        let keys = keys();
+        assert_eq!(
+            session.add_signatures(&sign(&session, &keys[2])).unwrap(),
+            1
+        );
+        assert_eq!(
+            session.add_signatures(&sign(&session, &keys[2])).unwrap(),
+            0
+        );
+        assert!(!session.is_complete());
+        assert_eq!(
+            session.add_signatures(&sign(&session, &keys[0])).unwrap(),
+            1
+        );
+        assert!(session.is_complete());
+
+        let tx = session.finalize().unwrap();
+        assert_eq!(tx.txid(), session.anchor().txid);
+        assert_eq!(tx.input[0].witness.len(), 4);
+
+        let stranger = PrivateKey::new(SecretKey::from_slice(&[9; 32]).unwrap(), Network::Signet);
+        assert!(matches!(
+            session.add_signatures(&sign(&session, &stranger)),
+            Err(CoSignError::UnknownSigner(_))
+        ));
+    }
+}
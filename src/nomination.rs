@@ -0,0 +1,411 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeSet;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use stens::AsciiString;
+use strict_encoding::{StrictDecode, StrictEncode};
+
+/// Minimal number of characters in the asset ticker
+pub const TICKER_MIN_LEN: usize = 3;
+/// Maximal number of characters in the asset ticker
+pub const TICKER_MAX_LEN: usize = 8;
+/// Maximal number of characters in the asset name
+pub const NAME_MAX_LEN: usize = 32;
+
+/// Errors in asset ticker or name
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum NominationError {
+    /// ticker must contain from 3 to 8 characters, while {0} characters were
+    /// provided
+    TickerLength(usize),
+
+    /// ticker must consist only of latin letters, while it contains '{0}'
+    TickerCharacter(char),
+
+    /// asset name must contain from 1 to 32 characters, while {0} characters
+    /// were provided
+    NameLength(usize),
+
+    /// asset name must consist only of printable ASCII characters, while it
+    /// contains '{0}'
+    NameCharacter(char),
+}
+
+/// Asset ticker: from 3 to 8 latin letters, always in upper case.
+///
+/// Lower-case letters are converted to the upper case upon parsing.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(SerializeDisplay, DeserializeFromStr))]
+pub struct Ticker(AsciiString);
+
+impl Ticker {
+    /// Returns string representation of the ticker
+    pub fn as_str(&self) -> &str { self.0.as_str() }
+}
+
+impl FromStr for Ticker {
+    type Err = NominationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let len = s.chars().count();
+        if !(TICKER_MIN_LEN..=TICKER_MAX_LEN).contains(&len) {
+            return Err(NominationError::TickerLength(len));
+        }
+        if let Some(c) = s.chars().find(|c| !c.is_ascii_alphabetic()) {
+            return Err(NominationError::TickerCharacter(c));
+        }
+        let ticker = AsciiString::from_str(&s.to_ascii_uppercase())
+            .expect("ticker characters are checked to be ASCII");
+        Ok(Ticker(ticker))
+    }
+}
+
+impl Display for Ticker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+impl From<Ticker> for AsciiString {
+    fn from(ticker: Ticker) -> Self { ticker.0 }
+}
+
+impl TryFrom<AsciiString> for Ticker {
+    type Error = NominationError;
+
+    fn try_from(ticker: AsciiString) -> Result<Self, Self::Error> {
+        Ticker::from_str(ticker.as_str())
+    }
+}
+
+impl StrictEncode for Ticker {
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, strict_encoding::Error> {
+        self.0.strict_encode(e)
+    }
+}
+
+impl StrictDecode for Ticker {
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, strict_encoding::Error> {
+        let ticker = AsciiString::strict_decode(d)?;
+        if ticker.as_str() != ticker.as_str().to_ascii_uppercase() {
+            return Err(strict_encoding::Error::DataIntegrityError(s!(
+                "ticker must be in upper case"
+            )));
+        }
+        Ticker::try_from(ticker)
+            .map_err(|err| strict_encoding::Error::DataIntegrityError(err.to_string()))
+    }
+}
+
+/// Asset name: from 1 to 32 printable ASCII characters.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(SerializeDisplay, DeserializeFromStr))]
+pub struct AssetName(AsciiString);
+
+impl AssetName {
+    /// Returns string representation of the asset name
+    pub fn as_str(&self) -> &str { self.0.as_str() }
+}
+
+impl FromStr for AssetName {
+    type Err = NominationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let len = s.chars().count();
+        if !(1..=NAME_MAX_LEN).contains(&len) {
+            return Err(NominationError::NameLength(len));
+        }
+        if let Some(c) = s.chars().find(|c| !(' '..='~').contains(c)) {
+            return Err(NominationError::NameCharacter(c));
+        }
+        let name = AsciiString::from_str(s).expect("asset name characters are checked to be ASCII");
+        Ok(AssetName(name))
+    }
+}
+
+impl Display for AssetName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+impl From<AssetName> for AsciiString {
+    fn from(name: AssetName) -> Self { name.0 }
+}
+
+impl TryFrom<AsciiString> for AssetName {
+    type Error = NominationError;
+
+    fn try_from(name: AsciiString) -> Result<Self, Self::Error> {
+        AssetName::from_str(name.as_str())
+    }
+}
+
+impl StrictEncode for AssetName {
+    fn strict_encode<E: io::Write>(&self, e: E) -> Result<usize, strict_encoding::Error> {
+        self.0.strict_encode(e)
+    }
+}
+
+impl StrictDecode for AssetName {
+    fn strict_decode<D: io::Read>(d: D) -> Result<Self, strict_encoding::Error> {
+        AssetName::try_from(AsciiString::strict_decode(d)?)
+            .map_err(|err| strict_encoding::Error::DataIntegrityError(err.to_string()))
+    }
+}
+
+/// Errors in asset nomination detected by [`NominationPolicy`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum PolicyError {
+    /// nomination policy limits must lie within the limits defined by RGB20
+    /// schema (LNPBP-20)
+    LimitsExceedSchema,
+
+    /// ticker must contain from {min} to {max} characters, while {len}
+    /// characters were provided
+    TickerLength {
+        /// Number of characters in the ticker
+        len: usize,
+        /// Minimal number of characters allowed by the policy
+        min: usize,
+        /// Maximal number of characters allowed by the policy
+        max: usize,
+    },
+
+    /// asset name must contain at most {max} characters, while {len}
+    /// characters were provided
+    NameLength {
+        /// Number of characters in the asset name
+        len: usize,
+        /// Maximal number of characters allowed by the policy
+        max: usize,
+    },
+
+    /// ticker is reserved and can't be used by new assets
+    TickerReserved,
+
+    /// {0}
+    #[from]
+    Nomination(NominationError),
+}
+
+/// Policy restricting asset tickers and names beyond the constraints of
+/// [`Ticker`] and [`AssetName`] types.
+///
+/// Registries and issuance services may tighten the LNPBP-20 limits, which
+/// are used by default, and reserve some tickers. Policies can't loosen the
+/// limits, so nominations passing a policy are always valid under RGB20
+/// schema.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NominationPolicy {
+    ticker_min_len: usize,
+    ticker_max_len: usize,
+    name_max_len: usize,
+    reserved_tickers: BTreeSet<Ticker>,
+}
+
+impl Default for NominationPolicy {
+    fn default() -> Self {
+        NominationPolicy {
+            ticker_min_len: TICKER_MIN_LEN,
+            ticker_max_len: TICKER_MAX_LEN,
+            name_max_len: NAME_MAX_LEN,
+            reserved_tickers: empty!(),
+        }
+    }
+}
+
+impl NominationPolicy {
+    /// Constructs policy with LNPBP-20 limits and no reserved tickers
+    pub fn lnpbp20() -> NominationPolicy { NominationPolicy::default() }
+
+    /// Restricts ticker length to the range from `min` to `max` characters.
+    /// Errors if the range is empty or not within LNPBP-20 limits.
+    pub fn with_ticker_len(mut self, min: usize, max: usize) -> Result<Self, PolicyError> {
+        if min < TICKER_MIN_LEN || max > TICKER_MAX_LEN || min > max {
+            return Err(PolicyError::LimitsExceedSchema);
+        }
+        self.ticker_min_len = min;
+        self.ticker_max_len = max;
+        Ok(self)
+    }
+
+    /// Restricts asset name length to `max` characters. Errors if the limit
+    /// is zero or exceeds LNPBP-20 limit.
+    pub fn with_name_max_len(mut self, max: usize) -> Result<Self, PolicyError> {
+        if max == 0 || max > NAME_MAX_LEN {
+            return Err(PolicyError::LimitsExceedSchema);
+        }
+        self.name_max_len = max;
+        Ok(self)
+    }
+
+    /// Reserves ticker, such that it can't be used by new assets
+    pub fn reserve_ticker(mut self, ticker: Ticker) -> Self {
+        self.reserved_tickers.insert(ticker);
+        self
+    }
+
+    /// Returns allowed range of the ticker lengths
+    pub fn ticker_len(&self) -> (usize, usize) { (self.ticker_min_len, self.ticker_max_len) }
+
+    /// Returns maximal allowed asset name length
+    pub fn name_max_len(&self) -> usize { self.name_max_len }
+
+    /// Returns set of reserved tickers
+    pub fn reserved_tickers(&self) -> &BTreeSet<Ticker> { &self.reserved_tickers }
+
+    /// Checks that the ticker is allowed by the policy
+    pub fn check_ticker(&self, ticker: &Ticker) -> Result<(), PolicyError> {
+        let len = ticker.as_str().len();
+        if !(self.ticker_min_len..=self.ticker_max_len).contains(&len) {
+            return Err(PolicyError::TickerLength {
+                len,
+                min: self.ticker_min_len,
+                max: self.ticker_max_len,
+            });
+        }
+        if self.reserved_tickers.contains(ticker) {
+            return Err(PolicyError::TickerReserved);
+        }
+        Ok(())
+    }
+
+    /// Checks that the asset name is allowed by the policy
+    pub fn check_name(&self, name: &AssetName) -> Result<(), PolicyError> {
+        let len = name.as_str().len();
+        if len > self.name_max_len {
+            return Err(PolicyError::NameLength {
+                len,
+                max: self.name_max_len,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks ticker and name given as strings (for instance, read from a
+    /// contract) against both RGB20 constraints and the policy
+    pub fn check_str(&self, ticker: &str, name: &str) -> Result<(), PolicyError> {
+        self.check_ticker(&Ticker::from_str(ticker)?)?;
+        self.check_name(&AssetName::from_str(name)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ticker_validation() {
+        assert_eq!(Ticker::from_str("usdt").unwrap().as_str(), "USDT");
+        assert_eq!(Ticker::from_str("BTC").unwrap().to_string(), "BTC");
+        assert_eq!(
+            Ticker::from_str("US"),
+            Err(NominationError::TickerLength(2))
+        );
+        assert_eq!(
+            Ticker::from_str("TOOLONGTK"),
+            Err(NominationError::TickerLength(9))
+        );
+        assert_eq!(
+            Ticker::from_str("US D"),
+            Err(NominationError::TickerCharacter(' '))
+        );
+        assert_eq!(
+            Ticker::from_str("USD1"),
+            Err(NominationError::TickerCharacter('1'))
+        );
+        assert_eq!(
+            Ticker::from_str("USDÜ"),
+            Err(NominationError::TickerCharacter('Ü'))
+        );
+    }
+
+    #[test]
+    fn name_validation() {
+        assert_eq!(
+            AssetName::from_str("Tether USD").unwrap().as_str(),
+            "Tether USD"
+        );
+        assert_eq!(AssetName::from_str(""), Err(NominationError::NameLength(0)));
+        assert_eq!(
+            AssetName::from_str(&"a".repeat(33)),
+            Err(NominationError::NameLength(33))
+        );
+        assert_eq!(
+            AssetName::from_str("Tab\tname"),
+            Err(NominationError::NameCharacter('\t'))
+        );
+    }
+
+    #[test]
+    fn nomination_policy() {
+        let default = NominationPolicy::lnpbp20();
+        assert_eq!(default.check_str("USDT", "Tether USD"), Ok(()));
+        assert_eq!(
+            default.check_str("US", "Tether USD"),
+            Err(PolicyError::Nomination(NominationError::TickerLength(2)))
+        );
+
+        let policy = NominationPolicy::default()
+            .with_ticker_len(3, 4)
+            .unwrap()
+            .with_name_max_len(8)
+            .unwrap()
+            .reserve_ticker(Ticker::from_str("BTC").unwrap());
+        assert_eq!(policy.check_str("USDT", "Tether"), Ok(()));
+        assert_eq!(
+            policy.check_str("TETHER", "Tether"),
+            Err(PolicyError::TickerLength {
+                len: 6,
+                min: 3,
+                max: 4
+            })
+        );
+        assert_eq!(
+            policy.check_str("USDT", "Tether USD"),
+            Err(PolicyError::NameLength { len: 10, max: 8 })
+        );
+        assert_eq!(
+            policy.check_str("btc", "Bitcoin"),
+            Err(PolicyError::TickerReserved)
+        );
+
+        assert_eq!(
+            NominationPolicy::default().with_ticker_len(2, 8),
+            Err(PolicyError::LimitsExceedSchema)
+        );
+        assert_eq!(
+            NominationPolicy::default().with_name_max_len(64),
+            Err(PolicyError::LimitsExceedSchema)
+        );
+    }
+
+    #[test]
+    fn strict_encoding() {
+        let ticker = Ticker::from_str("usdt").unwrap();
+        let data = ticker.strict_serialize().unwrap();
+        assert_eq!(Ticker::strict_deserialize(&data).unwrap(), ticker);
+        let lower = AsciiString::from_str("usdt")
+            .unwrap()
+            .strict_serialize()
+            .unwrap();
+        assert!(Ticker::strict_deserialize(lower).is_err());
+
+        let name = AssetName::from_str("Tether USD").unwrap();
+        let data = name.strict_serialize().unwrap();
+        assert_eq!(AssetName::strict_deserialize(&data).unwrap(), name);
+    }
+}
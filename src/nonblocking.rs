@@ -0,0 +1,207 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeSet;
+
+use async_trait::async_trait;
+use bitcoin::{OutPoint, Transaction, Txid};
+use bitcoin_onchain::{ResolveTx, TxResolverError};
+use rgb::{Consignment, ConsignmentType, InmemConsignment, OwnedValue};
+
+use crate::{
+    AllocationSync, Asset, OfflineResolver, ResolverError, ValidationReport, WitnessResolver,
+};
+
+/// Asynchronous version of [`WitnessResolver`], for the backends resolving
+/// witness transactions over the network without blocking the executor
+/// thread.
+#[async_trait]
+pub trait AsyncWitnessResolver {
+    /// Retrieves the witness transaction by its id
+    async fn resolve_tx(&self, txid: Txid) -> Result<Transaction, TxResolverError>;
+
+    /// Checks whether the transaction output is spent. Returns `None` if the
+    /// resolver has no information about the output.
+    async fn is_spent(&self, outpoint: OutPoint) -> Result<Option<bool>, ResolverError>;
+}
+
+/// Asynchronous version of [`crate::UtxoProvider`], used by
+/// [`Asset::sync_allocations_async`]
+#[async_trait]
+pub trait AsyncUtxoProvider {
+    /// Error type returned by the backend
+    type Error: std::error::Error + Send;
+
+    /// Lists all unspent transaction outputs controlled by the wallet
+    async fn list_unspent(&self) -> Result<BTreeSet<OutPoint>, Self::Error>;
+
+    /// Checks whether the transaction output is spent. Returns `None` if the
+    /// backend has no information about the output.
+    async fn is_spent(&self, outpoint: OutPoint) -> Result<Option<bool>, Self::Error>;
+}
+
+#[async_trait]
+impl AsyncWitnessResolver for OfflineResolver {
+    async fn resolve_tx(&self, txid: Txid) -> Result<Transaction, TxResolverError> {
+        ResolveTx::resolve_tx(self, txid)
+    }
+
+    async fn is_spent(&self, outpoint: OutPoint) -> Result<Option<bool>, ResolverError> {
+        WitnessResolver::is_spent(self, outpoint)
+    }
+}
+
+impl Asset {
+    /// Performs complete consignment validation in the same way as
+    /// [`Asset::validate_consignment`], retrieving witness transactions with
+    /// asynchronous `resolver`.
+    ///
+    /// Witness transactions are retrieved before the validation starts;
+    /// transactions which can't be resolved are reported by the validation
+    /// status as unresolved.
+    pub async fn validate_consignment_async<T, R>(
+        consignment: &InmemConsignment<T>,
+        resolver: &R,
+    ) -> ValidationReport
+    where
+        T: ConsignmentType,
+        R: AsyncWitnessResolver + ?Sized,
+    {
+        let txids = consignment
+            .anchored_bundles()
+            .map(|(anchor, _)| anchor.txid)
+            .collect::<BTreeSet<_>>();
+        let mut witnesses = OfflineResolver::new();
+        for txid in txids {
+            if let Ok(tx) = resolver.resolve_tx(txid).await {
+                witnesses.insert(tx);
+            }
+        }
+        Asset::validate_consignment(consignment, &witnesses)
+    }
+
+    /// Lists known asset allocations assigned to the outputs which are
+    /// reported unspent by the asynchronous `resolver`, like
+    /// [`Asset::unspent_allocations`]
+    pub async fn unspent_allocations_async<R>(
+        &self,
+        resolver: &R,
+    ) -> Result<Vec<OwnedValue>, ResolverError>
+    where
+        R: AsyncWitnessResolver + ?Sized,
+    {
+        let mut allocations = vec![];
+        for outpoint in self.outpoints() {
+            if resolver.is_spent(*outpoint).await? == Some(false) {
+                allocations.extend(self.allocations_at(*outpoint).iter().cloned());
+            }
+        }
+        Ok(allocations)
+    }
+
+    /// Classifies all known asset allocations as spendable, spent or unknown
+    /// using information from the asynchronous wallet backend `provider`,
+    /// like [`Asset::sync_allocations`]
+    pub async fn sync_allocations_async<P>(
+        &self,
+        provider: &P,
+    ) -> Result<AllocationSync, P::Error>
+    where
+        P: AsyncUtxoProvider + ?Sized,
+    {
+        let unspent = provider.list_unspent().await?;
+        let mut sync = AllocationSync::default();
+        for outpoint in self.outpoints() {
+            let coins = self.allocations_at(*outpoint).iter().cloned();
+            if unspent.contains(outpoint) {
+                sync.spendable.extend(coins);
+            } else if provider.is_spent(*outpoint).await? == Some(true) {
+                sync.spent.extend(coins);
+            } else {
+                sync.unknown.extend(coins);
+            }
+        }
+        Ok(sync)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::Infallible;
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) { self.0.unpark() }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    struct Wallet {
+        unspent: BTreeSet<OutPoint>,
+    }
+
+    #[async_trait]
+    impl AsyncUtxoProvider for Wallet {
+        type Error = Infallible;
+
+        async fn list_unspent(&self) -> Result<BTreeSet<OutPoint>, Self::Error> {
+            Ok(self.unspent.clone())
+        }
+
+        async fn is_spent(&self, _: OutPoint) -> Result<Option<bool>, Self::Error> { Ok(None) }
+    }
+
+    #[test]
+    fn async_validation_and_sync() {
+        let contract = builder().allocate(outpoint(2), 50).build().unwrap();
+        let resolver = OfflineResolver::new();
+
+        let report = block_on(Asset::validate_consignment_async(&contract, &resolver));
+        let expected = Asset::validate_consignment(&contract, &resolver);
+        assert_eq!(report.validity(), expected.validity());
+        assert_eq!(report.subschema, expected.subschema);
+        assert_eq!(
+            report.status.unresolved_txids,
+            expected.status.unresolved_txids
+        );
+
+        let asset = Asset::try_from(&contract).unwrap();
+        assert!(block_on(asset.unspent_allocations_async(&resolver))
+            .unwrap()
+            .is_empty());
+
+        let wallet = Wallet {
+            unspent: bset! { outpoint(1) },
+        };
+        let sync = block_on(asset.sync_allocations_async(&wallet)).unwrap();
+        assert_eq!(sync.spendable_value(), 100);
+        assert_eq!(sync.unknown.len(), 1);
+        assert!(sync.spent.is_empty());
+    }
+}
@@ -0,0 +1,97 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Construction of `OP_RETURN` outputs hosting opret commitments to RGB20
+//! state transition bundles.
+//!
+//! Opret commitment is an `OP_RETURN` output with a single 32-byte push of the
+//! LNPBP-4 multi-protocol commitment (see [`crate::tapret::bundle_commitment`]).
+//! Under opret-first seal closing method the commitment must be hosted by the
+//! first `OP_RETURN` output of the witness transaction.
+
+use bitcoin::blockdata::opcodes::all::OP_RETURN;
+use bitcoin::blockdata::script::Builder;
+use bitcoin::{Script, Transaction, TxOut};
+
+/// Constructs `OP_RETURN` script carrying the LNPBP-4 commitment `mpc`
+pub fn commitment_script(mpc: [u8; 32]) -> Script {
+    Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_slice(&mpc)
+        .into_script()
+}
+
+/// Constructs zero-value `OP_RETURN` output carrying the LNPBP-4 commitment
+/// `mpc`
+pub fn commitment_output(mpc: [u8; 32]) -> TxOut {
+    TxOut {
+        value: 0,
+        script_pubkey: commitment_script(mpc),
+    }
+}
+
+/// Returns number of the witness transaction output which must host opret
+/// commitment, i.e. the first `OP_RETURN` output
+pub fn host_output(tx: &Transaction) -> Option<u32> {
+    tx.output
+        .iter()
+        .position(|output| output.script_pubkey.is_op_return())
+        .map(|vout| vout as u32)
+}
+
+/// Verifies that the witness transaction commits to `mpc` with opret
+/// commitment in its first `OP_RETURN` output
+pub fn verify_transaction(tx: &Transaction, mpc: [u8; 32]) -> bool {
+    host_output(tx)
+        .map(|vout| tx.output[vout as usize].script_pubkey == commitment_script(mpc))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::hex::ToHex;
+    use bitcoin::PackedLockTime;
+
+    use super::*;
+
+    #[test]
+    fn opret_vector() {
+        let mpc = *b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f\
+                     \x10\x11\x12\x13\x14\x15\x16\x17\x18\x19\x1a\x1b\x1c\x1d\x1e\x1f";
+        assert_eq!(
+            commitment_script(mpc).to_hex(),
+            "6a20000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+        );
+    }
+
+    #[test]
+    fn opret_first() {
+        let mpc = [7u8; 32];
+        let mut tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                value: 1000,
+                script_pubkey: Script::new(),
+            }],
+        };
+        assert_eq!(host_output(&tx), None);
+        assert!(!verify_transaction(&tx, mpc));
+
+        tx.output.push(commitment_output(mpc));
+        assert_eq!(host_output(&tx), Some(1));
+        assert!(verify_transaction(&tx, mpc));
+
+        tx.output.insert(0, commitment_output([8u8; 32]));
+        assert!(!verify_transaction(&tx, mpc));
+    }
+}
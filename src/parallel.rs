@@ -0,0 +1,184 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bitcoin::Txid;
+use rayon::prelude::*;
+use rgb::{
+    Consignment, ConsignmentType, ContractState, Extension, GraphApi, InmemConsignment, Node,
+    NodeOutpoint, Transition,
+};
+
+use crate::asset::Validator;
+use crate::{Asset, Error};
+
+impl Asset {
+    /// Constructs asset from the consignment in the same way as
+    /// [`Asset::try_from`], but performs validation of the state transitions
+    /// and extensions and extraction of their state in parallel.
+    ///
+    /// Per-node results are merged in the consignment order, so both the
+    /// constructed asset and the reported error do not depend on the thread
+    /// scheduling. Unlike sequential construction, allocations are removed
+    /// from the contract state if they are spent by any of the consignment
+    /// nodes, independently from the node order.
+    pub fn par_try_from<T>(consignment: &InmemConsignment<T>) -> Result<Asset, Error>
+    where T: ConsignmentType {
+        let genesis = consignment.genesis();
+        let mut validator =
+            Validator::with(consignment.schema(), consignment.root_schema(), genesis)?;
+
+        let transitions = consignment
+            .anchored_bundles()
+            .flat_map(|(anchor, bundle)| {
+                bundle
+                    .revealed_iter()
+                    .map(move |(transition, _)| (anchor.txid, transition))
+            })
+            .collect::<Vec<(Txid, &Transition)>>();
+        let extensions = consignment.state_extensions().collect::<Vec<&Extension>>();
+
+        let checks = transitions
+            .par_iter()
+            .map(|(_, transition)| validator.check_node(*transition))
+            .chain(
+                extensions
+                    .par_iter()
+                    .map(|extension| validator.check_node(*extension)),
+            )
+            .collect::<Vec<_>>();
+        for node_checks in checks {
+            validator.merge(node_checks?)?;
+        }
+        let subschema = validator.finish(|node_id| consignment.node_by_id(node_id).is_some())?;
+
+        let mut state = ContractState::with(
+            consignment.schema_id(),
+            consignment.root_schema_id(),
+            consignment.contract_id(),
+            genesis,
+        );
+        let node_states = transitions
+            .par_iter()
+            .map(|(txid, transition)| {
+                let mut node_state = empty_state(&state);
+                node_state.add_transition(*txid, transition);
+                (node_state, transition.parent_outputs())
+            })
+            .chain(extensions.par_iter().map(|extension| {
+                let mut node_state = empty_state(&state);
+                node_state.add_extension(extension);
+                (node_state, extension.parent_outputs())
+            }))
+            .collect::<Vec<_>>();
+
+        let mut spent = BTreeSet::<NodeOutpoint>::new();
+        for (node_state, parent_outputs) in node_states {
+            state.metadata.extend(node_state.metadata);
+            state.owned_rights.extend(node_state.owned_rights);
+            state.owned_values.extend(node_state.owned_values);
+            state.owned_data.extend(node_state.owned_data);
+            state.owned_attachments.extend(node_state.owned_attachments);
+            spent.extend(parent_outputs);
+        }
+        state
+            .owned_rights
+            .retain(|assigned| !spent.contains(&assigned.outpoint));
+        state
+            .owned_values
+            .retain(|assigned| !spent.contains(&assigned.outpoint));
+        state
+            .owned_data
+            .retain(|assigned| !spent.contains(&assigned.outpoint));
+        state
+            .owned_attachments
+            .retain(|assigned| !spent.contains(&assigned.outpoint));
+
+        let transitions = transitions
+            .into_par_iter()
+            .map(|(txid, transition)| (transition.node_id(), (txid, transition.clone())))
+            .collect::<BTreeMap<_, _>>();
+        let extensions = extensions
+            .into_par_iter()
+            .map(|extension| (extension.node_id(), extension.clone()))
+            .collect::<BTreeMap<_, _>>();
+
+        Ok(Asset::with_history(
+            state,
+            subschema,
+            genesis.clone(),
+            transitions,
+            extensions,
+        ))
+    }
+}
+
+/// Constructs contract state without any data, used to collect state
+/// defined by a single node
+fn empty_state(state: &ContractState) -> ContractState {
+    ContractState {
+        schema_id: state.schema_id,
+        root_schema_id: state.root_schema_id,
+        contract_id: state.contract_id,
+        metadata: empty!(),
+        owned_rights: empty!(),
+        owned_values: empty!(),
+        owned_data: empty!(),
+        owned_attachments: empty!(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::Hash;
+    use rgb::{seal, SealEndpoint, StateTransfer, TransitionBundle};
+    use seals::txout::CloseMethod;
+
+    use super::*;
+    use crate::fixtures::{anchor, builder, outpoint};
+
+    #[test]
+    fn parallel_construction() {
+        let contract = builder().allocate(outpoint(2), 200).build().unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(Asset::par_try_from(&contract).unwrap(), asset);
+
+        let witness_txid = Txid::hash(b"witness");
+        let transition = asset
+            .transfer(
+                bset! { outpoint(1) },
+                bmap! { SealEndpoint::WitnessVout {
+                    method: CloseMethod::TapretFirst,
+                    vout: 0,
+                    blinding: 1,
+                } => 60 },
+                bmap! { seal::Revealed::from(outpoint(3)) => 40 },
+            )
+            .unwrap();
+        let bundle = TransitionBundle::try_from(bmap! { transition => bset! { 0 } }).unwrap();
+        let transfer = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            empty!(),
+            vec![(anchor(witness_txid), bundle)].try_into().unwrap(),
+            empty!(),
+        );
+
+        let asset = Asset::try_from(&transfer).unwrap();
+        let parallel = Asset::par_try_from(&transfer).unwrap();
+        assert_eq!(parallel, asset);
+        assert!(parallel.allocations_at(outpoint(1)).is_empty());
+        assert_eq!(parallel.allocations_at(outpoint(2)).len(), 1);
+        assert_eq!(parallel.allocations_at(outpoint(3)).len(), 1);
+    }
+}
@@ -0,0 +1,191 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeMap;
+
+use bitcoin::OutPoint;
+use rgb::{seal, AtomicValue, ContractId, EndpointValueMap, SealValueMap, Transition};
+use seals::txout::CloseMethod;
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr, Same};
+
+use crate::transitions::Error;
+use crate::{Asset, CoinSelection, TransitionEstimate};
+
+/// Planned asset transfer, created by [`Asset::plan_transfer`] for showing it
+/// to the user before the transition is constructed with [`Asset::execute`]
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct TransferPlan {
+    /// Id of the transferred asset contract
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub contract_id: ContractId,
+
+    /// Selected transfer inputs with the amount of the asset allocated to
+    /// each of them
+    #[cfg_attr(feature = "serde", serde(with = "As::<BTreeMap<DisplayFromStr, Same>>"))]
+    pub inputs: BTreeMap<OutPoint, AtomicValue>,
+
+    /// Amounts assigned to the beneficiaries
+    #[cfg_attr(feature = "serde", serde(with = "As::<BTreeMap<DisplayFromStr, Same>>"))]
+    pub payment: EndpointValueMap,
+
+    /// Amounts returned to the sender as change
+    #[cfg_attr(feature = "serde", serde(with = "As::<BTreeMap<DisplayFromStr, Same>>"))]
+    pub change: SealValueMap,
+
+    /// Total amount of the asset known to the sender before the transfer
+    pub balance_before: AtomicValue,
+
+    /// Total amount of the asset left to the sender after the transfer
+    pub balance_after: AtomicValue,
+
+    /// Size and weight estimates of the transfer
+    pub estimate: TransitionEstimate,
+}
+
+impl TransferPlan {
+    /// Returns total amount sent to the beneficiaries
+    pub fn amount(&self) -> AtomicValue {
+        self.payment
+            .values()
+            .fold(0u64, |sum, value| sum.saturating_add(*value))
+    }
+}
+
+impl Asset {
+    /// Plans transfer to the `payment` beneficiaries in the same way as
+    /// [`Asset::transfer_selected`], without returning the transition. The
+    /// commitment weight is estimated for the seal closing `method`.
+    ///
+    /// Size estimates are measured on a draft transition, which is discarded;
+    /// the transition created by [`Asset::execute`] has different blinding
+    /// factors but the same size.
+    pub fn plan_transfer(
+        &self,
+        strategy: CoinSelection,
+        payment: EndpointValueMap,
+        change: seal::Revealed,
+        method: CloseMethod,
+    ) -> Result<TransferPlan, Error> {
+        let target = payment
+            .values()
+            .fold(0u64, |sum, value| sum.saturating_add(*value));
+        let inputs = self
+            .select_coins(target, strategy)?
+            .into_iter()
+            .map(|outpoint| (outpoint, self.outpoint_value(outpoint)))
+            .collect::<BTreeMap<_, _>>();
+        let total_inputs = inputs
+            .values()
+            .fold(0u64, |sum, value| sum.saturating_add(*value));
+        let mut change_map = SealValueMap::new();
+        if total_inputs > target {
+            change_map.insert(change, total_inputs - target);
+        }
+
+        let draft = self.transfer(
+            inputs.keys().copied().collect(),
+            payment.clone(),
+            change_map.clone(),
+        )?;
+        let estimate = TransitionEstimate::with(self, &draft, payment.keys(), method);
+
+        let balance_before = self
+            .known_coins()
+            .fold(0u64, |sum, coin| sum.saturating_add(coin.state.value));
+        Ok(TransferPlan {
+            contract_id: self.contract_id(),
+            inputs,
+            payment,
+            change: change_map,
+            balance_before,
+            balance_after: balance_before - target,
+            estimate,
+        })
+    }
+
+    /// Constructs transfer transition according to the `plan` created by
+    /// [`Asset::plan_transfer`]. Fails if the plan was created for a
+    /// different asset or if allocations at the planned inputs have changed
+    /// since then.
+    pub fn execute(&self, plan: TransferPlan) -> Result<Transition, Error> {
+        if plan.contract_id != self.contract_id() {
+            return Err(Error::ContractMismatch(plan.contract_id));
+        }
+        for (outpoint, value) in &plan.inputs {
+            if self.outpoint_value(*outpoint) != *value {
+                return Err(Error::StalePlan(*outpoint));
+            }
+        }
+        self.transfer(plan.inputs.into_keys().collect(), plan.payment, plan.change)
+    }
+
+    /// Sums up known asset allocations at the `outpoint`
+    fn outpoint_value(&self, outpoint: OutPoint) -> AtomicValue {
+        self.allocations_at(outpoint)
+            .iter()
+            .fold(0u64, |sum, coin| sum.saturating_add(coin.state.value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rgb::{Node, SealEndpoint};
+
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+
+    #[test]
+    fn plan_and_execute() {
+        let contract = builder().allocate(outpoint(2), 50).build().unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        let endpoint = SealEndpoint::WitnessVout {
+            method: CloseMethod::TapretFirst,
+            vout: 0,
+            blinding: 1,
+        };
+        let change = seal::Revealed::from(outpoint(3));
+
+        let plan = asset
+            .plan_transfer(
+                CoinSelection::LargestFirst,
+                bmap! { endpoint => 70 },
+                change,
+                CloseMethod::TapretFirst,
+            )
+            .unwrap();
+        assert_eq!(plan.inputs, bmap! { outpoint(1) => 100 });
+        assert_eq!(plan.change, bmap! { change => 30 });
+        assert_eq!(plan.amount(), 70);
+        assert_eq!(plan.balance_before, 150);
+        assert_eq!(plan.balance_after, 80);
+        assert_eq!(plan.estimate.commitment_weight, 0);
+
+        let estimate = plan.estimate;
+        let transition = asset.execute(plan.clone()).unwrap();
+        assert_eq!(transition.parent_owned_rights().len(), 1);
+        assert_eq!(
+            estimate.transition_size,
+            strict_encoding::StrictEncode::strict_serialize(&transition)
+                .unwrap()
+                .len()
+        );
+
+        let mut stale = plan;
+        stale.inputs.insert(outpoint(2), 10);
+        assert_eq!(asset.execute(stale), Err(Error::StalePlan(outpoint(2))));
+    }
+}
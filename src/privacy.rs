@@ -0,0 +1,230 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use amplify::Wrapper;
+use rgb::{
+    Consignment, ConsignmentType, ContractId, InmemConsignment, Node, NodeId, TypedAssignments,
+};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+
+use crate::schema::OwnedRightType;
+use crate::Asset;
+
+/// Parties to which the data contained in a consignment become visible
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub enum Audience {
+    /// Genesis data, which are known to everybody holding the asset contract
+    #[display("contract holders")]
+    ContractHolders,
+
+    /// State transitions and extensions, which are known to the recipient of
+    /// the consignment and, since the history is passed on with each
+    /// transfer, to all future owners of the allocations descending from them
+    #[display("recipients and future owners")]
+    Recipients,
+}
+
+/// Disclosure of a seal and the state assigned to it by a consignment node
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct AssignmentDisclosure {
+    /// Id of the genesis, state transition or extension defining the
+    /// assignment
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub node_id: NodeId,
+
+    /// Type of the assigned owned right
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub right_type: OwnedRightType,
+
+    /// Index of the assignment within the node assignments of the same type
+    pub index: u16,
+
+    /// Whether the seal definition (transaction output) is revealed
+    pub seal_revealed: bool,
+
+    /// Whether the assigned amount is revealed; `None` for the declarative
+    /// rights which do not have an amount
+    pub amount_revealed: Option<bool>,
+
+    /// Parties which learn the revealed data
+    pub audience: Audience,
+}
+
+/// Disclosure of a metadata field by a consignment node. Metadata are never
+/// concealed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct MetadataDisclosure {
+    /// Id of the genesis, state transition or extension containing the field
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub node_id: NodeId,
+
+    /// Schema type of the metadata field
+    pub field_type: rgb::schema::FieldType,
+
+    /// Number of the field values
+    pub count: usize,
+
+    /// Parties which learn the field values
+    pub audience: Audience,
+}
+
+/// Report on the data revealed and concealed by a consignment, allowing to
+/// audit what is leaked to its recipients before sending it
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct PrivacyReport {
+    /// Id of the asset contract
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub contract_id: ContractId,
+
+    /// Seals and state assigned by all nodes of the consignment
+    pub assignments: Vec<AssignmentDisclosure>,
+
+    /// Metadata fields of all nodes of the consignment
+    pub metadata: Vec<MetadataDisclosure>,
+}
+
+impl PrivacyReport {
+    /// Lists assignments with revealed seal definitions
+    pub fn revealed_seals(&self) -> impl Iterator<Item = &AssignmentDisclosure> {
+        self.assignments
+            .iter()
+            .filter(|assignment| assignment.seal_revealed)
+    }
+
+    /// Lists assignments with revealed amounts
+    pub fn revealed_amounts(&self) -> impl Iterator<Item = &AssignmentDisclosure> {
+        self.assignments
+            .iter()
+            .filter(|assignment| assignment.amount_revealed == Some(true))
+    }
+
+    /// Lists assignments with both seal and amount (if any) concealed
+    pub fn concealed(&self) -> impl Iterator<Item = &AssignmentDisclosure> {
+        self.assignments.iter().filter(|assignment| {
+            !assignment.seal_revealed && assignment.amount_revealed != Some(true)
+        })
+    }
+}
+
+impl Asset {
+    /// Reports which seals, amounts and metadata of the consignment are
+    /// revealed and which are concealed, together with the parties to which
+    /// each disclosure becomes visible
+    pub fn privacy_report<T>(consignment: &InmemConsignment<T>) -> PrivacyReport
+    where T: ConsignmentType {
+        let mut report = PrivacyReport {
+            contract_id: consignment.contract_id(),
+            assignments: empty!(),
+            metadata: empty!(),
+        };
+        report.add_node(consignment.genesis(), Audience::ContractHolders);
+        for (_, bundle) in consignment.anchored_bundles() {
+            for (transition, _) in bundle.revealed_iter() {
+                report.add_node(transition, Audience::Recipients);
+            }
+        }
+        for extension in consignment.state_extensions() {
+            report.add_node(extension, Audience::Recipients);
+        }
+        report
+    }
+}
+
+impl PrivacyReport {
+    fn add_node(&mut self, node: &dyn Node, audience: Audience) {
+        let node_id = node.node_id();
+        for (ty, assignments) in node.owned_rights().iter() {
+            let right_type = match OwnedRightType::try_from(*ty) {
+                Ok(right_type) => right_type,
+                Err(_) => continue,
+            };
+            let count = assignments.to_confidential_seals().len();
+            for index in 0..count as u16 {
+                let seal_revealed = matches!(assignments.revealed_seal_at(index), Ok(Some(_)));
+                let amount_revealed = match assignments {
+                    TypedAssignments::Value(values) => values
+                        .get(index as usize)
+                        .map(|assignment| assignment.as_revealed_state().is_some()),
+                    _ => None,
+                };
+                self.assignments.push(AssignmentDisclosure {
+                    node_id,
+                    right_type,
+                    index,
+                    seal_revealed,
+                    amount_revealed,
+                    audience,
+                });
+            }
+        }
+        for (field_type, values) in node.metadata().as_inner() {
+            self.metadata.push(MetadataDisclosure {
+                node_id,
+                field_type: *field_type,
+                count: values.len(),
+                audience,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use commit_verify::CommitConceal;
+
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+    use crate::schema::FieldType;
+
+    #[test]
+    fn genesis_disclosure() {
+        let concealed = rgb::seal::Revealed::from(outpoint(2)).commit_conceal();
+        let contract = builder().allocate(concealed, 50).build().unwrap();
+
+        let report = Asset::privacy_report(&contract);
+        assert_eq!(report.contract_id, contract.contract_id());
+        assert_eq!(report.assignments.len(), 2);
+        assert_eq!(report.revealed_seals().count(), 1);
+        assert_eq!(report.revealed_amounts().count(), 2);
+        assert!(report
+            .assignments
+            .iter()
+            .all(
+                |assignment| assignment.audience == Audience::ContractHolders
+                    && assignment.right_type == OwnedRightType::Assets
+            ));
+        assert!(report
+            .metadata
+            .iter()
+            .any(|field| field.field_type == rgb::schema::FieldType::from(FieldType::Ticker)));
+    }
+}
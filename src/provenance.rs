@@ -0,0 +1,159 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use bitcoin::{OutPoint, Txid};
+use commit_verify::CommitConceal;
+use rgb::{seal, AtomicValue, NodeId};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+
+use crate::Asset;
+
+/// Provenance record of a known asset allocation, allowing wallets to show
+/// where the allocation comes from and to prove its ownership
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct AllocationRecord {
+    /// Id of the genesis or state transition defining the allocation
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub node_id: NodeId,
+
+    /// Index of the allocation within the asset assignments of the node
+    pub index: u16,
+
+    /// Witness transaction of the state transition; `None` for the genesis
+    #[cfg_attr(feature = "serde", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub witness_txid: Option<Txid>,
+
+    /// Transaction output holding the allocation
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub outpoint: OutPoint,
+
+    /// Revealed seal definition, including the blinding factor
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub seal: seal::Revealed,
+
+    /// Blinded seal, under which the allocation is known to the parties not
+    /// knowing the seal blinding factor
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub concealed_seal: seal::Confidential,
+
+    /// Whether the seal references an output of the witness transaction
+    /// instead of an explicit outpoint
+    pub witness_seal: bool,
+
+    /// Allocated value of the asset
+    pub value: AtomicValue,
+
+    /// Height of the block mining the transaction which created the output,
+    /// or `None` if the transaction is not mined or its status is unknown
+    pub mined_at: Option<u32>,
+}
+
+impl Asset {
+    /// Lists provenance records of all known allocations. Confirmation status
+    /// of the transactions creating allocation outputs is provided by
+    /// `mined_at`, which returns height of the block mining a transaction.
+    pub fn known_allocations(
+        &self,
+        mined_at: impl Fn(Txid) -> Option<u32>,
+    ) -> Vec<AllocationRecord> {
+        self.known_coins()
+            .filter_map(|coin| {
+                let (node, witness_txid) = self.node_by_id(coin.outpoint.node_id)?;
+                let seal = node
+                    .owned_rights_by_type(coin.outpoint.ty)?
+                    .revealed_seal_at(coin.outpoint.no)
+                    .ok()??;
+                Some(AllocationRecord {
+                    node_id: coin.outpoint.node_id,
+                    index: coin.outpoint.no,
+                    witness_txid,
+                    outpoint: coin.seal,
+                    seal,
+                    concealed_seal: seal.commit_conceal(),
+                    witness_seal: seal.txid.is_none(),
+                    value: coin.state.value,
+                    mined_at: mined_at(coin.seal.txid),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::Hash;
+    use rgb::{Consignment, Node, SealEndpoint, StateTransfer, TransitionBundle};
+    use seals::txout::CloseMethod;
+
+    use super::*;
+    use crate::fixtures::{anchor, builder, outpoint};
+
+    #[test]
+    fn allocation_provenance() {
+        let contract = builder().build().unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        let genesis_id = contract.genesis().node_id();
+
+        let records = asset.known_allocations(|_| Some(700_000));
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].node_id, genesis_id);
+        assert_eq!(records[0].witness_txid, None);
+        assert_eq!(records[0].outpoint, outpoint(1));
+        assert!(!records[0].witness_seal);
+        assert_eq!(records[0].mined_at, Some(700_000));
+
+        let witness_txid = Txid::hash(b"witness");
+        let transition = asset
+            .transfer(
+                bset! { outpoint(1) },
+                bmap! { SealEndpoint::WitnessVout {
+                    method: CloseMethod::TapretFirst,
+                    vout: 0,
+                    blinding: 1,
+                } => 60 },
+                bmap! { seal::Revealed::from(outpoint(2)) => 40 },
+            )
+            .unwrap();
+        let transition_id = transition.node_id();
+        let bundle = TransitionBundle::try_from(bmap! { transition => bset! { 0 } }).unwrap();
+        let transfer = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            empty!(),
+            vec![(anchor(witness_txid), bundle)].try_into().unwrap(),
+            empty!(),
+        );
+        let asset = Asset::try_from(&transfer).unwrap();
+
+        let records = asset.known_allocations(|_| None);
+        assert_eq!(records.len(), 2);
+        for record in records {
+            assert_eq!(record.node_id, transition_id);
+            assert_eq!(record.witness_txid, Some(witness_txid));
+            assert_eq!(record.concealed_seal, record.seal.commit_conceal());
+            assert_eq!(record.mined_at, None);
+            if record.witness_seal {
+                assert_eq!(record.outpoint, OutPoint::new(witness_txid, 0));
+                assert_eq!(record.value, 60);
+            } else {
+                assert_eq!(record.outpoint, outpoint(2));
+                assert_eq!(record.value, 40);
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Embedding of RGB20 state transitions into partially signed bitcoin
+//! transactions (PSBTs) which will serve as their witness transactions.
+//!
+//! The PSBT is extended with RGB-specific proprietary keys (asset contract,
+//! state transitions and PSBT inputs consumed by them) and the LNPBP-4
+//! message for the transition bundle is put into the output hosting the
+//! tapret or opret commitment. The commitment itself is created when the
+//! PSBT gets finalized by a wallet supporting deterministic bitcoin
+//! commitments.
+
+use ::psbt::commit::{Lnpbp4KeyError, OpretKeyError, ProprietaryKeyLnpbp4, TapretKeyError};
+use ::psbt::{ProprietaryKey, Psbt};
+use bitcoin::OutPoint;
+use bitcoin_scripts::taproot::{DfsOrder, DfsPath};
+use commit_verify::lnpbp4::ProtocolId;
+use rgb::psbt::{KeyError, RgbExt, RgbInExt};
+use rgb::{Contract, InmemConsignment, Node, NodeOutpoint, Transition};
+use seals::txout::CloseMethod;
+
+use crate::Asset;
+
+/// Errors embedding RGB20 state transition into PSBT
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum Error {
+    /// state transition closes seal {0} which definition is not known to the
+    /// asset or is confidential
+    UnknownSeal(NodeOutpoint),
+
+    /// PSBT does not contain input spending output {0}, which is required to
+    /// close the seal of the state transition
+    InputAbsent(OutPoint),
+
+    /// PSBT does not contain a taproot output which can host tapret commitment
+    NoTapretHost,
+
+    /// seal closing method {0} is not supported for PSBT commitments
+    UnsupportedMethod(CloseMethod),
+
+    /// invalid RGB data in PSBT. Details: {0}
+    #[from]
+    Rgb(KeyError),
+
+    /// invalid LNPBP-4 data in PSBT. Details: {0}
+    #[from]
+    Lnpbp4(Lnpbp4KeyError),
+
+    /// unable to host tapret commitment. Details: {0}
+    #[from]
+    Tapret(TapretKeyError),
+
+    /// unable to host opret commitment. Details: {0}
+    #[from]
+    Opret(OpretKeyError),
+}
+
+/// Embeds RGB20 state transition into the witness transaction PSBT, returning
+/// the modified PSBT.
+///
+/// The function:
+/// - adds asset contract and the state transition to the PSBT global
+///   proprietary keys;
+/// - marks PSBT inputs spending seals closed by the transition as consumed by
+///   the transition;
+/// - marks the output which will host the commitment according to the seal
+///   closing `method`: the first taproot output for tapret, or the first
+///   `OP_RETURN` output (added if absent) for opret;
+/// - sets LNPBP-4 messages for all RGB transition bundles present in the PSBT
+///   on the commitment host output.
+///
+/// The function may be called multiple times for different transitions of
+/// the same or different assets spending from the same witness transaction;
+/// LNPBP-4 messages are updated each time to commit to the complete bundles.
+pub fn embed_transition(
+    mut psbt: Psbt,
+    asset: &Asset,
+    transition: Transition,
+    method: impl Into<CloseMethod>,
+) -> Result<Psbt, Error> {
+    let method = method.into();
+    let contract_id = asset.contract_id();
+    if !psbt.has_rgb_contract(contract_id) {
+        psbt.set_rgb_contract(asset_contract(asset))?;
+    }
+
+    let node_id = transition.node_id();
+    for closed in transition.parent_outputs() {
+        let outpoint = asset
+            .resolve_seal(closed)
+            .ok_or(Error::UnknownSeal(closed))?;
+        let input = psbt
+            .inputs
+            .iter_mut()
+            .find(|input| input.previous_outpoint == outpoint)
+            .ok_or(Error::InputAbsent(outpoint))?;
+        input.set_rgb_consumer(contract_id, node_id)?;
+    }
+    psbt.push_rgb_transition(transition)?;
+
+    match method {
+        CloseMethod::TapretFirst => {
+            let output = psbt
+                .outputs
+                .iter_mut()
+                .find(|output| output.is_tapret_host() || output.script.is_v1_p2tr())
+                .ok_or(Error::NoTapretHost)?;
+            if output.tapret_dfs_path().is_none() {
+                output.set_tapret_dfs_path(&DfsPath::with([&DfsOrder::Last]))?;
+            }
+        }
+        CloseMethod::OpretFirst => {
+            if let Some(output) = psbt
+                .outputs
+                .iter_mut()
+                .find(|output| output.script.is_op_return())
+            {
+                output.set_opret_host()?;
+            }
+        }
+        _ => return Err(Error::UnsupportedMethod(method)),
+    }
+
+    // Bundles may have changed since the previous call, so we drop messages
+    // committing to their outdated versions
+    for contract_id in psbt.rgb_contract_ids() {
+        let key = ProprietaryKey::lnpbp4_message(ProtocolId::from(contract_id));
+        for output in &mut psbt.outputs {
+            output.proprietary.remove(&key);
+        }
+    }
+    psbt.rgb_bundle_to_lnpbp4()?;
+
+    // If there was no host output, an `OP_RETURN` output was just added
+    if method == CloseMethod::OpretFirst {
+        if let Some(output) = psbt
+            .outputs
+            .iter_mut()
+            .find(|output| output.script.is_op_return() && !output.is_opret_host())
+        {
+            output.set_opret_host()?;
+        }
+    }
+
+    Ok(psbt)
+}
+
+/// Reconstructs asset contract consignment, which is stored in the PSBT
+fn asset_contract(asset: &Asset) -> Contract {
+    let subschema = asset.subschema();
+    let root_schema = subschema.root_schema();
+    InmemConsignment::with(
+        subschema.schema(),
+        root_schema,
+        asset.genesis().clone(),
+        empty!(),
+        empty!(),
+        empty!(),
+    )
+}
@@ -0,0 +1,306 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Receipts for the RGB20 transfers.
+//!
+//! A [`Receipt`] proves that a transfer anchored in a bitcoin transaction
+//! assigned certain amount of the asset to the beneficiary seal. It contains
+//! only the transition bundle of the transfer, its anchor and the witness
+//! transaction, so it is much smaller than the consignment and can be verified
+//! offline, for instance by a marketplace settling RGB20 payments.
+
+use amplify::Wrapper;
+use bitcoin::hashes::Hash;
+use bitcoin::{Transaction, Txid};
+use bitcoin_onchain::ResolveTx;
+use commit_verify::{lnpbp4, CommitConceal};
+use rgb::{
+    seal, Anchor, AtomicValue, BundleId, Consignment, ContractId, Node, StateTransfer,
+    TransitionBundle, TypedAssignments,
+};
+
+use crate::schema::OwnedRightType;
+use crate::Armor;
+
+/// Errors producing and verifying transfer [`Receipt`]s
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ReceiptError {
+    /// seal {0} is not an endpoint of the transfer
+    EndpointAbsent(seal::Confidential),
+
+    /// transfer does not contain anchored bundle {0}
+    BundleAbsent(BundleId),
+
+    /// transition bundle does not assign revealed asset value to the seal {0}
+    NoAllocation(seal::Confidential),
+
+    /// witness transaction {0} is unknown to the resolver
+    WitnessUnknown(Txid),
+
+    /// witness transaction {actual} does not match the receipt witness
+    /// transaction id {expected}
+    WitnessMismatch {
+        /// Witness transaction id declared by the receipt
+        expected: Txid,
+        /// Id of the witness transaction or anchor provided by the receipt
+        actual: Txid,
+    },
+
+    /// witness transaction does not commit to the transition bundle of
+    /// contract {0}
+    AnchorInvalid(ContractId),
+
+    /// receipt amount {claimed} does not match the amount {assigned} assigned
+    /// to the beneficiary seal
+    AmountMismatch {
+        /// Amount declared by the receipt
+        claimed: AtomicValue,
+        /// Amount assigned to the beneficiary seal by the transition bundle
+        assigned: AtomicValue,
+    },
+}
+
+/// Receipt for the asset amount received by the beneficiary seal in an
+/// anchored transfer
+#[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+pub struct Receipt {
+    /// Id of the asset contract
+    pub contract_id: ContractId,
+
+    /// Revealed beneficiary seal
+    pub beneficiary: seal::Revealed,
+
+    /// Amount of the asset assigned to the beneficiary seal, in atomic units
+    pub amount: AtomicValue,
+
+    /// Id of the witness transaction anchoring the transfer
+    pub witness_txid: Txid,
+
+    /// Transition bundle of the contract containing the transfer
+    pub bundle: TransitionBundle,
+
+    /// Anchor of the transition bundle to the witness transaction
+    pub anchor: Anchor<lnpbp4::MerkleProof>,
+
+    /// Witness transaction anchoring the transfer
+    pub witness: Transaction,
+}
+
+impl Armor for Receipt {
+    const HRP: &'static str = "rgbreceipt";
+}
+
+impl Receipt {
+    /// Produces receipt for the transfer endpoint matching the `beneficiary`
+    /// seal revealed by the receiver, taking the witness transaction from the
+    /// `resolver`. The transfer should be already accepted, for instance with
+    /// [`crate::accept_transfer`].
+    pub fn with(
+        transfer: &StateTransfer,
+        beneficiary: seal::Revealed,
+        resolver: &impl ResolveTx,
+    ) -> Result<Receipt, ReceiptError> {
+        let concealed = beneficiary.commit_conceal();
+        let bundle_id = transfer
+            .endpoints()
+            .find(|(_, endpoint)| endpoint.commit_conceal() == concealed)
+            .map(|(bundle_id, _)| *bundle_id)
+            .ok_or(ReceiptError::EndpointAbsent(concealed))?;
+        let (anchor, bundle) = transfer
+            .anchored_bundles()
+            .find(|(_, bundle)| bundle.bundle_id() == bundle_id)
+            .ok_or(ReceiptError::BundleAbsent(bundle_id))?;
+        let amount = assigned_value(bundle, concealed)?;
+        let witness = resolver
+            .resolve_tx(anchor.txid)
+            .map_err(|_| ReceiptError::WitnessUnknown(anchor.txid))?;
+
+        Ok(Receipt {
+            contract_id: transfer.contract_id(),
+            beneficiary,
+            amount,
+            witness_txid: anchor.txid,
+            bundle: bundle.clone(),
+            anchor: anchor.clone(),
+            witness,
+        })
+    }
+
+    /// Verifies the receipt without access to the network: checks that the
+    /// witness transaction commits to the transition bundle under the
+    /// contract id and that the bundle assigns the receipt amount to the
+    /// beneficiary seal.
+    ///
+    /// The verification does not check that the witness transaction is mined
+    /// and that the spent allocations are valid; this requires the full
+    /// transfer consignment.
+    pub fn verify(&self) -> Result<(), ReceiptError> {
+        for actual in [self.witness.txid(), self.anchor.txid] {
+            if actual != self.witness_txid {
+                return Err(ReceiptError::WitnessMismatch {
+                    expected: self.witness_txid,
+                    actual,
+                });
+            }
+        }
+
+        let message =
+            lnpbp4::Message::from_inner(self.bundle.bundle_id().into_inner().into_inner());
+        if !matches!(
+            self.anchor
+                .verify(self.contract_id, message, self.witness.clone()),
+            Ok(true)
+        ) {
+            return Err(ReceiptError::AnchorInvalid(self.contract_id));
+        }
+
+        let assigned = assigned_value(&self.bundle, self.beneficiary.commit_conceal())?;
+        if assigned != self.amount {
+            return Err(ReceiptError::AmountMismatch {
+                claimed: self.amount,
+                assigned,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Sums revealed asset values assigned by the bundle transitions to the seal
+fn assigned_value(
+    bundle: &TransitionBundle,
+    seal: seal::Confidential,
+) -> Result<AtomicValue, ReceiptError> {
+    let values = bundle
+        .revealed_iter()
+        .filter_map(|(transition, _)| {
+            match transition.owned_rights_by_type(OwnedRightType::Assets.into())? {
+                TypedAssignments::Value(assignments) => Some(assignments),
+                _ => None,
+            }
+        })
+        .flatten()
+        .filter(|assignment| assignment.to_confidential_seal() == seal)
+        .filter_map(|assignment| assignment.as_revealed_state())
+        .map(|state| state.value)
+        .collect::<Vec<_>>();
+    if values.is_empty() {
+        return Err(ReceiptError::NoAllocation(seal));
+    }
+    Ok(values
+        .into_iter()
+        .fold(0u64, |sum, value| sum.saturating_add(value)))
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::PackedLockTime;
+    use rgb::{Contract, SealEndpoint};
+    use seals::txout::CloseMethod;
+
+    use super::*;
+    use crate::fixtures::{anchor, builder, outpoint};
+    use crate::{Asset, OfflineResolver};
+
+    fn witness(lock_time: u32) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime(lock_time),
+            input: vec![],
+            output: vec![],
+        }
+    }
+
+    /// Constructs transfer of 60 units to the returned beneficiary seal,
+    /// anchored to the `witness` transaction
+    fn transfer(witness: &Transaction) -> (Contract, seal::Revealed, StateTransfer) {
+        let contract = builder().build().unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+
+        let reveal = seal::Revealed::new(CloseMethod::TapretFirst, outpoint(3));
+        let endpoint = SealEndpoint::ConcealedUtxo(reveal.commit_conceal());
+        let transition = asset
+            .transfer(
+                bset! { outpoint(1) },
+                bmap! { endpoint => 60 },
+                bmap! { seal::Revealed::from(outpoint(2)) => 40 },
+            )
+            .unwrap();
+        let bundle = TransitionBundle::try_from(bmap! { transition => bset! { 0 } }).unwrap();
+        let transfer = StateTransfer::with(
+            contract.schema().clone(),
+            None,
+            contract.genesis().clone(),
+            vec![(bundle.bundle_id(), endpoint)],
+            vec![(anchor(witness.txid()), bundle)].try_into().unwrap(),
+            empty!(),
+        );
+        (contract, reveal, transfer)
+    }
+
+    #[test]
+    fn transfer_receipt() {
+        let tx = witness(0);
+        let (contract, reveal, transfer) = transfer(&tx);
+
+        let mut resolver = OfflineResolver::new();
+        assert_eq!(
+            Receipt::with(&transfer, reveal, &resolver),
+            Err(ReceiptError::WitnessUnknown(tx.txid()))
+        );
+        let stranger = seal::Revealed::new(CloseMethod::TapretFirst, outpoint(4));
+        assert_eq!(
+            Receipt::with(&transfer, stranger, &resolver),
+            Err(ReceiptError::EndpointAbsent(stranger.commit_conceal()))
+        );
+
+        resolver.insert(tx.clone());
+        let receipt = Receipt::with(&transfer, reveal, &resolver).unwrap();
+        assert_eq!(receipt.contract_id, contract.contract_id());
+        assert_eq!(receipt.amount, 60);
+        assert_eq!(receipt.witness_txid, tx.txid());
+
+        let armored = Receipt::from_armored_str(&receipt.to_armored_string()).unwrap();
+        assert_eq!(armored, receipt);
+
+        // Test anchors do not commit to the bundles
+        assert_eq!(
+            receipt.verify(),
+            Err(ReceiptError::AnchorInvalid(contract.contract_id()))
+        );
+
+        let mut forged = receipt;
+        forged.witness = witness(1);
+        assert!(matches!(
+            forged.verify(),
+            Err(ReceiptError::WitnessMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn receipt_anchor_mismatch() {
+        let tx = witness(0);
+        let (_, reveal, transfer) = transfer(&tx);
+        let mut resolver = OfflineResolver::new();
+        resolver.insert(tx.clone());
+        let mut forged = Receipt::with(&transfer, reveal, &resolver).unwrap();
+
+        let other = witness(1);
+        forged.anchor = anchor(other.txid());
+        assert_eq!(
+            forged.verify(),
+            Err(ReceiptError::WitnessMismatch {
+                expected: tx.txid(),
+                actual: other.txid(),
+            })
+        );
+    }
+}
@@ -0,0 +1,245 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Interchange format for public asset registries.
+//!
+//! Asset listing is represented by [`AssetRecord`], which is signed by the
+//! party publishing it (usually the asset issuer) into [`SignedRecord`]. The
+//! signature commits to the strict encoding of the record, which is also its
+//! canonical binary (and, with [`Armor`], textual) serialization. Registries
+//! and wallets check the received listings with [`verify_record`].
+
+use std::collections::BTreeMap;
+
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::secp256k1::{ecdsa, Message, PublicKey, Secp256k1, SecretKey, Signing, Verification};
+use rgb::validation::Validity;
+use rgb::{Contract, ContractId, Genesis};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+use strict_encoding::StrictEncode;
+
+use crate::{
+    Armor, Asset, Attachment, AttachmentType, Error, Nomination, OfflineResolver, Subschema,
+    ValidationReport,
+};
+
+/// Tag for deriving the digest of the asset record which is signed
+const RECORD_TAG: &[u8] = b"rgb20:registry";
+
+/// Errors detected during verification of [`SignedRecord`]
+#[derive(Clone, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum RegistryError {
+    /// asset record signature is invalid
+    InvalidSignature,
+
+    /// asset record declares contract id {declared}, while its genesis has
+    /// contract id {computed}
+    ContractIdMismatch {
+        /// Contract id declared by the record
+        declared: ContractId,
+        /// Contract id computed from the genesis
+        computed: ContractId,
+    },
+
+    /// asset record genesis is invalid; details are provided by the
+    /// validation report
+    InvalidGenesis(Box<ValidationReport>),
+
+    /// asset record nomination does not match the one defined by the genesis
+    NominationMismatch,
+
+    /// asset record media attachments do not match the ones defined by the
+    /// genesis
+    AttachmentsMismatch,
+
+    /// {0}
+    #[from]
+    Asset(Error),
+}
+
+/// Listing of an asset in a public asset registry
+#[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct AssetRecord {
+    /// Id of the asset contract
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub contract_id: ContractId,
+
+    /// Asset genesis
+    pub genesis: Genesis,
+
+    /// Asset nomination defined by the genesis
+    pub nomination: Nomination,
+
+    /// Free-form issuer metadata, like issuer name, website or contact
+    /// e-mail, which is not a part of the contract
+    pub issuer: BTreeMap<String, String>,
+
+    /// Media attachments defined by the genesis
+    pub attachments: BTreeMap<AttachmentType, Attachment>,
+}
+
+/// [`AssetRecord`] signed by the publishing party
+#[derive(Clone, PartialEq, Eq, Debug, StrictEncode, StrictDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct SignedRecord {
+    /// Asset record
+    pub record: AssetRecord,
+
+    /// Public key of the publishing party
+    pub pubkey: PublicKey,
+
+    /// Signature over the record digest
+    pub signature: ecdsa::Signature,
+}
+
+impl Armor for SignedRecord {
+    const HRP: &'static str = "rgbreg";
+}
+
+impl AssetRecord {
+    /// Constructs record for the asset `contract` produced by the issuance
+    /// API, with the provided `issuer` metadata. Errors if the contract does
+    /// not match RGB20 schema.
+    pub fn with(
+        contract: &Contract,
+        issuer: BTreeMap<String, String>,
+    ) -> Result<AssetRecord, Error> {
+        let asset = Asset::try_from(contract)?;
+        Ok(AssetRecord {
+            contract_id: asset.contract_id(),
+            genesis: asset.genesis().clone(),
+            nomination: asset.genesis_nomination(),
+            issuer,
+            attachments: asset.attachments(),
+        })
+    }
+
+    /// Computes tagged hash of the record, which is signed by the publishing
+    /// party
+    pub fn digest(&self) -> sha256::Hash {
+        let mut engine = sha256::Hash::engine();
+        engine.input(RECORD_TAG);
+        self.strict_encode(&mut engine)
+            .expect("in-memory hash engine does not fail");
+        sha256::Hash::from_engine(engine)
+    }
+
+    /// Signs the record with the key of the publishing party
+    pub fn sign<C: Signing>(self, secp: &Secp256k1<C>, key: &SecretKey) -> SignedRecord {
+        let msg = Message::from_slice(&self.digest()[..]).expect("digest has 32 bytes");
+        SignedRecord {
+            signature: secp.sign_ecdsa(&msg, key),
+            pubkey: PublicKey::from_secret_key(secp, key),
+            record: self,
+        }
+    }
+}
+
+/// Verifies signed asset record: checks the signature, validates the genesis
+/// against its schema and checks that the contract id, nomination and media
+/// attachments declared by the record match the genesis. Returns the asset
+/// constructed from the genesis.
+///
+/// The function does not check that the record is signed by the asset
+/// issuer; registries must check the `pubkey` against their own policies.
+pub fn verify_record<C: Verification>(
+    secp: &Secp256k1<C>,
+    signed: &SignedRecord,
+) -> Result<Asset, RegistryError> {
+    let record = &signed.record;
+    let msg = Message::from_slice(&record.digest()[..]).expect("digest has 32 bytes");
+    secp.verify_ecdsa(&msg, &signed.signature, &signed.pubkey)
+        .map_err(|_| RegistryError::InvalidSignature)?;
+
+    let computed = record.genesis.contract_id();
+    if record.contract_id != computed {
+        return Err(RegistryError::ContractIdMismatch {
+            declared: record.contract_id,
+            computed,
+        });
+    }
+
+    let schema_id = record.genesis.schema_id();
+    let subschema = Subschema::from_schema_id(schema_id).ok_or(Error::WrongSchemaId(schema_id))?;
+    let contract = Contract::with(
+        subschema.schema(),
+        subschema.root_schema(),
+        record.genesis.clone(),
+        empty!(),
+        empty!(),
+        empty!(),
+    );
+    let validation = Asset::validate_consignment(&contract, &OfflineResolver::new());
+    if validation.validity() == Validity::Invalid {
+        return Err(RegistryError::InvalidGenesis(Box::new(validation)));
+    }
+
+    let asset = Asset::try_from(&contract)?;
+    if asset.genesis_nomination() != record.nomination {
+        return Err(RegistryError::NominationMismatch);
+    }
+    if asset.attachments() != record.attachments {
+        return Err(RegistryError::AttachmentsMismatch);
+    }
+    Ok(asset)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::builder;
+
+    fn record() -> AssetRecord {
+        let contract = builder().build().unwrap();
+        AssetRecord::with(&contract, bmap! { s!("name") => s!("Test issuer") }).unwrap()
+    }
+
+    #[test]
+    fn record_verification() {
+        let secp = Secp256k1::new();
+        let key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let record = record();
+        assert_eq!(record.nomination.ticker, "TICK");
+
+        let signed = record.clone().sign(&secp, &key);
+        let asset = verify_record(&secp, &signed).unwrap();
+        assert_eq!(asset.contract_id(), record.contract_id);
+        assert_eq!(
+            SignedRecord::from_armored_str(&signed.to_armored_string()).unwrap(),
+            signed
+        );
+
+        let mut forged = signed.clone();
+        forged.record.issuer.insert(s!("name"), s!("Forged issuer"));
+        assert!(matches!(
+            verify_record(&secp, &forged),
+            Err(RegistryError::InvalidSignature)
+        ));
+
+        let mut renamed = record;
+        renamed.nomination.name = s!("Other asset");
+        assert!(matches!(
+            verify_record(&secp, &renamed.sign(&secp, &key)),
+            Err(RegistryError::NominationMismatch)
+        ));
+    }
+}
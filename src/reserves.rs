@@ -0,0 +1,249 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::secp256k1::{ecdsa, Message, PublicKey, Secp256k1, SecretKey, Signing, Verification};
+use bitcoin::OutPoint;
+use rgb::{AtomicValue, ContractId};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+use strict_encoding::StrictEncode;
+
+use crate::Asset;
+
+/// Tag for deriving the digest of the reserves report which is signed
+const RESERVES_TAG: &[u8] = b"rgb20:reserves";
+
+/// Errors detected during verification of [`SignedReserves`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ReservesError {
+    /// reserves report is given for the contract {0}, while the asset has
+    /// contract id {1}
+    ContractMismatch(ContractId, ContractId),
+
+    /// reserves report claims {claimed} at {outpoint}, while the asset has
+    /// only {known} allocated to it
+    AmountMismatch {
+        /// Outpoint holding the reserves
+        outpoint: OutPoint,
+        /// Amount claimed by the report
+        claimed: AtomicValue,
+        /// Amount known from the asset consignments
+        known: AtomicValue,
+    },
+
+    /// reserves report total {claimed} does not match the sum of per-outpoint
+    /// reserves {computed}
+    TotalMismatch {
+        /// Total claimed by the report
+        claimed: AtomicValue,
+        /// Sum of the per-outpoint reserves
+        computed: AtomicValue,
+    },
+
+    /// reserves report signature is invalid
+    InvalidSignature,
+}
+
+/// Report on the amount of the asset held at a set of outpoints controlled by
+/// the reporting party (for instance, an exchange)
+#[derive(Clone, PartialEq, Eq, Hash, Debug, StrictEncode, StrictDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct ReservesReport {
+    /// Id of the asset contract
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub contract_id: ContractId,
+
+    /// Height of the bitcoin block at which the reserves are reported
+    pub height: u32,
+
+    /// Total amount of the reserves
+    pub total: AtomicValue,
+
+    /// Amount of the reserves held at each of the outpoints
+    #[cfg_attr(feature = "serde", serde(with = "As::<BTreeMap<DisplayFromStr, DisplayFromStr>>"))]
+    pub outpoints: BTreeMap<OutPoint, AtomicValue>,
+}
+
+/// [`ReservesReport`] signed by the reporting party
+#[derive(Clone, PartialEq, Eq, Hash, Debug, StrictEncode, StrictDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct SignedReserves {
+    /// Reserves report
+    pub report: ReservesReport,
+
+    /// Public key of the reporting party
+    pub pubkey: PublicKey,
+
+    /// Signature over the report digest
+    pub signature: ecdsa::Signature,
+}
+
+impl ReservesReport {
+    /// Constructs report on the reserves held at the given `outpoints` as of
+    /// the block `height`, using allocations known to the `asset`.
+    /// Outpoints without known allocations are reported with zero amount.
+    pub fn with(
+        asset: &Asset,
+        outpoints: impl IntoIterator<Item = OutPoint>,
+        height: u32,
+    ) -> ReservesReport {
+        let outpoints = outpoints
+            .into_iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|outpoint| (outpoint, reserves_at(asset, outpoint)))
+            .collect::<BTreeMap<_, _>>();
+        ReservesReport {
+            contract_id: asset.contract_id(),
+            height,
+            total: outpoints
+                .values()
+                .fold(0u64, |sum, value| sum.saturating_add(*value)),
+            outpoints,
+        }
+    }
+
+    /// Computes tagged hash of the report, which is signed by the reporting
+    /// party
+    pub fn digest(&self) -> sha256::Hash {
+        let mut engine = sha256::Hash::engine();
+        engine.input(RESERVES_TAG);
+        self.strict_encode(&mut engine)
+            .expect("in-memory hash engine does not fail");
+        sha256::Hash::from_engine(engine)
+    }
+
+    /// Signs the report with the key of the reporting party
+    pub fn sign<C: Signing>(self, secp: &Secp256k1<C>, key: &SecretKey) -> SignedReserves {
+        let msg = Message::from_slice(&self.digest()[..]).expect("digest has 32 bytes");
+        SignedReserves {
+            signature: secp.sign_ecdsa(&msg, key),
+            pubkey: PublicKey::from_secret_key(secp, key),
+            report: self,
+        }
+    }
+}
+
+impl SignedReserves {
+    /// Verifies the report signature and checks the reported amounts against
+    /// the allocations known to the `asset`, which must be constructed from
+    /// the consignments provided by the reporting party.
+    ///
+    /// Verification does not check that the outpoints are unspent at the
+    /// reported height; use [`crate::WitnessResolver`] for that.
+    pub fn verify<C: Verification>(
+        &self,
+        secp: &Secp256k1<C>,
+        asset: &Asset,
+    ) -> Result<(), ReservesError> {
+        let report = &self.report;
+        let msg = Message::from_slice(&report.digest()[..]).expect("digest has 32 bytes");
+        secp.verify_ecdsa(&msg, &self.signature, &self.pubkey)
+            .map_err(|_| ReservesError::InvalidSignature)?;
+
+        if report.contract_id != asset.contract_id() {
+            return Err(ReservesError::ContractMismatch(
+                report.contract_id,
+                asset.contract_id(),
+            ));
+        }
+
+        let mut computed = 0u64;
+        for (outpoint, claimed) in &report.outpoints {
+            let known = reserves_at(asset, *outpoint);
+            if *claimed != known {
+                return Err(ReservesError::AmountMismatch {
+                    outpoint: *outpoint,
+                    claimed: *claimed,
+                    known,
+                });
+            }
+            computed = computed.saturating_add(known);
+        }
+        if computed != report.total {
+            return Err(ReservesError::TotalMismatch {
+                claimed: report.total,
+                computed,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Sums up asset allocations known at the outpoint
+fn reserves_at(asset: &Asset, outpoint: OutPoint) -> AtomicValue {
+    asset
+        .allocations_at(outpoint)
+        .iter()
+        .fold(0u64, |sum, coin| sum.saturating_add(coin.state.value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+
+    fn asset() -> Asset {
+        let contract = builder()
+            .allocate(outpoint(1), 50)
+            .allocate(outpoint(2), 200)
+            .allocate(outpoint(3), 300)
+            .build()
+            .unwrap();
+        Asset::try_from(&contract).unwrap()
+    }
+
+    #[test]
+    fn reserves() {
+        let secp = Secp256k1::new();
+        let key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let asset = asset();
+
+        let report = ReservesReport::with(&asset, [outpoint(1), outpoint(2), outpoint(4)], 700_000);
+        assert_eq!(report.total, 350);
+        assert_eq!(report.outpoints.get(&outpoint(4)), Some(&0));
+
+        let signed = report.sign(&secp, &key);
+        assert_eq!(signed.verify(&secp, &asset), Ok(()));
+
+        let mut forged = signed.clone();
+        forged.report.total = 1000;
+        assert_eq!(
+            forged.verify(&secp, &asset),
+            Err(ReservesError::InvalidSignature)
+        );
+
+        let mut report = signed.report;
+        report.outpoints.insert(outpoint(3), 1000);
+        report.total += 1000;
+        assert_eq!(
+            report.sign(&secp, &key).verify(&secp, &asset),
+            Err(ReservesError::AmountMismatch {
+                outpoint: outpoint(3),
+                claimed: 1000,
+                known: 300
+            })
+        );
+    }
+}
@@ -0,0 +1,179 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use bitcoin::OutPoint;
+use bitcoin_onchain::ResolveTx;
+use rgb::OwnedValue;
+
+use crate::Asset;
+
+/// Errors reported by [`WitnessResolver`] implementations
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ResolverError {
+    /// resolver backend failure: {0}
+    Backend(String),
+
+    /// resolver backend returned invalid data for the output {0}
+    InvalidData(OutPoint),
+}
+
+/// Source of bitcoin witness transactions and information about spending of
+/// their outputs, used by the consignment validation (as [`ResolveTx`]) and by
+/// the checks of asset allocations.
+pub trait WitnessResolver: ResolveTx {
+    /// Checks whether the transaction output is spent. Returns `None` if the
+    /// resolver has no information about the output.
+    fn is_spent(&self, outpoint: OutPoint) -> Result<Option<bool>, ResolverError>;
+}
+
+impl Asset {
+    /// Lists known asset allocations assigned to the outputs which are
+    /// reported unspent by the `resolver`. Allocations on the outputs which
+    /// are unknown to the resolver are not included.
+    pub fn unspent_allocations(
+        &self,
+        resolver: &impl WitnessResolver,
+    ) -> Result<Vec<OwnedValue>, ResolverError> {
+        let mut allocations = vec![];
+        for outpoint in self.outpoints() {
+            if resolver.is_spent(*outpoint)? == Some(false) {
+                allocations.extend(self.allocations_at(*outpoint).iter().cloned());
+            }
+        }
+        Ok(allocations)
+    }
+}
+
+#[cfg(feature = "electrum")]
+impl WitnessResolver for electrum_client::Client {
+    fn is_spent(&self, outpoint: OutPoint) -> Result<Option<bool>, ResolverError> {
+        use electrum_client::ElectrumApi;
+
+        let tx = self
+            .transaction_get(&outpoint.txid)
+            .map_err(|err| ResolverError::Backend(err.to_string()))?;
+        let output = tx
+            .output
+            .get(outpoint.vout as usize)
+            .ok_or(ResolverError::InvalidData(outpoint))?;
+        let unspent = self
+            .script_list_unspent(&output.script_pubkey)
+            .map_err(|err| ResolverError::Backend(err.to_string()))?;
+        Ok(Some(!unspent.iter().any(|utxo| {
+            utxo.tx_hash == outpoint.txid && utxo.tx_pos == outpoint.vout as usize
+        })))
+    }
+}
+
+#[cfg(feature = "esplora")]
+pub use esplora::EsploraResolver;
+
+#[cfg(feature = "esplora")]
+mod esplora {
+    use bitcoin::consensus::deserialize;
+    use bitcoin::hashes::hex::FromHex;
+    use bitcoin::{OutPoint, Transaction, Txid};
+    use bitcoin_onchain::{ResolveTx, TxResolverError};
+
+    use super::{ResolverError, WitnessResolver};
+
+    /// Witness resolver using HTTP API of an Esplora server. HTTPS servers
+    /// require `esplora-tls` feature.
+    #[derive(Clone, Debug)]
+    pub struct EsploraResolver {
+        url: String,
+        agent: ureq::Agent,
+    }
+
+    impl EsploraResolver {
+        /// Constructs resolver for the Esplora API at the given `url`, like
+        /// `https://blockstream.info/api`
+        pub fn new(url: impl ToString) -> EsploraResolver {
+            EsploraResolver {
+                url: url.to_string().trim_end_matches('/').to_owned(),
+                agent: ureq::AgentBuilder::new().build(),
+            }
+        }
+
+        /// Requests API `path`, returning `None` if the requested object is
+        /// not known to the server
+        fn get(&self, path: &str) -> Result<Option<String>, ResolverError> {
+            match self.agent.get(&format!("{}/{}", self.url, path)).call() {
+                Ok(response) => response
+                    .into_string()
+                    .map(Some)
+                    .map_err(|err| ResolverError::Backend(err.to_string())),
+                Err(ureq::Error::Status(404, _)) => Ok(None),
+                Err(err) => Err(ResolverError::Backend(err.to_string())),
+            }
+        }
+    }
+
+    impl ResolveTx for EsploraResolver {
+        fn resolve_tx(&self, txid: Txid) -> Result<Transaction, TxResolverError> {
+            self.get(&format!("tx/{}/hex", txid))
+                .ok()
+                .flatten()
+                .and_then(|hex| Vec::<u8>::from_hex(hex.trim()).ok())
+                .and_then(|data| deserialize(&data).ok())
+                .ok_or_else(|| TxResolverError::with(txid))
+        }
+    }
+
+    impl WitnessResolver for EsploraResolver {
+        fn is_spent(&self, outpoint: OutPoint) -> Result<Option<bool>, ResolverError> {
+            let data =
+                match self.get(&format!("tx/{}/outspend/{}", outpoint.txid, outpoint.vout))? {
+                    None => return Ok(None),
+                    Some(data) => data,
+                };
+            let status: serde_json::Value =
+                serde_json::from_str(&data).map_err(|_| ResolverError::InvalidData(outpoint))?;
+            status
+                .get("spent")
+                .and_then(serde_json::Value::as_bool)
+                .map(Some)
+                .ok_or(ResolverError::InvalidData(outpoint))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::{PackedLockTime, Script, Sequence, Transaction, TxIn, Witness};
+
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+    use crate::OfflineResolver;
+
+    #[test]
+    fn offline_spent_outputs() {
+        let mut resolver = OfflineResolver::new();
+        resolver.insert(Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: outpoint(1),
+                script_sig: Script::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![],
+        });
+        assert_eq!(resolver.is_spent(outpoint(1)), Ok(Some(true)));
+        assert_eq!(resolver.is_spent(outpoint(2)), Ok(None));
+
+        let contract = builder().build().unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        assert!(asset.unspent_allocations(&resolver).unwrap().is_empty());
+    }
+}
@@ -11,25 +11,35 @@
 
 //! RGB20 schemata defining fungible asset smart contract prototypes.
 
+use std::collections::BTreeSet;
 use std::str::FromStr;
 
+use once_cell::sync::Lazy;
 use rgb::schema::{
-    DiscreteFiniteFieldFormat, GenesisSchema, Occurrences, Schema, SchemaId, StateSchema,
-    TransitionSchema,
+    DiscreteFiniteFieldFormat, GenesisSchema, Occurrences, Schema, SchemaId, SchemaVerify,
+    StateSchema, TransitionSchema,
 };
 use rgb::script::OverrideRules;
+use rgb::validation::Failure;
 use rgb::vm::embedded::constants::*;
 use rgb::ValidationScript;
 use stens::{PrimitiveType, StructField, TypeRef, TypeSystem};
 
+#[cfg(feature = "legacy")]
+#[allow(deprecated)]
+pub use crate::legacy::{
+    deflationary_subschema, media_schema, schema, subschema, SCHEMA_ID_BECH32, SUBSCHEMA_ID_BECH32,
+};
+
 /// Schema identifier for full RGB20 fungible asset
-pub const SCHEMA_ID_BECH32: &str =
-    "rgbsh18kp34t5nn5zu4hz6g7lqjdjskw8aaf84ecdntrtrdvzs7gn3rnzskscfq8";
+const ROOT_SCHEMA_ID: &str = "rgbsh18kp34t5nn5zu4hz6g7lqjdjskw8aaf84ecdntrtrdvzs7gn3rnzskscfq8";
 
-/// Schema identifier for full RGB20 fungible asset subschema prohibiting burn &
-/// replace operations
-pub const SUBSCHEMA_ID_BECH32: &str =
-    "rgbsh1636y76cxrnsfqg7zjnl08f0kqt9j09tre2wfxzrrs86f76ssp7cqnn0yyf";
+/// Field type of the asset logo attachment
+pub const FIELD_TYPE_LOGO: u16 = 0x20;
+/// Field type of the asset icon attachment
+pub const FIELD_TYPE_ICON: u16 = 0x21;
+/// Field type of the asset terms document attachment
+pub const FIELD_TYPE_TERMS: u16 = 0x22;
 
 /// Field types for RGB20 schemata
 ///
@@ -48,6 +58,9 @@ pub enum FieldType {
     /// Used within context of genesis or renomination state transition
     Name = FIELD_TYPE_NAME,
 
+    /// Ricardian contract text, used by the media schema only
+    ContractText = FIELD_TYPE_CONTRACT_TEXT,
+
     /// Decimal precision
     Precision = FIELD_TYPE_PRECISION,
 
@@ -69,6 +82,15 @@ pub enum FieldType {
 
     /// Media format for the information proving burned supply
     HistoryProofFormat = FIELD_TYPE_HISTORY_PROOF_FORMAT,
+
+    /// Asset logo attachment, used by the media schema only
+    Logo = FIELD_TYPE_LOGO,
+
+    /// Asset icon attachment, used by the media schema only
+    Icon = FIELD_TYPE_ICON,
+
+    /// Asset terms document attachment, used by the media schema only
+    Terms = FIELD_TYPE_TERMS,
 }
 
 impl From<FieldType> for rgb::schema::FieldType {
@@ -76,6 +98,43 @@ impl From<FieldType> for rgb::schema::FieldType {
     fn from(ft: FieldType) -> Self { ft as rgb::schema::FieldType }
 }
 
+impl TryFrom<rgb::schema::FieldType> for FieldType {
+    type Error = rgb::schema::FieldType;
+
+    fn try_from(ft: rgb::schema::FieldType) -> Result<Self, Self::Error> {
+        Ok(match ft {
+            FIELD_TYPE_TICKER => FieldType::Ticker,
+            FIELD_TYPE_NAME => FieldType::Name,
+            FIELD_TYPE_CONTRACT_TEXT => FieldType::ContractText,
+            FIELD_TYPE_PRECISION => FieldType::Precision,
+            FIELD_TYPE_ISSUED_SUPPLY => FieldType::IssuedSupply,
+            FIELD_TYPE_BURN_SUPPLY => FieldType::BurnedSupply,
+            FIELD_TYPE_TIMESTAMP => FieldType::Timestamp,
+            FIELD_TYPE_BURN_UTXO => FieldType::BurnUtxo,
+            FIELD_TYPE_HISTORY_PROOF => FieldType::HistoryProof,
+            FIELD_TYPE_HISTORY_PROOF_FORMAT => FieldType::HistoryProofFormat,
+            FIELD_TYPE_LOGO => FieldType::Logo,
+            FIELD_TYPE_ICON => FieldType::Icon,
+            FIELD_TYPE_TERMS => FieldType::Terms,
+            unknown => return Err(unknown),
+        })
+    }
+}
+
+/// Format of the history proofs provided with burn and burn & replace
+/// operations under [`FieldType::HistoryProof`]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[display(Debug)]
+#[repr(u8)]
+pub enum HistoryProofFormat {
+    /// No history proofs are provided
+    ProofAbsent = 0x00,
+
+    /// Each proof is a strict-encoded [`crate::HistoryProof`], opening
+    /// confidential amount of the burned allocation
+    ProofV1 = 0x01,
+}
+
 /// Owned right types used by RGB20 schemata
 ///
 /// Subset of known RGB schema pre-defined types applicable to fungible assets.
@@ -104,6 +163,21 @@ impl From<OwnedRightType> for rgb::schema::OwnedRightType {
     fn from(t: OwnedRightType) -> Self { t as rgb::schema::OwnedRightType }
 }
 
+impl TryFrom<rgb::schema::OwnedRightType> for OwnedRightType {
+    type Error = rgb::schema::OwnedRightType;
+
+    fn try_from(t: rgb::schema::OwnedRightType) -> Result<Self, Self::Error> {
+        Ok(match t {
+            STATE_TYPE_INFLATION_RIGHT => OwnedRightType::Inflation,
+            STATE_TYPE_OWNERSHIP_RIGHT => OwnedRightType::Assets,
+            STATE_TYPE_ISSUE_EPOCH_RIGHT => OwnedRightType::OpenEpoch,
+            STATE_TYPE_ISSUE_REPLACEMENT_RIGHT => OwnedRightType::BurnReplace,
+            STATE_TYPE_RENOMINATION_RIGHT => OwnedRightType::Renomination,
+            unknown => return Err(unknown),
+        })
+    }
+}
+
 /// State transition types defined by RGB20 schemata
 ///
 /// Subset of known RGB schema pre-defined types applicable to fungible assets.
@@ -134,11 +208,276 @@ pub enum TransitionType {
     RightsSplit = TRANSITION_TYPE_RIGHTS_SPLIT,
 }
 
+impl TransitionType {
+    /// All state transition types defined by RGB20 schemata
+    pub const ALL: [TransitionType; 7] = [
+        TransitionType::Issue,
+        TransitionType::Transfer,
+        TransitionType::Epoch,
+        TransitionType::Burn,
+        TransitionType::BurnAndReplace,
+        TransitionType::Renomination,
+        TransitionType::RightsSplit,
+    ];
+}
+
 impl From<TransitionType> for rgb::schema::TransitionType {
     #[inline]
     fn from(t: TransitionType) -> Self { t as rgb::schema::TransitionType }
 }
 
+impl TryFrom<rgb::schema::TransitionType> for TransitionType {
+    type Error = rgb::schema::TransitionType;
+
+    fn try_from(t: rgb::schema::TransitionType) -> Result<Self, Self::Error> {
+        Ok(match t {
+            TRANSITION_TYPE_ISSUE_FUNGIBLE => TransitionType::Issue,
+            TRANSITION_TYPE_VALUE_TRANSFER => TransitionType::Transfer,
+            TRANSITION_TYPE_ISSUE_EPOCH => TransitionType::Epoch,
+            TRANSITION_TYPE_ISSUE_BURN => TransitionType::Burn,
+            TRANSITION_TYPE_ISSUE_REPLACE => TransitionType::BurnAndReplace,
+            TRANSITION_TYPE_RENOMINATION => TransitionType::Renomination,
+            TRANSITION_TYPE_RIGHTS_SPLIT => TransitionType::RightsSplit,
+            unknown => return Err(unknown),
+        })
+    }
+}
+
+/// RGB20 schemata which asset contracts can be issued under
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "kebab-case")
+)]
+pub enum Subschema {
+    /// Root RGB20 schema allowing all asset operations
+    #[display("full")]
+    Full,
+
+    /// RGB20 subschema prohibiting burn & replace procedure
+    #[display("no-replace")]
+    NoReplace,
+
+    /// RGB20 subschema allowing only transfers and burn operations
+    #[display("deflationary")]
+    Deflationary,
+
+    /// RGB20 schema extending the root schema with media attachments
+    #[display("media")]
+    Media,
+}
+
+impl Subschema {
+    /// Builds schema object for the subschema
+    pub fn schema(self) -> Schema {
+        match self {
+            Subschema::Full => full_schema(),
+            Subschema::NoReplace => no_replace_schema(),
+            Subschema::Deflationary => deflationary_schema(),
+            Subschema::Media => full_media_schema(),
+        }
+    }
+
+    /// Builds schema object for the root schema of the subschema. Returns
+    /// `None` for the schemata which are not derived from other schema.
+    pub fn root_schema(self) -> Option<Schema> {
+        match self {
+            Subschema::Full | Subschema::Media => None,
+            Subschema::NoReplace | Subschema::Deflationary => Some(Subschema::Full.schema()),
+        }
+    }
+
+    /// Returns id of the root schema of the subschema. Returns `None` for the
+    /// schemata which are not derived from other schema.
+    pub fn root_schema_id(self) -> Option<SchemaId> {
+        match self {
+            Subschema::Full | Subschema::Media => None,
+            Subschema::NoReplace | Subschema::Deflationary => Some(Subschema::Full.schema_id()),
+        }
+    }
+
+    /// Returns id of the subschema
+    pub fn schema_id(self) -> SchemaId {
+        SCHEMATA
+            .iter()
+            .find(|(subschema, _, _)| *subschema == self)
+            .map(|(_, id, _)| *id)
+            .expect("registry contains all subschemata")
+    }
+
+    /// Detects RGB20 subschema from its id. Returns `None` if the id does not
+    /// belong to any of RGB20 schemata.
+    pub fn from_schema_id(schema_id: SchemaId) -> Option<Subschema> {
+        SCHEMATA
+            .iter()
+            .find(|(_, id, _)| *id == schema_id)
+            .map(|(subschema, _, _)| *subschema)
+    }
+}
+
+/// All RGB20 schemata together with their ids, computed once upon the first
+/// use
+static SCHEMATA: Lazy<[(Subschema, SchemaId, Schema); 4]> = Lazy::new(|| {
+    [Subschema::Full, Subschema::NoReplace, Subschema::Deflationary, Subschema::Media].map(
+        |subschema| {
+            let schema = subschema.schema();
+            (subschema, schema.schema_id(), schema)
+        },
+    )
+});
+
+/// Registry of RGB20 schemata, allowing their lookup by id without
+/// re-constructing schema objects
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Rgb20Schemata;
+
+impl Rgb20Schemata {
+    /// Returns RGB20 schema with the given id, or `None` if the id does not
+    /// belong to any of RGB20 schemata
+    pub fn by_id(schema_id: SchemaId) -> Option<Schema> {
+        SCHEMATA
+            .iter()
+            .find(|(_, id, _)| *id == schema_id)
+            .map(|(_, _, schema)| schema.clone())
+    }
+
+    /// Lists ids of all RGB20 schemata
+    pub fn ids() -> impl Iterator<Item = SchemaId> { SCHEMATA.iter().map(|(_, id, _)| *id) }
+
+    /// Checks whether the `schema`, for instance received from other
+    /// software, is one of RGB20 schemata or a valid custom subschema of the
+    /// root RGB20 schema ([`Subschema::Full`])
+    pub fn check(schema: &Schema) -> Result<SchemaCompatibility, SubschemaError> {
+        if let Some(subschema) = Subschema::from_schema_id(schema.schema_id()) {
+            return Ok(SchemaCompatibility::Known(subschema));
+        }
+        let root = Subschema::Full.schema();
+        if schema.root_id != root.schema_id() {
+            return Err(SubschemaError::UnknownRoot(schema.root_id));
+        }
+        if !schema
+            .transitions
+            .contains_key(&TransitionType::Transfer.into())
+        {
+            return Err(SubschemaError::TransferRequired);
+        }
+        match schema.schema_verify(&root).failures.into_iter().next() {
+            Some(failure) => Err(SubschemaError::RootMismatch(Box::new(failure))),
+            None => Ok(SchemaCompatibility::Custom),
+        }
+    }
+}
+
+/// Compatibility of a schema with RGB20, detected by [`Rgb20Schemata::check`]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display)]
+pub enum SchemaCompatibility {
+    /// One of the RGB20 schemata supported by this library
+    #[display("known RGB20 schema {0}")]
+    Known(Subschema),
+
+    /// Custom subschema of the root RGB20 schema, like the ones constructed
+    /// with [`SubschemaBuilder`]
+    #[display("custom subschema of the root RGB20 schema")]
+    Custom,
+}
+
+/// Errors happening during construction of custom RGB20 subschema with
+/// [`SubschemaBuilder`]
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum SubschemaError {
+    /// subschema must allow asset transfers
+    TransferRequired,
+
+    /// constructed subschema does not conform to the root RGB20 schema: {0}
+    RootMismatch(Box<Failure>),
+
+    /// schema is neither one of RGB20 schemata nor a subschema of the root
+    /// RGB20 schema, having root schema {0}
+    UnknownRoot(SchemaId),
+}
+
+/// Builder for custom RGB20 subschemata, restricting the set of operations
+/// allowed by the root RGB20 schema ([`Subschema::Full`]).
+///
+/// Owned rights which are not closed by any of the allowed operations (except
+/// rights split) are removed from the subschema, as well as the fields which
+/// are not used by the genesis or allowed operations.
+///
+/// # Example
+///
+/// ```ignore
+/// let schema = SubschemaBuilder::new()
+///     .deny(TransitionType::Renomination)
+///     .deny(TransitionType::BurnAndReplace)
+///     .build()?;
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct SubschemaBuilder {
+    denied: BTreeSet<TransitionType>,
+}
+
+impl SubschemaBuilder {
+    /// Constructs builder allowing all operations of the root RGB20 schema
+    pub fn new() -> SubschemaBuilder { SubschemaBuilder::default() }
+
+    /// Prohibits operations of the given type
+    pub fn deny(mut self, transition_type: TransitionType) -> Self {
+        self.denied.insert(transition_type);
+        self
+    }
+
+    /// Constructs subschema and verifies it against the root RGB20 schema
+    pub fn build(self) -> Result<Schema, SubschemaError> {
+        if self.denied.contains(&TransitionType::Transfer) {
+            return Err(SubschemaError::TransferRequired);
+        }
+
+        let root = full_schema();
+        let mut subschema = root.clone();
+        subschema.root_id = root.schema_id();
+        subschema.transitions.retain(|ty, _| {
+            TransitionType::try_from(*ty)
+                .map(|ty| !self.denied.contains(&ty))
+                .unwrap_or_default()
+        });
+
+        let rights = subschema
+            .transitions
+            .iter()
+            .filter(|(ty, _)| TransitionType::try_from(**ty) != Ok(TransitionType::RightsSplit))
+            .flat_map(|(_, transition)| transition.closes.keys())
+            .copied()
+            .collect::<BTreeSet<_>>();
+        subschema
+            .owned_right_types
+            .retain(|ty, _| rights.contains(ty));
+        subschema
+            .genesis
+            .owned_rights
+            .retain(|ty, _| rights.contains(ty));
+        for transition in subschema.transitions.values_mut() {
+            transition.closes.retain(|ty, _| rights.contains(ty));
+            transition.owned_rights.retain(|ty, _| rights.contains(ty));
+        }
+
+        let fields = subschema
+            .transitions
+            .values()
+            .flat_map(|transition| transition.metadata.keys())
+            .chain(subschema.genesis.metadata.keys())
+            .copied()
+            .collect::<BTreeSet<_>>();
+        subschema.field_types.retain(|ty, _| fields.contains(ty));
+
+        match subschema.schema_verify(&root).failures.into_iter().next() {
+            Some(failure) => Err(SubschemaError::RootMismatch(Box::new(failure))),
+            None => Ok(subschema),
+        }
+    }
+}
+
 fn type_system() -> TypeSystem {
     type_system! {
         "OutPoint" :: {
@@ -237,7 +576,7 @@ fn renomination() -> TransitionSchema {
 }
 
 /// Builds & returns complete RGB20 schema (root schema object)
-pub fn schema() -> Schema {
+fn full_schema() -> Schema {
     use Occurrences::*;
 
     Schema {
@@ -323,11 +662,6 @@ pub fn schema() -> Schema {
             // i.e. > 208 trillions, which is sufficient amount
             FieldType::Ticker => TypeRef::ascii_string(),
             FieldType::Name => TypeRef::ascii_string(),
-            // Contract text may contain URL, text or text representation of
-            // Ricardian contract, up to 64kb. If the contract doesn't fit, a
-            // double SHA256 hash and URL should be used instead, pointing to
-            // the full contract text, where hash must be represented by a
-            // hexadecimal string, optionally followed by `\n` and text URL
             FieldType::Precision => TypeRef::u8(),
             // We need this b/c allocated amounts are hidden behind Pedersen
             // commitments
@@ -361,12 +695,12 @@ pub fn schema() -> Schema {
 
 /// Provides the only defined RGB20 subschema, which prohibits replace procedure
 /// and allows only burn operations
-pub fn subschema() -> Schema {
+fn no_replace_schema() -> Schema {
     use Occurrences::*;
 
     Schema {
         rgb_features: none!(),
-        root_id: SchemaId::from_str(SCHEMA_ID_BECH32)
+        root_id: SchemaId::from_str(ROOT_SCHEMA_ID)
             .expect("Broken root schema ID for RGB20 sub-schema"),
         type_system: type_system(),
         genesis: genesis(),
@@ -452,10 +786,56 @@ pub fn subschema() -> Schema {
     }
 }
 
+/// Provides deflationary RGB20 subschema, which allows asset transfers and
+/// burn operations, but prohibits secondary issuance, burn & replace procedure
+/// and renomination
+fn deflationary_schema() -> Schema {
+    SubschemaBuilder::new()
+        .deny(TransitionType::Issue)
+        .deny(TransitionType::BurnAndReplace)
+        .deny(TransitionType::Renomination)
+        .build()
+        .expect("deflationary RGB20 subschema does not conform to the root schema")
+}
+
+/// Builds & returns RGB20 schema extending the root schema with media
+/// attachments (asset logo, icon and terms document) and Ricardian contract
+/// text. Both are defined once by the genesis and can't be changed afterwards.
+///
+/// This is a separate root schema and not a subschema, since subschemata can't
+/// define fields which are absent in their root schema.
+fn full_media_schema() -> Schema {
+    use Occurrences::*;
+
+    let mut schema = full_schema();
+    for field_type in [FieldType::Logo, FieldType::Icon, FieldType::Terms] {
+        // Strict-encoded `Attachment` structure
+        schema
+            .field_types
+            .insert(field_type.into(), TypeRef::bytes());
+        schema
+            .genesis
+            .metadata
+            .insert(field_type.into(), NoneOrOnce);
+    }
+    // Contract text may contain URL, text or text representation of
+    // Ricardian contract, up to 64kb. If the contract doesn't fit, a
+    // double SHA256 hash and URL should be used instead, pointing to
+    // the full contract text, where hash must be represented by a
+    // hexadecimal string, optionally followed by `\n` and text URL
+    schema
+        .field_types
+        .insert(FieldType::ContractText.into(), TypeRef::unicode_string());
+    schema
+        .genesis
+        .metadata
+        .insert(FieldType::ContractText.into(), NoneOrOnce);
+    schema
+}
+
 #[cfg(test)]
 mod test {
     use lnpbp::bech32::Bech32ZipString;
-    use rgb::schema::SchemaVerify;
     use rgb::Validity;
     use strict_encoding::{StrictDecode, StrictEncode};
 
@@ -463,8 +843,8 @@ mod test {
 
     #[test]
     fn schema_id() {
-        let id = schema().schema_id();
-        assert_eq!(id.to_string(), SCHEMA_ID_BECH32);
+        let id = full_schema().schema_id();
+        assert_eq!(id.to_string(), ROOT_SCHEMA_ID);
         assert_eq!(
             id.to_string(),
             "rgbsh18kp34t5nn5zu4hz6g7lqjdjskw8aaf84ecdntrtrdvzs7gn3rnzskscfq8"
@@ -473,8 +853,7 @@ mod test {
 
     #[test]
     fn subschema_id() {
-        let id = subschema().schema_id();
-        assert_eq!(id.to_string(), SUBSCHEMA_ID_BECH32);
+        let id = no_replace_schema().schema_id();
         assert_eq!(
             id.to_string(),
             "rgbsh1636y76cxrnsfqg7zjnl08f0kqt9j09tre2wfxzrrs86f76ssp7cqnn0yyf"
@@ -483,7 +862,7 @@ mod test {
 
     #[test]
     fn schema_strict_encode() {
-        let data = schema()
+        let data = full_schema()
             .strict_serialize()
             .expect("RGB-20 schema serialization failed");
 
@@ -493,8 +872,8 @@ mod test {
         let schema20 =
             Schema::strict_deserialize(data).expect("RGB-20 schema deserialization failed");
 
-        assert_eq!(schema(), schema20);
-        assert_eq!(format!("{:#?}", schema()), format!("{:#?}", schema20));
+        assert_eq!(full_schema(), schema20);
+        assert_eq!(format!("{:#?}", full_schema()), format!("{:#?}", schema20));
         assert_eq!(
             bech32data,
             "z1qxz4zwcwcgcqcl2d2tgnrzqtwq33swqzfvt43zkyepg49ky655klwg7cfefgg4pf38ewe78em8u6qwq5rgwx\
@@ -504,9 +883,109 @@ mod test {
         );
     }
 
+    #[test]
+    fn registry() {
+        for subschema in
+            [Subschema::Full, Subschema::NoReplace, Subschema::Deflationary, Subschema::Media]
+        {
+            let id = subschema.schema().schema_id();
+            assert_eq!(subschema.schema_id(), id);
+            assert_eq!(Subschema::from_schema_id(id), Some(subschema));
+            assert_eq!(Rgb20Schemata::by_id(id), Some(subschema.schema()));
+        }
+        assert_eq!(Rgb20Schemata::ids().count(), 4);
+        assert_eq!(Rgb20Schemata::by_id(SchemaId::default()), None);
+    }
+
+    #[test]
+    fn subschema_builder() {
+        assert_eq!(
+            SubschemaBuilder::new()
+                .deny(TransitionType::Transfer)
+                .build(),
+            Err(SubschemaError::TransferRequired)
+        );
+
+        let subschema = SubschemaBuilder::new()
+            .deny(TransitionType::Renomination)
+            .deny(TransitionType::Epoch)
+            .deny(TransitionType::Burn)
+            .deny(TransitionType::BurnAndReplace)
+            .build()
+            .unwrap();
+        assert_eq!(subschema.root_id, Subschema::Full.schema_id());
+        assert_eq!(subschema.transitions.len(), 3);
+        assert_eq!(
+            subschema
+                .owned_right_types
+                .keys()
+                .copied()
+                .collect::<BTreeSet<_>>(),
+            bset! { OwnedRightType::Inflation.into(), OwnedRightType::Assets.into() }
+        );
+        assert!(!subschema
+            .field_types
+            .contains_key(&FieldType::BurnedSupply.into()));
+        assert_eq!(
+            subschema.schema_verify(&full_schema()).validity(),
+            Validity::Valid
+        );
+    }
+
     #[test]
     fn subschema_verify() {
-        let status = subschema().schema_verify(&schema());
+        let status = no_replace_schema().schema_verify(&full_schema());
         assert_eq!(status.validity(), Validity::Valid);
+
+        let status = deflationary_schema().schema_verify(&full_schema());
+        assert_eq!(status.validity(), Validity::Valid);
+    }
+
+    #[test]
+    fn schema_compatibility() {
+        assert_eq!(
+            Rgb20Schemata::check(&no_replace_schema()),
+            Ok(SchemaCompatibility::Known(Subschema::NoReplace))
+        );
+        let custom = SubschemaBuilder::new()
+            .deny(TransitionType::Renomination)
+            .build()
+            .unwrap();
+        assert_eq!(
+            Rgb20Schemata::check(&custom),
+            Ok(SchemaCompatibility::Custom)
+        );
+
+        let mut foreign = custom;
+        foreign.root_id = no_replace_schema().schema_id();
+        assert_eq!(
+            Rgb20Schemata::check(&foreign),
+            Err(SubschemaError::UnknownRoot(no_replace_schema().schema_id()))
+        );
+    }
+
+    #[test]
+    fn deflationary_subschema_operations() {
+        let subschema = deflationary_schema();
+        assert_eq!(subschema.root_id, full_schema().schema_id());
+        assert_eq!(
+            subschema
+                .transitions
+                .keys()
+                .copied()
+                .collect::<BTreeSet<_>>(),
+            bset! {
+                TransitionType::Transfer.into(),
+                TransitionType::Epoch.into(),
+                TransitionType::Burn.into(),
+                TransitionType::RightsSplit.into()
+            }
+        );
+        assert!(!subschema
+            .owned_right_types
+            .contains_key(&OwnedRightType::Inflation.into()));
+        assert!(!subschema
+            .owned_right_types
+            .contains_key(&OwnedRightType::Renomination.into()));
     }
 }
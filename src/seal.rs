@@ -0,0 +1,92 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Helpers for receivers of RGB20 assets creating blinded seals.
+//!
+//! Receiver provides payer with a blinded seal (which does not reveal the
+//! transaction output it is defined over), and keeps the reveal data (the
+//! outpoint and blinding secret) in order to be able to accept the transfer
+//! consignment and later spend the received assets.
+
+use std::collections::{btree_map, BTreeMap};
+use std::path::Path;
+
+use bitcoin::OutPoint;
+use commit_verify::CommitConceal;
+use rgb::seal;
+use seals::txout::CloseMethod;
+use strict_encoding::{StrictDecode, StrictEncode};
+
+/// Creates new seal over the `outpoint` with a random blinding secret,
+/// returning the blinded seal together with its reveal data
+pub fn blind(outpoint: OutPoint, method: CloseMethod) -> (seal::Confidential, seal::Revealed) {
+    let revealed = seal::Revealed::new(method, outpoint);
+    (revealed.commit_conceal(), revealed)
+}
+
+/// Persistent storage of the reveal data for the blinded seals created by the
+/// asset receiver, indexed by the blinded seal
+#[derive(Clone, PartialEq, Eq, Debug, Default, StrictEncode, StrictDecode)]
+pub struct SealVault {
+    seals: BTreeMap<seal::Confidential, seal::Revealed>,
+}
+
+impl SealVault {
+    /// Constructs empty vault
+    pub fn new() -> SealVault { SealVault::default() }
+
+    /// Loads vault from a file, returning empty vault if the file does not
+    /// exist
+    pub fn load(path: impl AsRef<Path>) -> Result<SealVault, strict_encoding::Error> {
+        if !path.as_ref().exists() {
+            return Ok(SealVault::new());
+        }
+        SealVault::strict_file_load(path)
+    }
+
+    /// Saves vault to a file, overwriting its previous content
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), strict_encoding::Error> {
+        self.strict_file_save(path).map(|_| ())
+    }
+
+    /// Creates new blinded seal over the `outpoint` and stores its reveal data
+    pub fn blind(&mut self, outpoint: OutPoint, method: CloseMethod) -> seal::Confidential {
+        self.insert(seal::Revealed::new(method, outpoint))
+    }
+
+    /// Stores reveal data for a seal, returning the blinded seal
+    pub fn insert(&mut self, revealed: seal::Revealed) -> seal::Confidential {
+        let concealed = revealed.commit_conceal();
+        self.seals.insert(concealed, revealed);
+        concealed
+    }
+
+    /// Returns reveal data for the blinded seal, if known
+    pub fn reveal(&self, concealed: seal::Confidential) -> Option<seal::Revealed> {
+        self.seals.get(&concealed).copied()
+    }
+
+    /// Removes reveal data for the blinded seal, returning it if it was known
+    pub fn remove(&mut self, concealed: seal::Confidential) -> Option<seal::Revealed> {
+        self.seals.remove(&concealed)
+    }
+
+    /// Iterates over all known blinded seals and their reveal data
+    pub fn iter(&self) -> btree_map::Iter<'_, seal::Confidential, seal::Revealed> {
+        self.seals.iter()
+    }
+
+    /// Returns number of the known seals
+    pub fn len(&self) -> usize { self.seals.len() }
+
+    /// Detects whether the vault does not contain any seals
+    pub fn is_empty(&self) -> bool { self.seals.is_empty() }
+}
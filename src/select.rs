@@ -0,0 +1,193 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use bitcoin::OutPoint;
+use rgb::AtomicValue;
+
+/// Maximal number of search steps performed by the branch-and-bound coin
+/// selection before it falls back to the largest-first strategy
+const BNB_MAX_TRIES: usize = 100_000;
+
+/// Strategy for selecting known asset allocations to be spent by a transfer.
+///
+/// Selection operates on bitcoin transaction outputs rather than individual
+/// allocations, since spending an output closes all the seals defined over it.
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
+pub enum CoinSelection {
+    /// Spend outputs with the largest allocated amount first, minimizing the
+    /// number of transfer inputs
+    #[display("largest-first")]
+    LargestFirst,
+
+    /// Spend outputs with the smallest allocated amount first, consolidating
+    /// dust allocations
+    #[display("smallest-first")]
+    SmallestFirst,
+
+    /// Spend only a set of outputs which sums exactly to the target amount,
+    /// such that the transfer has no change
+    #[display("exact-match")]
+    ExactMatch,
+
+    /// Search for a set of outputs with the smallest change, falling back to
+    /// the largest-first strategy if the search is taking too long
+    #[display("branch-and-bound")]
+    BranchAndBound,
+}
+
+impl Default for CoinSelection {
+    fn default() -> Self { CoinSelection::LargestFirst }
+}
+
+/// unknown coin selection strategy `{0}`; expected one of `largest-first`,
+/// `smallest-first`, `exact-match` or `branch-and-bound`
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub struct UnknownCoinSelection(pub String);
+
+impl FromStr for CoinSelection {
+    type Err = UnknownCoinSelection;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "largest-first" => CoinSelection::LargestFirst,
+            "smallest-first" => CoinSelection::SmallestFirst,
+            "exact-match" => CoinSelection::ExactMatch,
+            "branch-and-bound" => CoinSelection::BranchAndBound,
+            wrong => return Err(UnknownCoinSelection(wrong.to_owned())),
+        })
+    }
+}
+
+impl CoinSelection {
+    /// Selects outputs from the `candidates` map (listing total allocated
+    /// amount per output) covering `target` amount. Returns `None` if no
+    /// selection satisfying the strategy exists.
+    pub fn select(
+        self,
+        candidates: &BTreeMap<OutPoint, AtomicValue>,
+        target: AtomicValue,
+    ) -> Option<BTreeSet<OutPoint>> {
+        let mut coins = candidates
+            .iter()
+            .filter(|(_, value)| **value > 0)
+            .map(|(outpoint, value)| (*outpoint, *value))
+            .collect::<Vec<_>>();
+        match self {
+            CoinSelection::LargestFirst => {
+                coins.sort_by(|(_, a), (_, b)| b.cmp(a));
+                accumulate(coins, target)
+            }
+            CoinSelection::SmallestFirst => {
+                coins.sort_by_key(|(_, value)| *value);
+                accumulate(coins, target)
+            }
+            CoinSelection::ExactMatch => {
+                coins.sort_by(|(_, a), (_, b)| b.cmp(a));
+                BranchAndBound::new(&coins, target, true).run()
+            }
+            CoinSelection::BranchAndBound => {
+                coins.sort_by(|(_, a), (_, b)| b.cmp(a));
+                BranchAndBound::new(&coins, target, false)
+                    .run()
+                    .or_else(|| accumulate(coins, target))
+            }
+        }
+    }
+}
+
+/// Picks coins in the given order until their sum reaches `target`
+fn accumulate(
+    coins: impl IntoIterator<Item = (OutPoint, AtomicValue)>,
+    target: AtomicValue,
+) -> Option<BTreeSet<OutPoint>> {
+    let mut sum = 0u64;
+    let mut selected = bset![];
+    for (outpoint, value) in coins {
+        if sum >= target {
+            break;
+        }
+        sum = sum.saturating_add(value);
+        selected.insert(outpoint);
+    }
+    if sum < target {
+        return None;
+    }
+    Some(selected)
+}
+
+/// Depth-first search over coins sorted in descending order
+struct BranchAndBound<'coins> {
+    coins: &'coins [(OutPoint, AtomicValue)],
+    target: AtomicValue,
+    exact: bool,
+    tries: usize,
+    selected: Vec<usize>,
+    best: Option<(AtomicValue, Vec<usize>)>,
+}
+
+impl<'coins> BranchAndBound<'coins> {
+    fn new(coins: &'coins [(OutPoint, AtomicValue)], target: AtomicValue, exact: bool) -> Self {
+        BranchAndBound {
+            coins,
+            target,
+            exact,
+            tries: BNB_MAX_TRIES,
+            selected: vec![],
+            best: None,
+        }
+    }
+
+    fn run(mut self) -> Option<BTreeSet<OutPoint>> {
+        let remaining = self
+            .coins
+            .iter()
+            .fold(0u64, |sum, (_, value)| sum.saturating_add(*value));
+        self.search(0, 0, remaining);
+        self.best.map(|(_, selected)| {
+            selected
+                .into_iter()
+                .map(|index| self.coins[index].0)
+                .collect()
+        })
+    }
+
+    fn search(&mut self, index: usize, sum: AtomicValue, remaining: AtomicValue) {
+        if self.tries == 0 || matches!(self.best, Some((0, _))) {
+            return;
+        }
+        self.tries -= 1;
+
+        if sum >= self.target {
+            let excess = sum - self.target;
+            let improves = match self.best {
+                Some((best, _)) => excess < best,
+                None => true,
+            };
+            if improves && (!self.exact || excess == 0) {
+                self.best = Some((excess, self.selected.clone()));
+            }
+            return;
+        }
+        if index >= self.coins.len() || sum.saturating_add(remaining) < self.target {
+            return;
+        }
+
+        let value = self.coins[index].1;
+        self.selected.push(index);
+        self.search(index + 1, sum.saturating_add(value), remaining - value);
+        self.selected.pop();
+        self.search(index + 1, sum, remaining - value);
+    }
+}
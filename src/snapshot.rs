@@ -0,0 +1,128 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::io;
+
+use strict_encoding::{StrictDecode, StrictEncode};
+
+use crate::Asset;
+
+/// Magic bytes starting each asset snapshot
+const SNAPSHOT_MAGIC: [u8; 4] = *b"RGBs";
+
+/// Version of the asset snapshot format produced by [`Asset::to_snapshot`].
+///
+/// Version 2 adds allocation locks; snapshots of version 1 are still read.
+pub const SNAPSHOT_VERSION: u8 = 2;
+
+/// Errors happening during asset snapshot decoding
+#[derive(Debug, Display, From, Error)]
+#[display(doc_comments)]
+pub enum SnapshotError {
+    /// data are not an RGB20 asset snapshot
+    NotSnapshot,
+
+    /// asset snapshot has format version {0}, which is not supported by this
+    /// version of the library; the snapshot must be re-created from the
+    /// asset consignments or read with a newer library version
+    UnsupportedVersion(u8),
+
+    /// asset snapshot data are corrupted: {0}
+    #[from]
+    Encoding(strict_encoding::Error),
+}
+
+impl Asset {
+    /// Serializes asset into a versioned binary snapshot, which can be
+    /// persisted and loaded with [`Asset::from_snapshot`] without
+    /// re-processing asset consignments.
+    ///
+    /// The snapshot consists of 4 magic bytes, format version byte and the
+    /// strict-encoded asset data, including computed contract state.
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        let mut data = SNAPSHOT_MAGIC.to_vec();
+        data.push(SNAPSHOT_VERSION);
+        self.strict_encode(&mut data)
+            .expect("in-memory encoding does not fail");
+        data
+    }
+
+    /// Loads asset from a binary snapshot created by [`Asset::to_snapshot`].
+    ///
+    /// Snapshots produced by future library versions are reported with
+    /// [`SnapshotError::UnsupportedVersion`], allowing wallets to fall back to
+    /// re-processing of the asset consignments.
+    pub fn from_snapshot(data: impl AsRef<[u8]>) -> Result<Asset, SnapshotError> {
+        let data = data.as_ref();
+        if data.len() < SNAPSHOT_MAGIC.len() + 1 || data[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC {
+            return Err(SnapshotError::NotSnapshot);
+        }
+        let payload = &data[SNAPSHOT_MAGIC.len() + 1..];
+        match data[SNAPSHOT_MAGIC.len()] {
+            1 => {
+                let mut cursor = io::Cursor::new(payload);
+                let asset = Asset::strict_decode_legacy(&mut cursor)?;
+                if cursor.position() as usize != payload.len() {
+                    return Err(strict_encoding::Error::DataNotEntirelyConsumed.into());
+                }
+                Ok(asset)
+            }
+            SNAPSHOT_VERSION => Ok(Asset::strict_deserialize(payload)?),
+            version => Err(SnapshotError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use lnpbp::chain::Chain;
+
+    use super::*;
+    use crate::{vectors, AssetName, Rgb20Builder, Ticker};
+
+    #[test]
+    fn snapshot() {
+        let outpoint = vectors::outpoint(1);
+        let contract = Rgb20Builder::new(Chain::Signet)
+            .ticker(Ticker::from_str("TICK").unwrap())
+            .name(AssetName::from_str("Test asset").unwrap())
+            .allocate(outpoint, 100)
+            .build()
+            .unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+
+        let mut snapshot = asset.to_snapshot();
+        assert_eq!(&snapshot[..5], b"RGBs\x02");
+        assert_eq!(Asset::from_snapshot(&snapshot).unwrap(), asset);
+
+        // Version 1 snapshots had no allocation locks, encoded as an empty
+        // map in version 2
+        let mut legacy = snapshot[..snapshot.len() - 2].to_vec();
+        legacy[4] = 1;
+        assert_eq!(Asset::from_snapshot(&legacy).unwrap(), asset);
+
+        assert!(matches!(
+            Asset::from_snapshot(&snapshot[..snapshot.len() - 1]),
+            Err(SnapshotError::Encoding(_))
+        ));
+        assert!(matches!(
+            Asset::from_snapshot(&snapshot[1..]),
+            Err(SnapshotError::NotSnapshot)
+        ));
+        snapshot[4] = 3;
+        assert!(matches!(
+            Asset::from_snapshot(&snapshot),
+            Err(SnapshotError::UnsupportedVersion(3))
+        ));
+    }
+}
@@ -0,0 +1,192 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeMap;
+
+use bitcoin::{OutPoint, Txid};
+use rgb::{AtomicValue, Node, NodeId, NodeOutpoint, TypedAssignments};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+
+use crate::schema::OwnedRightType;
+use crate::Asset;
+
+/// Asset allocation which seal is closed by a known state transition
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct SpentAllocation {
+    /// Id of the genesis or state transition defining the allocation
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub node_id: NodeId,
+
+    /// Index of the allocation within the asset assignments of the node
+    pub index: u16,
+
+    /// Transaction output which held the allocation
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub outpoint: OutPoint,
+
+    /// Allocated value of the asset
+    pub value: AtomicValue,
+
+    /// Id of the state transition spending the allocation
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub spent_by: NodeId,
+
+    /// Witness transaction of the spending state transition, closing the
+    /// allocation seal
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub witness_txid: Txid,
+}
+
+impl Asset {
+    /// Lists asset allocations spent by the known state transitions.
+    /// Allocations with concealed seal or value are not reported.
+    pub fn spent_allocations(&self) -> Vec<SpentAllocation> {
+        self.transitions()
+            .flat_map(|(witness_txid, transition)| {
+                let spent_by = transition.node_id();
+                transition
+                    .parent_outputs_by_type(OwnedRightType::Assets.into())
+                    .into_iter()
+                    .filter_map(move |output| {
+                        let (node, _) = self.node_by_id(output.node_id)?;
+                        let value = match node.owned_rights_by_type(output.ty)? {
+                            TypedAssignments::Value(assignments) => {
+                                assignments
+                                    .get(output.no as usize)?
+                                    .as_revealed_state()?
+                                    .value
+                            }
+                            _ => return None,
+                        };
+                        Some(SpentAllocation {
+                            node_id: output.node_id,
+                            index: output.no,
+                            outpoint: self.resolve_seal(output)?,
+                            value,
+                            spent_by,
+                            witness_txid,
+                        })
+                    })
+            })
+            .collect()
+    }
+
+    /// Maps node outputs closed by the known state transitions to the ids of
+    /// the closing transitions
+    pub(crate) fn spending_transitions(&self) -> BTreeMap<NodeOutpoint, NodeId> {
+        self.transitions()
+            .flat_map(|(_, transition)| {
+                let node_id = transition.node_id();
+                transition
+                    .parent_outputs()
+                    .into_iter()
+                    .map(move |output| (output, node_id))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::Hash;
+    use rgb::{Consignment, SealEndpoint, StateTransfer, Transition, TransitionBundle};
+    use seals::txout::CloseMethod;
+
+    use super::*;
+    use crate::fixtures::{anchor, builder, outpoint};
+    use crate::Error;
+
+    fn bundle(transition: &Transition) -> TransitionBundle {
+        TransitionBundle::try_from(bmap! { transition.clone() => bset! { 0 } }).unwrap()
+    }
+
+    #[test]
+    fn double_spend() {
+        let contract = builder().build().unwrap();
+        let genesis_id = contract.genesis().node_id();
+        let asset = Asset::try_from(&contract).unwrap();
+        assert!(asset.spent_allocations().is_empty());
+
+        let spend = |blinding| {
+            asset
+                .transfer(
+                    bset! { outpoint(1) },
+                    bmap! { SealEndpoint::WitnessVout {
+                        method: CloseMethod::TapretFirst,
+                        vout: 0,
+                        blinding,
+                    } => 100 },
+                    empty!(),
+                )
+                .unwrap()
+        };
+        let first = spend(1);
+        let second = spend(2);
+        let first_txid = Txid::hash(b"first");
+        let second_txid = Txid::hash(b"second");
+        let transfer = |anchored_bundles: Vec<(Txid, &Transition)>| {
+            StateTransfer::with(
+                contract.schema().clone(),
+                None,
+                contract.genesis().clone(),
+                empty!(),
+                anchored_bundles
+                    .into_iter()
+                    .map(|(txid, transition)| (anchor(txid), bundle(transition)))
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .unwrap(),
+                empty!(),
+            )
+        };
+
+        let mut asset = Asset::try_from(&transfer(vec![(first_txid, &first)])).unwrap();
+        assert_eq!(asset.spent_allocations(), vec![SpentAllocation {
+            node_id: genesis_id,
+            index: 0,
+            outpoint: outpoint(1),
+            value: 100,
+            spent_by: first.node_id(),
+            witness_txid: first_txid,
+        }]);
+
+        let double_spend = Error::DoubleSpend {
+            outpoint: NodeOutpoint {
+                node_id: genesis_id,
+                ty: OwnedRightType::Assets.into(),
+                no: 0,
+            },
+            known: first.node_id(),
+            conflicting: second.node_id(),
+        };
+        assert_eq!(
+            asset.update_with(&transfer(vec![(second_txid, &second)])),
+            Err(double_spend.clone())
+        );
+        assert!(asset
+            .update_with(&transfer(vec![(first_txid, &first)]))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            Asset::try_from(&transfer(vec![
+                (first_txid, &first),
+                (second_txid, &second)
+            ])),
+            Err(double_spend)
+        );
+    }
+}
@@ -0,0 +1,255 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Persistence of RGB20 contracts, consignments and cached asset data.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::{fs, io};
+
+use bitcoin::OutPoint;
+use rgb::{Contract, ContractId, StateTransfer};
+use strict_encoding::{StrictDecode, StrictEncode};
+
+use crate::{Asset, Error, StateUpdate, Ticker};
+
+/// Storage for RGB20 assets, keeping contracts, accepted state transfers and
+/// the cached [`Asset`] data derived from them
+pub trait AssetStore {
+    /// Error type returned by the storage
+    type Error: std::error::Error + From<Error>;
+
+    /// Lists ids of all known contracts
+    fn contract_ids(&self) -> Result<BTreeSet<ContractId>, Self::Error>;
+
+    /// Retrieves contract with the given id
+    fn contract(&self, contract_id: ContractId) -> Result<Contract, Self::Error>;
+
+    /// Retrieves cached asset data for the contract with the given id
+    fn asset(&self, contract_id: ContractId) -> Result<Asset, Self::Error>;
+
+    /// Retrieves all state transfers accepted for the contract with the given
+    /// id
+    fn consignments(&self, contract_id: ContractId) -> Result<Vec<StateTransfer>, Self::Error>;
+
+    /// Imports new contract, replacing previously known contract with the
+    /// same id and all consignments accepted for it
+    fn import_contract(&mut self, contract: Contract) -> Result<Asset, Self::Error>;
+
+    /// Accepts state transfer for one of the known contracts, updating the
+    /// cached asset data
+    fn accept_transfer(&mut self, transfer: StateTransfer) -> Result<StateUpdate, Self::Error>;
+
+    /// Lists assets which current ticker matches the given one
+    fn assets_by_ticker(&self, ticker: &Ticker) -> Result<Vec<Asset>, Self::Error> {
+        let mut assets = vec![];
+        for contract_id in self.contract_ids()? {
+            let asset = self.asset(contract_id)?;
+            if asset.renominations()?.current.ticker == ticker.as_str() {
+                assets.push(asset);
+            }
+        }
+        Ok(assets)
+    }
+
+    /// Lists assets having known allocations at the given outpoint
+    fn assets_at(&self, outpoint: OutPoint) -> Result<Vec<Asset>, Self::Error> {
+        let mut assets = vec![];
+        for contract_id in self.contract_ids()? {
+            let asset = self.asset(contract_id)?;
+            if asset.contains_outpoint(outpoint) {
+                assets.push(asset);
+            }
+        }
+        Ok(assets)
+    }
+}
+
+/// Errors happening during [`FileStore`] operations
+#[derive(Debug, Display, From, Error)]
+#[display(doc_comments)]
+pub enum StoreError {
+    /// I/O error: {0}
+    #[from]
+    Io(io::Error),
+
+    /// stored data are corrupted: {0}
+    #[from]
+    Encoding(strict_encoding::Error),
+
+    /// {0}
+    #[from]
+    Asset(Error),
+
+    /// contract {0} is not known to the store
+    UnknownContract(ContractId),
+}
+
+/// File-based [`AssetStore`] implementation.
+///
+/// Each contract is kept in a separate directory named after the contract id,
+/// containing strict-encoded contract, cached asset data and accepted state
+/// transfers. All files are written to a temporary location first and then
+/// renamed, such that a failed update never leaves partially written data.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct FileStore {
+    root: PathBuf,
+}
+
+impl FileStore {
+    const CONTRACT_FILE: &'static str = "contract.rgbc";
+    const ASSET_FILE: &'static str = "asset.rgba";
+    const TRANSFER_DIR: &'static str = "transfers";
+    const TRANSFER_EXT: &'static str = "rgbt";
+
+    /// Opens store at the given directory, creating the directory if it does
+    /// not exist
+    pub fn open(root: impl AsRef<Path>) -> Result<FileStore, StoreError> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root)?;
+        Ok(FileStore { root })
+    }
+
+    fn contract_dir(&self, contract_id: ContractId) -> PathBuf {
+        self.root.join(contract_id.to_string())
+    }
+
+    fn known_dir(&self, contract_id: ContractId) -> Result<PathBuf, StoreError> {
+        let dir = self.contract_dir(contract_id);
+        if !dir.join(Self::CONTRACT_FILE).is_file() {
+            return Err(StoreError::UnknownContract(contract_id));
+        }
+        Ok(dir)
+    }
+
+    fn load<T: StrictDecode>(path: impl AsRef<Path>) -> Result<T, StoreError> {
+        Ok(T::strict_deserialize(fs::read(path)?)?)
+    }
+
+    fn save(path: impl AsRef<Path>, data: &impl StrictEncode) -> Result<(), StoreError> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, data.strict_serialize()?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+impl AssetStore for FileStore {
+    type Error = StoreError;
+
+    fn contract_ids(&self) -> Result<BTreeSet<ContractId>, Self::Error> {
+        let mut ids = bset![];
+        for entry in fs::read_dir(&self.root)? {
+            let entry = entry?;
+            if !entry.path().join(Self::CONTRACT_FILE).is_file() {
+                continue;
+            }
+            if let Some(contract_id) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| ContractId::from_str(name).ok())
+            {
+                ids.insert(contract_id);
+            }
+        }
+        Ok(ids)
+    }
+
+    fn contract(&self, contract_id: ContractId) -> Result<Contract, Self::Error> {
+        FileStore::load(self.known_dir(contract_id)?.join(Self::CONTRACT_FILE))
+    }
+
+    fn asset(&self, contract_id: ContractId) -> Result<Asset, Self::Error> {
+        FileStore::load(self.known_dir(contract_id)?.join(Self::ASSET_FILE))
+    }
+
+    fn consignments(&self, contract_id: ContractId) -> Result<Vec<StateTransfer>, Self::Error> {
+        let dir = self.known_dir(contract_id)?.join(Self::TRANSFER_DIR);
+        let mut paths = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.retain(|path| {
+            path.extension().and_then(|ext| ext.to_str()) == Some(Self::TRANSFER_EXT)
+        });
+        paths.sort();
+        paths.into_iter().map(FileStore::load).collect()
+    }
+
+    fn import_contract(&mut self, contract: Contract) -> Result<Asset, Self::Error> {
+        let asset = Asset::try_from(&contract)?;
+        let dir = self.contract_dir(contract.contract_id());
+        let transfers = dir.join(Self::TRANSFER_DIR);
+        if transfers.exists() {
+            fs::remove_dir_all(&transfers)?;
+        }
+        fs::create_dir_all(&transfers)?;
+        FileStore::save(dir.join(Self::ASSET_FILE), &asset)?;
+        FileStore::save(dir.join(Self::CONTRACT_FILE), &contract)?;
+        Ok(asset)
+    }
+
+    fn accept_transfer(&mut self, transfer: StateTransfer) -> Result<StateUpdate, Self::Error> {
+        let contract_id = transfer.contract_id();
+        let dir = self.known_dir(contract_id)?;
+        let mut asset: Asset = FileStore::load(dir.join(Self::ASSET_FILE))?;
+        let update = asset.update_with(&transfer)?;
+        let file = dir
+            .join(Self::TRANSFER_DIR)
+            .join(transfer.id().to_string())
+            .with_extension(Self::TRANSFER_EXT);
+        FileStore::save(file, &transfer)?;
+        FileStore::save(dir.join(Self::ASSET_FILE), &asset)?;
+        Ok(update)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use lnpbp::chain::Chain;
+
+    use super::*;
+    use crate::{vectors, AssetName, Rgb20Builder};
+
+    #[test]
+    fn file_store() {
+        let root = std::env::temp_dir().join(format!("rgb20-store-{}", std::process::id()));
+        let mut store = FileStore::open(&root).unwrap();
+        let outpoint = vectors::outpoint(1);
+        let contract = Rgb20Builder::new(Chain::Signet)
+            .ticker(Ticker::from_str("TICK").unwrap())
+            .name(AssetName::from_str("Test asset").unwrap())
+            .allocate(outpoint, 100)
+            .build()
+            .unwrap();
+        let contract_id = contract.contract_id();
+
+        let asset = store.import_contract(contract.clone()).unwrap();
+        assert_eq!(store.contract_ids().unwrap(), bset! { contract_id });
+        assert_eq!(store.contract(contract_id).unwrap(), contract);
+        assert_eq!(store.asset(contract_id).unwrap(), asset);
+        assert!(store.consignments(contract_id).unwrap().is_empty());
+        assert_eq!(
+            store
+                .assets_by_ticker(&Ticker::from_str("TICK").unwrap())
+                .unwrap(),
+            vec![asset.clone()]
+        );
+        assert!(store
+            .assets_by_ticker(&Ticker::from_str("OTHER").unwrap())
+            .unwrap()
+            .is_empty());
+        assert_eq!(store.assets_at(outpoint).unwrap(), vec![asset]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,226 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeSet;
+use std::io;
+
+use bitcoin::OutPoint;
+use commit_verify::lnpbp4;
+use rgb::{
+    Anchor, AttachmentId, BundleId, ContractId, Extension, Genesis, Node, Schema, SealEndpoint,
+    TransitionBundle, RGB_INMEM_CONSIGNMENT_VERSION,
+};
+use strict_encoding::{LargeVec, StrictDecode};
+
+use crate::asset::Validator;
+use crate::schema::{OwnedRightType, Subschema};
+use crate::{Asset, Error};
+
+/// Errors happening during streamed consignment parsing
+#[derive(Debug, Display, From, Error)]
+#[display(doc_comments)]
+pub enum StreamError {
+    /// consignment data are corrupted: {0}
+    #[from]
+    Encoding(strict_encoding::Error),
+
+    /// {0}
+    #[from]
+    Asset(Error),
+}
+
+/// Reader constructing [`Asset`] from strict-encoded consignment (contract or
+/// state transfer) bundle by bundle, without loading the whole consignment
+/// into memory.
+///
+/// The reader performs the same RGB20-specific validation as
+/// [`Asset::try_from`]; anchors are read but not kept, so the consignment
+/// must be validated against the blockchain separately.
+pub struct ConsignmentReader<R: io::Read> {
+    reader: R,
+    asset: Asset,
+    validator: Validator,
+    endpoints: Vec<(BundleId, SealEndpoint)>,
+    bundles_left: u32,
+}
+
+impl<R: io::Read> ConsignmentReader<R> {
+    /// Starts reading consignment, consuming its schemata, genesis and
+    /// endpoints
+    pub fn new(mut reader: R) -> Result<Self, StreamError> {
+        if u8::strict_decode(&mut reader)? != RGB_INMEM_CONSIGNMENT_VERSION {
+            return Err(strict_encoding::Error::UnsupportedDataStructure(
+                "State transfer versions above 0 are not supported",
+            )
+            .into());
+        }
+        let schema = Schema::strict_decode(&mut reader)?;
+        let root_schema = Option::<Schema>::strict_decode(&mut reader)?;
+        let genesis = Genesis::strict_decode(&mut reader)?;
+        let endpoints = StrictDecode::strict_decode(&mut reader)?;
+        let bundles_left = u32::strict_decode(&mut reader)?;
+
+        let validator = Validator::with(&schema, root_schema.as_ref(), &genesis)?;
+        let asset = Asset::with_genesis(
+            &schema,
+            root_schema.as_ref(),
+            genesis,
+            validator.subschema(),
+        );
+
+        Ok(ConsignmentReader {
+            reader,
+            asset,
+            validator,
+            endpoints,
+            bundles_left,
+        })
+    }
+
+    /// Returns id of the contract
+    pub fn contract_id(&self) -> ContractId { self.asset.contract_id() }
+
+    /// Returns RGB20 subschema used by the consignment
+    pub fn subschema(&self) -> Subschema { self.asset.subschema() }
+
+    /// Returns consignment endpoints
+    pub fn endpoints(&self) -> &[(BundleId, SealEndpoint)] { &self.endpoints }
+
+    /// Returns number of anchored bundles which are not read yet
+    pub fn bundles_left(&self) -> u32 { self.bundles_left }
+
+    /// Reads next anchored bundle, adding its state transitions to the asset.
+    ///
+    /// Returns outpoints receiving asset allocations in the bundle, or `None`
+    /// if all bundles were already read.
+    pub fn read_bundle(&mut self) -> Result<Option<BTreeSet<OutPoint>>, StreamError> {
+        if self.bundles_left == 0 {
+            return Ok(None);
+        }
+        let anchor = Anchor::<lnpbp4::MerkleProof>::strict_decode(&mut self.reader)?;
+        let bundle = TransitionBundle::strict_decode(&mut self.reader)?;
+        self.bundles_left -= 1;
+
+        let mut outpoints = bset! {};
+        for transition in bundle.known_transitions() {
+            self.validator.validate_node(transition)?;
+            if let Some(assignments) =
+                transition.owned_rights_by_type(OwnedRightType::Assets.into())
+            {
+                outpoints.extend(
+                    assignments
+                        .revealed_seal_outputs()
+                        .into_iter()
+                        .map(|(seal, _)| {
+                            OutPoint::new(seal.txid.unwrap_or(anchor.txid), seal.vout)
+                        }),
+                );
+            }
+        }
+        for (transition, _) in bundle.into_revealed_iter() {
+            self.asset.add_transition(anchor.txid, transition);
+        }
+        Ok(Some(outpoints))
+    }
+
+    /// Reads anchored bundles until each of the `outpoints` receives asset
+    /// allocation, skipping the rest of the consignment. If the outpoints
+    /// do not get allocations, reads the whole consignment.
+    ///
+    /// Since the consignment is not read in full, state transitions spending
+    /// the allocations which are located after the early exit point remain
+    /// unknown. Validation requiring all consignment nodes is performed on
+    /// the nodes read so far.
+    pub fn read_until(
+        mut self,
+        outpoints: impl IntoIterator<Item = OutPoint>,
+    ) -> Result<Asset, StreamError> {
+        let mut pending = outpoints
+            .into_iter()
+            .filter(|outpoint| !self.asset.contains_outpoint(*outpoint))
+            .collect::<BTreeSet<_>>();
+        while !pending.is_empty() {
+            match self.read_bundle()? {
+                None => return self.finish(),
+                Some(allocated) => pending.retain(|outpoint| !allocated.contains(outpoint)),
+            }
+        }
+        self.complete()
+    }
+
+    /// Reads the rest of the consignment, returning constructed asset
+    pub fn finish(mut self) -> Result<Asset, StreamError> {
+        while self.read_bundle()?.is_some() {}
+
+        let count = u32::strict_decode(&mut self.reader)?;
+        for _ in 0..count {
+            let extension = Extension::strict_decode(&mut self.reader)?;
+            self.validator.validate_node(&extension)?;
+            self.asset.add_extension(extension);
+        }
+
+        // Data containers are not used by RGB20, but we read them anyway to
+        // detect truncated data
+        let count = u16::strict_decode(&mut self.reader)?;
+        for _ in 0..count {
+            AttachmentId::strict_decode(&mut self.reader)?;
+            LargeVec::<u8>::strict_decode(&mut self.reader)?;
+        }
+
+        self.complete()
+    }
+
+    fn complete(self) -> Result<Asset, StreamError> {
+        let ConsignmentReader {
+            mut asset,
+            validator,
+            ..
+        } = self;
+        validator.finish(|node_id| asset.node_by_id(node_id).is_some())?;
+        asset.reindex();
+        Ok(asset)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use strict_encoding::StrictEncode;
+
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+
+    #[test]
+    fn stream_contract() {
+        let contract = builder()
+            .inflation_right(outpoint(2), 1000)
+            .build()
+            .unwrap();
+        let data = contract.strict_serialize().unwrap();
+
+        let reader = ConsignmentReader::new(&data[..]).unwrap();
+        assert_eq!(reader.contract_id(), contract.contract_id());
+        assert_eq!(reader.subschema(), Subschema::Full);
+        assert_eq!(reader.bundles_left(), 0);
+        assert_eq!(
+            reader.finish().unwrap(),
+            Asset::try_from(&contract).unwrap()
+        );
+
+        let reader = ConsignmentReader::new(&data[..]).unwrap();
+        let asset = reader.read_until([outpoint(1)]).unwrap();
+        assert_eq!(asset.allocations_at(outpoint(1)).len(), 1);
+
+        assert!(matches!(
+            ConsignmentReader::new(&data[..data.len() - 1]).and_then(ConsignmentReader::finish),
+            Err(StreamError::Encoding(_))
+        ));
+    }
+}
@@ -0,0 +1,253 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bitcoin::{OutPoint, Txid};
+use chrono::{DateTime, Utc};
+use rgb::{
+    BundleId, Consignment, ConsignmentType, ContractId, InmemConsignment, Node, NodeId, SchemaId,
+    SealEndpoint,
+};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+
+use crate::schema::{Subschema, TransitionType};
+use crate::{Asset, AssetAmount, Error, Nomination, SupplyMeasures};
+
+/// Human-readable summary of an RGB20 contract or state transfer consignment
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct ConsignmentSummary {
+    /// Id of the asset contract
+    pub contract_id: ContractId,
+
+    /// Id of the schema used by the consignment
+    pub schema_id: SchemaId,
+
+    /// RGB20 subschema used by the asset contract
+    pub subschema: Subschema,
+
+    /// Time of the asset issuance defined by the genesis
+    pub issued: DateTime<Utc>,
+
+    /// Asset nomination effective after all known renominations
+    pub nomination: Nomination,
+
+    /// Asset supply figures computed from the consignment data
+    pub supply: SupplyMeasures,
+
+    /// Known asset allocations, aggregated per bitcoin transaction output
+    pub allocations: Vec<Allocation>,
+
+    /// Operations contained in the consignment, parents always preceding
+    /// their descendants
+    pub operations: Vec<Operation>,
+
+    /// Final state of the consignment assigned to the transfer beneficiaries.
+    /// Contract consignments have no endpoints.
+    pub endpoints: Vec<Endpoint>,
+}
+
+/// Total amount of the asset assigned to a bitcoin transaction output
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Allocation {
+    /// Transaction output holding the asset
+    pub outpoint: OutPoint,
+
+    /// Sum of all known asset allocations at the output
+    pub amount: AssetAmount,
+}
+
+/// Node of the asset operation graph
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Operation {
+    /// Id of the genesis or state transition
+    pub node_id: NodeId,
+
+    /// Type of the state transition; `None` for the genesis
+    #[cfg_attr(feature = "serde", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub transition_type: Option<TransitionType>,
+
+    /// Witness transaction of the state transition; `None` for the genesis
+    pub witness_txid: Option<Txid>,
+
+    /// Operations which owned rights are closed by this operation
+    pub parents: BTreeSet<NodeId>,
+}
+
+/// Seal endpoint of the state transfer consignment
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Endpoint {
+    /// Id of the transition bundle assigning state to the seal
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub bundle_id: BundleId,
+
+    /// Seal receiving the state
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub seal: SealEndpoint,
+}
+
+impl ConsignmentSummary {
+    /// Summarizes RGB20 contract or state transfer consignment. Errors if the
+    /// consignment does not match RGB20 schema.
+    pub fn with<T>(consignment: &InmemConsignment<T>) -> Result<ConsignmentSummary, Error>
+    where T: ConsignmentType {
+        let asset = Asset::try_from(consignment)?;
+
+        let allocations = asset
+            .outpoints()
+            .map(|outpoint| Allocation {
+                outpoint: *outpoint,
+                amount: asset.amount(
+                    asset
+                        .allocations_at(*outpoint)
+                        .iter()
+                        .fold(0u64, |sum, coin| sum.saturating_add(coin.state.value)),
+                ),
+            })
+            .collect();
+
+        let endpoints = consignment
+            .endpoints()
+            .map(|(bundle_id, seal)| Endpoint {
+                bundle_id: *bundle_id,
+                seal: *seal,
+            })
+            .collect();
+
+        Ok(ConsignmentSummary {
+            contract_id: asset.contract_id(),
+            schema_id: consignment.schema_id(),
+            subschema: asset.subschema(),
+            issued: asset.issued(),
+            nomination: asset.renominations()?.current,
+            supply: asset.supply(),
+            allocations,
+            operations: asset.operations(),
+            endpoints,
+        })
+    }
+}
+
+impl Asset {
+    /// Lists genesis and all known state transitions as an operation graph,
+    /// ordering them such that each operation follows its known parents
+    pub fn operations(&self) -> Vec<Operation> {
+        let mut pending = self
+            .transitions()
+            .map(|(txid, transition)| {
+                let node_id = transition.node_id();
+                (node_id, Operation {
+                    node_id,
+                    transition_type: TransitionType::try_from(transition.transition_type()).ok(),
+                    witness_txid: Some(txid),
+                    parents: transition.parent_owned_rights().keys().copied().collect(),
+                })
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut operations = vec![Operation {
+            node_id: self.genesis().node_id(),
+            transition_type: None,
+            witness_txid: None,
+            parents: empty!(),
+        }];
+        while !pending.is_empty() {
+            let ready = pending
+                .iter()
+                .filter(|(_, operation)| {
+                    operation
+                        .parents
+                        .iter()
+                        .all(|parent| !pending.contains_key(parent))
+                })
+                .map(|(node_id, _)| *node_id)
+                .collect::<Vec<_>>();
+            // Transition graph is acyclic, but we protect against malformed
+            // data anyway
+            if ready.is_empty() {
+                operations.extend(pending.into_values());
+                break;
+            }
+            for node_id in ready {
+                let operation = pending
+                    .remove(&node_id)
+                    .expect("node id is taken from the map");
+                operations.push(operation);
+            }
+        }
+        operations
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use rgb::fungible::allocation::OutpointValue;
+
+    use super::*;
+    use crate::fixtures::{contract, outpoint};
+
+    #[test]
+    fn contract_summary() {
+        let contract = contract(
+            2,
+            [(1, 100), (2, 200), (1, 50)]
+                .into_iter()
+                .map(|(vout, value)| OutpointValue {
+                    value,
+                    outpoint: outpoint(vout),
+                })
+                .collect(),
+            bmap! { outpoint(10) => 1000 },
+        );
+        let summary = ConsignmentSummary::with(&contract).unwrap();
+
+        assert_eq!(summary.contract_id, contract.contract_id());
+        assert_eq!(summary.nomination.ticker, "TICK");
+        assert_eq!(summary.supply.issued, 350);
+        assert_eq!(summary.supply.max, 1350);
+        assert_eq!(summary.allocations, vec![
+            Allocation {
+                outpoint: outpoint(1),
+                amount: AssetAmount::from_str("1.50").unwrap(),
+            },
+            Allocation {
+                outpoint: outpoint(2),
+                amount: AssetAmount::from_str("2.00").unwrap(),
+            },
+        ]);
+        assert_eq!(summary.operations.len(), 1);
+        assert_eq!(summary.operations[0].node_id, contract.genesis().node_id());
+        assert!(summary.endpoints.is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use rgb::{AtomicValue, Node};
+
+use crate::schema::{FieldType, OwnedRightType, TransitionType};
+use crate::Asset;
+
+/// Asset supply figures computed from the known asset history
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct SupplyMeasures {
+    /// Supply issued by the genesis and all known secondary issuances
+    pub issued: AtomicValue,
+
+    /// Supply destroyed by all known burn and burn & replace operations
+    pub burned: AtomicValue,
+
+    /// Supply re-issued by all known burn & replace operations
+    pub replaced: AtomicValue,
+
+    /// Supply which is currently in circulation, i.e. issued and replaced
+    /// supply minus burned supply
+    pub circulating: AtomicValue,
+
+    /// Maximum supply which may ever be issued, i.e. issued supply plus the
+    /// allowance of all known unspent inflation rights
+    pub max: AtomicValue,
+
+    /// Whether the issuance history is complete, i.e. each secondary issuance
+    /// performed with the genesis inflation rights is known or the rights are
+    /// still unspent.
+    ///
+    /// Burn operations which are not known can't be detected, so even for a
+    /// complete issuance history `burned` supply may be underestimated and
+    /// `circulating` supply overestimated.
+    pub is_complete: bool,
+}
+
+impl Asset {
+    /// Returns maximum supply of the asset defined by the genesis, i.e. the
+    /// primary issuance plus the total allowance of the genesis inflation
+    /// rights.
+    ///
+    /// Assets with unlimited inflation assign `u64::MAX` allowance to their
+    /// inflation rights; for them the function returns `u64::MAX`.
+    pub fn max_supply(&self) -> AtomicValue {
+        self.genesis_issued()
+            .saturating_add(self.genesis_allowance())
+    }
+
+    /// Computes asset supply figures from the known asset history
+    pub fn supply(&self) -> SupplyMeasures {
+        let sum = |values: Vec<AtomicValue>| values.into_iter().fold(0u64, u64::saturating_add);
+
+        let genesis_issued = self.genesis_issued();
+        let inflation_issued = self
+            .transitions_by_type(TransitionType::Issue)
+            .map(|(_, transition)| sum(transition.metadata().u64(FieldType::IssuedSupply)))
+            .fold(0u64, u64::saturating_add);
+        let burned = self
+            .transitions_by_type(TransitionType::Burn)
+            .chain(self.transitions_by_type(TransitionType::BurnAndReplace))
+            .map(|(_, transition)| sum(transition.metadata().u64(FieldType::BurnedSupply)))
+            .fold(0u64, u64::saturating_add);
+        let replaced = self
+            .transitions_by_type(TransitionType::BurnAndReplace)
+            .map(|(_, transition)| sum(transition.metadata().u64(FieldType::IssuedSupply)))
+            .fold(0u64, u64::saturating_add);
+
+        let genesis_allowance = self.genesis_allowance();
+        let unspent_allowance = self
+            .known_inflation()
+            .fold(0u64, |acc, right| acc.saturating_add(right.state.value));
+
+        let issued = genesis_issued.saturating_add(inflation_issued);
+        SupplyMeasures {
+            issued,
+            burned,
+            replaced,
+            circulating: issued.saturating_add(replaced).saturating_sub(burned),
+            max: issued.saturating_add(unspent_allowance),
+            is_complete: inflation_issued.saturating_add(unspent_allowance) == genesis_allowance,
+        }
+    }
+
+    /// Supply issued by the genesis
+    fn genesis_issued(&self) -> AtomicValue {
+        self.genesis()
+            .metadata()
+            .u64(FieldType::IssuedSupply)
+            .into_iter()
+            .fold(0u64, u64::saturating_add)
+    }
+
+    /// Total allowance of the inflation rights defined by the genesis
+    fn genesis_allowance(&self) -> AtomicValue {
+        self.genesis()
+            .owned_rights_by_type(OwnedRightType::Inflation.into())
+            .map(|assignments| {
+                assignments
+                    .filter_revealed_state_values()
+                    .into_iter()
+                    .fold(0u64, |acc, value| acc.saturating_add(value.value))
+            })
+            .unwrap_or_default()
+    }
+}
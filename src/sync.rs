@@ -0,0 +1,138 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeSet;
+
+use bitcoin::OutPoint;
+use rgb::{AtomicValue, OwnedValue};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+
+use crate::Asset;
+
+/// Source of information about bitcoin transaction outputs, provided by a
+/// wallet backend (bdk, Electrum, Bitcoin Core RPC etc).
+pub trait UtxoProvider {
+    /// Error type returned by the backend
+    type Error: std::error::Error;
+
+    /// Lists all unspent transaction outputs controlled by the wallet
+    fn list_unspent(&self) -> Result<BTreeSet<OutPoint>, Self::Error>;
+
+    /// Checks whether the transaction output is spent. Returns `None` if the
+    /// backend has no information about the output.
+    fn is_spent(&self, outpoint: OutPoint) -> Result<Option<bool>, Self::Error>;
+}
+
+/// Known asset allocations classified by [`Asset::sync_allocations`]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct AllocationSync {
+    /// Allocations assigned to unspent outputs controlled by the wallet
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub spendable: Vec<OwnedValue>,
+
+    /// Allocations assigned to already spent outputs
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub spent: Vec<OwnedValue>,
+
+    /// Allocations assigned to outputs which are not controlled by the wallet
+    /// or which status is not known to the backend
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub unknown: Vec<OwnedValue>,
+}
+
+impl AllocationSync {
+    /// Returns total amount of the asset which can be spent by the wallet
+    pub fn spendable_value(&self) -> AtomicValue {
+        self.spendable
+            .iter()
+            .fold(0u64, |sum, coin| sum.saturating_add(coin.state.value))
+    }
+
+    /// Lists outpoints holding spendable allocations
+    pub fn spendable_outpoints(&self) -> BTreeSet<OutPoint> {
+        self.spendable.iter().map(|coin| coin.seal).collect()
+    }
+}
+
+impl Asset {
+    /// Classifies all known asset allocations as spendable, spent or unknown
+    /// using information from the wallet backend `provider`
+    pub fn sync_allocations<P>(&self, provider: &P) -> Result<AllocationSync, P::Error>
+    where P: UtxoProvider {
+        let unspent = provider.list_unspent()?;
+        let mut sync = AllocationSync::default();
+        for outpoint in self.outpoints() {
+            let coins = self.allocations_at(*outpoint).iter().cloned();
+            if unspent.contains(outpoint) {
+                sync.spendable.extend(coins);
+            } else if provider.is_spent(*outpoint)? == Some(true) {
+                sync.spent.extend(coins);
+            } else {
+                sync.unknown.extend(coins);
+            }
+        }
+        Ok(sync)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::Infallible;
+
+    use super::*;
+    use crate::fixtures::{builder, outpoint};
+
+    struct Wallet {
+        unspent: BTreeSet<OutPoint>,
+        spent: BTreeSet<OutPoint>,
+    }
+
+    impl UtxoProvider for Wallet {
+        type Error = Infallible;
+
+        fn list_unspent(&self) -> Result<BTreeSet<OutPoint>, Self::Error> {
+            Ok(self.unspent.clone())
+        }
+
+        fn is_spent(&self, outpoint: OutPoint) -> Result<Option<bool>, Self::Error> {
+            Ok(self.spent.contains(&outpoint).then(|| true))
+        }
+    }
+
+    #[test]
+    fn sync_allocations() {
+        let contract = builder()
+            .allocate(outpoint(1), 50)
+            .allocate(outpoint(2), 200)
+            .allocate(outpoint(3), 300)
+            .build()
+            .unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+
+        let wallet = Wallet {
+            unspent: bset! { outpoint(1) },
+            spent: bset! { outpoint(2) },
+        };
+        let sync = asset.sync_allocations(&wallet).unwrap();
+        assert_eq!(sync.spendable_value(), 150);
+        assert_eq!(sync.spendable_outpoints(), bset! { outpoint(1) });
+        assert_eq!(sync.spent.len(), 1);
+        assert_eq!(sync.spent[0].seal, outpoint(2));
+        assert_eq!(sync.unknown.len(), 1);
+        assert_eq!(sync.unknown[0].seal, outpoint(3));
+    }
+}
@@ -0,0 +1,180 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Derivation of taproot outputs hosting tapret commitments to RGB20 state
+//! transition bundles.
+//!
+//! Tapret commitment is a taproot script leaf consisting of 29 `OP_RESERVED`
+//! opcodes, `OP_RETURN` and a 33-byte push of the LNPBP-4 multi-protocol
+//! commitment followed by a nonce byte. The helpers here cover outputs
+//! without other script leaves (key-path-only wallets), where the commitment
+//! leaf is the only leaf of the tap tree and the output key is the internal
+//! key tweaked with the commitment leaf hash.
+
+use amplify::Wrapper;
+use bitcoin::blockdata::opcodes::all::{OP_RESERVED, OP_RETURN};
+use bitcoin::blockdata::script::Builder;
+use bitcoin::hashes::Hash;
+use bitcoin::schnorr::{TapTweak, TweakedPublicKey, UntweakedPublicKey};
+use bitcoin::secp256k1::{Secp256k1, Verification};
+use bitcoin::util::taproot::{LeafVersion, TapBranchHash, TapLeafHash};
+use bitcoin::Script;
+use commit_verify::lnpbp4::{self, ProtocolId};
+use commit_verify::ConsensusCommit;
+use rgb::{ContractId, TransitionBundle};
+
+/// Number of `OP_RESERVED` opcodes prefixing the commitment in the tapret
+/// script leaf
+pub const TAPRET_RESERVED_OPCODES: usize = 29;
+
+/// Minimal depth of the LNPBP-4 tree used for the bundle commitments
+pub const LNPBP4_MIN_DEPTH: u8 = 3;
+
+/// Constructs LNPBP-4 source for committing to the transition `bundle` of the
+/// asset `contract_id`, being the only bundle committed by the witness
+/// transaction.
+///
+/// The LNPBP-4 tree is built from the source with
+/// [`lnpbp4::MerkleTree::try_commit`], which picks random entropy for the tree
+/// placeholder leaves; the tree must be kept by the committing party, since
+/// the same entropy is required to re-derive the commitment.
+pub fn bundle_source(contract_id: ContractId, bundle: &TransitionBundle) -> lnpbp4::MultiSource {
+    lnpbp4::MultiSource {
+        min_depth: LNPBP4_MIN_DEPTH,
+        messages: bmap! {
+            ProtocolId::from(contract_id) =>
+            lnpbp4::Message::from_inner(bundle.bundle_id().into_inner().into_inner())
+        },
+    }
+}
+
+/// Computes LNPBP-4 multi-protocol commitment hosted by tapret or opret
+/// commitment from the LNPBP-4 `tree`, for instance the one built from
+/// [`bundle_source`]
+pub fn bundle_commitment(tree: &lnpbp4::MerkleTree) -> [u8; 32] {
+    tree.consensus_commit().into_inner().into_inner()
+}
+
+/// Constructs tapret commitment script leaf for the LNPBP-4 commitment `mpc`
+pub fn commitment_script(mpc: [u8; 32], nonce: u8) -> Script {
+    let mut data = [0u8; 33];
+    data[..32].copy_from_slice(&mpc);
+    data[32] = nonce;
+    let mut builder = Builder::new();
+    for _ in 0..TAPRET_RESERVED_OPCODES {
+        builder = builder.push_opcode(OP_RESERVED);
+    }
+    builder
+        .push_opcode(OP_RETURN)
+        .push_slice(&data)
+        .into_script()
+}
+
+/// Derives output key of the taproot output committing to `mpc` with the
+/// tapret commitment, for an output without other script leaves
+pub fn output_key<C: Verification>(
+    secp: &Secp256k1<C>,
+    internal_key: UntweakedPublicKey,
+    mpc: [u8; 32],
+    nonce: u8,
+) -> TweakedPublicKey {
+    let leaf_hash =
+        TapLeafHash::from_script(&commitment_script(mpc, nonce), LeafVersion::TapScript);
+    let merkle_root = TapBranchHash::from_inner(leaf_hash.into_inner());
+    let (output_key, _) = internal_key.tap_tweak(secp, Some(merkle_root));
+    output_key
+}
+
+/// Derives script pubkey of the taproot output committing to `mpc` with the
+/// tapret commitment, for an output without other script leaves
+pub fn script_pubkey<C: Verification>(
+    secp: &Secp256k1<C>,
+    internal_key: UntweakedPublicKey,
+    mpc: [u8; 32],
+    nonce: u8,
+) -> Script {
+    Script::new_v1_p2tr_tweaked(output_key(secp, internal_key, mpc, nonce))
+}
+
+/// Verifies that the `script_pubkey` of a witness transaction output commits
+/// to `mpc` with the tapret commitment under `internal_key`
+pub fn verify_script_pubkey<C: Verification>(
+    secp: &Secp256k1<C>,
+    internal_key: UntweakedPublicKey,
+    mpc: [u8; 32],
+    nonce: u8,
+    script_pubkey: &Script,
+) -> bool {
+    *script_pubkey == self::script_pubkey(secp, internal_key, mpc, nonce)
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::hashes::hex::{FromHex, ToHex};
+
+    use super::*;
+
+    fn internal_key() -> UntweakedPublicKey {
+        UntweakedPublicKey::from_str(
+            "1b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f",
+        )
+        .unwrap()
+    }
+
+    fn mpc(hex: &str) -> [u8; 32] {
+        let mut mpc = [0u8; 32];
+        mpc.copy_from_slice(&Vec::<u8>::from_hex(hex).unwrap());
+        mpc
+    }
+
+    #[test]
+    fn tapret_vectors() {
+        let secp = Secp256k1::verification_only();
+        let vectors = [
+            (
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                0u8,
+                "50505050505050505050505050505050505050505050505050505050506a21\
+                 000000000000000000000000000000000000000000000000000000000000000000",
+                "51208edfca7248c48b51af90d98bfc8c02fc4b2129747bf116f25e8a114a185f81d5",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+                1u8,
+                "50505050505050505050505050505050505050505050505050505050506a21\
+                 000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f01",
+                "5120c63e2522733c83613233ab9f79d52b607d84190fb41c0035b59570c8d684f84f",
+            ),
+        ];
+        for (commitment, nonce, leaf, spk) in vectors {
+            let commitment = mpc(commitment);
+            assert_eq!(commitment_script(commitment, nonce).to_hex(), leaf);
+            let derived = script_pubkey(&secp, internal_key(), commitment, nonce);
+            assert_eq!(derived.to_hex(), spk);
+            assert!(verify_script_pubkey(
+                &secp,
+                internal_key(),
+                commitment,
+                nonce,
+                &derived
+            ));
+            assert!(!verify_script_pubkey(
+                &secp,
+                internal_key(),
+                commitment,
+                nonce.wrapping_add(1),
+                &derived
+            ));
+        }
+    }
+}
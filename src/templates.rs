@@ -0,0 +1,229 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Catalog of contract templates for common asset types.
+//!
+//! Each template returns [`Rgb20Builder`] pre-configured with the subschema,
+//! precision and the rights the asset type requires:
+//! - [`Stablecoin`]: inflationary and burnable asset with 2 decimal digits;
+//! - [`FixedSupplyToken`]: asset with the supply fixed at genesis;
+//! - [`SecurityToken`]: indivisible renominatable asset with the prospectus
+//!   attached.
+//!
+//! The issuer completes the builder with allocations and, if required,
+//! the seal closing method and genesis timestamp. Templates are listed by
+//! [`Template`].
+
+use bitcoin::OutPoint;
+use lnpbp::chain::Chain;
+use rgb::AtomicValue;
+
+use crate::{AssetName, Attachment, AttachmentType, Rgb20Builder, Subschema, Ticker};
+
+/// Contract templates known to the library
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "kebab-case")
+)]
+pub enum Template {
+    /// Inflationary and burnable asset, see [`Stablecoin`]
+    #[display("stablecoin")]
+    Stablecoin,
+
+    /// Asset with the supply fixed at genesis, see [`FixedSupplyToken`]
+    #[display("fixed-supply")]
+    FixedSupplyToken,
+
+    /// Renominatable asset with the prospectus, see [`SecurityToken`]
+    #[display("security")]
+    SecurityToken,
+}
+
+impl Template {
+    /// All templates from the catalog
+    pub const ALL: [Template; 3] =
+        [Template::Stablecoin, Template::FixedSupplyToken, Template::SecurityToken];
+
+    /// Returns subschema used by the template
+    pub fn subschema(self) -> Subschema {
+        match self {
+            Template::Stablecoin => Stablecoin::SUBSCHEMA,
+            Template::FixedSupplyToken => FixedSupplyToken::SUBSCHEMA,
+            Template::SecurityToken => SecurityToken::SUBSCHEMA,
+        }
+    }
+
+    /// Returns decimal precision used by the template
+    pub fn precision(self) -> u8 {
+        match self {
+            Template::Stablecoin => Stablecoin::PRECISION,
+            Template::FixedSupplyToken => FixedSupplyToken::PRECISION,
+            Template::SecurityToken => SecurityToken::PRECISION,
+        }
+    }
+}
+
+/// Template for fiat-backed stablecoins, which are issued on deposits and
+/// burned on redemptions
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Stablecoin;
+
+impl Stablecoin {
+    /// Stablecoins are issued under the subschema prohibiting burn & replace,
+    /// so the redeemed supply can't be re-issued by the burn authority
+    pub const SUBSCHEMA: Subschema = Subschema::NoReplace;
+
+    /// Stablecoins use cents as atomic units
+    pub const PRECISION: u8 = 2;
+
+    /// Constructs builder assigning the right to issue up to `max_inflation`
+    /// atomic units to the `issuer` outpoint and the right to burn the asset
+    /// to the `burn_authority` outpoint
+    pub fn builder(
+        chain: Chain,
+        ticker: Ticker,
+        name: AssetName,
+        issuer: OutPoint,
+        max_inflation: AtomicValue,
+        burn_authority: OutPoint,
+    ) -> Rgb20Builder {
+        Rgb20Builder::new(chain)
+            .subschema(Self::SUBSCHEMA)
+            .ticker(ticker)
+            .name(name)
+            .precision(Self::PRECISION)
+            .inflation_right(issuer, max_inflation)
+            .epoch(burn_authority)
+    }
+}
+
+/// Template for tokens with the total supply allocated at genesis and no
+/// issuer rights
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct FixedSupplyToken;
+
+impl FixedSupplyToken {
+    /// Fixed supply tokens are issued under the simplest subschema, allowing
+    /// only transfers
+    pub const SUBSCHEMA: Subschema = Subschema::Deflationary;
+
+    /// Fixed supply tokens use the same precision as bitcoin
+    pub const PRECISION: u8 = 8;
+
+    /// Constructs builder without any issuer rights; the supply is defined
+    /// by the allocations added to the builder
+    pub fn builder(chain: Chain, ticker: Ticker, name: AssetName) -> Rgb20Builder {
+        Rgb20Builder::new(chain)
+            .subschema(Self::SUBSCHEMA)
+            .ticker(ticker)
+            .name(name)
+            .precision(Self::PRECISION)
+    }
+}
+
+/// Template for security tokens, representing indivisible shares described
+/// by the prospectus
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct SecurityToken;
+
+impl SecurityToken {
+    /// Security tokens are issued under the media schema, keeping the
+    /// prospectus as the asset terms attachment
+    pub const SUBSCHEMA: Subschema = Subschema::Media;
+
+    /// Shares are indivisible
+    pub const PRECISION: u8 = 0;
+
+    /// Constructs builder assigning the renomination right to the `registrar`
+    /// outpoint, such that corporate actions may update the asset nomination,
+    /// and attaching the `prospectus` as the asset terms
+    pub fn builder(
+        chain: Chain,
+        ticker: Ticker,
+        name: AssetName,
+        registrar: OutPoint,
+        prospectus: Attachment,
+    ) -> Rgb20Builder {
+        Rgb20Builder::new(chain)
+            .subschema(Self::SUBSCHEMA)
+            .ticker(ticker)
+            .name(name)
+            .precision(Self::PRECISION)
+            .renomination(registrar)
+            .attach(AttachmentType::Terms, prospectus)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::fixtures::outpoint;
+    use crate::Asset;
+
+    fn nomination() -> (Ticker, AssetName) {
+        (
+            Ticker::from_str("TICK").unwrap(),
+            AssetName::from_str("Test asset").unwrap(),
+        )
+    }
+
+    #[test]
+    fn templates() {
+        let (ticker, name) = nomination();
+        let contract = Stablecoin::builder(
+            Chain::Signet,
+            ticker,
+            name,
+            outpoint(10),
+            1000,
+            outpoint(11),
+        )
+        .allocate(outpoint(1), 100)
+        .build()
+        .unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(asset.subschema(), Template::Stablecoin.subschema());
+        assert_eq!(asset.precision(), 2);
+        assert_eq!(asset.known_inflation().count(), 1);
+
+        let (ticker, name) = nomination();
+        let contract = FixedSupplyToken::builder(Chain::Signet, ticker, name)
+            .allocate(outpoint(1), 100)
+            .build()
+            .unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(asset.subschema(), Subschema::Deflationary);
+        assert_eq!(asset.known_inflation().count(), 0);
+
+        let (ticker, name) = nomination();
+        let prospectus = Attachment::with("application/pdf", b"prospectus");
+        let contract = SecurityToken::builder(
+            Chain::Signet,
+            ticker,
+            name,
+            outpoint(20),
+            prospectus.clone(),
+        )
+        .allocate(outpoint(1), 100)
+        .build()
+        .unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(asset.precision(), Template::SecurityToken.precision());
+        assert_eq!(
+            asset.attachments(),
+            bmap! { AttachmentType::Terms => prospectus }
+        );
+    }
+}
@@ -0,0 +1,268 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Test utilities for the downstream crates (wallets, explorers), which need
+//! to property-test or fuzz their integration with RGB20 assets.
+//!
+//! The module provides [`proptest`] strategies generating random valid RGB20
+//! data and fuzz targets, which must never panic on arbitrary input.
+//!
+//! Issuing a contract involves construction of the range proofs, which is
+//! slow; properties using contract strategies should run a small number of
+//! cases.
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use bitcoin::hashes::Hash;
+use bitcoin::{OutPoint, Txid};
+use commit_verify::lnpbp4;
+use lnpbp::chain::Chain;
+pub use proptest;
+use proptest::prelude::*;
+use rgb::{
+    seal, Anchor, AtomicValue, Consignment, Contract, StateTransfer, Transition, TransitionBundle,
+};
+use strict_encoding::{StrictDecode, StrictEncode};
+
+use crate::invoice::Invoice;
+use crate::{
+    Armor, Asset, AssetAmount, AssetName, Features, Rgb20Builder, Subschema, Ticker, MAX_PRECISION,
+    NAME_MAX_LEN,
+};
+
+/// Maximal number of the allocations produced by [`allocations`]
+pub const MAX_ALLOCATIONS: usize = 4;
+
+/// Generates random transaction id
+pub fn txid() -> impl Strategy<Value = Txid> { any::<[u8; 32]>().prop_map(Txid::from_inner) }
+
+/// Generates random transaction output
+pub fn outpoint() -> impl Strategy<Value = OutPoint> {
+    (txid(), 0u32..16).prop_map(|(txid, vout)| OutPoint::new(txid, vout))
+}
+
+/// Generates valid asset ticker
+pub fn ticker() -> impl Strategy<Value = Ticker> {
+    "[A-Z]{3,8}".prop_map(|s| Ticker::from_str(&s).expect("valid ticker"))
+}
+
+/// Generates valid asset name
+pub fn asset_name() -> impl Strategy<Value = AssetName> {
+    proptest::string::string_regex(&format!("[A-Za-z0-9][ -~]{{0,{}}}", NAME_MAX_LEN - 1))
+        .expect("valid regex")
+        .prop_map(|s| AssetName::from_str(&s).expect("valid asset name"))
+}
+
+/// Generates valid decimal precision
+pub fn precision() -> impl Strategy<Value = u8> { 0..=MAX_PRECISION }
+
+/// Generates non-zero atomic amount, preferring values close to the
+/// boundaries of `u64`
+pub fn atomic_value() -> impl Strategy<Value = AtomicValue> {
+    prop_oneof![
+        1..=1000u64,
+        any::<AtomicValue>().prop_map(|value| value.max(1)),
+        (u64::MAX - 1000)..=u64::MAX,
+    ]
+}
+
+/// Generates asset amount with a random precision
+pub fn asset_amount() -> impl Strategy<Value = AssetAmount> {
+    (any::<AtomicValue>(), precision()).prop_map(|(atomic, precision)| {
+        AssetAmount::with_atomic(atomic, precision).expect("precision is in range")
+    })
+}
+
+/// Generates from one to [`MAX_ALLOCATIONS`] allocations at distinct
+/// outpoints, which total value does not overflow `u64`
+pub fn allocations() -> impl Strategy<Value = Vec<(OutPoint, AtomicValue)>> {
+    prop::collection::btree_set(outpoint(), 1..=MAX_ALLOCATIONS).prop_flat_map(|outpoints| {
+        let max = u64::MAX / MAX_ALLOCATIONS as u64;
+        let len = outpoints.len();
+        prop::collection::vec(prop_oneof![1..=1000u64, 1..=max, (max - 1000)..=max], len)
+            .prop_map(move |values| outpoints.iter().copied().zip(values).collect())
+    })
+}
+
+/// Generates RGB20 subschema
+pub fn subschema() -> impl Strategy<Value = Subschema> {
+    prop_oneof![
+        Just(Subschema::Full),
+        Just(Subschema::NoReplace),
+        Just(Subschema::Deflationary),
+        Just(Subschema::Media),
+    ]
+}
+
+/// Generates valid asset contract under the given subschema. Genesis assigns
+/// all the rights allowed by the subschema: inflation, renomination and epoch
+/// opening.
+pub fn contract_under(subschema: Subschema) -> impl Strategy<Value = Contract> {
+    (
+        ticker(),
+        asset_name(),
+        precision(),
+        allocations(),
+        prop::option::of((outpoint(), atomic_value())),
+        outpoint(),
+        outpoint(),
+    )
+        .prop_map(
+            move |(ticker, name, precision, allocations, inflation, renomination, epoch)| {
+                let features = Features::from(subschema);
+                let mut builder = Rgb20Builder::new(Chain::Signet)
+                    .subschema(subschema)
+                    .ticker(ticker)
+                    .name(name)
+                    .precision(precision);
+                for (outpoint, value) in allocations {
+                    builder = builder.allocate(outpoint, value);
+                }
+                if let Some((outpoint, value)) = inflation.filter(|_| features.inflatable) {
+                    builder = builder.inflation_right(outpoint, value);
+                }
+                if features.renominatable {
+                    builder = builder.renomination(renomination);
+                }
+                if features.burnable || features.replaceable {
+                    builder = builder.epoch(epoch);
+                }
+                builder.build().expect("generated data are valid")
+            },
+        )
+}
+
+/// Generates valid asset contract under a random subschema
+pub fn contract() -> impl Strategy<Value = Contract> { subschema().prop_flat_map(contract_under) }
+
+/// Generates contract together with a transfer state transition spending all
+/// the genesis allocations to from one to [`MAX_ALLOCATIONS`] random seals
+pub fn transfer() -> impl Strategy<Value = (Contract, Transition)> {
+    (
+        contract(),
+        prop::collection::btree_set(outpoint(), 1..=MAX_ALLOCATIONS),
+    )
+        .prop_map(|(contract, beneficiaries)| {
+            let asset = Asset::try_from(&contract).expect("generated contract is valid");
+            let inputs = asset
+                .known_coins()
+                .map(|coin| coin.seal)
+                .collect::<BTreeSet<_>>();
+            let total = asset
+                .known_coins()
+                .fold(0u64, |sum, coin| sum + coin.state.value);
+            let share = total / beneficiaries.len() as u64;
+            let remainder = total % beneficiaries.len() as u64;
+            let change = beneficiaries
+                .into_iter()
+                .enumerate()
+                .map(|(no, outpoint)| {
+                    let value = if no == 0 { share + remainder } else { share };
+                    (seal::Revealed::from(outpoint), value)
+                })
+                .filter(|(_, value)| *value > 0)
+                .collect();
+            let transition = asset
+                .transfer(inputs, empty!(), change)
+                .expect("generated transfer is valid");
+            (contract, transition)
+        })
+}
+
+/// Generates state transfer consignment containing the genesis and a single
+/// transfer produced by [`transfer`], together with the transfer witness
+/// transaction id.
+///
+/// The consignment anchor does not contain a valid commitment, so the
+/// consignment passes RGB20 validation with [`Asset::try_from`] but not the
+/// full anchor validation against the bitcoin blockchain.
+pub fn state_transfer() -> impl Strategy<Value = (StateTransfer, Txid)> {
+    (transfer(), txid()).prop_map(|((contract, transition), witness_txid)| {
+        let bundle = TransitionBundle::try_from(bmap! { transition => bset! { 0 } })
+            .expect("single transition bundle");
+        let transfer = StateTransfer::with(
+            contract.schema().clone(),
+            contract.root_schema().cloned(),
+            contract.genesis().clone(),
+            empty!(),
+            vec![(stub_anchor(witness_txid), bundle)]
+                .try_into()
+                .expect("single anchored bundle"),
+            empty!(),
+        );
+        (transfer, witness_txid)
+    })
+}
+
+/// Constructs anchor with empty multi-protocol commitment proof
+fn stub_anchor(txid: Txid) -> Anchor<lnpbp4::MerkleProof> {
+    let mut data = txid
+        .strict_serialize()
+        .expect("in-memory encoding does not fail");
+    data.extend([0u8; 5]);
+    Anchor::strict_deserialize(data).expect("anchor with empty proof")
+}
+
+/// Fuzz target decoding ASCII-armored or strict-encoded state transfer and
+/// extracting asset from it
+pub fn fuzz_consignment(data: &[u8]) {
+    if let Ok(transfer) = StateTransfer::from_armored_or_strict(data) {
+        if let Ok(asset) = Asset::try_from(&transfer) {
+            let _ = asset.supply();
+            let _ = asset.renominations();
+        }
+    }
+}
+
+/// Fuzz target decoding ASCII-armored or strict-encoded state transition
+pub fn fuzz_transition(data: &[u8]) { let _ = Transition::from_armored_or_strict(data); }
+
+/// Fuzz target parsing invoices and asset amounts
+pub fn fuzz_parse(data: &[u8]) {
+    if let Ok(s) = std::str::from_utf8(data) {
+        let _ = Invoice::from_str(s);
+        if let Ok(amount) = AssetAmount::from_str(s) {
+            let _ = amount.to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn fuzz_targets(data in prop::collection::vec(any::<u8>(), 0..512)) {
+            fuzz_consignment(&data);
+            fuzz_transition(&data);
+            fuzz_parse(&data);
+        }
+
+        #[test]
+        fn amounts(amount in asset_amount()) {
+            prop_assert_eq!(AssetAmount::from_str(&amount.to_string()), Ok(amount));
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4))]
+
+        #[test]
+        fn valid_state_transfer((transfer, witness_txid) in state_transfer()) {
+            let asset = Asset::try_from(&transfer).unwrap();
+            prop_assert!(asset.transitions().all(|(txid, _)| txid == witness_txid));
+            fuzz_consignment(&transfer.strict_serialize().unwrap());
+            fuzz_consignment(transfer.to_armored_string().as_bytes());
+        }
+    }
+}
@@ -9,15 +9,21 @@
 // You should have received a copy of the MIT License along with this software.
 // If not, see <https://opensource.org/licenses/MIT>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use bitcoin::OutPoint;
-use rgb::fungible::allocation::{AllocationMap, AllocationValueMap, AllocationValueVec};
+use commit_verify::{lnpbp4, CommitConceal};
+use rgb::fungible::allocation::{
+    AllocationMap, AllocationValueMap, AllocationValueVec, IntoSealValueMap,
+};
 use rgb::prelude::*;
-use seals::txout::ExplicitSeal;
+use rgb::secp256k1zkp;
+use seals::txout::{CloseMethod, ExplicitSeal};
+use strict_encoding::StrictEncode;
 
-use super::schema::{OwnedRightType, TransitionType};
-use super::Asset;
+use super::schema::{FieldType, HistoryProofFormat, OwnedRightType, TransitionType};
+use super::{Asset, AssetName, CoinSelection, HistoryProof, InflationAllocations, Ticker};
+use crate::asset::checked_sum;
 
 /// Errors happening during construction of RGB-20 asset state transitions
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display, Error)]
@@ -38,6 +44,15 @@ pub enum Error {
         requested: AtomicValue,
     },
 
+    /// secondary issuance of {requested} exceeds the remaining inflation cap
+    /// {remaining} defined by the genesis
+    InflationCapExceeded {
+        /// Supply which may still be issued
+        remaining: AtomicValue,
+        /// Requested issue value
+        requested: AtomicValue,
+    },
+
     /// the requested supply {requested} does not match the total supply
     /// {assigned} allocated to the owned rights consumed by the operation
     SupplyMismatch {
@@ -50,30 +65,227 @@ pub enum Error {
     /// method was provided with a set of seals for owned rights which are not
     /// a part of the asset data: {0:?}
     UnknownSeals(BTreeSet<OutPoint>),
+
+    /// no seal is provided for {0} rights closed by the rights split operation
+    NoSealForRight(OwnedRightType),
+
+    /// burn operation must reference at least one burned UTXO
+    NoBurnedUtxos,
+
+    /// renomination must change at least one of the asset ticker, name or
+    /// precision
+    EmptyRenomination,
+
+    /// replaced supply {replaced} exceeds the burned supply {burned}
+    ReplacedExceedsBurned {
+        /// Burned supply
+        burned: AtomicValue,
+        /// Requested replacement supply
+        replaced: AtomicValue,
+    },
+
+    /// replaced supply {replaced} exceeds the supply {budget} burned and not
+    /// yet replaced within epoch #{epoch}
+    EpochBudgetExceeded {
+        /// Number of the epoch
+        epoch: u32,
+        /// Supply burned within the epoch, including the operation, which was
+        /// not replaced yet
+        budget: AtomicValue,
+        /// Requested replacement supply
+        replaced: AtomicValue,
+    },
+
+    /// known allocations sum up to {available}, which is insufficient to
+    /// transfer the requested amount {requested}
+    InsufficientFunds {
+        /// Total value of known allocations
+        available: AtomicValue,
+        /// Requested transfer value
+        requested: AtomicValue,
+    },
+
+    /// no combination of known allocations sums exactly to {0}
+    NoExactMatch(AtomicValue),
+
+    /// source consignment belongs to a different contract {0}
+    ContractMismatch(ContractId),
+
+    /// transition {0} is an ancestor of the transferred state but is absent
+    /// from the source consignment
+    AncestorAbsent(NodeId),
+
+    /// transition bundle does not contain any revealed transitions
+    EmptyBundle,
+
+    /// number of anchored bundles exceeds the consignment size limit
+    TooManyBundles,
+
+    /// transfer plan is outdated since known allocations at its input {0} have
+    /// changed
+    StalePlan(OutPoint),
+
+    /// witness transaction output #{0} is used both by a beneficiary and by
+    /// the change of the transfer
+    WitnessVoutConflict(u32),
+
+    /// sum of the operation inputs or outputs exceeds the maximal asset amount
+    AmountOverflow,
 }
 
 impl Asset {
     /// Performs secondary issue closing an inflation-controlling seal over
     /// inflation state transition, which is constructed and returned by this
-    /// function
+    /// function.
+    ///
+    /// The issue allowance of the closed inflation rights must be fully
+    /// distributed between the newly issued `allocations` and the rights for
+    /// the future issuance assigned to `next_inflation`. The issued amount
+    /// can't exceed the part of [`Asset::max_supply`] which was not issued yet.
     pub fn inflate(
         &self,
-        _closing: BTreeSet<OutPoint>,
-        _next_inflation: AllocationValueMap,
-        _allocations: AllocationValueVec,
+        closing: BTreeSet<OutPoint>,
+        next_inflation: InflationAllocations<ExplicitSeal>,
+        allocations: AllocationValueVec,
     ) -> Result<Transition, Error> {
-        todo!()
+        let next_inflation = next_inflation.into_inner();
+        let (inputs, parent) = self.inflation_parent(&closing)?;
+        let allowed =
+            checked_sum(inputs.iter().map(|state| state.value)).ok_or(Error::AmountOverflow)?;
+
+        let issued = checked_sum(allocations.iter().map(|allocation| allocation.value))
+            .ok_or(Error::AmountOverflow)?;
+        if issued > allowed {
+            return Err(Error::InsufficientIssueAllowance {
+                allowed,
+                requested: issued,
+            });
+        }
+        let remaining = self.max_supply().saturating_sub(self.supply().issued);
+        if issued > remaining {
+            return Err(Error::InflationCapExceeded {
+                remaining,
+                requested: issued,
+            });
+        }
+        let distributed = checked_sum(next_inflation.values().copied())
+            .and_then(|sum| sum.checked_add(issued))
+            .ok_or(Error::AmountOverflow)?;
+        if distributed != allowed {
+            return Err(Error::SupplyMismatch {
+                assigned: allowed,
+                requested: distributed,
+            });
+        }
+
+        let mut owned_rights = BTreeMap::new();
+        if !allocations.is_empty() {
+            owned_rights.insert(
+                OwnedRightType::Assets.into(),
+                TypedAssignments::zero_balanced(
+                    vec![value::Revealed {
+                        value: issued,
+                        blinding: secp256k1zkp::key::ONE_KEY.into(),
+                    }],
+                    allocations.into_seal_value_map(),
+                    empty!(),
+                ),
+            );
+        }
+        if !next_inflation.is_empty() {
+            owned_rights.insert(
+                OwnedRightType::Inflation.into(),
+                next_inflation.into_assignments(),
+            );
+        }
+
+        let transition = Transition::with(
+            TransitionType::Issue,
+            type_map! { FieldType::IssuedSupply => field!(U64, issued) }.into(),
+            empty!(),
+            owned_rights.into(),
+            empty!(),
+            parent,
+        );
+
+        Ok(transition)
+    }
+
+    /// Transfers inflation rights assigned to the `closing` outpoints, which
+    /// allows issuer to sell or delegate the right of the secondary issuance.
+    /// The state transition constructed and returned by this function
+    /// re-assigns the issue allowance to the new owners according to the
+    /// issue caps given in `to`.
+    ///
+    /// The issue allowance of the closed inflation rights must be fully
+    /// distributed between the new owners. Other rights and allocations
+    /// assigned to the same outpoints are not transferred and must be moved
+    /// with [`Asset::split_rights`] first.
+    pub fn transfer_inflation_right(
+        &self,
+        closing: BTreeSet<OutPoint>,
+        to: AllocationValueMap,
+    ) -> Result<Transition, Error> {
+        let (inputs, parent) = self.inflation_parent(&closing)?;
+        let allowed =
+            checked_sum(inputs.iter().map(|state| state.value)).ok_or(Error::AmountOverflow)?;
+        let distributed = checked_sum(to.values().copied()).ok_or(Error::AmountOverflow)?;
+        if distributed != allowed {
+            return Err(Error::SupplyMismatch {
+                assigned: allowed,
+                requested: distributed,
+            });
+        }
+
+        let owned_rights = bmap! {
+            OwnedRightType::Inflation.into() =>
+                TypedAssignments::zero_balanced(inputs, to.into_seal_value_map(), empty!())
+        };
+
+        let transition = Transition::with(
+            TransitionType::RightsSplit,
+            empty!(),
+            empty!(),
+            owned_rights.into(),
+            empty!(),
+            parent,
+        );
+
+        Ok(transition)
     }
 
     /// Opens a new epoch by closing epoch-controlling seal over epoch opening
-    /// state transition, which is constructed and returned by this function
-    pub fn epoch(
+    /// state transition, which is constructed and returned by this function.
+    ///
+    /// The right to open the next epoch is assigned to `next_epoch` and the
+    /// right to burn or burn & replace the asset within the opened epoch – to
+    /// `burning_seal`.
+    pub fn open_epoch(
         &self,
-        _closing: OutPoint,
-        _next_epoch: Option<ExplicitSeal>,
-        _burning_seal: Option<ExplicitSeal>,
+        closing: OutPoint,
+        next_epoch: Option<ExplicitSeal>,
+        burning_seal: Option<ExplicitSeal>,
     ) -> Result<Transition, Error> {
-        todo!()
+        let parent = self.right_parent(closing, OwnedRightType::OpenEpoch)?;
+
+        let mut owned_rights = BTreeMap::new();
+        if let Some(seal) = next_epoch {
+            owned_rights.insert(OwnedRightType::OpenEpoch.into(), right_assignment(seal));
+        }
+        if let Some(seal) = burning_seal {
+            owned_rights.insert(OwnedRightType::BurnReplace.into(), right_assignment(seal));
+        }
+
+        let transition = Transition::with(
+            TransitionType::Epoch,
+            empty!(),
+            empty!(),
+            owned_rights.into(),
+            empty!(),
+            parent,
+        );
+
+        Ok(transition)
     }
 
     /// Burns certain amount of the asset by closing burn-controlling seal over
@@ -81,36 +293,329 @@ impl Asset {
     /// this function
     pub fn burn(
         &self,
-        _closing: OutPoint,
-        _burned_value: AtomicValue,
-        _burned_utxos: BTreeSet<OutPoint>,
-        _next_burn: Option<ExplicitSeal>,
+        closing: OutPoint,
+        burned_value: AtomicValue,
+        burned_utxos: BTreeSet<OutPoint>,
+        next_burn: Option<ExplicitSeal>,
     ) -> Result<Transition, Error> {
-        todo!()
+        let parent = self.right_parent(closing, OwnedRightType::BurnReplace)?;
+        let metadata = self.burn_metadata(burned_value, &burned_utxos)?;
+
+        let mut owned_rights = BTreeMap::new();
+        if let Some(seal) = next_burn {
+            owned_rights.insert(OwnedRightType::BurnReplace.into(), right_assignment(seal));
+        }
+
+        let transition = Transition::with(
+            TransitionType::Burn,
+            metadata.into(),
+            empty!(),
+            owned_rights.into(),
+            empty!(),
+            parent,
+        );
+
+        Ok(transition)
     }
 
     /// Burns and re-allocates certain amount of the asset by closing
     /// burn-controlling seal over proof-of-burn state transition, which is
-    /// constructed and returned by this function
-    pub fn burn_replace(
-        &self,
-        _closing: OutPoint,
-        _burned_value: AtomicValue,
-        _burned_utxos: BTreeSet<OutPoint>,
-        _next_burn: Option<ExplicitSeal>,
-        _allocations: AllocationValueVec,
+    /// constructed and returned by this function.
+    ///
+    /// History proofs are assembled from the allocations known to be assigned
+    /// to the burned UTXOs. Replaced supply is assigned to our own
+    /// `allocations` and external `beneficiaries`. Within an epoch the total
+    /// replaced supply can't exceed the total burned supply (see
+    /// [`Asset::epoch_totals`]); if the epoch of the `closing` seal is not
+    /// known, the replaced supply can't exceed `burned_value`.
+    pub fn burn_and_replace(
+        &self,
+        closing: OutPoint,
+        burned_value: AtomicValue,
+        burned_utxos: BTreeSet<OutPoint>,
+        next_burn: Option<ExplicitSeal>,
+        allocations: SealValueMap,
+        beneficiaries: EndpointValueMap,
+    ) -> Result<Transition, Error> {
+        let replaced_value =
+            checked_sum(allocations.values().chain(beneficiaries.values()).copied())
+                .ok_or(Error::AmountOverflow)?;
+        let parent = self.right_parent(closing, OwnedRightType::BurnReplace)?;
+        let epoch = parent
+            .keys()
+            .next()
+            .and_then(|node_id| self.burn_right_epoch(*node_id));
+        match epoch {
+            Some(totals) => {
+                let budget = totals
+                    .replace_budget()
+                    .checked_add(burned_value)
+                    .ok_or(Error::AmountOverflow)?;
+                if replaced_value > budget {
+                    return Err(Error::EpochBudgetExceeded {
+                        epoch: totals.epoch,
+                        budget,
+                        replaced: replaced_value,
+                    });
+                }
+            }
+            None if replaced_value > burned_value => {
+                return Err(Error::ReplacedExceedsBurned {
+                    burned: burned_value,
+                    replaced: replaced_value,
+                });
+            }
+            None => {}
+        }
+
+        let mut metadata = self.burn_metadata(burned_value, &burned_utxos)?;
+        metadata.insert(FieldType::IssuedSupply.into(), field!(U64, replaced_value));
+
+        let mut owned_rights = BTreeMap::new();
+        owned_rights.insert(
+            OwnedRightType::Assets.into(),
+            TypedAssignments::zero_balanced(
+                vec![value::Revealed {
+                    value: replaced_value,
+                    blinding: secp256k1zkp::key::ONE_KEY.into(),
+                }],
+                allocations,
+                beneficiaries,
+            ),
+        );
+        if let Some(seal) = next_burn {
+            owned_rights.insert(OwnedRightType::BurnReplace.into(), right_assignment(seal));
+        }
+
+        let transition = Transition::with(
+            TransitionType::BurnAndReplace,
+            metadata.into(),
+            empty!(),
+            owned_rights.into(),
+            empty!(),
+            parent,
+        );
+
+        Ok(transition)
+    }
+
+    /// Changes asset nomination by closing renomination-controlling seal over
+    /// renomination state transition, which is constructed and returned by
+    /// this function. Only the provided nomination fields are changed.
+    ///
+    /// The asset nomination is updated once the transition becomes a part of
+    /// the consignment from which the asset is constructed.
+    pub fn renominate(
+        &self,
+        closing: OutPoint,
+        ticker: Option<Ticker>,
+        name: Option<AssetName>,
+        precision: Option<u8>,
+        next_renomination: Option<ExplicitSeal>,
     ) -> Result<Transition, Error> {
-        todo!()
+        if ticker.is_none() && name.is_none() && precision.is_none() {
+            return Err(Error::EmptyRenomination);
+        }
+
+        let parent = self.right_parent(closing, OwnedRightType::Renomination)?;
+
+        let mut metadata = BTreeMap::new();
+        if let Some(ticker) = ticker {
+            metadata.insert(FieldType::Ticker.into(), field!(AsciiString, ticker.into()));
+        }
+        if let Some(name) = name {
+            metadata.insert(FieldType::Name.into(), field!(AsciiString, name.into()));
+        }
+        if let Some(precision) = precision {
+            metadata.insert(FieldType::Precision.into(), field!(U8, precision));
+        }
+
+        let mut owned_rights = BTreeMap::new();
+        if let Some(seal) = next_renomination {
+            owned_rights.insert(OwnedRightType::Renomination.into(), right_assignment(seal));
+        }
+
+        let transition = Transition::with(
+            TransitionType::Renomination,
+            metadata.into(),
+            empty!(),
+            owned_rights.into(),
+            empty!(),
+            parent,
+        );
+
+        Ok(transition)
+    }
+
+    /// Moves renomination right assigned to the `closing` outpoint to the
+    /// `next_renomination` seal, for instance controlled by a DAO or multisig,
+    /// without changing the asset nomination. If no seal is provided, the
+    /// right is revoked and the asset can't be renominated anymore.
+    ///
+    /// The right is moved with rights split state transition, which is
+    /// constructed and returned by this function.
+    pub fn transfer_renomination_right(
+        &self,
+        closing: OutPoint,
+        next_renomination: Option<ExplicitSeal>,
+    ) -> Result<Transition, Error> {
+        let parent = self.right_parent(closing, OwnedRightType::Renomination)?;
+
+        let mut owned_rights = BTreeMap::new();
+        if let Some(seal) = next_renomination {
+            owned_rights.insert(OwnedRightType::Renomination.into(), right_assignment(seal));
+        }
+
+        let transition = Transition::with(
+            TransitionType::RightsSplit,
+            empty!(),
+            empty!(),
+            owned_rights.into(),
+            empty!(),
+            parent,
+        );
+
+        Ok(transition)
+    }
+
+    /// Splits rights and allocations which happen to be assigned to the same
+    /// `closing` outpoint, constructing and returning rights split state
+    /// transition.
+    ///
+    /// All known rights and allocations of each type are re-assigned to the
+    /// seal provided for that type in `seals`; allocations and inflation
+    /// allowances of the same type are summed up. Every right type present at
+    /// the `closing` outpoint must have a seal provided.
+    pub fn split_rights(
+        &self,
+        closing: OutPoint,
+        seals: BTreeMap<OwnedRightType, ExplicitSeal>,
+    ) -> Result<Transition, Error> {
+        self.reassign(&bset! { closing }, |ty| {
+            seals.get(&ty).copied().ok_or(Error::NoSealForRight(ty))
+        })?
+        .ok_or(Error::UnrelatedInput(closing))
+    }
+
+    /// Creates blank state transition moving all known rights and
+    /// allocations assigned to any of the `outpoints` to the `change_seal`.
+    ///
+    /// Wallets must add such transition for each asset having state at the
+    /// outputs spent by a witness transaction of some other asset operation,
+    /// otherwise the state of the asset is lost. Returns `None` if the asset
+    /// has no known state at the `outpoints`.
+    pub fn blank_transition(
+        &self,
+        outpoints: &BTreeSet<OutPoint>,
+        change_seal: ExplicitSeal,
+    ) -> Result<Option<Transition>, Error> {
+        self.reassign(outpoints, |_| Ok(change_seal))
+    }
+
+    /// Constructs rights split transition re-assigning all known rights and
+    /// allocations at the `closing` outpoints to the seals returned by
+    /// `seal_for` for each of the right types; allocations and inflation
+    /// allowances of the same type are summed up. Returns `None` if there is
+    /// no known state at the `closing` outpoints.
+    fn reassign(
+        &self,
+        closing: &BTreeSet<OutPoint>,
+        seal_for: impl Fn(OwnedRightType) -> Result<ExplicitSeal, Error>,
+    ) -> Result<Option<Transition>, Error> {
+        let values = self
+            .known_coins()
+            .chain(self.known_inflation())
+            .filter(|coin| closing.contains(&coin.seal));
+        let rights = self
+            .known_rights()
+            .filter(|right| closing.contains(&right.seal));
+
+        let mut parent = ParentOwnedRights::default();
+        let mut inputs = BTreeMap::<OwnedRightType, Vec<value::Revealed>>::new();
+        let mut declarative = BTreeSet::<OwnedRightType>::new();
+        for (outpoint, state) in values
+            .map(|coin| (coin.outpoint, Some(coin.state)))
+            .chain(rights.map(|right| (right.outpoint, None)))
+        {
+            let ty = OwnedRightType::try_from(outpoint.ty)
+                .expect("asset state contains only RGB20 rights");
+            match state {
+                Some(state) => inputs.entry(ty).or_default().push(state),
+                None => {
+                    declarative.insert(ty);
+                }
+            }
+            parent
+                .entry(outpoint.node_id)
+                .or_insert_with(|| empty!())
+                .entry(ty.into())
+                .or_insert_with(|| empty!())
+                .push(outpoint.no);
+        }
+        if parent.is_empty() {
+            return Ok(None);
+        }
+
+        let mut owned_rights = BTreeMap::new();
+        for (ty, inputs) in inputs {
+            let seal = seal_for(ty)?;
+            let sum =
+                checked_sum(inputs.iter().map(|state| state.value)).ok_or(Error::AmountOverflow)?;
+            owned_rights.insert(
+                ty.into(),
+                TypedAssignments::zero_balanced(inputs, bmap! { seal.into() => sum }, empty!()),
+            );
+        }
+        for ty in declarative {
+            owned_rights.insert(ty.into(), right_assignment(seal_for(ty)?));
+        }
+
+        let transition = Transition::with(
+            TransitionType::RightsSplit,
+            empty!(),
+            empty!(),
+            owned_rights.into(),
+            empty!(),
+            parent,
+        );
+
+        Ok(Some(transition))
     }
 
     /// Creates a fungible asset-specific state transition (i.e. RGB-20
     /// schema-based) given an asset information, inputs and desired outputs
+    ///
+    /// Both `payment` beneficiaries and `change` may be defined over outputs
+    /// of the witness transaction which is not created yet: with
+    /// [`SealEndpoint::WitnessVout`] for the beneficiaries and with seals
+    /// without txid for the change. These are resolved to the witness
+    /// transaction outputs once the transition is added to the asset. A
+    /// witness output can't be shared by a beneficiary and the change.
+    ///
+    /// Asset assignments of the transition are ordered by their confidential
+    /// seals, so the index of each assignment, which is used for spending it,
+    /// depends only on the set of the transfer outputs. Value blinding
+    /// factors are random.
     pub fn transfer(
         &self,
         inputs: BTreeSet<OutPoint>,
         payment: EndpointValueMap,
         change: SealValueMap,
     ) -> Result<Transition, Error> {
+        let witness_vouts = payment
+            .keys()
+            .filter_map(|endpoint| match endpoint {
+                SealEndpoint::WitnessVout { vout, .. } => Some(*vout),
+                SealEndpoint::ConcealedUtxo(_) => None,
+            })
+            .collect::<BTreeSet<_>>();
+        if let Some(seal) = change
+            .keys()
+            .find(|seal| seal.txid.is_none() && witness_vouts.contains(&seal.vout))
+        {
+            return Err(Error::WitnessVoutConflict(seal.vout));
+        }
+
         // Collecting all input allocations
         let mut input_usto = Vec::<OwnedValue>::new();
         for outpoint in inputs {
@@ -122,18 +627,17 @@ impl Asset {
         }
         // Computing sum of inputs
         let input_amounts: Vec<_> = input_usto.iter().map(|coin| coin.state).collect();
-        let total_inputs = input_amounts
-            .iter()
-            .fold(0u64, |acc, coin| acc + coin.value);
-        let total_outputs = change.sum() + payment.sum();
+        let total_inputs = checked_sum(input_amounts.iter().map(|coin| coin.value))
+            .ok_or(Error::AmountOverflow)?;
+        let total_outputs = checked_sum(change.values().chain(payment.values()).copied())
+            .ok_or(Error::AmountOverflow)?;
 
         if total_inputs != total_outputs {
             Err(Error::InputsNotEqualOutputs)?
         }
 
         let assignments = type_map! {
-            OwnedRightType::Assets =>
-            TypedAssignments::zero_balanced(input_amounts, change, payment)
+            OwnedRightType::Assets => canonical_assignments(&input_amounts, change, payment)
         };
 
         let mut parent = ParentOwnedRights::default();
@@ -157,4 +661,868 @@ impl Asset {
 
         Ok(transition)
     }
+
+    /// Creates transfer state transition assigning asset to the outputs of the
+    /// witness transaction, given as `payment` and `change` maps from output
+    /// numbers to the assigned amounts. Seals are closed with the provided
+    /// `method` and use random blinding factors.
+    ///
+    /// This is the common wallet pattern of paying to fresh outputs of the
+    /// transaction which also anchors the transfer.
+    pub fn transfer_to_witness(
+        &self,
+        inputs: BTreeSet<OutPoint>,
+        payment: BTreeMap<u32, AtomicValue>,
+        change: BTreeMap<u32, AtomicValue>,
+        method: impl Into<CloseMethod>,
+    ) -> Result<Transition, Error> {
+        let method = method.into();
+        let seal = |vout| {
+            seal::Revealed::from(ExplicitSeal {
+                method,
+                txid: None,
+                vout,
+            })
+        };
+        let payment = payment
+            .into_iter()
+            .map(|(vout, value)| (SealEndpoint::from(seal(vout)), value))
+            .collect();
+        let change = change
+            .into_iter()
+            .map(|(vout, value)| (seal(vout), value))
+            .collect();
+        self.transfer(inputs, payment, change)
+    }
+
+    /// Creates transfer state transition like [`Asset::transfer`], but with
+    /// the amounts assigned to the `payment` beneficiaries concealed as
+    /// Pedersen commitments. Change allocations are kept revealed.
+    ///
+    /// Returns the transition together with the revealed amounts and blinding
+    /// factors of the beneficiary allocations, which must be kept by the
+    /// sender for its records.
+    pub fn transfer_confidential(
+        &self,
+        inputs: BTreeSet<OutPoint>,
+        payment: EndpointValueMap,
+        change: SealValueMap,
+    ) -> Result<(Transition, BTreeMap<SealEndpoint, value::Revealed>), Error> {
+        let beneficiaries = payment
+            .keys()
+            .map(SealEndpoint::commit_conceal)
+            .collect::<BTreeSet<_>>();
+        let mut transition = self.transfer(inputs, payment, change)?;
+
+        let mut revealed = BTreeMap::new();
+        for (ty, assignments) in transition.owned_rights_mut().iter_mut() {
+            if OwnedRightType::try_from(*ty) != Ok(OwnedRightType::Assets) {
+                continue;
+            }
+            if let TypedAssignments::Value(assignments) = assignments {
+                for assignment in assignments.iter_mut() {
+                    *assignment = match assignment.clone() {
+                        Assignment::ConfidentialSeal { seal, state }
+                            if beneficiaries.contains(&seal) =>
+                        {
+                            revealed.insert(SealEndpoint::ConcealedUtxo(seal), state);
+                            Assignment::Confidential {
+                                seal,
+                                state: state.commit_conceal(),
+                            }
+                        }
+                        Assignment::Revealed { seal, state }
+                            if beneficiaries.contains(&seal.commit_conceal()) =>
+                        {
+                            revealed.insert(SealEndpoint::from(seal), state);
+                            Assignment::ConfidentialState {
+                                seal,
+                                state: state.commit_conceal(),
+                            }
+                        }
+                        assignment => assignment,
+                    };
+                }
+            }
+        }
+
+        Ok((transition, revealed))
+    }
+
+    /// Selects outputs with known allocations which cover `target` amount
+    /// using the provided coin selection `strategy`
+    pub fn select_coins(
+        &self,
+        target: AtomicValue,
+        strategy: CoinSelection,
+    ) -> Result<BTreeSet<OutPoint>, Error> {
+        let mut candidates = BTreeMap::<OutPoint, AtomicValue>::new();
+        for coin in self.known_coins() {
+            let value = candidates.entry(coin.seal).or_default();
+            *value = value
+                .checked_add(coin.state.value)
+                .ok_or(Error::AmountOverflow)?;
+        }
+        let available = checked_sum(candidates.values().copied()).ok_or(Error::AmountOverflow)?;
+        if available < target {
+            return Err(Error::InsufficientFunds {
+                available,
+                requested: target,
+            });
+        }
+        strategy
+            .select(&candidates, target)
+            .ok_or(Error::NoExactMatch(target))
+    }
+
+    /// Transfers asset to the `payment` beneficiaries, selecting inputs among
+    /// known allocations with the provided coin selection `strategy`. Any
+    /// excess value is assigned to the `change` seal.
+    pub fn transfer_selected(
+        &self,
+        strategy: CoinSelection,
+        payment: EndpointValueMap,
+        change: seal::Revealed,
+    ) -> Result<Transition, Error> {
+        let target = checked_sum(payment.values().copied()).ok_or(Error::AmountOverflow)?;
+        let inputs = self.select_coins(target, strategy)?;
+        let total_inputs = checked_sum(
+            inputs
+                .iter()
+                .flat_map(|outpoint| self.outpoint_coins(*outpoint))
+                .map(|coin| coin.state.value),
+        )
+        .ok_or(Error::AmountOverflow)?;
+        let mut change_map = SealValueMap::new();
+        if total_inputs > target {
+            change_map.insert(change, total_inputs - target);
+        }
+        self.transfer(inputs, payment, change_map)
+    }
+
+    /// Composes state transfer consignment which can be sent to the
+    /// beneficiaries of the transition `bundle` committed to the witness
+    /// transaction with `anchor`.
+    ///
+    /// The consignment includes all ancestor transitions of the `bundle` taken
+    /// from the `source` consignment together with their anchors. Only the
+    /// state assigned to the provided `endpoints` is left revealed.
+    pub fn compose_transfer<T>(
+        &self,
+        source: &InmemConsignment<T>,
+        bundle: TransitionBundle,
+        anchor: Anchor<lnpbp4::MerkleProof>,
+        endpoints: &BTreeSet<SealEndpoint>,
+    ) -> Result<StateTransfer, Error>
+    where
+        T: ConsignmentType,
+    {
+        let contract_id = source.contract_id();
+        if contract_id != self.contract_id() {
+            return Err(Error::ContractMismatch(contract_id));
+        }
+        let genesis_id = source.genesis().node_id();
+
+        let mut ancestors = BTreeSet::<BundleId>::new();
+        let mut queue = bundle
+            .known_transitions()
+            .flat_map(|transition| transition.parent_owned_rights().keys())
+            .copied()
+            .collect::<Vec<_>>();
+        let mut visited = BTreeSet::new();
+        while let Some(node_id) = queue.pop() {
+            if node_id == genesis_id || !visited.insert(node_id) {
+                continue;
+            }
+            let (_, ancestor_bundle) = source
+                .anchored_bundles()
+                .find(|(_, bundle)| bundle.is_revealed(node_id))
+                .ok_or(Error::AncestorAbsent(node_id))?;
+            let transition = source
+                .transition_by_id(node_id)
+                .map_err(|_| Error::AncestorAbsent(node_id))?;
+            queue.extend(transition.parent_owned_rights().keys().copied());
+            ancestors.insert(ancestor_bundle.bundle_id());
+        }
+
+        let bundle_id = bundle.bundle_id();
+        let mut anchored_bundles = source
+            .anchored_bundles()
+            .filter(|(_, bundle)| ancestors.contains(&bundle.bundle_id()))
+            .cloned()
+            .collect::<Vec<_>>();
+        anchored_bundles.push((anchor, bundle));
+        let endseals = endpoints
+            .iter()
+            .map(|endpoint| (bundle_id, *endpoint))
+            .collect();
+
+        let mut consignment = StateTransfer::with(
+            source.schema().clone(),
+            source.root_schema().cloned(),
+            source.genesis().clone(),
+            endseals,
+            anchored_bundles
+                .try_into()
+                .map_err(|_| Error::TooManyBundles)?,
+            source
+                .state_extensions()
+                .cloned()
+                .collect::<Vec<_>>()
+                .try_into()
+                .map_err(|_| Error::TooManyBundles)?,
+        );
+        consignment
+            .finalize(endpoints)
+            .map_err(|_| Error::EmptyBundle)?;
+
+        Ok(consignment)
+    }
+
+    /// Constructs parent owned rights closing declarative right of type `ty`
+    /// assigned to the `closing` outpoint
+    fn right_parent(
+        &self,
+        closing: OutPoint,
+        ty: OwnedRightType,
+    ) -> Result<ParentOwnedRights, Error> {
+        let right = self
+            .known_rights()
+            .find(|right| right.seal == closing && right.outpoint.ty == ty as u16)
+            .ok_or(Error::UnrelatedInput(closing))?;
+
+        let mut parent = ParentOwnedRights::default();
+        parent
+            .entry(right.outpoint.node_id)
+            .or_insert_with(|| empty!())
+            .entry(ty.into())
+            .or_insert_with(|| empty!())
+            .push(right.outpoint.no);
+        Ok(parent)
+    }
+
+    /// Collects state of the inflation rights assigned to the `closing`
+    /// outpoints and constructs parent owned rights closing them. Fails if
+    /// some of the outpoints do not have known inflation rights.
+    fn inflation_parent(
+        &self,
+        closing: &BTreeSet<OutPoint>,
+    ) -> Result<(Vec<value::Revealed>, ParentOwnedRights), Error> {
+        let mut unknown = closing.clone();
+        let mut inputs = vec![];
+        let mut parent = ParentOwnedRights::default();
+        for right in self
+            .known_inflation()
+            .filter(|right| closing.contains(&right.seal))
+        {
+            unknown.remove(&right.seal);
+            inputs.push(right.state);
+            parent
+                .entry(right.outpoint.node_id)
+                .or_insert_with(|| empty!())
+                .entry(OwnedRightType::Inflation.into())
+                .or_insert_with(|| empty!())
+                .push(right.outpoint.no);
+        }
+        if !unknown.is_empty() {
+            return Err(Error::UnknownSeals(unknown));
+        }
+        Ok((inputs, parent))
+    }
+
+    /// Assembles metadata common to burn and burn & replace operations,
+    /// including history proofs for the allocations known to be assigned to
+    /// the burned UTXOs
+    fn burn_metadata(
+        &self,
+        burned_value: AtomicValue,
+        burned_utxos: &BTreeSet<OutPoint>,
+    ) -> Result<BTreeMap<rgb::schema::FieldType, Vec<data::Revealed>>, Error> {
+        if burned_utxos.is_empty() {
+            return Err(Error::NoBurnedUtxos);
+        }
+
+        let proofs = burned_utxos
+            .iter()
+            .flat_map(|outpoint| self.outpoint_coins(*outpoint))
+            .map(|coin| {
+                let data = HistoryProof {
+                    outpoint: coin.outpoint,
+                    state: coin.state,
+                }
+                .strict_serialize()
+                .expect("in-memory strict encoding of history proof can't fail");
+                data::Revealed::Bytes(data)
+            })
+            .collect::<Vec<_>>();
+        let format = if proofs.is_empty() {
+            HistoryProofFormat::ProofAbsent
+        } else {
+            HistoryProofFormat::ProofV1
+        };
+
+        let mut metadata = type_map! {
+            FieldType::BurnedSupply => field!(U64, burned_value),
+            FieldType::BurnUtxo => burned_utxos
+                .iter()
+                .map(|outpoint| {
+                    let data = outpoint
+                        .strict_serialize()
+                        .expect("in-memory strict encoding of outpoint can't fail");
+                    data::Revealed::Bytes(data)
+                })
+                .collect::<Vec<_>>(),
+            FieldType::HistoryProofFormat => field!(U8, format as u8)
+        };
+        if !proofs.is_empty() {
+            metadata.insert(FieldType::HistoryProof.into(), proofs);
+        }
+        Ok(metadata)
+    }
+}
+
+/// Constructs asset assignments to the `change` and `payment` outputs, which
+/// blinding factors balance the `inputs`, ordered by the confidential seals.
+///
+/// Blinding factors are random, so the confidential amounts can't be
+/// recovered from the ones of the spent allocations, and only the ordering of
+/// the assignments is deterministic.
+fn canonical_assignments(
+    inputs: &[value::Revealed],
+    change: SealValueMap,
+    payment: EndpointValueMap,
+) -> TypedAssignments {
+    let mut outputs = change
+        .into_iter()
+        .map(|(seal, value)| (seal.commit_conceal(), Some(seal), value))
+        .chain(payment.into_iter().map(|(endpoint, value)| match endpoint {
+            SealEndpoint::ConcealedUtxo(seal) => (seal, None, value),
+            SealEndpoint::WitnessVout {
+                method,
+                vout,
+                blinding,
+            } => {
+                let seal = seal::Revealed {
+                    method,
+                    txid: None,
+                    vout,
+                    blinding,
+                };
+                (seal.commit_conceal(), Some(seal), value)
+            }
+        }))
+        .collect::<Vec<_>>();
+    outputs.sort_by_key(|(concealed, ..)| *concealed);
+
+    let secp = secp256k1zkp::Secp256k1::with_caps(secp256k1zkp::ContextFlag::Commit);
+    let mut rng = secp256k1zkp::rand::thread_rng();
+    let mut factors = (1..outputs.len())
+        .map(|_| secp256k1zkp::SecretKey::new(&secp, &mut rng))
+        .collect::<Vec<_>>();
+    if !outputs.is_empty() {
+        let correction = secp
+            .blind_sum(
+                inputs.iter().map(|input| input.blinding.into()).collect(),
+                factors.clone(),
+            )
+            .expect("secp256k1zkp failure has negligible probability");
+        factors.push(correction);
+    }
+
+    let assignments = outputs
+        .into_iter()
+        .zip(factors)
+        .map(|((concealed, revealed, value), blinding)| {
+            let state = value::Revealed {
+                value,
+                blinding: blinding.into(),
+            };
+            match revealed {
+                Some(seal) => Assignment::Revealed { seal, state },
+                None => Assignment::ConfidentialSeal {
+                    seal: concealed,
+                    state,
+                },
+            }
+        })
+        .collect();
+    TypedAssignments::Value(assignments)
+}
+
+/// Constructs assignment of a declarative right to the provided seal
+fn right_assignment(seal: ExplicitSeal) -> TypedAssignments {
+    TypedAssignments::Void(vec![Assignment::Revealed {
+        seal: seal.into(),
+        state: data::Void(),
+    }])
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::hashes::Hash;
+    use bitcoin::Txid;
+    use proptest::prelude::*;
+    use rgb::fungible::allocation::{AllocatedValue, OutpointValue};
+
+    use super::*;
+    use crate::fixtures::{builder, contract, outpoint};
+
+    fn asset() -> Asset {
+        let contract = contract(
+            8,
+            vec![OutpointValue {
+                value: 100,
+                outpoint: outpoint(1),
+            }],
+            bmap! { outpoint(1) => 1000, outpoint(2) => 500 },
+        );
+        Asset::try_from(&contract).unwrap()
+    }
+
+    #[test]
+    fn inflate_allowance() {
+        let asset = asset();
+        let allocations = vec![AllocatedValue {
+            value: 300,
+            seal: ExplicitSeal::from(outpoint(3)),
+        }];
+
+        let transition = asset
+            .inflate(
+                bset![outpoint(2)],
+                bmap! { ExplicitSeal::from(outpoint(4)) => 200 }.into(),
+                allocations.clone(),
+            )
+            .unwrap();
+        assert_eq!(transition.metadata().u64(FieldType::IssuedSupply), vec![
+            300
+        ]);
+        assert_eq!(transition.parent_outputs().len(), 1);
+
+        assert_eq!(
+            asset.inflate(bset![outpoint(2)], empty!(), allocations.clone()),
+            Err(Error::SupplyMismatch {
+                assigned: 500,
+                requested: 300
+            })
+        );
+        assert_eq!(
+            asset.inflate(bset![outpoint(2), outpoint(5)], empty!(), allocations),
+            Err(Error::UnknownSeals(bset![outpoint(5)]))
+        );
+        assert_eq!(
+            asset.inflate(bset![outpoint(2)], empty!(), vec![AllocatedValue {
+                value: 600,
+                seal: ExplicitSeal::from(outpoint(3)),
+            }]),
+            Err(Error::InsufficientIssueAllowance {
+                allowed: 500,
+                requested: 600
+            })
+        );
+    }
+
+    #[test]
+    fn inflate_amount_overflow() {
+        let asset = asset();
+        assert_eq!(
+            asset.inflate(bset![outpoint(2)], empty!(), vec![
+                AllocatedValue {
+                    value: u64::MAX,
+                    seal: ExplicitSeal::from(outpoint(3)),
+                },
+                AllocatedValue {
+                    value: 1,
+                    seal: ExplicitSeal::from(outpoint(4)),
+                }
+            ]),
+            Err(Error::AmountOverflow)
+        );
+        assert_eq!(
+            asset.inflate(
+                bset![outpoint(2)],
+                bmap! { ExplicitSeal::from(outpoint(4)) => u64::MAX }.into(),
+                vec![AllocatedValue {
+                    value: 300,
+                    seal: ExplicitSeal::from(outpoint(3)),
+                }]
+            ),
+            Err(Error::AmountOverflow)
+        );
+    }
+
+    #[test]
+    fn transfer_inflation_right() {
+        let asset = asset();
+
+        assert_eq!(
+            asset.transfer_inflation_right(bset![outpoint(1), outpoint(2)], bmap! {
+                ExplicitSeal::from(outpoint(3)) => 1000
+            }),
+            Err(Error::SupplyMismatch {
+                assigned: 1500,
+                requested: 1000
+            })
+        );
+        assert_eq!(
+            asset.transfer_inflation_right(bset![outpoint(5)], empty!()),
+            Err(Error::UnknownSeals(bset![outpoint(5)]))
+        );
+
+        let transition = asset
+            .transfer_inflation_right(bset![outpoint(1), outpoint(2)], bmap! {
+                ExplicitSeal::from(outpoint(3)) => 1200,
+                ExplicitSeal::from(outpoint(4)) => 300
+            })
+            .unwrap();
+        assert_eq!(transition.parent_outputs().len(), 2);
+        assert!(transition
+            .owned_rights_by_type(OwnedRightType::Assets.into())
+            .is_none());
+        let mut caps = transition
+            .owned_rights_by_type(OwnedRightType::Inflation.into())
+            .unwrap()
+            .filter_revealed_state_values()
+            .into_iter()
+            .map(|state| state.value)
+            .collect::<Vec<_>>();
+        caps.sort_unstable();
+        assert_eq!(caps, vec![300, 1200]);
+    }
+
+    #[test]
+    fn transfer_confidential() {
+        let asset = asset();
+        let beneficiary = seal::Revealed::from(outpoint(5));
+        let witness = SealEndpoint::WitnessVout {
+            method: CloseMethod::TapretFirst,
+            vout: 1,
+            blinding: 0x5EA1,
+        };
+        let change = seal::Revealed::from(outpoint(6));
+
+        let (transition, revealed) = asset
+            .transfer_confidential(
+                bset! { outpoint(1) },
+                bmap! { SealEndpoint::from(beneficiary.commit_conceal()) => 60, witness => 30 },
+                bmap! { change => 10 },
+            )
+            .unwrap();
+
+        let assignments = transition
+            .owned_rights_by_type(OwnedRightType::Assets.into())
+            .unwrap();
+        assert_eq!(assignments.to_confidential_state_pedersen().len(), 3);
+        let values = assignments.filter_revealed_state_values();
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].value, 10);
+        assert_eq!(revealed.len(), 2);
+        assert_eq!(
+            revealed[&SealEndpoint::from(beneficiary.commit_conceal())].value,
+            60
+        );
+        assert_eq!(revealed[&witness].value, 30);
+    }
+
+    #[test]
+    fn transfer_to_witness() {
+        let mut asset = asset();
+        let transition = asset
+            .transfer_to_witness(
+                bset! { outpoint(1) },
+                bmap! { 1 => 70 },
+                bmap! { 0 => 30 },
+                CloseMethod::OpretFirst,
+            )
+            .unwrap();
+
+        let witness_txid =
+            Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap();
+        asset.add_transition(witness_txid, transition);
+        asset.reindex();
+        assert!(asset.allocations_at(outpoint(1)).is_empty());
+        assert_eq!(
+            asset.allocations_at(OutPoint::new(witness_txid, 1))[0]
+                .state
+                .value,
+            70
+        );
+        assert_eq!(
+            asset.allocations_at(OutPoint::new(witness_txid, 0))[0]
+                .state
+                .value,
+            30
+        );
+
+        assert_eq!(
+            self::asset().transfer_to_witness(
+                bset! { outpoint(1) },
+                bmap! { 1 => 70 },
+                bmap! { 1 => 30 },
+                CloseMethod::OpretFirst,
+            ),
+            Err(Error::WitnessVoutConflict(1))
+        );
+    }
+
+    #[test]
+    fn transfer_amount_overflow() {
+        let payment = bmap! {
+            SealEndpoint::from(seal::Revealed::from(outpoint(3))) => u64::MAX,
+            SealEndpoint::from(seal::Revealed::from(outpoint(4))) => 1
+        };
+        assert_eq!(
+            asset().transfer(bset! { outpoint(1) }, payment.clone(), empty!()),
+            Err(Error::AmountOverflow)
+        );
+        assert_eq!(
+            asset().transfer_selected(
+                CoinSelection::LargestFirst,
+                payment,
+                seal::Revealed::from(outpoint(5))
+            ),
+            Err(Error::AmountOverflow)
+        );
+    }
+
+    #[test]
+    fn max_supply() {
+        assert_eq!(asset().max_supply(), 1600);
+
+        let contract = contract(
+            8,
+            vec![OutpointValue {
+                value: 100,
+                outpoint: outpoint(1),
+            }],
+            bmap! { outpoint(1) => u64::MAX, outpoint(2) => u64::MAX },
+        );
+        let asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(asset.max_supply(), u64::MAX);
+    }
+
+    #[test]
+    fn transfer_renomination_right() {
+        let contract = builder().renomination(outpoint(2)).build().unwrap();
+        let asset = Asset::try_from(&contract).unwrap();
+        let renomination = OwnedRightType::Renomination.into();
+
+        assert_eq!(
+            asset.transfer_renomination_right(outpoint(1), None),
+            Err(Error::UnrelatedInput(outpoint(1)))
+        );
+
+        let transition = asset
+            .transfer_renomination_right(outpoint(2), Some(ExplicitSeal::from(outpoint(3))))
+            .unwrap();
+        assert_eq!(
+            transition.transition_type(),
+            TransitionType::RightsSplit as u16
+        );
+        assert_eq!(transition.parent_outputs_by_type(renomination).len(), 1);
+        assert_eq!(
+            transition
+                .owned_rights_by_type(renomination)
+                .unwrap()
+                .revealed_seal_outputs()
+                .len(),
+            1
+        );
+
+        let transition = asset
+            .transfer_renomination_right(outpoint(2), None)
+            .unwrap();
+        assert_eq!(transition.parent_outputs_by_type(renomination).len(), 1);
+        assert!(transition.owned_rights().is_empty());
+    }
+
+    #[test]
+    fn split_rights() {
+        let asset = asset();
+        assert_eq!(
+            asset.split_rights(outpoint(1), bmap! {
+                OwnedRightType::Assets => ExplicitSeal::from(outpoint(3))
+            }),
+            Err(Error::NoSealForRight(OwnedRightType::Inflation))
+        );
+        assert_eq!(
+            asset.split_rights(outpoint(7), empty!()),
+            Err(Error::UnrelatedInput(outpoint(7)))
+        );
+
+        let transition = asset
+            .split_rights(outpoint(1), bmap! {
+                OwnedRightType::Assets => ExplicitSeal::from(outpoint(3)),
+                OwnedRightType::Inflation => ExplicitSeal::from(outpoint(4))
+            })
+            .unwrap();
+        assert_eq!(transition.parent_outputs().len(), 2);
+        let inflation = transition
+            .owned_rights_by_type(OwnedRightType::Inflation.into())
+            .unwrap()
+            .filter_revealed_state_values();
+        assert_eq!(inflation.len(), 1);
+        assert_eq!(inflation[0].value, 1000);
+    }
+
+    #[test]
+    fn blank_transition() {
+        let contract = contract(
+            8,
+            vec![
+                OutpointValue {
+                    value: 100,
+                    outpoint: outpoint(1),
+                },
+                OutpointValue {
+                    value: 50,
+                    outpoint: outpoint(1),
+                },
+                OutpointValue {
+                    value: 25,
+                    outpoint: outpoint(2),
+                },
+            ],
+            bmap! { outpoint(1) => 1000, outpoint(2) => 500 },
+        );
+        let asset = Asset::try_from(&contract).unwrap();
+        let change = ExplicitSeal::from(outpoint(3));
+
+        assert_eq!(
+            asset.blank_transition(&bset! { outpoint(7) }, change),
+            Ok(None)
+        );
+
+        let transition = asset
+            .blank_transition(&bset! { outpoint(1), outpoint(2), outpoint(7) }, change)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            transition.transition_type(),
+            TransitionType::RightsSplit as u16
+        );
+        assert_eq!(transition.parent_outputs().len(), 5);
+        for (ty, value) in [(OwnedRightType::Assets, 175), (OwnedRightType::Inflation, 1500)] {
+            let values = transition
+                .owned_rights_by_type(ty.into())
+                .unwrap()
+                .filter_revealed_state_values();
+            assert_eq!(values.len(), 1);
+            assert_eq!(values[0].value, value);
+        }
+    }
+
+    #[test]
+    fn transfer_ordering() {
+        let asset = asset();
+        let change = seal::Revealed::new(CloseMethod::OpretFirst, outpoint(2));
+        let payment = bmap! {
+            SealEndpoint::from(seal::Revealed::new(CloseMethod::TapretFirst, outpoint(3))) => 20,
+            SealEndpoint::from(seal::Revealed::new(CloseMethod::TapretFirst, outpoint(4))) => 30,
+            SealEndpoint::WitnessVout {
+                method: CloseMethod::TapretFirst,
+                vout: 1,
+                blinding: 13,
+            } => 10
+        };
+        let transfer = || {
+            asset
+                .transfer(
+                    bset! { outpoint(1) },
+                    payment.clone(),
+                    bmap! { change => 40 },
+                )
+                .unwrap()
+        };
+
+        let transition = transfer();
+        let assignments = transition
+            .owned_rights_by_type(OwnedRightType::Assets.into())
+            .unwrap();
+        let seals = assignments.to_confidential_seals();
+        assert_eq!(
+            seals,
+            transfer()
+                .owned_rights_by_type(OwnedRightType::Assets.into())
+                .unwrap()
+                .to_confidential_seals()
+        );
+        assert_eq!(seals.len(), 4);
+        assert!(seals.windows(2).all(|pair| pair[0] < pair[1]));
+        let index = seals
+            .iter()
+            .position(|seal| *seal == change.commit_conceal())
+            .unwrap();
+        assert_eq!(
+            assignments.as_revealed_state_values().unwrap()[index].value,
+            40
+        );
+    }
+
+    fn burn_seal(no: u8) -> OutPoint { OutPoint::new(Txid::from_inner([no; 32]), 0) }
+
+    fn epoch_asset() -> Asset {
+        let contract = builder().epoch(outpoint(3)).build().unwrap();
+        let mut asset = Asset::try_from(&contract).unwrap();
+        let epoch = asset
+            .open_epoch(outpoint(3), None, Some(ExplicitSeal::from(burn_seal(0))))
+            .unwrap();
+        asset.add_transition(Txid::from_inner([0xFF; 32]), epoch);
+        asset.reindex();
+        asset
+    }
+
+    proptest! {
+        // Each case issues a new contract, which is slow
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn epoch_burn_budget(
+            ops in prop::collection::vec((1u64..1000, prop::option::of(1u64..1500)), 1..8)
+        ) {
+            let mut asset = epoch_asset();
+            let (mut burned, mut replaced, mut no) = (0u64, 0u64, 0u8);
+            for (burn, replace) in ops {
+                let closing = burn_seal(no);
+                let next_burn = Some(ExplicitSeal::from(burn_seal(no + 1)));
+                let transition = match replace {
+                    None => asset.burn(closing, burn, bset! { outpoint(1) }, next_burn).unwrap(),
+                    Some(replace) => {
+                        let res = asset.burn_and_replace(
+                            closing,
+                            burn,
+                            bset! { outpoint(1) },
+                            next_burn,
+                            bmap! { seal::Revealed::from(outpoint(10)) => replace },
+                            empty!(),
+                        );
+                        if replaced + replace > burned + burn {
+                            prop_assert_eq!(res, Err(Error::EpochBudgetExceeded {
+                                epoch: 0,
+                                budget: burned + burn - replaced,
+                                replaced: replace,
+                            }));
+                            continue;
+                        }
+                        replaced += replace;
+                        res.unwrap()
+                    }
+                };
+                burned += burn;
+                no += 1;
+                asset.add_transition(Txid::from_inner([0x80 + no; 32]), transition);
+                asset.reindex();
+
+                let totals = asset.epoch_totals();
+                prop_assert_eq!(totals.len(), 1);
+                prop_assert_eq!(totals[0].operations, no as u32);
+                prop_assert_eq!(totals[0].burned, burned);
+                prop_assert_eq!(totals[0].replaced, replaced);
+                prop_assert_eq!(totals[0].replace_budget(), burned - replaced);
+            }
+        }
+    }
 }
@@ -0,0 +1,149 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+use std::path::Path;
+
+use bitcoin::consensus::deserialize;
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::{OutPoint, Transaction, Txid};
+use bitcoin_onchain::{ResolveTx, TxResolverError};
+use rgb::validation::{Status, Validity};
+use rgb::{ConsignmentType, InmemConsignment, Validator};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+
+use crate::schema::Subschema;
+use crate::{Asset, Error, ResolverError, WitnessResolver};
+
+/// Report on the complete consignment validation, combining RGB consensus
+/// validation against the schema and witness transactions with RGB20-specific
+/// semantic validation
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct ValidationReport {
+    /// RGB20 subschema used by the consignment, if it is known
+    pub subschema: Option<Subschema>,
+
+    /// Status of the RGB consensus validation
+    pub status: Status,
+
+    /// Violation of RGB20 rules detected by the semantic validation, if any
+    #[cfg_attr(feature = "serde", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub rgb20_failure: Option<Error>,
+}
+
+impl ValidationReport {
+    /// Returns validity of the consignment, taking into account both RGB
+    /// consensus validation and RGB20 semantic validation
+    pub fn validity(&self) -> Validity {
+        if self.rgb20_failure.is_some() {
+            return Validity::Invalid;
+        }
+        self.status.validity()
+    }
+
+    /// Detects whether the consignment is fully valid
+    pub fn is_valid(&self) -> bool { self.validity() == Validity::Valid }
+}
+
+impl Asset {
+    /// Performs complete validation of the consignment: RGB consensus
+    /// validation, using `resolver` for retrieving witness transactions, and
+    /// RGB20-specific semantic validation.
+    pub fn validate_consignment<T, R>(
+        consignment: &InmemConsignment<T>,
+        resolver: &R,
+    ) -> ValidationReport
+    where
+        T: ConsignmentType,
+        R: ResolveTx,
+    {
+        let status = Validator::validate(consignment, resolver);
+        let (subschema, rgb20_failure) = match Asset::validate(consignment) {
+            Ok(subschema) => (Some(subschema), None),
+            Err(err) => (
+                Subschema::from_schema_id(consignment.schema_id()),
+                Some(err),
+            ),
+        };
+        ValidationReport {
+            subschema,
+            status,
+            rgb20_failure,
+        }
+    }
+}
+
+/// Witness transaction resolver working with a pre-defined set of
+/// transactions, for instance loaded from a file. Useful for offline
+/// validation.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct OfflineResolver {
+    transactions: BTreeMap<Txid, Transaction>,
+}
+
+impl OfflineResolver {
+    /// Constructs resolver without any known transactions
+    pub fn new() -> OfflineResolver { OfflineResolver::default() }
+
+    /// Loads transactions from a file containing hex-encoded raw transactions,
+    /// one per line. Empty lines are ignored.
+    pub fn load(path: impl AsRef<Path>) -> Result<OfflineResolver, io::Error> {
+        let file = io::BufReader::new(std::fs::File::open(path)?);
+        let mut resolver = OfflineResolver::new();
+        for line in file.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let tx = Vec::<u8>::from_hex(line)
+                .ok()
+                .and_then(|data| deserialize::<Transaction>(&data).ok())
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "invalid raw transaction data")
+                })?;
+            resolver.insert(tx);
+        }
+        Ok(resolver)
+    }
+
+    /// Adds transaction to the set of known transactions
+    pub fn insert(&mut self, tx: Transaction) { self.transactions.insert(tx.txid(), tx); }
+}
+
+impl ResolveTx for OfflineResolver {
+    fn resolve_tx(&self, txid: Txid) -> Result<Transaction, TxResolverError> {
+        self.transactions
+            .get(&txid)
+            .cloned()
+            .ok_or_else(|| TxResolverError::with(txid))
+    }
+}
+
+impl WitnessResolver for OfflineResolver {
+    /// Detects outputs spent by the known transactions; returns `None` for
+    /// all other outputs, since their status can't be known offline
+    fn is_spent(&self, outpoint: OutPoint) -> Result<Option<bool>, ResolverError> {
+        Ok(self
+            .transactions
+            .values()
+            .flat_map(|tx| &tx.input)
+            .any(|txin| txin.previous_output == outpoint)
+            .then(|| true))
+    }
+}
@@ -0,0 +1,398 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Canonical test vectors for interoperating implementations of RGB20.
+//!
+//! The vectors consist of the genesis of a reference asset and a state
+//! transition of each type defined by RGB20 schema, together with their
+//! strict encodings and ids. All the data – timestamps, seals, amounts and
+//! blinding factors – are fixed, so the vectors are identical across runs,
+//! platforms and releases of this crate (unless the consensus encoding
+//! changes). Bindings and alternative implementations (e.g. in JS or Python)
+//! may obtain them with [`vectors`] (or in JSON via the `serde` feature) and
+//! check that they produce the same data.
+//!
+//! The transitions are not a valid history of the asset: each of them spends
+//! the state defined by the genesis (or, for burns, by the epoch transition)
+//! and is not anchored to any witness transaction.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use bitcoin::hashes::Hash;
+use bitcoin::{OutPoint, Txid};
+use chrono::{TimeZone, Utc};
+use commit_verify::CommitConceal;
+use lnpbp::chain::Chain;
+use rgb::prelude::*;
+use rgb::secp256k1zkp;
+use seals::txout::CloseMethod;
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+use strict_encoding::StrictEncode;
+
+use crate::schema::{FieldType, OwnedRightType, TransitionType};
+use crate::{Asset, AssetName, Rgb20Builder, Subschema, Ticker};
+
+/// Genesis timestamp of the reference asset (2023-01-01 00:00:00 UTC)
+pub const TIMESTAMP: i64 = 1_672_531_200;
+
+/// Ticker of the reference asset
+pub const TICKER: &str = "VECT";
+
+/// Name of the reference asset
+pub const NAME: &str = "RGB20 test vector asset";
+
+/// Precision of the reference asset
+pub const PRECISION: u8 = 8;
+
+/// Supply issued by the genesis of the reference asset
+pub const ISSUED_SUPPLY: AtomicValue = 100_000_000;
+
+/// Supply which may be issued by the secondary issue
+pub const INFLATION: AtomicValue = 50_000_000;
+
+/// Supply burned by the burn and burn & replace transitions
+pub const BURNED_SUPPLY: AtomicValue = 1_000_000;
+
+/// Blinding factor used by all seals defined by the transitions
+pub const SEAL_BLINDING: u64 = 0x5247_4232_3056_4543;
+
+/// Transaction id of all the outpoints used by the vectors
+pub fn txid() -> Txid { Txid::from_inner([0x20; 32]) }
+
+/// Outpoint at the output `vout` of the [`txid`] transaction
+pub fn outpoint(vout: u32) -> OutPoint { OutPoint::new(txid(), vout) }
+
+/// Seal defined by the transitions over the [`outpoint`]`(vout)`
+pub fn seal(vout: u32) -> seal::Revealed {
+    seal::Revealed {
+        method: CloseMethod::TapretFirst,
+        txid: Some(txid()),
+        vout,
+        blinding: SEAL_BLINDING,
+    }
+}
+
+/// Test vector for a single contract node
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct TestVector {
+    /// Name of the vector: `genesis` or the name of the transition type
+    pub name: String,
+
+    /// Id of the node
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub node_id: NodeId,
+
+    /// Id of the contract the node belongs to
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub contract_id: ContractId,
+
+    /// Strict encoding of the node
+    #[cfg_attr(feature = "serde", serde(with = "As::<serde_with::hex::Hex>"))]
+    pub data: Vec<u8>,
+}
+
+impl TestVector {
+    fn with(
+        name: impl ToString,
+        node: &(impl Node + StrictEncode),
+        contract_id: ContractId,
+    ) -> TestVector {
+        TestVector {
+            name: name.to_string(),
+            node_id: node.node_id(),
+            contract_id,
+            data: node
+                .strict_serialize()
+                .expect("in-memory encoding does not fail"),
+        }
+    }
+}
+
+/// Constructs contract of the reference asset. The asset has all rights
+/// defined by the full RGB20 subschema, each assigned to a distinct output
+/// of the [`txid`] transaction:
+/// - #0: issued supply;
+/// - #1: inflation right;
+/// - #2: renomination right;
+/// - #3: epoch opening right.
+pub fn contract() -> Contract {
+    Rgb20Builder::new(Chain::Testnet3)
+        .subschema(Subschema::Full)
+        .ticker(Ticker::from_str(TICKER).expect("valid ticker"))
+        .name(AssetName::from_str(NAME).expect("valid name"))
+        .precision(PRECISION)
+        .allocate(outpoint(0), ISSUED_SUPPLY)
+        .inflation_right(outpoint(1), INFLATION)
+        .renomination(outpoint(2))
+        .epoch(outpoint(3))
+        .timestamp(Utc.timestamp_opt(TIMESTAMP, 0).unwrap())
+        .build()
+        .expect("reference asset parameters are valid")
+}
+
+/// Constructs the reference asset from the [`contract`]
+pub fn asset() -> Asset { Asset::try_from(&contract()).expect("reference asset is valid") }
+
+/// Constructs state transitions of each of the RGB20 transition types over the
+/// reference asset
+pub fn transitions() -> BTreeMap<TransitionType, Transition> {
+    let mut asset = asset();
+    let genesis_id = asset.genesis().node_id();
+    let right_parent = |ty: OwnedRightType| {
+        let mut parent = ParentOwnedRights::default();
+        parent
+            .entry(genesis_id)
+            .or_insert_with(|| empty!())
+            .insert(ty.into(), vec![0u16]);
+        parent
+    };
+    let right = |vout: u32| {
+        TypedAssignments::Void(vec![Assignment::Revealed {
+            seal: seal(vout),
+            state: data::Void(),
+        }])
+    };
+
+    // Transitions assigning revealed seals are constructed directly, since
+    // the asset API uses random seal blinding factors
+    let issue = Transition::with(
+        TransitionType::Issue,
+        type_map! { FieldType::IssuedSupply => field!(U64, INFLATION) }.into(),
+        empty!(),
+        type_map! {
+            OwnedRightType::Assets => TypedAssignments::Value(vec![Assignment::Revealed {
+                seal: seal(10),
+                state: value::Revealed {
+                    value: INFLATION,
+                    blinding: secp256k1zkp::key::ONE_KEY.into(),
+                },
+            }])
+        }
+        .into(),
+        empty!(),
+        right_parent(OwnedRightType::Inflation),
+    );
+    let epoch = Transition::with(
+        TransitionType::Epoch,
+        empty!(),
+        empty!(),
+        type_map! {
+            OwnedRightType::OpenEpoch => right(11),
+            OwnedRightType::BurnReplace => right(12)
+        }
+        .into(),
+        empty!(),
+        right_parent(OwnedRightType::OpenEpoch),
+    );
+    // Burn right is defined by the epoch transition
+    asset.add_transition(txid(), epoch.clone());
+
+    // Transfers with a single output have deterministic value blinding factor
+    let transfer = asset
+        .transfer(
+            bset! { outpoint(0) },
+            bmap! { SealEndpoint::ConcealedUtxo(seal(13).commit_conceal()) => ISSUED_SUPPLY },
+            empty!(),
+        )
+        .expect("reference transfer is valid");
+    let burn = asset
+        .burn(outpoint(12), BURNED_SUPPLY, bset! { outpoint(0) }, None)
+        .expect("reference burn is valid");
+    let burn_replace = asset
+        .burn_and_replace(
+            outpoint(12),
+            BURNED_SUPPLY,
+            bset! { outpoint(0) },
+            None,
+            bmap! { seal(14) => BURNED_SUPPLY },
+            empty!(),
+        )
+        .expect("reference burn & replace is valid");
+    let renomination = asset
+        .renominate(
+            outpoint(2),
+            Some(Ticker::from_str("VECTB").expect("valid ticker")),
+            None,
+            None,
+            None,
+        )
+        .expect("reference renomination is valid");
+    // Revokes the renomination right
+    let rights_split = asset
+        .transfer_renomination_right(outpoint(2), None)
+        .expect("reference rights split is valid");
+
+    bmap! {
+        TransitionType::Issue => issue,
+        TransitionType::Transfer => transfer,
+        TransitionType::Epoch => epoch,
+        TransitionType::Burn => burn,
+        TransitionType::BurnAndReplace => burn_replace,
+        TransitionType::Renomination => renomination,
+        TransitionType::RightsSplit => rights_split
+    }
+}
+
+/// Returns all test vectors: the reference asset genesis followed by the
+/// transitions in the order of [`TransitionType::ALL`]
+pub fn vectors() -> Vec<TestVector> {
+    let contract = contract();
+    let contract_id = contract.contract_id();
+    let mut vectors = vec![TestVector::with("genesis", contract.genesis(), contract_id)];
+    let transitions = transitions();
+    vectors.extend(
+        TransitionType::ALL
+            .iter()
+            .map(|ty| TestVector::with(ty, &transitions[ty], contract_id)),
+    );
+    vectors
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::hex::ToHex;
+    use strict_encoding::StrictDecode;
+
+    use super::*;
+
+    /// Contract id of the reference asset
+    const CONTRACT_ID: &str = "rgb1ysp8mvnqhkcds9k6vtexu7lmn757zy3l07h9cgpcvvh2s57ggzvq04jup0";
+
+    /// Names, node ids and strict encodings of the vectors
+    const VECTORS: [(&str, &str, &str); 8] = [
+        (
+            "genesis",
+            "9840c853a82e6338205cae7f3f12e1a99ffb7b6ef262da16d8b0bd60b27d0224",
+            concat!(
+                "3d831aae939d05cadc5a47be093650b38fdea4f5ce1b358d636b050f22711cc50100990043497fd7",
+                "f826957108f4a30fd9cec3aeba79972084e90ead01ea3309000000000700746573746e65740b1109",
+                "07040074657374020074629d479c4701000000ec011c000022020000000000000400744254430c00",
+                "5465737420426974636f696e0c0054657374207361746f73686900e1f5050000000043497fd7f826",
+                "957108f4a30fd9cec3aeba79972084e90ead01ea330900000000000101050000000100ee04005645",
+                "435401000100ee17005247423230207465737420766563746f722061737365740300010000080400",
+                "01001300cdb06300000000a00001000300e1f5050000000004000100000100010101202020202020",
+                "2020202020202020202020202020202020202020202020202020020000000e57bc708f47b0cfa000",
+                "01010001010120202020202020202020202020202020202020202020202020202020202020200100",
+                "00001d448af9030249e280f0fa02000000001ee81de57668bfea15742074641027a30b16860d5795",
+                "d991cb5eae8bfd0a3893a10001010001010120202020202020202020202020202020202020202020",
+                "202020202020202020200000000034391588c4fe0b9a00e1f5050000000000000000000000000000",
+                "00000000000000000000000000000000000000000001aa0000010001010120202020202020202020",
+                "2020202020202020202020202020202020202020202003000000da30ff2124db08d70000",
+            ),
+        ),
+        (
+            "Issue",
+            "a8542895ad3ad098337000fff6bcd35eb1ff3f8522e3a92732c2336eb3e48d25",
+            concat!(
+                "a0100100a00001000380f0fa0200000000010024027db260bdb0d816da62f26e7bfb9fa9e1123f7f",
+                "ae5c2038632ea853c840980100a000010000000100a1000101000101012020202020202020202020",
+                "2020202020202020202020202020202020202020200a000000434556303242475280f0fa02000000",
+                "00000000000000000000000000000000000000000000000000000000000000000100000000",
+            ),
+        ),
+        (
+            "Transfer",
+            "57aa61237ff65af5a2c9761bc4150e4c5c0ce0df2eaac1d5a41221266a430ea1",
+            concat!(
+                "00000000010024027db260bdb0d816da62f26e7bfb9fa9e1123f7fae5c2038632ea853c840980100",
+                "a100010000000100a100010100029fdbbfb9ce19d8a6405e6a3cfb3276239ff28e07859573b6720c",
+                "ac4a765c3a0800e1f505000000000000000000000000000000000000000000000000000000000000",
+                "00000000000100000000",
+            ),
+        ),
+        (
+            "Epoch",
+            "e31260938ac50b725a92166c711c733445a4408157dfd0e9abb89bc6da935475",
+            concat!(
+                "a1100000010024027db260bdb0d816da62f26e7bfb9fa9e1123f7fae5c2038632ea853c840980100",
+                "aa00010000000200aa00000100010101202020202020202020202020202020202020202020202020",
+                "20202020202020200b0000004345563032424752ab00000100010101202020202020202020202020",
+                "20202020202020202020202020202020202020200c000000434556303242475200000000",
+            ),
+        ),
+        (
+            "Burn",
+            "05829cba01e5023ba343612c6f5811fe79f259ba9503adc2dcb37ab950f849f2",
+            concat!(
+                "a2100400b00001000340420f0000000000b1000100e0240020202020202020202020202020202020",
+                "2020202020202020202020202020202000000000b2000100e04c0024027db260bdb0d816da62f26e",
+                "7bfb9fa9e1123f7fae5c2038632ea853c84098a100000000e1f50500000000000000000000000000",
+                "0000000000000000000000000000000000000000000001b300010000010100755493dac69bb8abe9",
+                "d0df578140a44534731c716c16925a720bc58a936012e30100ab0001000000000000000000",
+            ),
+        ),
+        (
+            "BurnAndReplace",
+            "72e963f038404edfc44e75a478d6b6a0d767c9100e8fd492db03504cf317fb04",
+            concat!(
+                "a3100500a00001000340420f0000000000b00001000340420f0000000000b1000100e02400202020",
+                "202020202020202020202020202020202020202020202020202020202000000000b2000100e04c00",
+                "24027db260bdb0d816da62f26e7bfb9fa9e1123f7fae5c2038632ea853c84098a100000000e1f505",
+                "000000000000000000000000000000000000000000000000000000000000000000000001b3000100",
+                "00010100755493dac69bb8abe9d0df578140a44534731c716c16925a720bc58a936012e30100ab00",
+                "010000000100a1000101000101012020202020202020202020202020202020202020202020202020",
+                "2020202020200e000000434556303242475240420f00000000000000000000000000000000000000",
+                "00000000000000000000000000000000000100000000",
+            ),
+        ),
+        (
+            "Renomination",
+            "a09e7dacf3a31bcf7be67666475be442e993a568f76c067044025a275853f89f",
+            concat!(
+                "1010010000000100ee05005645435442010024027db260bdb0d816da62f26e7bfb9fa9e1123f7fae",
+                "5c2038632ea853c840980100010001000000000000000000",
+            ),
+        ),
+        (
+            "RightsSplit",
+            "c9f482bb1b5776b90cd4033f277aacd84965ce4a6f65dcf0e1209a069ba86f48",
+            concat!(
+                "00800000010024027db260bdb0d816da62f26e7bfb9fa9e1123f7fae5c2038632ea853c840980100",
+                "010001000000000000000000",
+            ),
+        ),
+    ];
+
+    #[test]
+    fn vectors_are_stable() {
+        let vectors = vectors();
+        assert_eq!(vectors.len(), TransitionType::ALL.len() + 1);
+        for (vector, (name, node_id, data)) in vectors.iter().zip(VECTORS) {
+            assert_eq!(vector.name, name);
+            assert_eq!(vector.contract_id.to_string(), CONTRACT_ID);
+            assert_eq!(vector.node_id.to_string(), node_id);
+            assert_eq!(vector.data.to_hex(), data);
+        }
+
+        let asset = asset();
+        assert_eq!(vectors[0].contract_id, asset.contract_id());
+        assert_eq!(vectors[0].node_id, asset.genesis().node_id());
+        assert_eq!(
+            Genesis::strict_deserialize(&vectors[0].data)
+                .unwrap()
+                .contract_id(),
+            asset.contract_id()
+        );
+        for (vector, ty) in vectors[1..].iter().zip(TransitionType::ALL) {
+            assert_eq!(vector.name, ty.to_string());
+            let transition = Transition::strict_deserialize(&vector.data).unwrap();
+            assert_eq!(transition.node_id(), vector.node_id);
+            assert_eq!(transition.transition_type(), ty as u16);
+        }
+    }
+}
@@ -0,0 +1,279 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+use bitcoin::blockdata::locktime::LOCK_TIME_THRESHOLD;
+use bitcoin::{OutPoint, Transaction, Txid};
+use rgb::fungible::allocation::AllocatedValue;
+use rgb::AtomicValue;
+use seals::txout::{CloseMethod, ExplicitSeal};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+
+use crate::Asset;
+
+/// Errors happening during construction of locked seals
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum VestingError {
+    /// transaction {0} does not have output #{1}
+    NoOutput(Txid, u32),
+
+    /// transaction {0} is not timelocked to a block height
+    NotHeightLocked(Txid),
+}
+
+/// Seal defined on an output of a pre-signed timelocked transaction, such
+/// that the allocations assigned to it can't be spent before the transaction
+/// is mined.
+///
+/// Allocations to such seals are used for vesting: the beneficiary receives
+/// the asset together with the pre-signed transaction, which can't be mined
+/// before its timelock expires.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct LockedSeal {
+    /// Seal on the output of the timelocked transaction
+    pub seal: ExplicitSeal,
+
+    /// Chain tip height starting from which the timelocked transaction can be
+    /// mined in the next block
+    pub locked_until: u32,
+}
+
+impl LockedSeal {
+    /// Constructs seal on the `vout` output of transaction `tx` with an
+    /// absolute timelock (`nLockTime`, checked with `OP_CHECKLOCKTIMEVERIFY`
+    /// scripts), which must be set to a block height
+    pub fn with_absolute_lock(
+        tx: &Transaction,
+        vout: u32,
+        method: CloseMethod,
+    ) -> Result<LockedSeal, VestingError> {
+        let txid = tx.txid();
+        if !tx.is_lock_time_enabled() || tx.lock_time.0 >= LOCK_TIME_THRESHOLD {
+            return Err(VestingError::NotHeightLocked(txid));
+        }
+        LockedSeal::with(tx, vout, method, tx.lock_time.0)
+    }
+
+    /// Constructs seal on the `vout` output of transaction `tx` with a
+    /// relative timelock (`nSequence`, checked with
+    /// `OP_CHECKSEQUENCEVERIFY` scripts) defined in blocks. The outputs
+    /// spent by the transaction must be mined in the block `confirmed_at`.
+    pub fn with_relative_lock(
+        tx: &Transaction,
+        vout: u32,
+        method: CloseMethod,
+        confirmed_at: u32,
+    ) -> Result<LockedSeal, VestingError> {
+        let blocks = tx
+            .input
+            .iter()
+            .filter(|input| input.sequence.is_height_locked())
+            .map(|input| input.sequence.0 & 0xFFFF)
+            .max()
+            .ok_or_else(|| VestingError::NotHeightLocked(tx.txid()))?;
+        // The transaction can be included into the block at height
+        // `confirmed_at + blocks`
+        let locked_until = confirmed_at.saturating_add(blocks).saturating_sub(1);
+        LockedSeal::with(tx, vout, method, locked_until)
+    }
+
+    fn with(
+        tx: &Transaction,
+        vout: u32,
+        method: CloseMethod,
+        locked_until: u32,
+    ) -> Result<LockedSeal, VestingError> {
+        let txid = tx.txid();
+        if vout as usize >= tx.output.len() {
+            return Err(VestingError::NoOutput(txid, vout));
+        }
+        Ok(LockedSeal {
+            seal: ExplicitSeal {
+                method,
+                txid: Some(txid),
+                vout,
+            },
+            locked_until,
+        })
+    }
+
+    /// Returns outpoint of the seal
+    pub fn outpoint(&self) -> OutPoint {
+        OutPoint::new(
+            self.seal.txid.expect("locked seal always has txid"),
+            self.seal.vout,
+        )
+    }
+
+    /// Constructs allocation of `value` to the seal, which can be used for
+    /// secondary issuance or transfers
+    pub fn allocate(&self, value: AtomicValue) -> AllocatedValue {
+        AllocatedValue {
+            value,
+            seal: self.seal,
+        }
+    }
+}
+
+/// Asset allocation which can't be spent before the chain tip reaches
+/// `locked_until` height
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct LockedAllocation {
+    /// Outpoint the allocation is assigned to
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub outpoint: OutPoint,
+
+    /// Allocated value of the asset
+    pub value: AtomicValue,
+
+    /// Chain tip height starting from which the allocation can be spent
+    pub locked_until: u32,
+}
+
+/// Balance of the known asset allocations at a given chain height
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct Balance {
+    /// Total value of the allocations which can be spent
+    pub spendable: AtomicValue,
+
+    /// Allocations which are still locked
+    pub locked: Vec<LockedAllocation>,
+}
+
+impl Balance {
+    /// Returns total value of the locked allocations
+    pub fn locked_value(&self) -> AtomicValue {
+        self.locked
+            .iter()
+            .fold(0u64, |sum, allocation| sum.saturating_add(allocation.value))
+    }
+}
+
+impl Asset {
+    /// Marks allocations assigned to the locked `seal` as locked, such that
+    /// they are reported by [`Asset::balance`] until the timelock expires
+    pub fn lock_seal(&mut self, seal: &LockedSeal) {
+        self.lock_outpoint(seal.outpoint(), seal.locked_until);
+    }
+
+    /// Computes balance of the known asset allocations when the chain tip is
+    /// at the given `height`, separating allocations locked with
+    /// [`Asset::lock_outpoint`] which can't be spent yet
+    pub fn balance(&self, height: u32) -> Balance {
+        let mut balance = Balance::default();
+        for coin in self.known_coins() {
+            match self.locked_until(coin.seal) {
+                Some(locked_until) if height < locked_until => {
+                    balance.locked.push(LockedAllocation {
+                        outpoint: coin.seal,
+                        value: coin.state.value,
+                        locked_until,
+                    })
+                }
+                _ => balance.spendable = balance.spendable.saturating_add(coin.state.value),
+            }
+        }
+        balance
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use bitcoin::{PackedLockTime, Sequence, TxIn, TxOut};
+    use lnpbp::chain::Chain;
+
+    use super::*;
+    use crate::{AssetName, Rgb20Builder, Ticker};
+
+    fn timelocked_tx(lock_time: u32, sequence: Sequence) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime(lock_time),
+            input: vec![TxIn {
+                sequence,
+                ..TxIn::default()
+            }],
+            output: vec![TxOut::default()],
+        }
+    }
+
+    #[test]
+    fn locked_seals() {
+        let tx = timelocked_tx(800_000, Sequence::ENABLE_LOCKTIME_NO_RBF);
+        let seal = LockedSeal::with_absolute_lock(&tx, 0, CloseMethod::TapretFirst).unwrap();
+        assert_eq!(seal.locked_until, 800_000);
+        assert_eq!(seal.outpoint(), OutPoint::new(tx.txid(), 0));
+        assert_eq!(
+            LockedSeal::with_absolute_lock(&tx, 1, CloseMethod::TapretFirst),
+            Err(VestingError::NoOutput(tx.txid(), 1))
+        );
+
+        let tx = timelocked_tx(1_700_000_000, Sequence::ENABLE_LOCKTIME_NO_RBF);
+        assert_eq!(
+            LockedSeal::with_absolute_lock(&tx, 0, CloseMethod::TapretFirst),
+            Err(VestingError::NotHeightLocked(tx.txid()))
+        );
+
+        let tx = timelocked_tx(0, Sequence::from_height(144));
+        let seal =
+            LockedSeal::with_relative_lock(&tx, 0, CloseMethod::TapretFirst, 800_000).unwrap();
+        assert_eq!(seal.locked_until, 800_143);
+        let tx = timelocked_tx(0, Sequence::MAX);
+        assert_eq!(
+            LockedSeal::with_relative_lock(&tx, 0, CloseMethod::TapretFirst, 800_000),
+            Err(VestingError::NotHeightLocked(tx.txid()))
+        );
+    }
+
+    #[test]
+    fn locked_balance() {
+        let tx = timelocked_tx(800_000, Sequence::ENABLE_LOCKTIME_NO_RBF);
+        let seal = LockedSeal::with_absolute_lock(&tx, 0, CloseMethod::TapretFirst).unwrap();
+        let free = OutPoint::new(tx.input[0].previous_output.txid, 1);
+        let contract = Rgb20Builder::new(Chain::Signet)
+            .ticker(Ticker::from_str("TICK").unwrap())
+            .name(AssetName::from_str("Test asset").unwrap())
+            .allocate(seal.outpoint(), 100)
+            .allocate(free, 50)
+            .build()
+            .unwrap();
+        let mut asset = Asset::try_from(&contract).unwrap();
+        assert_eq!(asset.balance(0).spendable, 150);
+
+        asset.lock_seal(&seal);
+        assert_eq!(asset.locked_until(seal.outpoint()), Some(800_000));
+        let balance = asset.balance(799_999);
+        assert_eq!(balance.spendable, 50);
+        assert_eq!(balance.locked, vec![LockedAllocation {
+            outpoint: seal.outpoint(),
+            value: 100,
+            locked_until: 800_000,
+        }]);
+        assert_eq!(balance.locked_value(), 100);
+        assert_eq!(asset.balance(800_000).spendable, 150);
+
+        let restored = Asset::from_snapshot(asset.to_snapshot()).unwrap();
+        assert_eq!(restored.locked_until(seal.outpoint()), Some(800_000));
+    }
+}
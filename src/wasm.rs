@@ -0,0 +1,239 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! WebAssembly bindings for asset issuance, transfers and asset parsing.
+//!
+//! All functions take and return JSON strings; binary data (contracts, state
+//! transfers and transitions) are represented by hex-encoded strict encoding.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bitcoin::hashes::hex::{FromHex, ToHex};
+use bitcoin::OutPoint;
+use chrono::{TimeZone, Utc};
+use lnpbp::chain::Chain;
+use rgb::{seal, AtomicValue, Contract, Node, SealEndpoint, StateTransfer};
+use seals::txout::CloseMethod;
+use serde_with::{As, DisplayFromStr, Same};
+use strict_encoding::{StrictDecode, StrictEncode};
+use wasm_bindgen::prelude::*;
+
+use crate::{Asset, AssetName, Rgb20Builder, Subschema, Ticker};
+
+/// Parameters of the asset issuance, provided to [`issue`]
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[serde(crate = "serde_crate", rename_all = "camelCase")]
+struct IssueRequest {
+    #[serde(with = "As::<DisplayFromStr>")]
+    chain: Chain,
+    #[serde(default = "default_subschema")]
+    subschema: Subschema,
+    #[serde(with = "As::<DisplayFromStr>")]
+    ticker: Ticker,
+    #[serde(with = "As::<DisplayFromStr>")]
+    name: AssetName,
+    #[serde(default = "default_precision")]
+    precision: u8,
+    #[serde(default, with = "As::<Vec<(DisplayFromStr, Same)>>")]
+    allocations: Vec<(OutPoint, AtomicValue)>,
+    #[serde(default, with = "As::<Vec<(DisplayFromStr, Same)>>")]
+    inflation: Vec<(OutPoint, AtomicValue)>,
+    #[serde(default, with = "As::<Option<DisplayFromStr>>")]
+    renomination: Option<OutPoint>,
+    #[serde(default, with = "As::<Option<DisplayFromStr>>")]
+    epoch: Option<OutPoint>,
+    #[serde(default, with = "As::<Option<DisplayFromStr>>")]
+    close_method: Option<CloseMethod>,
+    #[serde(default)]
+    timestamp: Option<i64>,
+}
+
+fn default_subschema() -> Subschema { Subschema::Full }
+
+fn default_precision() -> u8 { 8 }
+
+/// Parameters of the asset transfer, provided to [`transfer`]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(crate = "serde_crate", rename_all = "camelCase")]
+struct TransferRequest {
+    asset: Asset,
+    #[serde(with = "As::<BTreeSet<DisplayFromStr>>")]
+    inputs: BTreeSet<OutPoint>,
+    #[serde(with = "As::<BTreeMap<DisplayFromStr, Same>>")]
+    payment: BTreeMap<SealEndpoint, AtomicValue>,
+    #[serde(default, with = "As::<BTreeMap<DisplayFromStr, Same>>")]
+    change: BTreeMap<seal::Revealed, AtomicValue>,
+}
+
+/// Result of the asset issuance
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(crate = "serde_crate", rename_all = "camelCase")]
+struct IssueResponse {
+    contract_id: String,
+    contract: String,
+}
+
+/// Result of the transfer transition construction
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(crate = "serde_crate", rename_all = "camelCase")]
+struct TransferResponse {
+    node_id: String,
+    transition: String,
+}
+
+/// Issues new asset from JSON-encoded issuance parameters, returning JSON
+/// object with the contract id and hex-encoded contract
+#[wasm_bindgen]
+pub fn issue(request: &str) -> Result<String, JsValue> { issue_json(request).map_err(js_error) }
+
+/// Parses hex-encoded asset contract, returning JSON-encoded [`Asset`]
+#[wasm_bindgen(js_name = parseContract)]
+pub fn parse_contract(contract: &str) -> Result<String, JsValue> {
+    parse_contract_json(contract).map_err(js_error)
+}
+
+/// Updates JSON-encoded [`Asset`] with hex-encoded state transfer, returning
+/// updated asset in JSON
+#[wasm_bindgen(js_name = acceptTransfer)]
+pub fn accept_transfer(asset: &str, transfer: &str) -> Result<String, JsValue> {
+    accept_transfer_json(asset, transfer).map_err(js_error)
+}
+
+/// Constructs transfer state transition from JSON-encoded asset and transfer
+/// parameters, returning JSON object with the transition id and hex-encoded
+/// transition
+#[wasm_bindgen]
+pub fn transfer(request: &str) -> Result<String, JsValue> {
+    transfer_json(request).map_err(js_error)
+}
+
+fn js_error(err: String) -> JsValue { JsValue::from_str(&err) }
+
+fn issue_json(request: &str) -> Result<String, String> {
+    let request: IssueRequest = serde_json::from_str(request).map_err(|err| err.to_string())?;
+    let mut builder = Rgb20Builder::new(request.chain)
+        .subschema(request.subschema)
+        .ticker(request.ticker)
+        .name(request.name)
+        .precision(request.precision);
+    for (outpoint, value) in request.allocations {
+        builder = builder.allocate(outpoint, value);
+    }
+    for (outpoint, value) in request.inflation {
+        builder = builder.inflation_right(outpoint, value);
+    }
+    if let Some(outpoint) = request.renomination {
+        builder = builder.renomination(outpoint);
+    }
+    if let Some(outpoint) = request.epoch {
+        builder = builder.epoch(outpoint);
+    }
+    if let Some(method) = request.close_method {
+        builder = builder.close_method(method);
+    }
+    if let Some(timestamp) = request.timestamp {
+        let timestamp = Utc
+            .timestamp_opt(timestamp, 0)
+            .single()
+            .ok_or_else(|| format!("invalid timestamp {}", timestamp))?;
+        builder = builder.timestamp(timestamp);
+    }
+    let contract = builder.build().map_err(|err| err.to_string())?;
+    to_json(&IssueResponse {
+        contract_id: contract.contract_id().to_string(),
+        contract: to_hex(&contract)?,
+    })
+}
+
+fn parse_contract_json(contract: &str) -> Result<String, String> {
+    let contract: Contract = from_hex(contract)?;
+    let asset = Asset::try_from(&contract).map_err(|err| err.to_string())?;
+    to_json(&asset)
+}
+
+fn accept_transfer_json(asset: &str, transfer: &str) -> Result<String, String> {
+    let mut asset: Asset = serde_json::from_str(asset).map_err(|err| err.to_string())?;
+    let transfer: StateTransfer = from_hex(transfer)?;
+    asset
+        .update_with(&transfer)
+        .map_err(|err| err.to_string())?;
+    to_json(&asset)
+}
+
+fn transfer_json(request: &str) -> Result<String, String> {
+    let request: TransferRequest = serde_json::from_str(request).map_err(|err| err.to_string())?;
+    let transition = request
+        .asset
+        .transfer(request.inputs, request.payment, request.change)
+        .map_err(|err| err.to_string())?;
+    to_json(&TransferResponse {
+        node_id: transition.node_id().to_string(),
+        transition: to_hex(&transition)?,
+    })
+}
+
+fn to_json(data: &impl serde::Serialize) -> Result<String, String> {
+    serde_json::to_string(data).map_err(|err| err.to_string())
+}
+
+fn to_hex(data: &impl StrictEncode) -> Result<String, String> {
+    data.strict_serialize()
+        .map(|data| data.to_hex())
+        .map_err(|err| err.to_string())
+}
+
+fn from_hex<T: StrictDecode>(hex: &str) -> Result<T, String> {
+    let data = Vec::<u8>::from_hex(hex.trim()).map_err(|err| err.to_string())?;
+    T::strict_deserialize(data).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vectors;
+
+    #[test]
+    fn issue_and_transfer() {
+        let request = format!(
+            r#"{{
+                "chain": "signet",
+                "ticker": "TICK",
+                "name": "Test asset",
+                "allocations": [["{txid}:1", 100]],
+                "timestamp": 1650000000
+            }}"#,
+            txid = vectors::txid()
+        );
+        let issued: serde_json::Value =
+            serde_json::from_str(&issue_json(&request).unwrap()).unwrap();
+        let contract = issued["contract"].as_str().unwrap();
+
+        let asset: serde_json::Value =
+            serde_json::from_str(&parse_contract_json(contract).unwrap()).unwrap();
+        let contract: Contract = from_hex(contract).unwrap();
+        assert_eq!(
+            issued["contractId"].as_str().unwrap(),
+            contract.contract_id().to_string()
+        );
+
+        let request = serde_json::json!({
+            "asset": asset,
+            "inputs": [vectors::outpoint(1).to_string()],
+            "payment": { "tapret1st:~:0#0x1": 100 }
+        });
+        let transferred: serde_json::Value =
+            serde_json::from_str(&transfer_json(&request.to_string()).unwrap()).unwrap();
+        assert!(transferred["transition"].is_string());
+
+        assert!(issue_json("{}").is_err());
+        assert!(parse_contract_json("00").is_err());
+    }
+}
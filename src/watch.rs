@@ -0,0 +1,324 @@
+// RGB20 Library: high-level API to RGB fungible assets.
+// Written in 2019-2022 by
+//     Dr. Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// To the extent possible under law, the author(s) have dedicated all copyright
+// and related and neighboring rights to this software to the public domain
+// worldwide. This software is distributed without any warranty.
+//
+// You should have received a copy of the MIT License along with this software.
+// If not, see <https://opensource.org/licenses/MIT>.
+
+//! Watch-only tracking of RGB20 assets.
+//!
+//! A watch-only wallet is defined by a set of scripts, which can be derived
+//! from extended public keys. Asset allocations are attributed to the wallet
+//! when their seals (including the outputs of witness transactions) hold one
+//! of the watched scripts. No private keys are required, which makes the
+//! module suitable for auditors and accountants.
+
+use std::collections::BTreeSet;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use bitcoin::secp256k1::{Secp256k1, Verification, XOnlyPublicKey};
+use bitcoin::util::bip32::{self, ChildNumber, ExtendedPubKey};
+use bitcoin::{OutPoint, PublicKey, Script};
+use bitcoin_onchain::ResolveTx;
+use rgb::{AtomicValue, ConsignmentType, ContractId, InmemConsignment, OwnedValue};
+#[cfg(feature = "serde")]
+use serde_with::{As, DisplayFromStr};
+
+use crate::Asset;
+
+/// Errors defining watch-only wallets
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum WatchError {
+    /// unsupported or invalid descriptor `{0}`; only `pkh`, `sh(wpkh)`,
+    /// `wpkh` and key-path `tr` descriptors over a single extended public key
+    /// are supported
+    InvalidDescriptor(String),
+
+    /// unable to derive wallet keys. Details: {0}
+    #[from]
+    Derivation(bip32::Error),
+}
+
+/// Type of the scripts derived from the wallet keys
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum ScriptType {
+    /// Pay to public key hash
+    Pkh,
+
+    /// Pay to witness public key hash nested into pay to script hash
+    ShWpkh,
+
+    /// Pay to witness public key hash
+    Wpkh,
+
+    /// Pay to taproot output without script paths
+    Tr,
+}
+
+/// Descriptor of watch-only wallet scripts derived from an extended public
+/// key, using BIP-32 derivation paths `<xpub>/<chain>/<index>`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct WatchDescriptor {
+    /// Type of the derived scripts
+    pub script_type: ScriptType,
+
+    /// Account-level extended public key of the wallet
+    pub xpub: ExtendedPubKey,
+}
+
+impl WatchDescriptor {
+    /// Derives script for the key with the given `chain` (0 for receiving and
+    /// 1 for change addresses) and `index`
+    pub fn derive<C: Verification>(
+        &self,
+        secp: &Secp256k1<C>,
+        chain: u32,
+        index: u32,
+    ) -> Result<Script, WatchError> {
+        let xpub = self.xpub.derive_pub(secp, &[
+            ChildNumber::from_normal_idx(chain)?,
+            ChildNumber::from_normal_idx(index)?,
+        ])?;
+        let pubkey = PublicKey::new(xpub.public_key);
+        let wpkh =
+            || Script::new_v0_p2wpkh(&pubkey.wpubkey_hash().expect("derived keys are compressed"));
+        Ok(match self.script_type {
+            ScriptType::Pkh => Script::new_p2pkh(&pubkey.pubkey_hash()),
+            ScriptType::ShWpkh => wpkh().to_p2sh(),
+            ScriptType::Wpkh => wpkh(),
+            ScriptType::Tr => {
+                Script::new_v1_p2tr(secp, XOnlyPublicKey::from(xpub.public_key), None)
+            }
+        })
+    }
+}
+
+impl Display for WatchDescriptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.script_type {
+            ScriptType::Pkh => write!(f, "pkh({})", self.xpub),
+            ScriptType::ShWpkh => write!(f, "sh(wpkh({}))", self.xpub),
+            ScriptType::Wpkh => write!(f, "wpkh({})", self.xpub),
+            ScriptType::Tr => write!(f, "tr({})", self.xpub),
+        }
+    }
+}
+
+impl FromStr for WatchDescriptor {
+    type Err = WatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || WatchError::InvalidDescriptor(s.to_owned());
+        let (script_type, inner) = [
+            ("sh(wpkh(", ScriptType::ShWpkh, "))"),
+            ("pkh(", ScriptType::Pkh, ")"),
+            ("wpkh(", ScriptType::Wpkh, ")"),
+            ("tr(", ScriptType::Tr, ")"),
+        ]
+        .into_iter()
+        .find_map(|(prefix, script_type, suffix)| {
+            Some((script_type, s.strip_prefix(prefix)?.strip_suffix(suffix)?))
+        })
+        .ok_or_else(invalid)?;
+        let xpub = ExtendedPubKey::from_str(inner).map_err(|_| invalid())?;
+        Ok(WatchDescriptor { script_type, xpub })
+    }
+}
+
+/// Watch-only wallet defined by the set of scripts it controls
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct WatchOnly {
+    scripts: BTreeSet<Script>,
+}
+
+impl WatchOnly {
+    /// Constructs watch-only wallet without any scripts
+    pub fn new() -> WatchOnly { WatchOnly::default() }
+
+    /// Constructs watch-only wallet controlling the provided `scripts`
+    pub fn with_scripts(scripts: impl IntoIterator<Item = Script>) -> WatchOnly {
+        WatchOnly {
+            scripts: scripts.into_iter().collect(),
+        }
+    }
+
+    /// Adds script to the set of watched scripts
+    pub fn add_script(&mut self, script: Script) { self.scripts.insert(script); }
+
+    /// Adds scripts derived from the `descriptor` for the first `gap_limit`
+    /// indexes of both receiving and change chains
+    pub fn add_descriptor<C: Verification>(
+        &mut self,
+        secp: &Secp256k1<C>,
+        descriptor: &WatchDescriptor,
+        gap_limit: u32,
+    ) -> Result<(), WatchError> {
+        for chain in 0..=1 {
+            for index in 0..gap_limit {
+                self.scripts.insert(descriptor.derive(secp, chain, index)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks whether the script is watched
+    pub fn contains(&self, script: &Script) -> bool { self.scripts.contains(script) }
+
+    /// Returns number of watched scripts
+    pub fn len(&self) -> usize { self.scripts.len() }
+
+    /// Detects whether the wallet has no watched scripts
+    pub fn is_empty(&self) -> bool { self.scripts.is_empty() }
+
+    /// Finds known allocations of the `asset` assigned to the watched
+    /// scripts. Transactions holding the allocations are retrieved with the
+    /// `resolver`; outputs of the transactions it does not know are reported
+    /// as unresolved.
+    pub fn scan(&self, asset: &Asset, resolver: &impl ResolveTx) -> WatchReport {
+        let mut report = WatchReport {
+            contract_id: asset.contract_id(),
+            allocations: empty!(),
+            unresolved: empty!(),
+        };
+        for outpoint in asset.outpoints() {
+            let script_pubkey = resolver.resolve_tx(outpoint.txid).ok().and_then(|tx| {
+                tx.output
+                    .get(outpoint.vout as usize)
+                    .map(|output| output.script_pubkey.clone())
+            });
+            match script_pubkey {
+                Some(script) if self.contains(&script) => report
+                    .allocations
+                    .extend(asset.allocations_at(*outpoint).iter().cloned()),
+                Some(_) => {}
+                None => {
+                    report.unresolved.insert(*outpoint);
+                }
+            }
+        }
+        report
+    }
+
+    /// Finds asset allocations of the consignment assigned to the watched
+    /// scripts, like [`WatchOnly::scan`]. Errors if the consignment does not
+    /// match RGB20 schema.
+    pub fn scan_consignment<T>(
+        &self,
+        consignment: &InmemConsignment<T>,
+        resolver: &impl ResolveTx,
+    ) -> Result<WatchReport, crate::Error>
+    where
+        T: ConsignmentType,
+    {
+        Ok(self.scan(&Asset::try_from(consignment)?, resolver))
+    }
+}
+
+/// Asset allocations found by [`WatchOnly::scan`]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct WatchReport {
+    /// Id of the asset contract
+    #[cfg_attr(feature = "serde", serde(with = "As::<DisplayFromStr>"))]
+    pub contract_id: ContractId,
+
+    /// Allocations assigned to the watched scripts
+    #[cfg_attr(feature = "serde", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub allocations: Vec<OwnedValue>,
+
+    /// Outputs holding asset allocations which transactions are unknown to
+    /// the resolver
+    pub unresolved: BTreeSet<OutPoint>,
+}
+
+impl WatchReport {
+    /// Returns total value of the allocations assigned to the watched scripts
+    pub fn balance(&self) -> AtomicValue {
+        self.allocations
+            .iter()
+            .fold(0u64, |sum, coin| sum.saturating_add(coin.state.value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::util::bip32::ExtendedPrivKey;
+    use bitcoin::{Network, PackedLockTime, Transaction, TxOut};
+    use lnpbp::chain::Chain;
+
+    use super::*;
+    use crate::fixtures::outpoint;
+    use crate::{AssetName, OfflineResolver, Rgb20Builder, Ticker};
+
+    fn descriptor(script_type: ScriptType) -> WatchDescriptor {
+        let secp = Secp256k1::new();
+        let xpriv = ExtendedPrivKey::new_master(Network::Testnet, &[7u8; 32]).unwrap();
+        WatchDescriptor {
+            script_type,
+            xpub: ExtendedPubKey::from_priv(&secp, &xpriv),
+        }
+    }
+
+    #[test]
+    fn descriptor_roundtrip() {
+        for script_type in [ScriptType::Pkh, ScriptType::ShWpkh, ScriptType::Wpkh, ScriptType::Tr] {
+            let descriptor = descriptor(script_type);
+            assert_eq!(
+                WatchDescriptor::from_str(&descriptor.to_string()),
+                Ok(descriptor)
+            );
+        }
+        assert!(WatchDescriptor::from_str("wsh(xpub)").is_err());
+    }
+
+    #[test]
+    fn scan_allocations() {
+        let secp = Secp256k1::verification_only();
+        let descriptor = descriptor(ScriptType::Wpkh);
+        let mut wallet = WatchOnly::new();
+        wallet.add_descriptor(&secp, &descriptor, 5).unwrap();
+        assert_eq!(wallet.len(), 10);
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![],
+            output: vec![
+                TxOut {
+                    value: 1000,
+                    script_pubkey: descriptor.derive(&secp, 1, 3).unwrap(),
+                },
+                TxOut {
+                    value: 1000,
+                    script_pubkey: Script::new(),
+                },
+            ],
+        };
+        let unknown = outpoint(1);
+        let contract = Rgb20Builder::new(Chain::Signet)
+            .ticker(Ticker::from_str("TICK").unwrap())
+            .name(AssetName::from_str("Test asset").unwrap())
+            .allocate(OutPoint::new(tx.txid(), 0), 100)
+            .allocate(OutPoint::new(tx.txid(), 1), 20)
+            .allocate(unknown, 50)
+            .build()
+            .unwrap();
+        let mut resolver = OfflineResolver::new();
+        resolver.insert(tx);
+
+        let report = wallet.scan_consignment(&contract, &resolver).unwrap();
+        assert_eq!(report.balance(), 100);
+        assert_eq!(report.allocations.len(), 1);
+        assert_eq!(report.unresolved, bset! { unknown });
+    }
+}